@@ -15,8 +15,10 @@ mkdir -p docs/reference
 echo "Copying test results..."
 result_count=0
 
-# Check if results are in subdirectories or flat structure
-if ls results/*/ &>/dev/null; then
+# Check if results are in subdirectories or flat structure. Flat files win:
+# the orchestrator's per-run artifact directories (results/<run-id>/) sit
+# alongside them and hold copies.
+if ! ls results/*.json &>/dev/null && ls results/*/ &>/dev/null; then
     # Subdirectory structure
     for impl_dir in results/*/; do
         if [ -d "$impl_dir" ]; then
@@ -295,6 +295,19 @@ json processTestCase(const json& testCase) {
 }
 
 int main(int argc, char* argv[]) {
+    // Self-check for the orchestrator: report versions and exit
+    if (argc == 2 && std::string(argv[1]) == "--version") {
+        json version = {
+            {"implementation", "cpp-sgp4"},
+            {"version", "1.0.0"},
+            {"libraryName", "sat_code (Bill Gray)"},
+            {"libraryVersion", "2023"},
+            {"platform", "C++17"}
+        };
+        std::cout << version.dump() << std::endl;
+        return 0;
+    }
+
     // Configuration
     std::string testDataDir = fs::exists("/test-data/cases") ? "/test-data/cases" : "../../test-data/cases";
     std::string resultsDir = fs::exists("/results") ? "/results" : "../../results";
@@ -15,6 +15,20 @@ namespace VisibilityCalculator
 
         static void Main(string[] args)
         {
+            // Self-check for the orchestrator: report versions and exit
+            if (args.Length == 1 && args[0] == "--version")
+            {
+                Console.WriteLine(JsonConvert.SerializeObject(new
+                {
+                    implementation = "csharp-sgp.net",
+                    version = "1.0.0",
+                    libraryName = "Zeptomoby.OrbitTools.Core",
+                    libraryVersion = "2.0.0",
+                    platform = $".NET {Environment.Version}"
+                }));
+                return;
+            }
+
             // Configuration - prefer Docker paths, fall back to local
             string testDataDir = Directory.Exists("/test-data/cases")
                 ? "/test-data/cases"
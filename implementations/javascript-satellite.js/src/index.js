@@ -303,6 +303,18 @@ function main() {
   const args = process.argv.slice(2);
   let testFiles;
 
+  // Self-check for the orchestrator: report versions and exit
+  if (args.length === 1 && args[0] === '--version') {
+    console.log(JSON.stringify({
+      implementation: 'javascript-satellite.js',
+      version: new VisibilityCalculator().version,
+      libraryName: 'satellite.js',
+      libraryVersion: '5.0.0',
+      platform: `Node.js ${process.version}`
+    }));
+    process.exit(0);
+  }
+
   if (args.length > 0) {
     const testCaseName = args[0];
     testFiles = [join(testDataDir, `${testCaseName}.json`)];
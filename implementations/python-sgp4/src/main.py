@@ -37,6 +37,18 @@ def write_result(result, output_dir):
 
 def main():
     """Main entry point."""
+    # Self-check for the orchestrator: report versions and exit
+    if sys.argv[1:] == ['--version']:
+        calculator = VisibilityCalculator()
+        print(json.dumps({
+            'implementation': 'python-sgp4',
+            'version': '1.0.0',
+            'libraryName': 'sgp4',
+            'libraryVersion': calculator.get_version(),
+            'platform': f"Python {sys.version.split()[0]}"
+        }))
+        sys.exit(0)
+
     # Configuration - prefer Docker paths, fall back to local
     if Path("/test-data/cases").exists():
         test_data_dir = Path("/test-data/cases")
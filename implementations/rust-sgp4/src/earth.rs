@@ -0,0 +1,68 @@
+//! Earth model constants shared by propagation and geodetic conversions.
+//!
+//! SGP4 element sets are fitted with WGS-72, so that is the default; WGS-84
+//! is available for comparison against libraries that use it throughout.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EarthModel {
+    #[default]
+    Wgs72,
+    Wgs84,
+}
+
+impl EarthModel {
+    /// Equatorial radius in km
+    pub fn radius_km(self) -> f64 {
+        self.geopotential().ae
+    }
+
+    pub fn flattening(self) -> f64 {
+        match self {
+            EarthModel::Wgs72 => 1.0 / 298.26,
+            EarthModel::Wgs84 => 1.0 / 298.257223563,
+        }
+    }
+
+    /// First eccentricity squared of the reference ellipsoid
+    pub fn eccentricity_sq(self) -> f64 {
+        let f = self.flattening();
+        2.0 * f - f * f
+    }
+
+    pub fn geopotential(self) -> sgp4::Geopotential {
+        match self {
+            EarthModel::Wgs72 => sgp4::WGS72,
+            EarthModel::Wgs84 => sgp4::WGS84,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EarthModel::Wgs72 => "wgs72",
+            EarthModel::Wgs84 => "wgs84",
+        }
+    }
+}
+
+impl fmt::Display for EarthModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EarthModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wgs72" => Ok(EarthModel::Wgs72),
+            "wgs84" => Ok(EarthModel::Wgs84),
+            _ => anyhow::bail!("Unknown Earth model '{}' (expected wgs72 or wgs84)", s),
+        }
+    }
+}
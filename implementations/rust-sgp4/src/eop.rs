@@ -0,0 +1,116 @@
+//! Earth orientation parameters (ΔUT1 and polar motion) from IERS data.
+//!
+//! Reads the fixed-width `finals2000A.data` format published by the IERS
+//! Rapid Service (or any trimmed subset of its lines). Bulletin A values are
+//! used; rows without them, such as the far end of the prediction span, are
+//! skipped.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default location checked when no file is given explicitly.
+pub const DEFAULT_EOP_FILE: &str = "/test-data/eop/finals2000A.data";
+
+const ARCSEC2RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EopValues {
+    /// UT1 - UTC in seconds
+    pub dut1: f64,
+    /// Polar motion x in radians
+    pub xp: f64,
+    /// Polar motion y in radians
+    pub yp: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EopEntry {
+    mjd: f64,
+    values: EopValues,
+}
+
+#[derive(Debug)]
+pub struct EopTable {
+    source: PathBuf,
+    entries: Vec<EopEntry>,
+}
+
+impl EopTable {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read EOP file {}", path.display()))?;
+
+        let mut entries: Vec<EopEntry> = content.lines().filter_map(parse_line).collect();
+        entries.sort_by(|a, b| a.mjd.total_cmp(&b.mjd));
+
+        if entries.is_empty() {
+            anyhow::bail!("No usable EOP rows in {}", path.display());
+        }
+
+        Ok(Self {
+            source: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Whether the table has data for every instant in `[start_mjd, end_mjd]`.
+    pub fn covers(&self, start_mjd: f64, end_mjd: f64) -> bool {
+        let first = self.entries.first().map_or(f64::INFINITY, |e| e.mjd);
+        let last = self.entries.last().map_or(f64::NEG_INFINITY, |e| e.mjd);
+        first <= start_mjd && end_mjd <= last
+    }
+
+    /// Values at `mjd` (UTC), linearly interpolated between daily rows.
+    ///
+    /// ΔUT1 is not interpolated across a leap second, where it jumps by
+    /// a whole second; the earlier day's value is held instead.
+    pub fn lookup(&self, mjd: f64) -> Option<EopValues> {
+        let idx = self.entries.partition_point(|e| e.mjd <= mjd);
+        if idx == 0 {
+            return None;
+        }
+
+        let a = self.entries[idx - 1];
+        if a.mjd == mjd {
+            return Some(a.values);
+        }
+        let b = *self.entries.get(idx)?;
+
+        let f = (mjd - a.mjd) / (b.mjd - a.mjd);
+        let lerp = |x: f64, y: f64| x + (y - x) * f;
+
+        let dut1 = if (b.values.dut1 - a.values.dut1).abs() > 0.5 {
+            a.values.dut1
+        } else {
+            lerp(a.values.dut1, b.values.dut1)
+        };
+
+        Some(EopValues {
+            dut1,
+            xp: lerp(a.values.xp, b.values.xp),
+            yp: lerp(a.values.yp, b.values.yp),
+        })
+    }
+}
+
+/// Parse one finals2000A row. Columns (1-based, inclusive):
+/// MJD 8-15, PM-x 19-27 ("), PM-y 38-46 ("), UT1-UTC 59-68 (s).
+fn parse_line(line: &str) -> Option<EopEntry> {
+    let field = |start: usize, end: usize| -> Option<f64> {
+        line.get(start - 1..end)?.trim().parse().ok()
+    };
+
+    Some(EopEntry {
+        mjd: field(8, 15)?,
+        values: EopValues {
+            dut1: field(59, 68)?,
+            xp: field(19, 27)? * ARCSEC2RAD,
+            yp: field(38, 46)? * ARCSEC2RAD,
+        },
+    })
+}
@@ -0,0 +1,72 @@
+//! Self-validation against expected windows embedded in a test case, for
+//! standalone runs without the orchestrator.
+
+use crate::VisibilityWindow;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Maximum-elevation tolerance in degrees (see docs/tolerance-spec.md).
+const MAX_ELEVATION_TOLERANCE: f64 = 0.1;
+
+/// Minimum start/end tolerance in seconds; the effective tolerance is one
+/// time step, since window edges are only resolved to the sampling grid.
+const MIN_TIME_TOLERANCE: i64 = 1;
+
+#[derive(Debug, Deserialize)]
+pub struct ExpectedWindow {
+    start: String,
+    end: String,
+    #[serde(rename = "maxElevation")]
+    max_elevation: Option<f64>,
+}
+
+/// Compare computed windows against expectations, returning one message per
+/// discrepancy (empty when everything agrees).
+pub fn check(expected: &[ExpectedWindow], actual: &[VisibilityWindow], step_seconds: i64) -> Result<Vec<String>> {
+    let mut mismatches = Vec::new();
+
+    if expected.len() != actual.len() {
+        mismatches.push(format!(
+            "{} window(s), expected {}",
+            actual.len(),
+            expected.len()
+        ));
+        return Ok(mismatches);
+    }
+
+    let time_tolerance = step_seconds.max(MIN_TIME_TOLERANCE);
+
+    for (i, (want, got)) in expected.iter().zip(actual).enumerate() {
+        for (edge, want_time, got_time) in [("start", &want.start, &got.start), ("end", &want.end, &got.end)] {
+            let delta = (parse_time(got_time)? - parse_time(want_time)?).num_seconds();
+            if delta.abs() > time_tolerance {
+                mismatches.push(format!(
+                    "window {} {} {}, expected {} ({:+} s)",
+                    i + 1,
+                    edge,
+                    got_time,
+                    want_time,
+                    delta
+                ));
+            }
+        }
+
+        if let Some(want_elevation) = want.max_elevation
+            && (got.max_elevation - want_elevation).abs() > MAX_ELEVATION_TOLERANCE
+        {
+            mismatches.push(format!(
+                "window {} max elevation {:.2}°, expected {:.2}°",
+                i + 1,
+                got.max_elevation,
+                want_elevation
+            ));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+fn parse_time(time: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(time)?.with_timezone(&Utc))
+}
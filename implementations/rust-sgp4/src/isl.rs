@@ -0,0 +1,89 @@
+//! Geometry for inter-satellite link (ISL) test cases, where the observer is
+//! itself a satellite. Everything here works on inertial (TEME) vectors in km.
+
+use crate::earth::EarthModel;
+use std::f64::consts::PI;
+
+const RAD2DEG: f64 = 180.0 / PI;
+
+/// Whether the straight line from `a` to `b` clears the Earth ellipsoid.
+///
+/// Stretching z by a/b turns the ellipsoid into a sphere of the equatorial
+/// radius, where the closest approach of the segment is easy to find.
+pub fn line_of_sight_clear(a: &[f64; 3], b: &[f64; 3], earth_model: EarthModel) -> bool {
+    let radius = earth_model.radius_km();
+    let z_scale = 1.0 / (1.0 - earth_model.flattening());
+
+    let p = [a[0], a[1], a[2] * z_scale];
+    let q = [b[0], b[1], b[2] * z_scale];
+    let d = [q[0] - p[0], q[1] - p[1], q[2] - p[2]];
+
+    // Parameter of the point on the segment closest to the Earth's center
+    let t = (-dot(&p, &d) / dot(&d, &d)).clamp(0.0, 1.0);
+    let closest = [p[0] + t * d[0], p[1] + t * d[1], p[2] + t * d[2]];
+
+    dot(&closest, &closest).sqrt() > radius
+}
+
+/// Azimuth and elevation (degrees) of `range_vec` in the local horizontal
+/// frame of an observer at `observer_pos`: up is radial, north points toward
+/// the pole, and azimuth runs clockwise from north.
+pub fn local_azel(observer_pos: &[f64; 3], range_vec: &[f64; 3]) -> (f64, f64) {
+    let up = unit(observer_pos);
+
+    // East = pole × up; over the poles fall back to the x axis
+    let mut east = cross(&[0.0, 0.0, 1.0], &up);
+    if dot(&east, &east) < 1e-12 {
+        east = cross(&up, &[1.0, 0.0, 0.0]);
+    }
+    let east = unit(&east);
+    let north = cross(&up, &east);
+
+    let range = unit(range_vec);
+    let elevation = dot(&range, &up).clamp(-1.0, 1.0).asin() * RAD2DEG;
+    let azimuth = dot(&range, &east).atan2(dot(&range, &north)) * RAD2DEG;
+
+    (azimuth.rem_euclid(360.0), elevation)
+}
+
+/// Azimuth and elevation rates (deg/s) in the observer's local horizontal
+/// frame. That frame turns with the observer's orbit, so the rates come from
+/// stepping both states along their velocities by a short interval.
+pub fn local_azel_rates(
+    observer_pos: &[f64; 3],
+    observer_vel: &[f64; 3],
+    range_vec: &[f64; 3],
+    relative_velocity: &[f64; 3],
+) -> (f64, f64) {
+    const DT: f64 = 0.5;
+    let step = |v: &[f64; 3], rate: &[f64; 3], dt: f64| [v[0] + rate[0] * dt, v[1] + rate[1] * dt, v[2] + rate[2] * dt];
+
+    let (az0, el0) = local_azel(&step(observer_pos, observer_vel, -DT), &step(range_vec, relative_velocity, -DT));
+    let (az1, el1) = local_azel(&step(observer_pos, observer_vel, DT), &step(range_vec, relative_velocity, DT));
+
+    // Unwrap azimuth across north
+    let delta_az = (az1 - az0 + 180.0).rem_euclid(360.0) - 180.0;
+    (delta_az / (2.0 * DT), (el1 - el0) / (2.0 * DT))
+}
+
+/// Rate of change of the separation between two satellites, in km/s.
+pub fn range_rate(range_vec: &[f64; 3], relative_velocity: &[f64; 3]) -> f64 {
+    dot(range_vec, relative_velocity) / dot(range_vec, range_vec).sqrt()
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn unit(a: &[f64; 3]) -> [f64; 3] {
+    let n = dot(a, a).sqrt();
+    [a[0] / n, a[1] / n, a[2] / n]
+}
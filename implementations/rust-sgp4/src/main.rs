@@ -1,24 +1,76 @@
+mod catalog;
+mod earth;
+mod eop;
+mod expectations;
+mod isl;
+mod moon;
+mod rss;
+mod sun;
+mod time_format;
+mod trajectory;
+mod uncertainty;
+mod warnings;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
+use earth::EarthModel;
+use eop::{EopTable, EopValues};
+use expectations::ExpectedWindow;
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use serde::{Deserialize, Serialize};
 use sgp4::*;
+use time_format::{FormattedTime, TimeFormat};
+use trajectory::{Trajectory, Waypoint};
+use uncertainty::{PointUncertainty, Sensitivity};
+use warnings::Warning;
 use std::f64::consts::PI;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
-const EARTH_RADIUS_KM: f64 = 6378.137;
 const DEG2RAD: f64 = PI / 180.0;
 const RAD2DEG: f64 = 180.0 / PI;
+const MJD_TO_JD: f64 = 2400000.5;
+/// Earth rotation rate in rad/s
+const EARTH_ROTATION_RATE: f64 = 7.292115146706979e-5;
+
+/// Time windows with more steps than this report progress on stderr
+const PROGRESS_STEPS: usize = 10_000;
+
+/// Reported in result metadata and by `--version`
+const VERSION: &str = "1.0.0";
+const LIBRARY_NAME: &str = "sgp4";
+const LIBRARY_VERSION: &str = "2.0";
 
 #[derive(Debug, Deserialize)]
 struct TestCase {
     name: String,
     satellite: SatelliteData,
-    observer: Observer,
+    /// Fixed ground observer; exactly one of the observer fields is set
+    observer: Option<Observer>,
+    /// Observing satellite for inter-satellite link cases
+    #[serde(rename = "observerSatellite")]
+    observer_satellite: Option<SatelliteData>,
+    /// Moving ground observer (ship, aircraft)
+    #[serde(rename = "observerTrajectory")]
+    observer_trajectory: Option<Vec<Waypoint>>,
     #[serde(rename = "timeWindow")]
     time_window: TimeWindow,
     #[serde(rename = "minElevation")]
     min_elevation: f64,
+    /// Compute illumination conditions per point for optical observations
+    #[serde(default)]
+    optical: bool,
+    /// Earth model for propagation and geodesy; overrides `--earth-model`
+    #[serde(rename = "earthModel")]
+    earth_model: Option<EarthModel>,
+    /// Extra timestamp format for points; overrides `--time-format`
+    #[serde(rename = "timeFormat")]
+    time_format: Option<TimeFormat>,
+    /// Windows to self-validate against when run without the orchestrator
+    #[serde(rename = "expectedWindows")]
+    expected_windows: Option<Vec<ExpectedWindow>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,7 +78,7 @@ struct SatelliteData {
     tle: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 struct Observer {
     latitude: f64,
     longitude: f64,
@@ -51,6 +103,8 @@ struct TestResult {
     #[serde(rename = "executionTime")]
     execution_time: f64,
     timestamp: String,
+    /// Non-fatal issues; the result is still computed
+    warnings: Vec<Warning>,
     metadata: Metadata,
 }
 
@@ -63,18 +117,67 @@ struct VisibilityWindow {
     #[serde(rename = "maxElevationTime")]
     max_elevation_time: String,
     duration: f64,
+    /// 1σ uncertainty of `start` and `end` in seconds, with `--uncertainty`
+    #[serde(rename = "startUncertainty", skip_serializing_if = "Option::is_none")]
+    start_uncertainty: Option<f64>,
+    #[serde(rename = "endUncertainty", skip_serializing_if = "Option::is_none")]
+    end_uncertainty: Option<f64>,
+    /// Left out of `--catalog` output
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     points: Vec<Point>,
 }
 
 #[derive(Debug, Serialize, Clone)]
 struct Point {
     time: String,
+    #[serde(flatten)]
+    formatted_time: Option<FormattedTime>,
     azimuth: f64,
     elevation: f64,
     range: f64,
     #[serde(rename = "rangeRate")]
     range_rate: f64,
     altitude: f64,
+    /// Azimuth rate in deg/s
+    #[serde(rename = "azimuthRate")]
+    azimuth_rate: f64,
+    /// Elevation rate in deg/s
+    #[serde(rename = "elevationRate")]
+    elevation_rate: f64,
+    #[serde(flatten)]
+    julian: Option<JulianDates>,
+    #[serde(flatten)]
+    uncertainty: Option<PointUncertainty>,
+    #[serde(flatten)]
+    optical: Option<Optical>,
+}
+
+/// Point time as Julian dates, for tools that work in day numbers.
+#[derive(Debug, Serialize, Clone, Copy)]
+struct JulianDates {
+    /// Julian date (UTC)
+    jd: f64,
+    /// Modified Julian date (UTC)
+    mjd: f64,
+}
+
+/// Illumination conditions for optical test cases.
+#[derive(Debug, Serialize, Clone, Copy)]
+struct Optical {
+    /// Satellite is outside Earth's shadow
+    sunlit: bool,
+    /// Sun is below civil twilight at the observer
+    #[serde(rename = "observerDark")]
+    observer_dark: bool,
+    /// Angle between the satellite and the Sun as seen by the observer (degrees)
+    #[serde(rename = "sunSeparation")]
+    sun_separation: f64,
+    /// Angle between the satellite and the Moon as seen by the observer (degrees)
+    #[serde(rename = "moonSeparation")]
+    moon_separation: f64,
+    /// A bright, risen Moon is close enough to wash out the satellite
+    #[serde(rename = "moonlightInterference")]
+    moonlight_interference: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -84,6 +187,21 @@ struct Metadata {
     #[serde(rename = "libraryVersion")]
     library_version: String,
     platform: String,
+    #[serde(rename = "earthModel")]
+    earth_model: EarthModel,
+    #[serde(rename = "timeFormat")]
+    time_format: TimeFormat,
+    #[serde(rename = "eopApplied")]
+    eop_applied: bool,
+    #[serde(rename = "eopSource", skip_serializing_if = "Option::is_none")]
+    eop_source: Option<String>,
+    /// `--uncertainty` growth rate (km/day), when uncertainties are output
+    #[serde(rename = "alongTrackGrowth", skip_serializing_if = "Option::is_none")]
+    along_track_growth: Option<f64>,
+    /// Peak resident set size of the process while calculating (bytes),
+    /// where `/proc` is available
+    #[serde(rename = "peakRss", skip_serializing_if = "Option::is_none")]
+    peak_rss: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -94,61 +212,204 @@ struct Position {
     range: f64,
     range_rate: f64,
     altitude: f64,
+    /// Look-angle rates in deg/s
+    azimuth_rate: f64,
+    elevation_rate: f64,
+    /// False when the Earth blocks an inter-satellite line of sight
+    line_of_sight: bool,
+    uncertainty: Option<PointUncertainty>,
+    optical: Option<Optical>,
+}
+
+/// What the satellite is observed from.
+enum ObserverKind<'a> {
+    Ground(GroundSite<'a>),
+    Satellite(Box<Satellite>),
+}
+
+/// A fixed or moving observer on the ground.
+enum GroundSite<'a> {
+    Fixed(&'a Observer),
+    Moving(Trajectory),
+}
+
+impl GroundSite<'_> {
+    fn observer_at(&self, time: DateTime<Utc>) -> Observer {
+        match self {
+            GroundSite::Fixed(observer) => (*observer).clone(),
+            GroundSite::Moving(trajectory) => trajectory.at(time),
+        }
+    }
+}
+
+/// A TLE together with its propagator constants.
+struct Satellite {
+    elements: Elements,
+    constants: Constants,
+}
+
+impl Satellite {
+    fn from_tle(data: &SatelliteData, earth_model: EarthModel) -> Result<Self> {
+        anyhow::ensure!(data.tle.len() == 3, "TLE must have a name line and two element lines");
+        let elements = Elements::from_tle(None, data.tle[1].as_bytes(), data.tle[2].as_bytes())?;
+
+        let constants = match earth_model {
+            EarthModel::Wgs72 => Constants::from_elements_afspc_compatibility_mode(&elements)?,
+            EarthModel::Wgs84 => Constants::from_elements(&elements)?,
+        };
+
+        Ok(Self { elements, constants })
+    }
+
+    fn epoch(&self) -> DateTime<Utc> {
+        self.elements.datetime.and_utc()
+    }
+
+    /// TEME position (km) and velocity (km/s) at `time`.
+    fn propagate(&self, time: DateTime<Utc>) -> Result<Prediction> {
+        let time_diff_seconds = (time.timestamp() - self.epoch().timestamp()) as f64;
+        Ok(self.constants.propagate(MinutesSinceEpoch(time_diff_seconds / 60.0))?)
+    }
 }
 
 struct VisibilityCalculator {
     version: String,
+    eop: Option<EopTable>,
+    /// Model used for test cases that don't specify one
+    earth_model: EarthModel,
+    /// Add `jd`/`mjd` fields to every point
+    julian_dates: bool,
+    /// Format for test cases that don't specify one
+    time_format: TimeFormat,
+    /// Along-track error model for look-angle uncertainties, if output
+    uncertainty: Option<uncertainty::Model>,
+    /// Leave out per-case progress and EOP coverage warnings, which would
+    /// repeat for every satellite of a `--catalog` run
+    quiet: bool,
 }
 
 impl VisibilityCalculator {
-    fn new() -> Self {
+    fn new(eop: Option<EopTable>, options: &Options) -> Self {
         Self {
-            version: "1.0.0".to_string(),
+            version: VERSION.to_string(),
+            eop,
+            earth_model: options.earth_model,
+            julian_dates: options.julian_dates,
+            time_format: options.time_format,
+            uncertainty: options.uncertainty,
+            quiet: options.catalog.is_some(),
         }
     }
 
     fn calculate(&self, test_case: &TestCase) -> Result<TestResult> {
-        let start_time = std::time::Instant::now();
+        rss::reset_peak();
+        let mut result = self.calculate_satellite(test_case, &test_case.satellite)?;
+        result.metadata.peak_rss = rss::peak();
+        Ok(result)
+    }
 
-        // Parse TLE
-        let elements = Elements::from_tle(
-            None,
-            test_case.satellite.tle[1].as_bytes(),
-            test_case.satellite.tle[2].as_bytes(),
-        )?;
+    /// Calculate `test_case` for `satellite` in place of its own.
+    fn calculate_satellite(&self, test_case: &TestCase, satellite: &SatelliteData) -> Result<TestResult> {
+        let start_time = std::time::Instant::now();
 
-        let constants = Constants::from_elements_afspc_compatibility_mode(&elements)?;
+        // Parse TLEs
+        let earth_model = test_case.earth_model.unwrap_or(self.earth_model);
+        let time_format = test_case.time_format.unwrap_or(self.time_format);
+        let satellite = Satellite::from_tle(satellite, earth_model)?;
+
+        let observer = match (
+            &test_case.observer,
+            &test_case.observer_satellite,
+            &test_case.observer_trajectory,
+        ) {
+            (Some(observer), None, None) => ObserverKind::Ground(GroundSite::Fixed(observer)),
+            (None, Some(data), None) => {
+                anyhow::ensure!(!test_case.optical, "optical is not supported with observerSatellite");
+                ObserverKind::Satellite(Box::new(Satellite::from_tle(data, earth_model)?))
+            }
+            (None, None, Some(waypoints)) => ObserverKind::Ground(GroundSite::Moving(Trajectory::new(waypoints)?)),
+            _ => anyhow::bail!(
+                "Test case needs exactly one of observer, observerSatellite, and observerTrajectory"
+            ),
+        };
 
         // Parse time window
         let start = DateTime::parse_from_rfc3339(&test_case.time_window.start)?
             .with_timezone(&Utc);
         let end = DateTime::parse_from_rfc3339(&test_case.time_window.end)?
             .with_timezone(&Utc);
+        anyhow::ensure!(test_case.time_window.step > 0, "timeWindow.step must be positive");
         let step = Duration::seconds(test_case.time_window.step);
 
-        // Generate times
-        let times = self.generate_times(start, end, step);
+        if let ObserverKind::Ground(GroundSite::Moving(trajectory)) = &observer {
+            anyhow::ensure!(
+                trajectory.covers(start, end),
+                "observerTrajectory must span the whole time window"
+            );
+        }
 
-        // Calculate observer ECEF position
-        let observer_ecef = self.geodetic_to_ecef(
-            test_case.observer.latitude,
-            test_case.observer.longitude,
-            test_case.observer.altitude / 1000.0,
-        );
+        // Earth orientation corrections apply only if the table spans the whole window
+        let eop = self.eop.as_ref().filter(|table| {
+            let covered = table.covers(mjd(start), mjd(end));
+            if !covered && !self.quiet {
+                eprintln!(
+                    "  Warning: {} does not cover the time window; EOP corrections disabled",
+                    table.source().display()
+                );
+            }
+            covered
+        });
+
+        let total_steps = ((end - start).num_seconds() / step.num_seconds()).max(0) as usize + 1;
+        let mut progress = (total_steps > PROGRESS_STEPS && !self.quiet).then(|| Progress::new(total_steps));
+
+        // Stream the time steps one at a time, keeping only in-window points,
+        // so memory grows with the points reported rather than the length of
+        // the time window. A ground observer's range rate is the change in
+        // range to the next step, so each position is tracked once the next
+        // is known; the last one's stays 0.
+        let mut tracker = WindowTracker::default();
+        let mut pending: Option<Position> = None;
+        let mut time = start;
+        while time <= end {
+            let position = match &observer {
+                ObserverKind::Satellite(observer_satellite) => {
+                    self.isl_position(&satellite, observer_satellite, time, eop, earth_model)?
+                }
+                ObserverKind::Ground(site) => {
+                    self.ground_position(&satellite, time, eop, site, test_case.optical, earth_model)?
+                }
+            };
+            if let Some(mut previous) = pending.take() {
+                if let ObserverKind::Ground(_) = observer {
+                    previous.range_rate = (position.range - previous.range) / step.num_seconds() as f64;
+                }
+                self.track_position(&mut tracker, &previous, test_case.min_elevation, time_format);
+            }
+            pending = Some(position);
 
-        // Calculate positions
-        let positions = self.calculate_positions(
-            &constants,
-            &elements,
-            &times,
-            &observer_ecef,
-            test_case.observer.latitude,
-            test_case.observer.longitude,
-        )?;
+            if let Some(progress) = progress.as_mut() {
+                progress.advance(1);
+            }
+            time += step;
+        }
+        if let Some(last) = pending {
+            self.track_position(&mut tracker, &last, test_case.min_elevation, time_format);
+        }
 
-        // Find visibility windows
-        let visibility_windows =
-            self.find_visibility_windows(&positions, test_case.min_elevation);
+        let mut visibility_windows = self.finish_windows(tracker);
+        if self.uncertainty.is_some() {
+            let last_sample = start + step * (total_steps - 1) as i32;
+            set_boundary_uncertainties(&mut visibility_windows, start, last_sample);
+        }
+        let warnings = warnings::collect(&warnings::Context {
+            tle_epoch: satellite.elements.datetime.and_utc(),
+            mean_motion: satellite.elements.mean_motion,
+            start,
+            end,
+            step,
+            windows: &visibility_windows,
+        });
 
         let execution_time = start_time.elapsed().as_secs_f64();
 
@@ -159,38 +420,27 @@ impl VisibilityCalculator {
             visibility_windows,
             execution_time: (execution_time * 1000.0).round() / 1000.0,
             timestamp: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            warnings,
             metadata: Metadata {
-                library_name: "sgp4".to_string(),
-                library_version: "2.0".to_string(),
+                library_name: LIBRARY_NAME.to_string(),
+                library_version: LIBRARY_VERSION.to_string(),
                 platform: "Rust".to_string(),
+                earth_model,
+                time_format,
+                eop_applied: eop.is_some(),
+                eop_source: eop.map(|table| table.source().display().to_string()),
+                along_track_growth: self.uncertainty.map(|model| model.growth_km_per_day),
+                peak_rss: None,
             },
         })
     }
 
-    fn generate_times(
-        &self,
-        start: DateTime<Utc>,
-        end: DateTime<Utc>,
-        step: Duration,
-    ) -> Vec<DateTime<Utc>> {
-        let mut times = Vec::new();
-        let mut current = start;
-
-        while current <= end {
-            times.push(current);
-            current = current + step;
-        }
-
-        times
-    }
-
-    fn geodetic_to_ecef(&self, lat_deg: f64, lon_deg: f64, alt_km: f64) -> [f64; 3] {
+    fn geodetic_to_ecef(&self, lat_deg: f64, lon_deg: f64, alt_km: f64, earth_model: EarthModel) -> [f64; 3] {
         let lat = lat_deg * DEG2RAD;
         let lon = lon_deg * DEG2RAD;
 
-        let f = 1.0 / 298.257223563;
-        let e_sq = 2.0 * f - f * f;
-        let n = EARTH_RADIUS_KM / (1.0 - e_sq * lat.sin().powi(2)).sqrt();
+        let e_sq = earth_model.eccentricity_sq();
+        let n = earth_model.radius_km() / (1.0 - e_sq * lat.sin().powi(2)).sqrt();
 
         let x = (n + alt_km) * lat.cos() * lon.cos();
         let y = (n + alt_km) * lat.cos() * lon.sin();
@@ -199,103 +449,231 @@ impl VisibilityCalculator {
         [x, y, z]
     }
 
-    fn calculate_positions(
+    /// Geodetic altitude (km) of an ECEF position, using Bowring's method.
+    /// Three iterations, each refining the parametric latitude, reach
+    /// sub-millimetre accuracy from the surface out past GEO.
+    fn ecef_to_geodetic_altitude(&self, ecef: &[f64; 3], earth_model: EarthModel) -> f64 {
+        let a = earth_model.radius_km();
+        let f = earth_model.flattening();
+        let b = a * (1.0 - f);
+        let e_sq = earth_model.eccentricity_sq();
+        let ep_sq = e_sq / (1.0 - e_sq);
+
+        let p = (ecef[0].powi(2) + ecef[1].powi(2)).sqrt();
+        let z = ecef[2];
+
+        let mut beta = (a * z).atan2(b * p);
+        let mut lat = 0.0;
+        for _ in 0..3 {
+            let (sin_beta, cos_beta) = beta.sin_cos();
+            lat = (z + ep_sq * b * sin_beta.powi(3)).atan2(p - e_sq * a * cos_beta.powi(3));
+            beta = ((1.0 - f) * lat.sin()).atan2(lat.cos());
+        }
+
+        // Well-conditioned at every latitude, including the poles
+        let (sin_lat, cos_lat) = lat.sin_cos();
+        p * cos_lat + z * sin_lat - a * (1.0 - e_sq * sin_lat * sin_lat).sqrt()
+    }
+
+    /// Position of `satellite` as seen from `site` at `time`. Its range
+    /// rate is left at 0 for the caller to take from the next time step.
+    fn ground_position(
         &self,
-        constants: &Constants,
-        elements: &Elements,
-        times: &[DateTime<Utc>],
-        observer_ecef: &[f64; 3],
-        observer_lat: f64,
-        observer_lon: f64,
-    ) -> Result<Vec<Position>> {
-        let mut positions = Vec::new();
-
-        // Get TLE epoch as DateTime
-        let epoch = elements.datetime;
-
-        for (i, time) in times.iter().enumerate() {
-            // Calculate minutes since TLE epoch
-            let time_diff_seconds = (time.timestamp() - epoch.timestamp()) as f64;
-            let minutes_since_epoch = time_diff_seconds / 60.0;
-
-            // Propagate satellite
-            let prediction = constants.propagate(sgp4::MinutesSinceEpoch(minutes_since_epoch))?;
-
-            // Get position in TEME frame (km)
-            let sat_teme = [
-                prediction.position[0],
-                prediction.position[1],
-                prediction.position[2],
-            ];
+        satellite: &Satellite,
+        time: DateTime<Utc>,
+        eop: Option<&EopTable>,
+        site: &GroundSite,
+        optical: bool,
+        earth_model: EarthModel,
+    ) -> Result<Position> {
+        // Calculate observer ECEF position
+        let observer_ecef_at = |observer: &Observer| {
+            self.geodetic_to_ecef(
+                observer.latitude,
+                observer.longitude,
+                observer.altitude / 1000.0,
+                earth_model,
+            )
+        };
+
+        let observer = site.observer_at(time);
+        let observer_ecef = observer_ecef_at(&observer);
+
+        // Propagate satellite
+        let prediction = satellite.propagate(time)?;
+
+        // Get position in TEME frame (km)
+        let sat_teme = [
+            prediction.position[0],
+            prediction.position[1],
+            prediction.position[2],
+        ];
+
+        // Convert TEME to ECEF
+        let sat_ecef = self.teme_to_ecef(&sat_teme, time, eop);
+
+        // Calculate range vector
+        let range_vec = [
+            sat_ecef[0] - observer_ecef[0],
+            sat_ecef[1] - observer_ecef[1],
+            sat_ecef[2] - observer_ecef[2],
+        ];
+
+        let range = (range_vec[0].powi(2) + range_vec[1].powi(2) + range_vec[2].powi(2)).sqrt();
+
+        // Calculate look angles
+        let (azimuth, elevation) = self.ecef_to_azel(&range_vec, observer.latitude, observer.longitude);
+
+        // Look-angle rates from the relative Earth-fixed velocity. A moving
+        // observer's velocity comes from its trajectory over one second.
+        let sat_velocity_ecef = self.teme_velocity_to_ecef(&prediction.velocity, &sat_ecef, time, eop);
+        let observer_next_ecef = observer_ecef_at(&site.observer_at(time + Duration::seconds(1)));
+        let range_velocity = [
+            sat_velocity_ecef[0] - (observer_next_ecef[0] - observer_ecef[0]),
+            sat_velocity_ecef[1] - (observer_next_ecef[1] - observer_ecef[1]),
+            sat_velocity_ecef[2] - (observer_next_ecef[2] - observer_ecef[2]),
+        ];
+        let (azimuth_rate, elevation_rate) =
+            self.azel_rates(&range_vec, &range_velocity, observer.latitude, observer.longitude);
+
+        // Height above the ellipsoid, not above a sphere of equatorial radius
+        let sat_altitude = self.ecef_to_geodetic_altitude(&sat_ecef, earth_model);
+
+        // Look-angle change per km along the track: the rates above, for
+        // a unit displacement along the velocity instead of the velocity
+        let uncertainty = self.uncertainty.map(|model| {
+            let speed = norm(&prediction.velocity);
+            let along_track = prediction.velocity.map(|v| v / speed);
+            let along_track_ecef = self.teme_to_ecef(&along_track, time, eop);
+            let (azimuth, elevation) =
+                self.azel_rates(&range_vec, &along_track_ecef, observer.latitude, observer.longitude);
+            model.at(satellite.epoch(), time, Sensitivity { azimuth, elevation, speed })
+        });
+
+        let optical = optical.then(|| {
+            self.optical_conditions(&sat_teme, &range_vec, time, eop, &observer_ecef, &observer)
+        });
+
+        Ok(Position {
+            time,
+            elevation,
+            azimuth,
+            range,
+            range_rate: 0.0,
+            altitude: sat_altitude,
+            azimuth_rate,
+            elevation_rate,
+            line_of_sight: true,
+            uncertainty,
+            optical,
+        })
+    }
 
-            // Convert TEME to ECEF
-            let gmst = self.gmst(*time);
-            let sat_ecef = self.teme_to_ecef(&sat_teme, gmst);
+    /// Position of `target` as seen from `observer` at `time` in
+    /// inter-satellite link cases. Geometry stays in TEME; look angles are
+    /// relative to the observer's local horizontal, and the Earth may block
+    /// the line of sight.
+    fn isl_position(
+        &self,
+        target: &Satellite,
+        observer: &Satellite,
+        time: DateTime<Utc>,
+        eop: Option<&EopTable>,
+        earth_model: EarthModel,
+    ) -> Result<Position> {
+        let target_state = target.propagate(time)?;
+        let observer_state = observer.propagate(time)?;
+
+        let range_vec = [
+            target_state.position[0] - observer_state.position[0],
+            target_state.position[1] - observer_state.position[1],
+            target_state.position[2] - observer_state.position[2],
+        ];
+        let relative_velocity = [
+            target_state.velocity[0] - observer_state.velocity[0],
+            target_state.velocity[1] - observer_state.velocity[1],
+            target_state.velocity[2] - observer_state.velocity[2],
+        ];
+
+        let range = (range_vec[0].powi(2) + range_vec[1].powi(2) + range_vec[2].powi(2)).sqrt();
+        let (azimuth, elevation) = isl::local_azel(&observer_state.position, &range_vec);
+        let (azimuth_rate, elevation_rate) = isl::local_azel_rates(
+            &observer_state.position,
+            &observer_state.velocity,
+            &range_vec,
+            &relative_velocity,
+        );
+
+        let target_ecef = self.teme_to_ecef(&target_state.position, time, eop);
+
+        // Only the target's along-track error is modelled
+        let uncertainty = self.uncertainty.map(|model| {
+            let speed = norm(&target_state.velocity);
+            let along_track = target_state.velocity.map(|v| v / speed);
+            let (azimuth, elevation) =
+                isl::local_azel_rates(&observer_state.position, &[0.0; 3], &range_vec, &along_track);
+            model.at(target.epoch(), time, Sensitivity { azimuth, elevation, speed })
+        });
+
+        Ok(Position {
+            time,
+            elevation,
+            azimuth,
+            range,
+            range_rate: isl::range_rate(&range_vec, &relative_velocity),
+            altitude: self.ecef_to_geodetic_altitude(&target_ecef, earth_model),
+            azimuth_rate,
+            elevation_rate,
+            line_of_sight: isl::line_of_sight_clear(&observer_state.position, &target_state.position, earth_model),
+            uncertainty,
+            optical: None,
+        })
+    }
 
-            // Calculate range vector
+    fn optical_conditions(
+        &self,
+        sat_teme: &[f64; 3],
+        range_vec: &[f64; 3],
+        time: DateTime<Utc>,
+        eop: Option<&EopTable>,
+        observer_ecef: &[f64; 3],
+        observer: &Observer,
+    ) -> Optical {
+        let jd = mjd(time) + MJD_TO_JD;
+        let topocentric = |teme: &[f64; 3]| {
+            let ecef = self.teme_to_ecef(teme, time, eop);
             let range_vec = [
-                sat_ecef[0] - observer_ecef[0],
-                sat_ecef[1] - observer_ecef[1],
-                sat_ecef[2] - observer_ecef[2],
+                ecef[0] - observer_ecef[0],
+                ecef[1] - observer_ecef[1],
+                ecef[2] - observer_ecef[2],
             ];
-
-            let range = (range_vec[0].powi(2) + range_vec[1].powi(2) + range_vec[2].powi(2)).sqrt();
-
-            // Calculate look angles
-            let (azimuth, elevation) = self.ecef_to_azel(&range_vec, observer_lat, observer_lon);
-
-            // Calculate range rate
-            let range_rate = if i < times.len() - 1 {
-                let next_time = times[i + 1];
-                let next_time_diff_seconds = (next_time.timestamp() - epoch.timestamp()) as f64;
-                let next_minutes_since_epoch = next_time_diff_seconds / 60.0;
-                let next_prediction = constants.propagate(sgp4::MinutesSinceEpoch(next_minutes_since_epoch))?;
-
-                let next_sat_teme = [
-                    next_prediction.position[0],
-                    next_prediction.position[1],
-                    next_prediction.position[2],
-                ];
-
-                let next_gmst = self.gmst(next_time);
-                let next_sat_ecef = self.teme_to_ecef(&next_sat_teme, next_gmst);
-
-                let next_range = ((next_sat_ecef[0] - observer_ecef[0]).powi(2)
-                    + (next_sat_ecef[1] - observer_ecef[1]).powi(2)
-                    + (next_sat_ecef[2] - observer_ecef[2]).powi(2))
-                    .sqrt();
-
-                let time_diff = (next_time.timestamp() - time.timestamp()) as f64;
-                if time_diff > 0.0 {
-                    (next_range - range) / time_diff
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            };
-
-            // Calculate satellite altitude
-            let sat_altitude =
-                (sat_ecef[0].powi(2) + sat_ecef[1].powi(2) + sat_ecef[2].powi(2)).sqrt()
-                    - EARTH_RADIUS_KM;
-
-            positions.push(Position {
-                time: *time,
-                elevation,
-                azimuth,
-                range,
-                range_rate,
-                altitude: sat_altitude,
-            });
+            let (_, elevation) = self.ecef_to_azel(&range_vec, observer.latitude, observer.longitude);
+            (range_vec, elevation)
+        };
+
+        let sun_teme = sun::sun_position(jd).eci();
+        let (sun_range_vec, sun_elevation) = topocentric(&sun_teme);
+
+        // The Moon is close enough that topocentric parallax (~1°) matters
+        let moon_teme = moon::moon_position(jd).eci();
+        let (moon_range_vec, moon_elevation) = topocentric(&moon_teme);
+        let moon_separation = sun::separation_deg(range_vec, &moon_range_vec);
+
+        Optical {
+            sunlit: sun::is_sunlit(sat_teme, &sun_teme),
+            observer_dark: sun_elevation < sun::TWILIGHT_ELEVATION_DEG,
+            sun_separation: sun::separation_deg(range_vec, &sun_range_vec),
+            moon_separation,
+            moonlight_interference: moon_elevation > 0.0
+                && moon::illuminated_fraction(&moon_teme, &sun_teme) >= moon::INTERFERENCE_MIN_ILLUMINATION
+                && moon_separation < moon::INTERFERENCE_MAX_SEPARATION_DEG,
         }
-
-        Ok(positions)
     }
 
-    fn gmst(&self, time: DateTime<Utc>) -> f64 {
-        let jd = 2440587.5 + (time.timestamp() as f64 / 86400.0);
-        let fr = (time.timestamp() % 86400) as f64 / 86400.0;
+    /// Greenwich mean sidereal time for `ut1_seconds` since the Unix epoch.
+    fn gmst(&self, ut1_seconds: f64) -> f64 {
+        let jd = 2440587.5 + (ut1_seconds / 86400.0);
+        let fr = ut1_seconds.rem_euclid(86400.0) / 86400.0;
 
         let t = (jd - 2451545.0 + fr) / 36525.0;
 
@@ -306,28 +684,51 @@ impl VisibilityCalculator {
 
         let gmst_0h = gmst_0h + 86400.0 * 1.00273790935 * fr;
 
-        let gmst_rad = (gmst_0h % 86400.0) * (2.0 * PI / 86400.0);
-
-        gmst_rad
+        (gmst_0h % 86400.0) * (2.0 * PI / 86400.0)
     }
 
-    fn teme_to_ecef(&self, teme_pos: &[f64; 3], gmst: f64) -> [f64; 3] {
+    /// Rotate TEME to the pseudo Earth-fixed frame, then apply polar motion
+    /// to reach ITRF when EOP data is available.
+    fn teme_to_ecef(&self, teme_pos: &[f64; 3], time: DateTime<Utc>, eop: Option<&EopTable>) -> [f64; 3] {
+        let eop = eop.and_then(|table| table.lookup(mjd(time))).unwrap_or_default();
+        let EopValues { dut1, xp, yp } = eop;
+
+        let gmst = self.gmst(time.timestamp() as f64 + dut1);
         let cos_gmst = gmst.cos();
         let sin_gmst = gmst.sin();
 
-        let x_ecef = cos_gmst * teme_pos[0] + sin_gmst * teme_pos[1];
-        let y_ecef = -sin_gmst * teme_pos[0] + cos_gmst * teme_pos[1];
-        let z_ecef = teme_pos[2];
+        let x_pef = cos_gmst * teme_pos[0] + sin_gmst * teme_pos[1];
+        let y_pef = -sin_gmst * teme_pos[0] + cos_gmst * teme_pos[1];
+        let z_pef = teme_pos[2];
 
-        [x_ecef, y_ecef, z_ecef]
+        // Small-angle polar motion rotation (identity when xp = yp = 0)
+        [
+            x_pef + xp * z_pef,
+            y_pef - yp * z_pef,
+            z_pef - xp * x_pef + yp * y_pef,
+        ]
     }
 
-    fn ecef_to_azel(
+    /// Earth-fixed velocity of a TEME velocity vector at ECEF position `ecef`,
+    /// removing the frame's rotation.
+    fn teme_velocity_to_ecef(
         &self,
-        range_vec: &[f64; 3],
-        observer_lat_deg: f64,
-        observer_lon_deg: f64,
-    ) -> (f64, f64) {
+        teme_vel: &[f64; 3],
+        ecef: &[f64; 3],
+        time: DateTime<Utc>,
+        eop: Option<&EopTable>,
+    ) -> [f64; 3] {
+        // The frame rotation is linear, so it applies to velocities unchanged
+        let v = self.teme_to_ecef(teme_vel, time, eop);
+        [
+            v[0] + EARTH_ROTATION_RATE * ecef[1],
+            v[1] - EARTH_ROTATION_RATE * ecef[0],
+            v[2],
+        ]
+    }
+
+    /// Rotate an ECEF vector into the observer's topocentric SEZ frame.
+    fn ecef_to_sez(&self, vec: &[f64; 3], observer_lat_deg: f64, observer_lon_deg: f64) -> [f64; 3] {
         let lat = observer_lat_deg * DEG2RAD;
         let lon = observer_lon_deg * DEG2RAD;
 
@@ -336,15 +737,23 @@ impl VisibilityCalculator {
         let sin_lon = lon.sin();
         let cos_lon = lon.cos();
 
-        // Transform to topocentric SEZ frame
-        let south =
-            sin_lat * cos_lon * range_vec[0] + sin_lat * sin_lon * range_vec[1] - cos_lat * range_vec[2];
+        let south = sin_lat * cos_lon * vec[0] + sin_lat * sin_lon * vec[1] - cos_lat * vec[2];
+
+        let east = -sin_lon * vec[0] + cos_lon * vec[1];
 
-        let east = -sin_lon * range_vec[0] + cos_lon * range_vec[1];
+        let zenith = cos_lat * cos_lon * vec[0] + cos_lat * sin_lon * vec[1] + sin_lat * vec[2];
 
-        let zenith = cos_lat * cos_lon * range_vec[0]
-            + cos_lat * sin_lon * range_vec[1]
-            + sin_lat * range_vec[2];
+        [south, east, zenith]
+    }
+
+    fn ecef_to_azel(
+        &self,
+        range_vec: &[f64; 3],
+        observer_lat_deg: f64,
+        observer_lon_deg: f64,
+    ) -> (f64, f64) {
+        // Transform to topocentric SEZ frame
+        let [south, east, zenith] = self.ecef_to_sez(range_vec, observer_lat_deg, observer_lon_deg);
 
         // Calculate azimuth
         let mut azimuth = east.atan2(-south) * RAD2DEG;
@@ -359,110 +768,484 @@ impl VisibilityCalculator {
         (azimuth, elevation)
     }
 
-    fn find_visibility_windows(
+    /// Azimuth and elevation rates (deg/s) from the topocentric range vector
+    /// and its rate of change, both ECEF.
+    fn azel_rates(
         &self,
-        positions: &[Position],
-        min_elevation: f64,
-    ) -> Vec<VisibilityWindow> {
-        let mut windows = Vec::new();
-        let mut in_window = false;
-        let mut window_start: Option<DateTime<Utc>> = None;
-        let mut window_positions = Vec::new();
-        let mut window_max_elevation = -90.0;
-        let mut window_max_elevation_time: Option<DateTime<Utc>> = None;
-
-        for (i, pos) in positions.iter().enumerate() {
-            if pos.elevation >= min_elevation {
-                if !in_window {
-                    in_window = true;
-                    window_start = Some(pos.time);
-                    window_positions.clear();
-                    window_max_elevation = pos.elevation;
-                    window_max_elevation_time = Some(pos.time);
-                }
+        range_vec: &[f64; 3],
+        range_velocity: &[f64; 3],
+        observer_lat_deg: f64,
+        observer_lon_deg: f64,
+    ) -> (f64, f64) {
+        let [south, east, zenith] = self.ecef_to_sez(range_vec, observer_lat_deg, observer_lon_deg);
+        let [south_dot, east_dot, zenith_dot] = self.ecef_to_sez(range_velocity, observer_lat_deg, observer_lon_deg);
 
-                if pos.elevation > window_max_elevation {
-                    window_max_elevation = pos.elevation;
-                    window_max_elevation_time = Some(pos.time);
-                }
+        let horizontal_sq = south * south + east * east;
+        let horizontal = horizontal_sq.sqrt();
+        let range_sq = horizontal_sq + zenith * zenith;
+
+        // Derivatives of atan2(east, -south) and atan2(zenith, horizontal)
+        let azimuth_rate = (east_dot * -south + east * south_dot) / horizontal_sq;
+        let horizontal_dot = (south * south_dot + east * east_dot) / horizontal;
+        let elevation_rate = (zenith_dot * horizontal - zenith * horizontal_dot) / range_sq;
+
+        (azimuth_rate * RAD2DEG, elevation_rate * RAD2DEG)
+    }
 
-                window_positions.push(Point {
-                    time: pos.time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                    azimuth: (pos.azimuth * 100.0).round() / 100.0,
-                    elevation: (pos.elevation * 100.0).round() / 100.0,
-                    range: (pos.range * 100.0).round() / 100.0,
-                    range_rate: (pos.range_rate * 1000.0).round() / 1000.0,
-                    altitude: (pos.altitude * 100.0).round() / 100.0,
-                });
-            } else if in_window {
-                // End of window
-                let window_end = positions[i - 1].time;
-                let duration = (window_end - window_start.unwrap()).num_seconds() as f64;
-
-                windows.push(VisibilityWindow {
-                    start: window_start.unwrap().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                    end: window_end.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                    max_elevation: (window_max_elevation * 100.0).round() / 100.0,
-                    max_elevation_time: window_max_elevation_time
-                        .unwrap()
-                        .format("%Y-%m-%dT%H:%M:%SZ")
-                        .to_string(),
-                    duration,
-                    points: window_positions.clone(),
-                });
-
-                in_window = false;
-                window_positions.clear();
+    /// Feed the next position into window detection. Windows span calls;
+    /// `finish_windows` closes any still open.
+    fn track_position(&self, tracker: &mut WindowTracker, pos: &Position, min_elevation: f64, time_format: TimeFormat) {
+        if pos.line_of_sight && pos.elevation >= min_elevation {
+            if tracker.window_start.is_none() {
+                tracker.window_start = Some(pos.time);
+                tracker.window_positions.clear();
+                tracker.window_max_elevation = pos.elevation;
+                tracker.window_max_elevation_time = Some(pos.time);
             }
+
+            if pos.elevation > tracker.window_max_elevation {
+                tracker.window_max_elevation = pos.elevation;
+                tracker.window_max_elevation_time = Some(pos.time);
+            }
+
+            tracker.window_positions.push(Point {
+                time: pos.time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                formatted_time: time_format.format(pos.time),
+                // Rounding can carry 359.996° up to 360°
+                azimuth: ((pos.azimuth * 100.0).round() / 100.0) % 360.0,
+                elevation: (pos.elevation * 100.0).round() / 100.0,
+                range: (pos.range * 100.0).round() / 100.0,
+                range_rate: (pos.range_rate * 1000.0).round() / 1000.0,
+                altitude: (pos.altitude * 100.0).round() / 100.0,
+                azimuth_rate: (pos.azimuth_rate * 10000.0).round() / 10000.0,
+                elevation_rate: (pos.elevation_rate * 10000.0).round() / 10000.0,
+                julian: self.julian_dates.then(|| JulianDates {
+                    jd: mjd(pos.time) + MJD_TO_JD,
+                    mjd: mjd(pos.time),
+                }),
+                uncertainty: pos.uncertainty.map(PointUncertainty::rounded),
+                optical: pos.optical.map(|o| Optical {
+                    sun_separation: (o.sun_separation * 100.0).round() / 100.0,
+                    moon_separation: (o.moon_separation * 100.0).round() / 100.0,
+                    ..o
+                }),
+            });
+        } else if let Some(window_end) = tracker.last_time
+            && tracker.window_start.is_some()
+        {
+            tracker.close_window(window_end);
         }
 
+        tracker.last_time = Some(pos.time);
+    }
+
+    fn finish_windows(&self, mut tracker: WindowTracker) -> Vec<VisibilityWindow> {
         // Handle window extending to end
-        if in_window && !window_positions.is_empty() {
-            let window_end = positions.last().unwrap().time;
-            let duration = (window_end - window_start.unwrap()).num_seconds() as f64;
-
-            windows.push(VisibilityWindow {
-                start: window_start.unwrap().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                end: window_end.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-                max_elevation: (window_max_elevation * 100.0).round() / 100.0,
-                max_elevation_time: window_max_elevation_time
-                    .unwrap()
-                    .format("%Y-%m-%dT%H:%M:%SZ")
-                    .to_string(),
-                duration,
-                points: window_positions,
-            });
+        if let Some(window_end) = tracker.last_time
+            && tracker.window_start.is_some()
+            && !tracker.window_positions.is_empty()
+        {
+            tracker.close_window(window_end);
         }
 
-        windows
+        tracker.windows
     }
 }
 
-fn main() -> Result<()> {
-    // Configuration
-    let test_data_dir = if Path::new("/test-data/cases").exists() {
-        PathBuf::from("/test-data/cases")
-    } else {
-        PathBuf::from("../../test-data/cases")
-    };
+/// Window detection state carried from one position to the next.
+#[derive(Debug, Default)]
+struct WindowTracker {
+    windows: Vec<VisibilityWindow>,
+    /// Set while the satellite is above the elevation mask
+    window_start: Option<DateTime<Utc>>,
+    window_positions: Vec<Point>,
+    window_max_elevation: f64,
+    window_max_elevation_time: Option<DateTime<Utc>>,
+    /// Time of the most recent position seen
+    last_time: Option<DateTime<Utc>>,
+}
 
-    let results_dir = if Path::new("/results").exists() {
-        PathBuf::from("/results")
+impl WindowTracker {
+    fn close_window(&mut self, window_end: DateTime<Utc>) {
+        let Some(window_start) = self.window_start.take() else {
+            return;
+        };
+        let duration = (window_end - window_start).num_seconds() as f64;
+
+        self.windows.push(VisibilityWindow {
+            start: window_start.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            end: window_end.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            max_elevation: (self.window_max_elevation * 100.0).round() / 100.0,
+            max_elevation_time: self
+                .window_max_elevation_time
+                .unwrap_or(window_start)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+            duration,
+            start_uncertainty: None,
+            end_uncertainty: None,
+            points: std::mem::take(&mut self.window_positions),
+        });
+    }
+}
+
+/// Percent-complete and ETA reporting on stderr for long time windows.
+struct Progress {
+    total: usize,
+    done: usize,
+    started: std::time::Instant,
+    last_report: std::time::Instant,
+}
+
+impl Progress {
+    const INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+    fn new(total: usize) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            total,
+            done: 0,
+            started: now,
+            last_report: now,
+        }
+    }
+
+    fn advance(&mut self, steps: usize) {
+        self.done += steps;
+
+        let finished = self.done >= self.total;
+        if !finished && self.last_report.elapsed() < Self::INTERVAL {
+            return;
+        }
+        self.last_report = std::time::Instant::now();
+
+        let fraction = self.done as f64 / self.total.max(1) as f64;
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let eta = if fraction > 0.0 { elapsed / fraction - elapsed } else { 0.0 };
+
+        eprintln!(
+            "  Progress: {:5.1}% ({}/{} steps), ETA {}m {:02}s",
+            fraction * 100.0,
+            self.done,
+            self.total,
+            (eta / 60.0) as u64,
+            (eta % 60.0) as u64
+        );
+    }
+}
+
+/// Command-line options: `[test-case] [--eop <file>] [--earth-model <model>]
+/// [--time-format <format>] [--julian-dates] [--uncertainty <km/day>]
+/// [--compress] [--catalog <tle-file> [--threads <n>]]`, or `--version`.
+#[derive(Debug, Default)]
+struct Options {
+    /// Print versions as JSON and exit, the orchestrator's self-check
+    version: bool,
+    test_case: Option<String>,
+    eop_file: Option<PathBuf>,
+    earth_model: EarthModel,
+    time_format: TimeFormat,
+    julian_dates: bool,
+    /// Output look-angle and window uncertainties with this along-track growth
+    uncertainty: Option<uncertainty::Model>,
+    /// Write results as `.json.gz`
+    compress: bool,
+    /// TLE file whose satellites replace the test case's
+    catalog: Option<PathBuf>,
+    /// Worker threads for `--catalog` (default: one per CPU)
+    threads: Option<usize>,
+    /// Overrides the default `/test-data/cases` or `../../test-data/cases`
+    test_data_dir: Option<PathBuf>,
+    /// Overrides the default `/results` or `../../results`
+    results_dir: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Options> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--eop" => {
+                options.eop_file = Some(args.next().context("--eop requires a file path")?.into());
+            }
+            "--earth-model" => {
+                options.earth_model = args.next().context("--earth-model requires wgs72 or wgs84")?.parse()?;
+            }
+            "--time-format" => {
+                options.time_format = args.next().context("--time-format requires rfc3339, unix, or gps")?.parse()?;
+            }
+            "--version" => options.version = true,
+            "--julian-dates" => options.julian_dates = true,
+            "--uncertainty" => {
+                options.uncertainty = Some(args.next().context("--uncertainty requires a growth rate in km/day")?.parse()?);
+            }
+            "--compress" => options.compress = true,
+            "--catalog" => {
+                options.catalog = Some(args.next().context("--catalog requires a TLE file")?.into());
+            }
+            "--threads" => {
+                let threads = args.next().context("--threads requires a thread count")?;
+                options.threads = Some(
+                    threads
+                        .parse()
+                        .ok()
+                        .filter(|&n: &usize| n > 0)
+                        .with_context(|| format!("Invalid thread count '{}'", threads))?,
+                );
+            }
+            "--test-data" => {
+                options.test_data_dir = Some(args.next().context("--test-data requires a directory")?.into());
+            }
+            "--results" => {
+                options.results_dir = Some(args.next().context("--results requires a directory")?.into());
+            }
+            _ if arg.starts_with("--") => anyhow::bail!("Unknown option: {}", arg),
+            _ => options.test_case = Some(arg),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Load EOP data from `--eop`, `$EOP_FILE`, or the default mount, falling
+/// back to uncorrected UTC-based rotation when none is usable.
+fn load_eop(options: &Options) -> Option<EopTable> {
+    let path = options
+        .eop_file
+        .clone()
+        .or_else(|| std::env::var_os("EOP_FILE").map(PathBuf::from))
+        .or_else(|| Some(PathBuf::from(eop::DEFAULT_EOP_FILE)).filter(|p| p.exists()))?;
+
+    match EopTable::load(&path) {
+        Ok(table) => Some(table),
+        Err(e) => {
+            eprintln!("Warning: {:#}; continuing without EOP corrections", e);
+            None
+        }
+    }
+}
+
+/// Write result JSON to `path`, gzip-compressed if `compress` is set.
+fn write_result(path: &Path, json: &str, compress: bool) -> Result<()> {
+    if compress {
+        let file = fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(json.as_bytes())?;
+        encoder.finish()?;
     } else {
-        PathBuf::from("../../results")
+        fs::write(path, json)?;
+    }
+    Ok(())
+}
+
+/// `--catalog` output: every satellite of a TLE file over one test case's
+/// observer and time window.
+#[derive(Debug, Serialize)]
+struct CatalogResult {
+    #[serde(rename = "testCase")]
+    test_case: String,
+    implementation: String,
+    version: String,
+    catalog: String,
+    threads: usize,
+    #[serde(rename = "executionTime")]
+    execution_time: f64,
+    #[serde(rename = "satellitesPerSecond")]
+    satellites_per_second: f64,
+    /// Peak resident set size of the whole run (bytes), where `/proc` is
+    /// available
+    #[serde(rename = "peakRss", skip_serializing_if = "Option::is_none")]
+    peak_rss: Option<u64>,
+    timestamp: String,
+    satellites: Vec<CatalogSatellite>,
+}
+
+#[derive(Debug, Serialize)]
+struct CatalogSatellite {
+    name: String,
+    /// Without points, which would dwarf everything else for a large catalog
+    #[serde(rename = "visibilityWindows")]
+    visibility_windows: Vec<VisibilityWindow>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Warning>,
+    /// Why the satellite couldn't be calculated, e.g. a decayed orbit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Calculate every satellite in `catalog` against the test case at
+/// `test_file`, and write one `catalog_<case>_<timestamp>.json` result.
+fn run_catalog(options: &Options, catalog: &Path, test_file: &Path, results_dir: &Path) -> Result<()> {
+    let content = fs::read_to_string(test_file).context(format!("Failed to read {}", test_file.display()))?;
+    let test_case: TestCase = serde_json::from_str(&content).context(format!("Failed to parse {}", test_file.display()))?;
+    let entries = catalog::load(catalog)?;
+    let threads = options
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from));
+
+    println!("Catalog: {} ({} satellite(s))", catalog.display(), entries.len());
+    println!(
+        "Observer and time window: {} ({} to {})",
+        test_case.name, test_case.time_window.start, test_case.time_window.end
+    );
+    println!("Threads: {}", threads);
+    println!();
+
+    let calculator = VisibilityCalculator::new(load_eop(options), options);
+    rss::reset_peak();
+    let start_time = std::time::Instant::now();
+    let satellites = catalog::process(&entries, threads, |entry| {
+        let data = SatelliteData { tle: entry.tle.clone() };
+        match calculator.calculate_satellite(&test_case, &data) {
+            Ok(mut result) => {
+                for window in &mut result.visibility_windows {
+                    window.points.clear();
+                }
+                CatalogSatellite {
+                    name: entry.name.clone(),
+                    visibility_windows: result.visibility_windows,
+                    warnings: result.warnings,
+                    error: None,
+                }
+            }
+            Err(e) => CatalogSatellite {
+                name: entry.name.clone(),
+                visibility_windows: Vec::new(),
+                warnings: Vec::new(),
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    });
+    let execution_time = start_time.elapsed().as_secs_f64();
+
+    // Passes in time order, then satellites that couldn't be calculated
+    let mut passes: Vec<(&VisibilityWindow, &str)> = satellites
+        .iter()
+        .flat_map(|satellite| satellite.visibility_windows.iter().map(|window| (window, satellite.name.as_str())))
+        .collect();
+    passes.sort_by(|a, b| a.0.start.cmp(&b.0.start));
+    for (window, name) in &passes {
+        println!("  {} - {}  {:5.1}°  {}", window.start, window.end, window.max_elevation, name);
+    }
+    for satellite in &satellites {
+        if let Some(error) = &satellite.error {
+            println!("  Error [{}]: {}", satellite.name, error);
+        }
+    }
+    let window_count = passes.len();
+
+    let visible = satellites.iter().filter(|satellite| !satellite.visibility_windows.is_empty()).count();
+    let result = CatalogResult {
+        test_case: test_case.name.clone(),
+        implementation: "rust-sgp4".to_string(),
+        version: VERSION.to_string(),
+        catalog: catalog.display().to_string(),
+        threads,
+        execution_time: (execution_time * 1000.0).round() / 1000.0,
+        satellites_per_second: (entries.len() as f64 / execution_time.max(1e-9)).round(),
+        peak_rss: rss::peak(),
+        timestamp: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        satellites,
     };
 
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let extension = if options.compress { "json.gz" } else { "json" };
+    let output_file = results_dir.join(format!("catalog_{}_{}.{}", test_case.name, timestamp, extension));
+    write_result(&output_file, &serde_json::to_string_pretty(&result)?, options.compress)?;
+
+    println!();
+    println!("✓ Wrote results to {}", output_file.display());
+    println!(
+        "  {} of {} satellite(s) visible, {} window(s)",
+        visible,
+        entries.len(),
+        window_count
+    );
+    println!(
+        "  Execution time: {:.3}s ({} satellites/s on {} thread(s))",
+        result.execution_time, result.satellites_per_second, threads
+    );
+    Ok(())
+}
+
+/// Window start and end uncertainties from their first and last points'
+/// timing. An edge clipped by the first or last sample is exact.
+fn set_boundary_uncertainties(windows: &mut [VisibilityWindow], first_sample: DateTime<Utc>, last_sample: DateTime<Utc>) {
+    let format = |t: DateTime<Utc>| t.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let (first_time, last_time) = (format(first_sample), format(last_sample));
+    let time_uncertainty = |point: Option<&Point>| point.and_then(|p| p.uncertainty).map(|u| u.time);
+    for window in windows {
+        window.start_uncertainty = if window.start == first_time {
+            Some(0.0)
+        } else {
+            time_uncertainty(window.points.first())
+        };
+        window.end_uncertainty = if window.end == last_time {
+            Some(0.0)
+        } else {
+            time_uncertainty(window.points.last())
+        };
+    }
+}
+
+fn norm(v: &[f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+/// Modified Julian Date (UTC) of `time`.
+fn mjd(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + 40587.0
+}
+
+fn main() -> Result<()> {
+    let options = parse_args()?;
+    if options.version {
+        println!(
+            "{}",
+            serde_json::json!({
+                "implementation": "rust-sgp4",
+                "version": VERSION,
+                "libraryName": LIBRARY_NAME,
+                "libraryVersion": LIBRARY_VERSION,
+                "platform": "Rust",
+            })
+        );
+        return Ok(());
+    }
+
+    // Configuration
+    let test_data_dir = options.test_data_dir.clone().unwrap_or_else(|| {
+        if Path::new("/test-data/cases").exists() {
+            PathBuf::from("/test-data/cases")
+        } else {
+            PathBuf::from("../../test-data/cases")
+        }
+    });
+
+    let results_dir = options.results_dir.clone().unwrap_or_else(|| {
+        if Path::new("/results").exists() {
+            PathBuf::from("/results")
+        } else {
+            PathBuf::from("../../results")
+        }
+    });
+
     fs::create_dir_all(&results_dir)?;
 
+    if let Some(catalog) = &options.catalog {
+        let test_case = options.test_case.as_deref().context("--catalog needs a test case for the observer and time window")?;
+        return run_catalog(&options, catalog, &test_data_dir.join(format!("{}.json", test_case)), &results_dir);
+    }
+
     // Get test cases
-    let args: Vec<String> = std::env::args().collect();
-    let test_files: Vec<PathBuf> = if args.len() > 1 {
-        vec![test_data_dir.join(format!("{}.json", args[1]))]
+    let test_files: Vec<PathBuf> = if let Some(test_case) = &options.test_case {
+        vec![test_data_dir.join(format!("{}.json", test_case))]
     } else {
         let mut files: Vec<PathBuf> = fs::read_dir(&test_data_dir)?
             .filter_map(|e| e.ok())
             .map(|e| e.path())
-            .filter(|p| p.extension().map_or(false, |ext| ext == "json"))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
             .collect();
         files.sort();
         files
@@ -473,9 +1256,17 @@ fn main() -> Result<()> {
     println!("Test data directory: {}", test_data_dir.display());
     println!("Results directory: {}", results_dir.display());
     println!("Found {} test case(s)", test_files.len());
+
+    let eop = load_eop(&options);
+    match &eop {
+        Some(table) => println!("EOP data: {}", table.source().display()),
+        None => println!("EOP data: none (ΔUT1 and polar motion not applied)"),
+    }
+    println!("Earth model: {} (unless set by the test case)", options.earth_model);
     println!();
 
-    let calculator = VisibilityCalculator::new();
+    let calculator = VisibilityCalculator::new(eop, &options);
+    let mut failed_validation = Vec::new();
 
     for test_file in &test_files {
         println!("Processing: {}", test_file.file_name().unwrap().to_string_lossy());
@@ -489,16 +1280,163 @@ fn main() -> Result<()> {
         let result = calculator.calculate(&test_case)?;
 
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let output_file = results_dir.join(format!("rust-sgp4_{}_{}.json", result.test_case, timestamp));
-        fs::write(&output_file, serde_json::to_string_pretty(&result)?)?;
+        let extension = if options.compress { "json.gz" } else { "json" };
+        let output_file = results_dir.join(format!("rust-sgp4_{}_{}.{}", result.test_case, timestamp, extension));
+        write_result(&output_file, &serde_json::to_string_pretty(&result)?, options.compress)?;
 
         println!("✓ Wrote results to {}", output_file.display());
         println!("  Execution time: {:.3}s", result.execution_time);
         println!("  Visibility windows: {}", result.visibility_windows.len());
+        for warning in &result.warnings {
+            println!("  Warning [{}]: {}", warning.code, warning.message);
+        }
+
+        if let Some(expected) = &test_case.expected_windows {
+            let mismatches =
+                expectations::check(expected, &result.visibility_windows, test_case.time_window.step)?;
+            if mismatches.is_empty() {
+                println!("  Self-validation: PASS ({} expected window(s))", expected.len());
+            } else {
+                println!("  Self-validation: FAIL");
+                for mismatch in &mismatches {
+                    println!("    - {}", mismatch);
+                }
+                failed_validation.push(result.test_case.clone());
+            }
+        }
         println!();
     }
 
     println!("✓ Successfully processed {} test case(s)", test_files.len());
 
+    if !failed_validation.is_empty() {
+        println!(
+            "✗ {} test case(s) failed self-validation: {}",
+            failed_validation.len(),
+            failed_validation.join(", ")
+        );
+        std::process::exit(1);
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const START: &str = "2024-01-01T12:00:00Z";
+    const END: &str = "2024-01-02T12:00:00Z";
+
+    /// Append the TLE modulo-10 checksum (digits, with '-' counting as 1).
+    fn with_checksum(line: String) -> String {
+        let sum: u32 = line
+            .chars()
+            .map(|c| match c {
+                '-' => 1,
+                _ => c.to_digit(10).unwrap_or(0),
+            })
+            .sum();
+        format!("{}{}", line, sum % 10)
+    }
+
+    /// A near-Earth TLE with its epoch at the start of the time window.
+    fn tle(inclination: f64, raan: f64, eccentricity: f64, perigee: f64, anomaly: f64, mean_motion: f64) -> Vec<String> {
+        vec![
+            "PROPTEST".to_string(),
+            with_checksum("1 99999U 24001A   24001.50000000  .00000000  00000-0  00000-0 0  999".to_string()),
+            with_checksum(format!(
+                "2 99999 {:8.4} {:8.4} {:07.0} {:8.4} {:8.4} {:11.8}    1",
+                inclination,
+                raan,
+                eccentricity * 1e7,
+                perigee,
+                anomaly,
+                mean_motion
+            )),
+        ]
+    }
+
+    fn test_case(tle: Vec<String>, latitude: f64, longitude: f64, altitude: f64, min_elevation: f64, step: i64) -> TestCase {
+        serde_json::from_value(serde_json::json!({
+            "name": "proptest",
+            "satellite": { "tle": tle },
+            "observer": { "latitude": latitude, "longitude": longitude, "altitude": altitude },
+            "timeWindow": { "start": START, "end": END, "step": step },
+            "minElevation": min_elevation,
+        }))
+        .unwrap()
+    }
+
+    fn parse(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn window_invariants(
+            inclination in 0.0..180.0f64,
+            raan in 0.0..360.0f64,
+            eccentricity in 0.0..0.01f64,
+            perigee in 0.0..360.0f64,
+            anomaly in 0.0..360.0f64,
+            mean_motion in 11.0..15.5f64,
+            latitude in -89.0..89.0f64,
+            longitude in -180.0..180.0f64,
+            altitude in 0.0..3000.0f64,
+            min_elevation in 0.0..30.0f64,
+            step in 10..120i64,
+        ) {
+            let calculator = VisibilityCalculator::new(None, &Options::default());
+            let case = test_case(
+                tle(inclination, raan, eccentricity, perigee, anomaly, mean_motion),
+                latitude,
+                longitude,
+                altitude,
+                min_elevation,
+                step,
+            );
+            let result = calculator.calculate(&case).unwrap();
+            let windows = &result.visibility_windows;
+
+            // Sorted and disjoint, with at least one sample between windows
+            for pair in windows.windows(2) {
+                prop_assert!(parse(&pair[0].start) <= parse(&pair[0].end));
+                prop_assert!(parse(&pair[0].end) < parse(&pair[1].start));
+            }
+
+            // Every point is above the mask (points are rounded to 0.01°)
+            // with a normalized azimuth
+            for point in windows.iter().flat_map(|w| &w.points) {
+                prop_assert!(point.elevation >= min_elevation - 0.005, "{} below mask", point.elevation);
+                prop_assert!((0.0..360.0).contains(&point.azimuth), "azimuth {}", point.azimuth);
+            }
+
+            // Each boundary is within one step of a crossing: the sample just
+            // outside the window, if inside the time window, is below the mask
+            let satellite = Satellite::from_tle(&case.satellite, EarthModel::default()).unwrap();
+            let site = GroundSite::Fixed(case.observer.as_ref().unwrap());
+            let step = Duration::seconds(step);
+            let (start, end) = (parse(START), parse(END));
+            let outside: Vec<_> = windows
+                .iter()
+                .flat_map(|w| [parse(&w.start) - step, parse(&w.end) + step])
+                .filter(|t| (start..=end).contains(t))
+                .collect();
+            for time in outside {
+                let position = calculator
+                    .ground_position(&satellite, time, None, &site, false, EarthModel::default())
+                    .unwrap();
+                prop_assert!(
+                    position.elevation < min_elevation,
+                    "{} at {} should be below the mask",
+                    position.elevation,
+                    position.time
+                );
+            }
+        }
+    }
+}
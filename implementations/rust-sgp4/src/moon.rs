@@ -0,0 +1,299 @@
+//! Analytic lunar ephemeris (Meeus, Astronomical Algorithms, ch. 47).
+//! Good to about 10" in longitude and 4" in latitude - ample for
+//! Moon-separation constraints and moonlight-interference flags.
+
+use std::f64::consts::PI;
+
+const DEG2RAD: f64 = PI / 180.0;
+
+/// Moon illuminated fraction at or above which it brightens the sky enough
+/// to interfere with optical tracking.
+pub const INTERFERENCE_MIN_ILLUMINATION: f64 = 0.5;
+
+/// Satellite-Moon separation (degrees) below which moonlight interferes.
+pub const INTERFERENCE_MAX_SEPARATION_DEG: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MoonPosition {
+    /// Apparent right ascension in radians, [0, 2π)
+    pub right_ascension: f64,
+    /// Apparent declination in radians
+    pub declination: f64,
+    /// Earth-Moon distance in km
+    pub distance_km: f64,
+}
+
+impl MoonPosition {
+    /// Geocentric position in km, equator and equinox of date.
+    pub fn eci(&self) -> [f64; 3] {
+        let (sin_ra, cos_ra) = self.right_ascension.sin_cos();
+        let (sin_dec, cos_dec) = self.declination.sin_cos();
+        [
+            self.distance_km * cos_dec * cos_ra,
+            self.distance_km * cos_dec * sin_ra,
+            self.distance_km * sin_dec,
+        ]
+    }
+}
+
+// Periodic terms: multiples of D, M, M', F followed by the coefficients.
+// Table 47.A: longitude in 1e-6 degrees, distance in 1e-3 km.
+const LONGITUDE_DISTANCE_TERMS: [(f64, f64, f64, f64, f64, f64); 60] = [
+    (0.0, 0.0, 1.0, 0.0, 6288774.0, -20905355.0),
+    (2.0, 0.0, -1.0, 0.0, 1274027.0, -3699111.0),
+    (2.0, 0.0, 0.0, 0.0, 658314.0, -2955968.0),
+    (0.0, 0.0, 2.0, 0.0, 213618.0, -569925.0),
+    (0.0, 1.0, 0.0, 0.0, -185116.0, 48888.0),
+    (0.0, 0.0, 0.0, 2.0, -114332.0, -3149.0),
+    (2.0, 0.0, -2.0, 0.0, 58793.0, 246158.0),
+    (2.0, -1.0, -1.0, 0.0, 57066.0, -152138.0),
+    (2.0, 0.0, 1.0, 0.0, 53322.0, -170733.0),
+    (2.0, -1.0, 0.0, 0.0, 45758.0, -204586.0),
+    (0.0, 1.0, -1.0, 0.0, -40923.0, -129620.0),
+    (1.0, 0.0, 0.0, 0.0, -34720.0, 108743.0),
+    (0.0, 1.0, 1.0, 0.0, -30383.0, 104755.0),
+    (2.0, 0.0, 0.0, -2.0, 15327.0, 10321.0),
+    (0.0, 0.0, 1.0, 2.0, -12528.0, 0.0),
+    (0.0, 0.0, 1.0, -2.0, 10980.0, 79661.0),
+    (4.0, 0.0, -1.0, 0.0, 10675.0, -34782.0),
+    (0.0, 0.0, 3.0, 0.0, 10034.0, -23210.0),
+    (4.0, 0.0, -2.0, 0.0, 8548.0, -21636.0),
+    (2.0, 1.0, -1.0, 0.0, -7888.0, 24208.0),
+    (2.0, 1.0, 0.0, 0.0, -6766.0, 30824.0),
+    (1.0, 0.0, -1.0, 0.0, -5163.0, -8379.0),
+    (1.0, 1.0, 0.0, 0.0, 4987.0, -16675.0),
+    (2.0, -1.0, 1.0, 0.0, 4036.0, -12831.0),
+    (2.0, 0.0, 2.0, 0.0, 3994.0, -10445.0),
+    (4.0, 0.0, 0.0, 0.0, 3861.0, -11650.0),
+    (2.0, 0.0, -3.0, 0.0, 3665.0, 14403.0),
+    (0.0, 1.0, -2.0, 0.0, -2689.0, -7003.0),
+    (2.0, 0.0, -1.0, 2.0, -2602.0, 0.0),
+    (2.0, -1.0, -2.0, 0.0, 2390.0, 10056.0),
+    (1.0, 0.0, 1.0, 0.0, -2348.0, 6322.0),
+    (2.0, -2.0, 0.0, 0.0, 2236.0, -9884.0),
+    (0.0, 1.0, 2.0, 0.0, -2120.0, 5751.0),
+    (0.0, 2.0, 0.0, 0.0, -2069.0, 0.0),
+    (2.0, -2.0, -1.0, 0.0, 2048.0, -4950.0),
+    (2.0, 0.0, 1.0, -2.0, -1773.0, 4130.0),
+    (2.0, 0.0, 0.0, 2.0, -1595.0, 0.0),
+    (4.0, -1.0, -1.0, 0.0, 1215.0, -3958.0),
+    (0.0, 0.0, 2.0, 2.0, -1110.0, 0.0),
+    (3.0, 0.0, -1.0, 0.0, -892.0, 3258.0),
+    (2.0, 1.0, 1.0, 0.0, -810.0, 2616.0),
+    (4.0, -1.0, -2.0, 0.0, 759.0, -1897.0),
+    (0.0, 2.0, -1.0, 0.0, -713.0, -2117.0),
+    (2.0, 2.0, -1.0, 0.0, -700.0, 2354.0),
+    (2.0, 1.0, -2.0, 0.0, 691.0, 0.0),
+    (2.0, -1.0, 0.0, -2.0, 596.0, 0.0),
+    (4.0, 0.0, 1.0, 0.0, 549.0, -1423.0),
+    (0.0, 0.0, 4.0, 0.0, 537.0, -1117.0),
+    (4.0, -1.0, 0.0, 0.0, 520.0, -1571.0),
+    (1.0, 0.0, -2.0, 0.0, -487.0, -1739.0),
+    (2.0, 1.0, 0.0, -2.0, -399.0, 0.0),
+    (0.0, 0.0, 2.0, -2.0, -381.0, -4421.0),
+    (1.0, 1.0, 1.0, 0.0, 351.0, 0.0),
+    (3.0, 0.0, -2.0, 0.0, -340.0, 0.0),
+    (4.0, 0.0, -3.0, 0.0, 330.0, 0.0),
+    (2.0, -1.0, 2.0, 0.0, 327.0, 0.0),
+    (0.0, 2.0, 1.0, 0.0, -323.0, 1165.0),
+    (1.0, 1.0, -1.0, 0.0, 299.0, 0.0),
+    (2.0, 0.0, 3.0, 0.0, 294.0, 0.0),
+    (2.0, 0.0, -1.0, -2.0, 0.0, 8752.0),
+];
+
+// Table 47.B: latitude in 1e-6 degrees.
+const LATITUDE_TERMS: [(f64, f64, f64, f64, f64); 60] = [
+    (0.0, 0.0, 0.0, 1.0, 5128122.0),
+    (0.0, 0.0, 1.0, 1.0, 280602.0),
+    (0.0, 0.0, 1.0, -1.0, 277693.0),
+    (2.0, 0.0, 0.0, -1.0, 173237.0),
+    (2.0, 0.0, -1.0, 1.0, 55413.0),
+    (2.0, 0.0, -1.0, -1.0, 46271.0),
+    (2.0, 0.0, 0.0, 1.0, 32573.0),
+    (0.0, 0.0, 2.0, 1.0, 17198.0),
+    (2.0, 0.0, 1.0, -1.0, 9266.0),
+    (0.0, 0.0, 2.0, -1.0, 8822.0),
+    (2.0, -1.0, 0.0, -1.0, 8216.0),
+    (2.0, 0.0, -2.0, -1.0, 4324.0),
+    (2.0, 0.0, 1.0, 1.0, 4200.0),
+    (2.0, 1.0, 0.0, -1.0, -3359.0),
+    (2.0, -1.0, -1.0, 1.0, 2463.0),
+    (2.0, -1.0, 0.0, 1.0, 2211.0),
+    (2.0, -1.0, -1.0, -1.0, 2065.0),
+    (0.0, 1.0, -1.0, -1.0, -1870.0),
+    (4.0, 0.0, -1.0, -1.0, 1828.0),
+    (0.0, 1.0, 0.0, 1.0, -1794.0),
+    (0.0, 0.0, 0.0, 3.0, -1749.0),
+    (0.0, 1.0, -1.0, 1.0, -1565.0),
+    (1.0, 0.0, 0.0, 1.0, -1491.0),
+    (0.0, 1.0, 1.0, 1.0, -1475.0),
+    (0.0, 1.0, 1.0, -1.0, -1410.0),
+    (0.0, 1.0, 0.0, -1.0, -1344.0),
+    (1.0, 0.0, 0.0, -1.0, -1335.0),
+    (0.0, 0.0, 3.0, 1.0, 1107.0),
+    (4.0, 0.0, 0.0, -1.0, 1021.0),
+    (4.0, 0.0, -1.0, 1.0, 833.0),
+    (0.0, 0.0, 1.0, -3.0, 777.0),
+    (4.0, 0.0, -2.0, 1.0, 671.0),
+    (2.0, 0.0, 0.0, -3.0, 607.0),
+    (2.0, 0.0, 2.0, -1.0, 596.0),
+    (2.0, -1.0, 1.0, -1.0, 491.0),
+    (2.0, 0.0, -2.0, 1.0, -451.0),
+    (0.0, 0.0, 3.0, -1.0, 439.0),
+    (2.0, 0.0, 2.0, 1.0, 422.0),
+    (2.0, 0.0, -3.0, -1.0, 421.0),
+    (2.0, 1.0, -1.0, 1.0, -366.0),
+    (2.0, 1.0, 0.0, 1.0, -351.0),
+    (4.0, 0.0, 0.0, 1.0, 331.0),
+    (2.0, -1.0, 1.0, 1.0, 315.0),
+    (2.0, -2.0, 0.0, -1.0, 302.0),
+    (0.0, 0.0, 1.0, 3.0, -283.0),
+    (2.0, 1.0, 1.0, -1.0, -229.0),
+    (1.0, 1.0, 0.0, -1.0, 223.0),
+    (1.0, 1.0, 0.0, 1.0, 223.0),
+    (0.0, 1.0, -2.0, -1.0, -220.0),
+    (2.0, 1.0, -1.0, -1.0, -220.0),
+    (1.0, 0.0, 1.0, 1.0, -185.0),
+    (2.0, -1.0, -2.0, -1.0, 181.0),
+    (0.0, 1.0, 2.0, 1.0, -177.0),
+    (4.0, 0.0, -2.0, -1.0, 176.0),
+    (4.0, -1.0, -1.0, -1.0, 166.0),
+    (1.0, 0.0, 1.0, -1.0, -164.0),
+    (4.0, 0.0, 1.0, -1.0, 132.0),
+    (1.0, 0.0, -1.0, -1.0, -119.0),
+    (4.0, -1.0, 0.0, -1.0, 115.0),
+    (2.0, -2.0, 0.0, 1.0, 107.0),
+];
+
+/// Apparent geocentric position of the Moon at Julian date `jd`.
+pub fn moon_position(jd: f64) -> MoonPosition {
+    let t = (jd - 2451545.0) / 36525.0;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let t4 = t3 * t;
+
+    // Fundamental arguments in degrees
+    let lp = 218.3164477 + 481267.88123421 * t - 0.0015786 * t2 + t3 / 538841.0 - t4 / 65194000.0;
+    let d = 297.8501921 + 445267.1114034 * t - 0.0018819 * t2 + t3 / 545868.0 - t4 / 113065000.0;
+    let m = 357.5291092 + 35999.0502909 * t - 0.0001536 * t2 + t3 / 24490000.0;
+    let mp = 134.9633964 + 477198.8675055 * t + 0.0087414 * t2 + t3 / 69699.0 - t4 / 14712000.0;
+    let f = 93.2720950 + 483202.0175233 * t - 0.0036539 * t2 - t3 / 3526000.0 + t4 / 863310000.0;
+
+    let a1 = (119.75 + 131.849 * t) * DEG2RAD;
+    let a2 = (53.09 + 479264.290 * t) * DEG2RAD;
+    let a3 = (313.45 + 481266.484 * t) * DEG2RAD;
+
+    // Eccentricity of Earth's orbit scales terms involving the Sun's anomaly
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t2;
+
+    let term = |cd: f64, cm: f64, cmp: f64, cf: f64| -> (f64, f64) {
+        let arg = (cd * d + cm * m + cmp * mp + cf * f) * DEG2RAD;
+        (arg, e.powi(cm.abs() as i32))
+    };
+
+    let (mut sigma_l, mut sigma_r) = (0.0, 0.0);
+    for &(cd, cm, cmp, cf, coeff_l, coeff_r) in &LONGITUDE_DISTANCE_TERMS {
+        let (arg, scale) = term(cd, cm, cmp, cf);
+        sigma_l += coeff_l * scale * arg.sin();
+        sigma_r += coeff_r * scale * arg.cos();
+    }
+
+    let mut sigma_b = 0.0;
+    for &(cd, cm, cmp, cf, coeff_b) in &LATITUDE_TERMS {
+        let (arg, scale) = term(cd, cm, cmp, cf);
+        sigma_b += coeff_b * scale * arg.sin();
+    }
+
+    let lp_rad = lp * DEG2RAD;
+    let f_rad = f * DEG2RAD;
+    let mp_rad = mp * DEG2RAD;
+
+    // Additive terms for Venus, Jupiter, and Earth's flattening
+    sigma_l += 3958.0 * a1.sin() + 1962.0 * (lp_rad - f_rad).sin() + 318.0 * a2.sin();
+    sigma_b += -2235.0 * lp_rad.sin()
+        + 382.0 * a3.sin()
+        + 175.0 * (a1 - f_rad).sin()
+        + 175.0 * (a1 + f_rad).sin()
+        + 127.0 * (lp_rad - mp_rad).sin()
+        - 115.0 * (lp_rad + mp_rad).sin();
+
+    // Nutation in longitude and obliquity (low-precision, ch. 22)
+    let omega = (125.04452 - 1934.136261 * t) * DEG2RAD;
+    let l_sun = (280.4665 + 36000.7698 * t) * DEG2RAD;
+    let delta_psi = (-17.20 * omega.sin() - 1.32 * (2.0 * l_sun).sin() - 0.23 * (2.0 * lp_rad).sin()
+        + 0.21 * (2.0 * omega).sin())
+        / 3600.0;
+    let delta_eps = (9.20 * omega.cos() + 0.57 * (2.0 * l_sun).cos() + 0.10 * (2.0 * lp_rad).cos()
+        - 0.09 * (2.0 * omega).cos())
+        / 3600.0;
+
+    let lambda = (lp + sigma_l / 1e6 + delta_psi) * DEG2RAD;
+    let beta = (sigma_b / 1e6) * DEG2RAD;
+    let distance_km = 385000.56 + sigma_r / 1000.0;
+
+    let epsilon0 = 23.0 + (26.0 + (21.448 - 46.8150 * t - 0.00059 * t2 + 0.001813 * t3) / 60.0) / 60.0;
+    let epsilon = (epsilon0 + delta_eps) * DEG2RAD;
+
+    let right_ascension = (lambda.sin() * epsilon.cos() - beta.tan() * epsilon.sin())
+        .atan2(lambda.cos())
+        .rem_euclid(2.0 * PI);
+    let declination =
+        (beta.sin() * epsilon.cos() + beta.cos() * epsilon.sin() * lambda.sin()).asin();
+
+    MoonPosition {
+        right_ascension,
+        declination,
+        distance_km,
+    }
+}
+
+/// Illuminated fraction of the lunar disk (0 = new, 1 = full), from the
+/// geocentric Moon and Sun positions in km.
+pub fn illuminated_fraction(moon_eci: &[f64; 3], sun_eci: &[f64; 3]) -> f64 {
+    // Phase angle ≈ 180° - elongation, ignoring the Moon's parallax
+    let elongation = crate::sun::separation_deg(moon_eci, sun_eci) * DEG2RAD;
+    let phase_angle = PI - elongation;
+    (1.0 + phase_angle.cos()) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sun;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected} ± {tolerance}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn meeus_example_47a() {
+        // 1992 April 12.0 TD: α = 134.688470°, δ = 13.768368°, Δ = 368409.7 km
+        let moon = moon_position(2448724.5);
+        assert_close(moon.right_ascension / DEG2RAD, 134.688470, 0.001);
+        assert_close(moon.declination / DEG2RAD, 13.768368, 0.001);
+        assert_close(moon.distance_km, 368409.7, 0.5);
+    }
+
+    #[test]
+    fn total_solar_eclipse_2024() {
+        // Greatest eclipse 2024 April 8 18:17 UT: geocentric Sun and Moon
+        // within half a degree of each other, Moon new
+        let jd = 2460409.262;
+        let moon = moon_position(jd).eci();
+        let sun = sun::sun_position(jd).eci();
+        assert!(sun::separation_deg(&moon, &sun) < 0.5);
+        assert!(illuminated_fraction(&moon, &sun) < 0.01);
+    }
+
+    #[test]
+    fn full_moon_2024() {
+        // Full Moon 2024 April 23 23:49 UT
+        let jd = 2460424.492;
+        let moon = moon_position(jd).eci();
+        let sun = sun::sun_position(jd).eci();
+        assert!(illuminated_fraction(&moon, &sun) > 0.99);
+    }
+}
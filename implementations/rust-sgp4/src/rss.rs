@@ -0,0 +1,30 @@
+//! Peak resident set size of this process, for `peakRss` in result metadata.
+//!
+//! The kernel keeps the high-water mark as `VmHWM` in `/proc/self/status`.
+//! Writing `5` to `/proc/self/clear_refs` (Linux 4.0+) resets it, so each
+//! case's figure covers that case rather than every case run before it in
+//! the same process. Where the reset isn't allowed, the figure is the peak
+//! so far; where there is no `/proc`, there is none.
+//!
+//! Unlike the orchestrator's cgroup reading, this leaves out other
+//! processes in the container and the page cache, so it is the memory the
+//! calculation itself holds.
+
+use std::fs;
+
+/// Start a new high-water mark.
+pub fn reset_peak() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak resident set size in bytes since the last reset.
+pub fn peak() -> Option<u64> {
+    vm_hwm(&fs::read_to_string("/proc/self/status").ok()?)
+}
+
+/// `VmHWM:    12345 kB` in bytes.
+fn vm_hwm(status: &str) -> Option<u64> {
+    let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,164 @@
+//! Low-precision analytic solar ephemeris (Meeus, Astronomical Algorithms,
+//! ch. 25). Accurate to about 0.01° between 1950 and 2050, which is plenty
+//! for eclipse, twilight, and Sun-separation checks.
+
+use std::f64::consts::PI;
+
+const DEG2RAD: f64 = PI / 180.0;
+const AU_KM: f64 = 149_597_870.7;
+const EARTH_RADIUS_KM: f64 = 6378.137;
+
+/// Sun elevation below which the observer is in darkness (civil twilight).
+pub const TWILIGHT_ELEVATION_DEG: f64 = -6.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SunPosition {
+    /// Apparent right ascension in radians, [0, 2π)
+    pub right_ascension: f64,
+    /// Apparent declination in radians
+    pub declination: f64,
+    /// Earth-Sun distance in km
+    pub distance_km: f64,
+}
+
+impl SunPosition {
+    /// Geocentric position in km, equator and equinox of date - close enough
+    /// to TEME at this precision.
+    pub fn eci(&self) -> [f64; 3] {
+        let (sin_ra, cos_ra) = self.right_ascension.sin_cos();
+        let (sin_dec, cos_dec) = self.declination.sin_cos();
+        [
+            self.distance_km * cos_dec * cos_ra,
+            self.distance_km * cos_dec * sin_ra,
+            self.distance_km * sin_dec,
+        ]
+    }
+}
+
+/// Apparent position of the Sun at Julian date `jd`.
+pub fn sun_position(jd: f64) -> SunPosition {
+    let t = (jd - 2451545.0) / 36525.0;
+
+    let l0 = 280.46646 + 36000.76983 * t + 0.0003032 * t * t;
+    let m = (357.52911 + 35999.05029 * t - 0.0001537 * t * t) * DEG2RAD;
+    let e = 0.016708634 - 0.000042037 * t - 0.0000001267 * t * t;
+
+    // Equation of center
+    let c = (1.914602 - 0.004817 * t - 0.000014 * t * t) * m.sin()
+        + (0.019993 - 0.000101 * t) * (2.0 * m).sin()
+        + 0.000289 * (3.0 * m).sin();
+
+    let true_longitude = l0 + c;
+    let true_anomaly = m + c * DEG2RAD;
+    let distance_au = 1.000001018 * (1.0 - e * e) / (1.0 + e * true_anomaly.cos());
+
+    // Nutation and aberration
+    let omega = (125.04 - 1934.136 * t) * DEG2RAD;
+    let lambda = (true_longitude - 0.00569 - 0.00478 * omega.sin()) * DEG2RAD;
+
+    let epsilon0 = 23.0 + (26.0 + (21.448 - 46.8150 * t - 0.00059 * t * t + 0.001813 * t * t * t) / 60.0) / 60.0;
+    let epsilon = (epsilon0 + 0.00256 * omega.cos()) * DEG2RAD;
+
+    let right_ascension = (epsilon.cos() * lambda.sin())
+        .atan2(lambda.cos())
+        .rem_euclid(2.0 * PI);
+    let declination = (epsilon.sin() * lambda.sin()).asin();
+
+    SunPosition {
+        right_ascension,
+        declination,
+        distance_km: distance_au * AU_KM,
+    }
+}
+
+/// Whether a satellite at `sat_eci` is illuminated, using a cylindrical
+/// Earth-shadow model. Both vectors in km, same inertial frame.
+pub fn is_sunlit(sat_eci: &[f64; 3], sun_eci: &[f64; 3]) -> bool {
+    let sun_norm = norm(sun_eci);
+    let sun_unit = [sun_eci[0] / sun_norm, sun_eci[1] / sun_norm, sun_eci[2] / sun_norm];
+
+    // Projection onto the Sun direction; positive means the day side
+    let along = dot(sat_eci, &sun_unit);
+    if along >= 0.0 {
+        return true;
+    }
+
+    let perpendicular = [
+        sat_eci[0] - along * sun_unit[0],
+        sat_eci[1] - along * sun_unit[1],
+        sat_eci[2] - along * sun_unit[2],
+    ];
+    norm(&perpendicular) > EARTH_RADIUS_KM
+}
+
+/// Angle in degrees between two direction vectors.
+pub fn separation_deg(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (dot(a, b) / (norm(a) * norm(b))).clamp(-1.0, 1.0).acos() / DEG2RAD
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn norm(a: &[f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected} ± {tolerance}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn meeus_example_25a() {
+        // 1992 October 13.0 TD: α = 198.38083°, δ = -7.78507°, R = 0.99766 AU
+        let sun = sun_position(2448908.5);
+        assert_close(sun.right_ascension / DEG2RAD, 198.38083, 0.001);
+        assert_close(sun.declination / DEG2RAD, -7.78507, 0.001);
+        assert_close(sun.distance_km / AU_KM, 0.99766, 0.00001);
+    }
+
+    #[test]
+    fn march_equinox_2024() {
+        // Astronomical Almanac: equinox 2024 March 20 03:06 UT
+        let sun = sun_position(2460389.629);
+        assert_close(sun.declination / DEG2RAD, 0.0, 0.01);
+        assert_close(sun.right_ascension / DEG2RAD, 0.0, 0.02);
+    }
+
+    #[test]
+    fn june_solstice_2024() {
+        // Astronomical Almanac: solstice 2024 June 20 20:51 UT, δ = +23.44°
+        let sun = sun_position(2460482.369);
+        assert_close(sun.declination / DEG2RAD, 23.4393, 0.005);
+        assert_close(sun.right_ascension / DEG2RAD, 90.0, 0.02);
+    }
+
+    #[test]
+    fn perihelion_2024() {
+        // Astronomical Almanac: perihelion 2024 January 3 00:39 UT, 0.9833 AU
+        let sun = sun_position(2460312.527);
+        assert_close(sun.distance_km / AU_KM, 0.98331, 0.00005);
+    }
+
+    #[test]
+    fn earth_shadow() {
+        let sun = [AU_KM, 0.0, 0.0];
+        // Day side, night side inside the shadow cylinder, night side outside it
+        assert!(is_sunlit(&[7000.0, 0.0, 0.0], &sun));
+        assert!(!is_sunlit(&[-7000.0, 0.0, 0.0], &sun));
+        assert!(is_sunlit(&[-7000.0, 0.0, 7000.0], &sun));
+    }
+
+    #[test]
+    fn separation() {
+        assert_close(separation_deg(&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0]), 90.0, 1e-9);
+        assert_close(separation_deg(&[1.0, 0.0, 0.0], &[2.0, 0.0, 0.0]), 0.0, 1e-6);
+    }
+}
@@ -0,0 +1,105 @@
+//! Alternative timestamp formats for downstream consumers. RFC 3339 strings
+//! stay in the canonical fields the orchestrator compares; other formats are
+//! added alongside them.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Unix time of the GPS epoch, 1980-01-06T00:00:00Z
+const GPS_EPOCH_UNIX: i64 = 315_964_800;
+
+/// Months (year, month) at whose start GPS - UTC grew by a leap second.
+const LEAP_SECONDS: [(i32, u32); 18] = [
+    (1981, 7),
+    (1982, 7),
+    (1983, 7),
+    (1985, 7),
+    (1988, 1),
+    (1990, 1),
+    (1991, 1),
+    (1992, 7),
+    (1993, 7),
+    (1994, 7),
+    (1996, 1),
+    (1997, 7),
+    (1999, 1),
+    (2006, 1),
+    (2009, 1),
+    (2012, 7),
+    (2015, 7),
+    (2017, 1),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    #[default]
+    Rfc3339,
+    /// Seconds since 1970-01-01T00:00:00Z
+    Unix,
+    /// Continuous GPS seconds since 1980-01-06T00:00:00Z
+    Gps,
+}
+
+/// A timestamp in the selected alternative format, flattened into points.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum FormattedTime {
+    #[serde(rename = "unixTime")]
+    Unix(i64),
+    #[serde(rename = "gpsTime")]
+    Gps(i64),
+}
+
+impl TimeFormat {
+    /// The extra field for `time`, or `None` for plain RFC 3339 output.
+    pub fn format(self, time: DateTime<Utc>) -> Option<FormattedTime> {
+        match self {
+            TimeFormat::Rfc3339 => None,
+            TimeFormat::Unix => Some(FormattedTime::Unix(time.timestamp())),
+            TimeFormat::Gps => Some(FormattedTime::Gps(
+                time.timestamp() - GPS_EPOCH_UNIX + gps_leap_seconds(time),
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeFormat::Rfc3339 => "rfc3339",
+            TimeFormat::Unix => "unix",
+            TimeFormat::Gps => "gps",
+        }
+    }
+}
+
+impl fmt::Display for TimeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rfc3339" => Ok(TimeFormat::Rfc3339),
+            "unix" => Ok(TimeFormat::Unix),
+            "gps" => Ok(TimeFormat::Gps),
+            _ => anyhow::bail!("Unknown time format '{}' (expected rfc3339, unix, or gps)", s),
+        }
+    }
+}
+
+/// GPS - UTC in seconds at `time`.
+fn gps_leap_seconds(time: DateTime<Utc>) -> i64 {
+    LEAP_SECONDS
+        .iter()
+        .filter(|&&(year, month)| {
+            Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+                .single()
+                .is_some_and(|start| start <= time)
+        })
+        .count() as i64
+}
@@ -0,0 +1,71 @@
+//! Moving ground observers (ships, aircraft) described by time-tagged
+//! waypoints.
+
+use crate::Observer;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct Waypoint {
+    time: String,
+    latitude: f64,
+    longitude: f64,
+    /// Meters above sea level, like a fixed observer
+    altitude: f64,
+}
+
+#[derive(Debug)]
+pub struct Trajectory {
+    points: Vec<(DateTime<Utc>, Observer)>,
+}
+
+impl Trajectory {
+    pub fn new(waypoints: &[Waypoint]) -> Result<Self> {
+        let mut points = waypoints
+            .iter()
+            .map(|w| {
+                let time = DateTime::parse_from_rfc3339(&w.time)
+                    .with_context(|| format!("Invalid waypoint time '{}'", w.time))?
+                    .with_timezone(&Utc);
+                let observer = Observer {
+                    latitude: w.latitude,
+                    longitude: w.longitude,
+                    altitude: w.altitude,
+                };
+                Ok((time, observer))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        points.sort_by_key(|(time, _)| *time);
+
+        anyhow::ensure!(!points.is_empty(), "observerTrajectory has no waypoints");
+        Ok(Self { points })
+    }
+
+    /// Whether every instant in `[start, end]` lies within the waypoints.
+    pub fn covers(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.points[0].0 <= start && end <= self.points[self.points.len() - 1].0
+    }
+
+    /// Observer location at `time`, linearly interpolated between waypoints
+    /// and held at the ends. Longitude takes the short way across ±180°.
+    pub fn at(&self, time: DateTime<Utc>) -> Observer {
+        let idx = self.points.partition_point(|(t, _)| *t <= time);
+        if idx == 0 {
+            return self.points[0].1.clone();
+        }
+        let (t0, a) = &self.points[idx - 1];
+        let Some((t1, b)) = self.points.get(idx) else {
+            return a.clone();
+        };
+
+        let f = (time - *t0).num_milliseconds() as f64 / (*t1 - *t0).num_milliseconds() as f64;
+        let delta_lon = (b.longitude - a.longitude + 180.0).rem_euclid(360.0) - 180.0;
+
+        Observer {
+            latitude: a.latitude + (b.latitude - a.latitude) * f,
+            longitude: (a.longitude + delta_lon * f + 180.0).rem_euclid(360.0) - 180.0,
+            altitude: a.altitude + (b.altitude - a.altitude) * f,
+        }
+    }
+}
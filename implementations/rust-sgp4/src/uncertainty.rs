@@ -0,0 +1,100 @@
+//! Rough look-angle uncertainty from TLE along-track error, for `--uncertainty`.
+//!
+//! A TLE's position error is dominated by the along-track direction, where
+//! drag and other unmodelled forces leave the satellite ahead of or behind
+//! its prediction, and it grows roughly linearly away from the epoch. The
+//! model takes a 1σ along-track error of [`EPOCH_ALONG_TRACK_KM`] at epoch,
+//! growing by a configurable rate in km/day, and maps it linearly onto each
+//! point:
+//!
+//! - the look angles move by their derivative along the velocity direction
+//!   times the along-track error
+//! - the satellite runs early or late by the along-track error over its
+//!   speed, and window starts and ends shift by the same time
+//!
+//! Cross-track and radial errors are ignored, so these are estimates to
+//! choose tolerances by rather than a full covariance.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// 1σ along-track error at the TLE epoch (km)
+pub const EPOCH_ALONG_TRACK_KM: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Model {
+    /// Growth of the 1σ along-track error away from the epoch (km/day)
+    pub growth_km_per_day: f64,
+}
+
+/// How a point responds to moving the satellite along its track.
+#[derive(Debug, Clone, Copy)]
+pub struct Sensitivity {
+    /// Azimuth change per km along-track (deg/km)
+    pub azimuth: f64,
+    /// Elevation change per km along-track (deg/km)
+    pub elevation: f64,
+    /// Inertial speed of the satellite (km/s)
+    pub speed: f64,
+}
+
+/// 1σ uncertainty of a point.
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct PointUncertainty {
+    /// Along-track position (km)
+    #[serde(rename = "alongTrackUncertainty")]
+    pub along_track: f64,
+    /// Time the satellite may run early or late (s)
+    #[serde(rename = "timeUncertainty")]
+    pub time: f64,
+    /// Azimuth (degrees)
+    #[serde(rename = "azimuthUncertainty")]
+    pub azimuth: f64,
+    /// Elevation (degrees)
+    #[serde(rename = "elevationUncertainty")]
+    pub elevation: f64,
+}
+
+impl Model {
+    /// 1σ along-track error (km) at `time` for a TLE with epoch `epoch`.
+    pub fn along_track_km(&self, epoch: DateTime<Utc>, time: DateTime<Utc>) -> f64 {
+        let days = (time - epoch).num_seconds().abs() as f64 / 86400.0;
+        EPOCH_ALONG_TRACK_KM + self.growth_km_per_day * days
+    }
+
+    pub fn at(&self, epoch: DateTime<Utc>, time: DateTime<Utc>, sensitivity: Sensitivity) -> PointUncertainty {
+        let along_track = self.along_track_km(epoch, time);
+        PointUncertainty {
+            along_track,
+            time: along_track / sensitivity.speed,
+            azimuth: (sensitivity.azimuth * along_track).abs(),
+            elevation: (sensitivity.elevation * along_track).abs(),
+        }
+    }
+}
+
+impl PointUncertainty {
+    /// Rounded for output, like the other point fields.
+    pub fn rounded(self) -> Self {
+        Self {
+            along_track: (self.along_track * 100.0).round() / 100.0,
+            time: (self.time * 100.0).round() / 100.0,
+            azimuth: (self.azimuth * 1000.0).round() / 1000.0,
+            elevation: (self.elevation * 1000.0).round() / 1000.0,
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<f64>() {
+            Ok(growth_km_per_day) if growth_km_per_day.is_finite() && growth_km_per_day >= 0.0 => {
+                Ok(Self { growth_km_per_day })
+            }
+            _ => anyhow::bail!("Invalid uncertainty growth '{}' (expected km/day, e.g. 2)", s),
+        }
+    }
+}
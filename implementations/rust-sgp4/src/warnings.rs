@@ -0,0 +1,104 @@
+//! Non-fatal issues worth flagging alongside a result: the numbers are still
+//! computed, but may deserve less trust or a closer look.
+
+use crate::VisibilityWindow;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// TLEs older than this (relative to the time window) lose accuracy quickly.
+const MAX_TLE_AGE_DAYS: f64 = 7.0;
+
+/// Orbital period at which SGP4 switches to deep-space (SDP4) propagation.
+const DEEP_SPACE_PERIOD_MINUTES: f64 = 225.0;
+
+/// Fewest points that resolve a pass's rise, peak, and set.
+const MIN_POINTS_PER_WINDOW: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    /// Stable identifier: `tle_age`, `deep_space`, `window_clipped`, or `coarse_step`
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Warning {
+    fn new(code: &'static str, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+/// Inputs the checks need, gathered once per test case.
+pub struct Context<'a> {
+    pub tle_epoch: DateTime<Utc>,
+    /// Revolutions per day
+    pub mean_motion: f64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub step: Duration,
+    pub windows: &'a [VisibilityWindow],
+}
+
+pub fn collect(context: &Context) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    let age_days = [context.start, context.end]
+        .iter()
+        .map(|t| (*t - context.tle_epoch).num_seconds().abs() as f64 / 86400.0)
+        .fold(0.0, f64::max);
+    if age_days > MAX_TLE_AGE_DAYS {
+        warnings.push(Warning::new(
+            "tle_age",
+            format!(
+                "TLE epoch {} is up to {:.1} days from the time window (> {} days)",
+                context.tle_epoch.format("%Y-%m-%dT%H:%M:%SZ"),
+                age_days,
+                MAX_TLE_AGE_DAYS
+            ),
+        ));
+    }
+
+    let period_minutes = 1440.0 / context.mean_motion;
+    if period_minutes >= DEEP_SPACE_PERIOD_MINUTES {
+        warnings.push(Warning::new(
+            "deep_space",
+            format!(
+                "Orbital period {:.1} min is in the deep-space regime (>= {} min); SDP4 is used",
+                period_minutes, DEEP_SPACE_PERIOD_MINUTES
+            ),
+        ));
+    }
+
+    // Last sampled instant; the window end itself may fall between steps
+    let steps = (context.end - context.start).num_seconds() / context.step.num_seconds();
+    let last_sample = context.start + context.step * steps as i32;
+    let format = |t: DateTime<Utc>| t.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let (first_time, last_time) = (format(context.start), format(last_sample));
+
+    for (i, window) in context.windows.iter().enumerate() {
+        let clipped_start = window.start == first_time;
+        let clipped_end = window.end == last_time;
+        if clipped_start || clipped_end {
+            let edge = match (clipped_start, clipped_end) {
+                (true, true) => "spans the whole time window",
+                (true, false) => "is already open at the start of the time window",
+                _ => "is still open at the end of the time window",
+            };
+            warnings.push(Warning::new(
+                "window_clipped",
+                format!("Window {} {}; its duration is truncated", i + 1, edge),
+            ));
+        } else if window.points.len() < MIN_POINTS_PER_WINDOW {
+            warnings.push(Warning::new(
+                "coarse_step",
+                format!(
+                    "Window {} has only {} point(s); a {} s step is too coarse to resolve the pass",
+                    i + 1,
+                    window.points.len(),
+                    context.step.num_seconds()
+                ),
+            ));
+        }
+    }
+
+    warnings
+}
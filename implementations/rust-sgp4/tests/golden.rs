@@ -0,0 +1,160 @@
+//! Golden-file regression tests: run the calculator on the fixtures in
+//! `tests/fixtures` and compare each result against `tests/golden`.
+//!
+//! After an intentional output change, regenerate the golden files with
+//! `UPDATE_GOLDEN=1 cargo test --test golden` and review the diff.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Fields that change on every run.
+const VOLATILE_FIELDS: [&str; 2] = ["timestamp", "executionTime"];
+/// Metadata fields that change on every run.
+const VOLATILE_METADATA: [&str; 1] = ["peakRss"];
+
+/// Numbers are rounded to at most 0.001 in results; allow a couple of ulps
+/// of that rounding so platform float differences don't fail the suite.
+const ABSOLUTE_TOLERANCE: f64 = 0.002;
+const RELATIVE_TOLERANCE: f64 = 1e-9;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Run the calculator over every fixture, returning the results directory.
+fn run_calculator() -> PathBuf {
+    let results_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden-results");
+    if results_dir.exists() {
+        fs::remove_dir_all(&results_dir).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_visibility-calculator"))
+        .arg("--test-data")
+        .arg(manifest_dir().join("tests/fixtures"))
+        .arg("--results")
+        .arg(&results_dir)
+        .output()
+        .expect("failed to run visibility-calculator");
+    assert!(
+        output.status.success(),
+        "visibility-calculator failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    results_dir
+}
+
+/// The result written for `test_case` (file names carry a timestamp).
+fn find_result(results_dir: &Path, test_case: &str) -> PathBuf {
+    let prefix = format!("rust-sgp4_{}_", test_case);
+    fs::read_dir(results_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| {
+            let name = p.file_name().unwrap().to_string_lossy();
+            // Match only `<prefix><YYYYMMDD_HHMMSS>.json`, not cases sharing the prefix
+            name.strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".json"))
+                .is_some_and(|stamp| stamp.chars().all(|c| c.is_ascii_digit() || c == '_'))
+        })
+        .unwrap_or_else(|| panic!("no result for {}", test_case))
+}
+
+fn strip_volatile(value: &mut Value) {
+    if let Value::Object(map) = value {
+        for field in VOLATILE_FIELDS {
+            map.remove(field);
+        }
+        if let Some(Value::Object(metadata)) = map.get_mut("metadata") {
+            for field in VOLATILE_METADATA {
+                metadata.remove(field);
+            }
+        }
+    }
+}
+
+/// Collect differences between `actual` and `expected` under `path`.
+fn compare(path: &str, actual: &Value, expected: &Value, diffs: &mut Vec<String>) {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => {
+            let (a, e) = (a.as_f64().unwrap(), e.as_f64().unwrap());
+            if (a - e).abs() > ABSOLUTE_TOLERANCE + RELATIVE_TOLERANCE * e.abs() {
+                diffs.push(format!("{}: {} != {}", path, a, e));
+            }
+        }
+        (Value::Array(a), Value::Array(e)) => {
+            if a.len() != e.len() {
+                diffs.push(format!("{}: {} items != {}", path, a.len(), e.len()));
+                return;
+            }
+            for (i, (a, e)) in a.iter().zip(e).enumerate() {
+                compare(&format!("{}[{}]", path, i), a, e, diffs);
+            }
+        }
+        (Value::Object(a), Value::Object(e)) => {
+            for key in a.keys().chain(e.keys().filter(|k| !a.contains_key(*k))) {
+                let child = format!("{}.{}", path, key);
+                match (a.get(key), e.get(key)) {
+                    (Some(a), Some(e)) => compare(&child, a, e, diffs),
+                    (Some(_), None) => diffs.push(format!("{}: unexpected field", child)),
+                    (None, _) => diffs.push(format!("{}: missing field", child)),
+                }
+            }
+        }
+        _ if actual != expected => diffs.push(format!("{}: {} != {}", path, actual, expected)),
+        _ => {}
+    }
+}
+
+#[test]
+fn results_match_golden_files() {
+    let results_dir = run_calculator();
+    let golden_dir = manifest_dir().join("tests/golden");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(manifest_dir().join("tests/fixtures"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures found");
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let test_case = fixture.file_stem().unwrap().to_string_lossy();
+        let mut actual: Value =
+            serde_json::from_str(&fs::read_to_string(find_result(&results_dir, &test_case)).unwrap()).unwrap();
+        strip_volatile(&mut actual);
+
+        let golden_file = golden_dir.join(format!("{}.json", test_case));
+        if update {
+            fs::write(&golden_file, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            continue;
+        }
+
+        let expected: Value = serde_json::from_str(
+            &fs::read_to_string(&golden_file)
+                .unwrap_or_else(|_| panic!("missing {}; run with UPDATE_GOLDEN=1", golden_file.display())),
+        )
+        .unwrap();
+
+        let mut diffs = Vec::new();
+        compare("", &actual, &expected, &mut diffs);
+        if !diffs.is_empty() {
+            let shown = diffs.len().min(10);
+            failures.push(format!(
+                "{} ({} difference(s)):\n  {}",
+                test_case,
+                diffs.len(),
+                diffs[..shown].join("\n  ")
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "results differ from golden files:\n{}", failures.join("\n"));
+}
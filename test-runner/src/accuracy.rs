@@ -0,0 +1,236 @@
+//! Point-by-point accuracy against the reference.
+//!
+//! Window validation only asks whether an implementation found the same
+//! passes. `validate --points` instead pairs the sampled `points` of an
+//! implementation's windows with the reference's by timestamp and reports
+//! the RMS and maximum error of azimuth, elevation, and range, so
+//! implementations can be ranked by how closely they track the reference.
+//! Azimuth errors are taken the short way round. Points at times the
+//! reference didn't sample, or fields either side lacks, aren't compared.
+
+use crate::truth::Reference;
+use crate::{Orchestrator, read_result};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde_json::{Value, json};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// RMS and maximum of absolute errors.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stat {
+    count: usize,
+    sum_squares: f64,
+    max: f64,
+}
+
+impl Stat {
+    fn add(&mut self, error: f64) {
+        self.count += 1;
+        self.sum_squares += error * error;
+        self.max = self.max.max(error.abs());
+    }
+
+    fn merge(&mut self, other: &Stat) {
+        self.count += other.count;
+        self.sum_squares += other.sum_squares;
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn rms(&self) -> Option<f64> {
+        (self.count > 0).then(|| (self.sum_squares / self.count as f64).sqrt())
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Errors {
+    /// Points matched by timestamp
+    pub points: usize,
+    /// Degrees
+    pub azimuth: Stat,
+    /// Degrees
+    pub elevation: Stat,
+    /// Kilometers
+    pub range: Stat,
+}
+
+impl Stat {
+    fn to_json(self) -> Value {
+        json!({ "rms": self.rms(), "max": self.max() })
+    }
+}
+
+impl Errors {
+    fn to_json(self) -> Value {
+        json!({
+            "points": self.points,
+            "azimuth": self.azimuth.to_json(),
+            "elevation": self.elevation.to_json(),
+            "range": self.range.to_json(),
+        })
+    }
+
+    fn merge(&mut self, other: &Errors) {
+        self.points += other.points;
+        self.azimuth.merge(&other.azimuth);
+        self.elevation.merge(&other.elevation);
+        self.range.merge(&other.range);
+    }
+}
+
+pub struct Accuracy {
+    pub implementation: String,
+    pub cases: Vec<(String, Errors)>,
+    pub overall: Errors,
+}
+
+impl Accuracy {
+    /// For `--json`: errors per case and overall, in degrees and kilometers.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "implementation": self.implementation,
+            "cases": self
+                .cases
+                .iter()
+                .map(|(test_case, errors)| json!({ "test_case": test_case, "errors": errors.to_json() }))
+                .collect::<Vec<_>>(),
+            "overall": self.overall.to_json(),
+        })
+    }
+}
+
+/// Errors of every point in `result` windows sampled at the same time as a
+/// point in `reference` windows.
+fn compare(result: &[Value], reference: &[Value]) -> Errors {
+    let reference_points: HashMap<DateTime<Utc>, &Value> = points(reference).collect();
+
+    let mut errors = Errors::default();
+    for (time, point) in points(result) {
+        let Some(expected) = reference_points.get(&time) else {
+            continue;
+        };
+        errors.points += 1;
+        let difference = |field: &str| Some(point[field].as_f64()? - expected[field].as_f64()?);
+        if let Some(delta) = difference("azimuth") {
+            errors.azimuth.add((delta + 180.0).rem_euclid(360.0) - 180.0);
+        }
+        if let Some(delta) = difference("elevation") {
+            errors.elevation.add(delta);
+        }
+        if let Some(delta) = difference("range") {
+            errors.range.add(delta);
+        }
+    }
+    errors
+}
+
+fn points(windows: &[Value]) -> impl Iterator<Item = (DateTime<Utc>, &Value)> {
+    windows
+        .iter()
+        .flat_map(|window| window["points"].as_array().into_iter().flatten())
+        .filter_map(|point| {
+            let time = DateTime::parse_from_rfc3339(point["time"].as_str()?).ok()?;
+            Some((time.with_timezone(&Utc), point))
+        })
+}
+
+/// Compare the latest result of every case `impl_name` has results for.
+pub fn measure(orchestrator: &Orchestrator, impl_name: &str, reference: &Reference) -> Result<Accuracy> {
+    let mut accuracy = Accuracy {
+        implementation: impl_name.to_string(),
+        cases: Vec::new(),
+        overall: Errors::default(),
+    };
+    for path in orchestrator.collect_results(impl_name)? {
+        let result: Value = serde_json::from_str(&read_result(&path)?)?;
+        let Some(test_case) = result["testCase"].as_str() else {
+            continue;
+        };
+        let Some(expected) = reference.load(test_case)? else {
+            continue;
+        };
+        let errors = compare(
+            result["visibilityWindows"].as_array().map(Vec::as_slice).unwrap_or_default(),
+            &expected.visibility_windows,
+        );
+        accuracy.overall.merge(&errors);
+        accuracy.cases.push((test_case.to_string(), errors));
+    }
+    Ok(accuracy)
+}
+
+pub fn print(accuracy: &Accuracy, reference: &Reference) {
+    println!(
+        "Point accuracy for {} against {}...",
+        accuracy.implementation.bright_cyan(),
+        reference.name()
+    );
+    if accuracy.cases.is_empty() {
+        println!("  {} No results with a reference found for {}", "⚠".yellow(), accuracy.implementation);
+        return;
+    }
+
+    println!(
+        "  {:<28} {:>6}  {:>9} {:>9}  {:>9} {:>9}  {:>11} {:>11}",
+        "Test case", "Points", "Az RMS", "Az max", "El RMS", "El max", "Range RMS", "Range max"
+    );
+    for (test_case, errors) in &accuracy.cases {
+        println!("  {}", row(test_case, errors));
+    }
+    println!("  {}", row("All", &accuracy.overall).bold());
+    if accuracy.overall.points == 0 {
+        println!("  {} No points sampled at the reference's times", "⚠".yellow());
+    }
+}
+
+fn row(label: &str, errors: &Errors) -> String {
+    let value = |value: Option<f64>, width: usize, unit: &str| match value {
+        Some(value) => format!("{:>width$}", format!("{:.4}{}", value, unit)),
+        None => format!("{:>width$}", "-"),
+    };
+    format!(
+        "{:<28} {:>6}  {} {}  {} {}  {} {}",
+        label,
+        errors.points,
+        value(errors.azimuth.rms(), 9, "°"),
+        value(errors.azimuth.max(), 9, "°"),
+        value(errors.elevation.rms(), 9, "°"),
+        value(errors.elevation.max(), 9, "°"),
+        value(errors.range.rms(), 11, " km"),
+        value(errors.range.max(), 11, " km"),
+    )
+}
+
+/// Rank implementations by overall elevation RMS error, which decides
+/// where windows start and end, then azimuth and range. Implementations
+/// without matched points come last.
+pub fn print_ranking(accuracies: &[Accuracy]) {
+    let mut ranked: Vec<&Accuracy> = accuracies.iter().collect();
+    let key = |accuracy: &Accuracy| {
+        let overall = &accuracy.overall;
+        [overall.elevation.rms(), overall.azimuth.rms(), overall.range.rms()].map(|rms| rms.unwrap_or(f64::INFINITY))
+    };
+    ranked.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal));
+
+    println!("{}", "Accuracy ranking (by elevation RMS error):".bold().bright_green());
+    for (i, accuracy) in ranked.iter().enumerate() {
+        let overall = &accuracy.overall;
+        match overall.elevation.rms() {
+            Some(rms) => println!(
+                "  {}. {} - el {:.4}°, az {}, range {} over {} point(s)",
+                i + 1,
+                accuracy.implementation.bright_white(),
+                rms,
+                overall.azimuth.rms().map(|v| format!("{:.4}°", v)).unwrap_or_else(|| "-".to_string()),
+                overall.range.rms().map(|v| format!("{:.4} km", v)).unwrap_or_else(|| "-".to_string()),
+                overall.points
+            ),
+            None => println!("  {}. {} - no comparable points", i + 1, accuracy.implementation.bright_white()),
+        }
+    }
+}
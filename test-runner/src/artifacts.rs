@@ -0,0 +1,365 @@
+//! Per-run artifact directories.
+//!
+//! Each `run` or `all` invocation files everything about one execution under
+//! `results/<run-id>/<impl>/<case>/`:
+//!
+//! - `result.json` (or `result.json.gz`) - copy of the implementation's result
+//! - `container.log` - the slice of container stdout for this case
+//! - `verdict.json` - validation outcome, when the run validates
+//! - `elevation.svg` - elevation profile of the visibility windows
+//!
+//! The full container output goes to `results/<run-id>/<impl>/container.log`,
+//! its stdout alone to `results/<run-id>/<impl>/stdout.log` (for
+//! [`log_diff`](crate::log_diff)),
+//! the containers' CPU time and peak memory to
+//! `results/<run-id>/<impl>/usage.json`, and the
+//! versions the image declared to the health check to
+//! `results/<run-id>/<impl>/versions.json`.
+//! Flat result files in `results/` are left in place for validation and the
+//! dashboard.
+//!
+//! Every run also writes `results/<run-id>/host.json`, the machine and
+//! hardware profile it ran on (see [`profiles`](crate::profiles)).
+//!
+//! `all` also writes `results/<run-id>/manifest.json`, the planned
+//! (implementation, case) pairs and which have completed, so an interrupted
+//! run can be resumed.
+//!
+//! Everything recorded here also goes into the results database (see
+//! [`database`](crate::database)), and is summed up in
+//! `results/<run-id>/metrics.txt` (see [`openmetrics`](crate::openmetrics)).
+
+use crate::changelog;
+use crate::database::Database;
+use crate::health::Versions;
+use crate::openmetrics;
+use crate::profiles::Host;
+use crate::usage::Usage;
+use crate::{CaseVerdict, TestResult, ValidationSummary, read_result, result_test_case};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Plot size in pixels, including margins.
+const PLOT_WIDTH: f64 = 720.0;
+const PLOT_HEIGHT: f64 = 240.0;
+const PLOT_MARGIN: f64 = 40.0;
+
+pub struct RunArtifacts {
+    run_id: String,
+    dir: PathBuf,
+    results_dir: PathBuf,
+}
+
+impl RunArtifacts {
+    /// Create `results/<run-id>/` for a new run on `host`, identified by its
+    /// start time.
+    pub fn create(results_dir: &Path, host: &Host) -> Result<Self> {
+        let run_id = Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let dir = results_dir.join(&run_id);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        fs::write(dir.join("host.json"), serde_json::to_string_pretty(host)?)?;
+        Database::open(results_dir)?.record_run(&run_id, host)?;
+        Ok(Self {
+            run_id,
+            dir,
+            results_dir: results_dir.to_path_buf(),
+        })
+    }
+
+    /// Reopen the directory of an earlier run.
+    pub fn open(results_dir: &Path, run_id: &str) -> Result<Self> {
+        let dir = results_dir.join(run_id);
+        if !dir.is_dir() {
+            bail!("No run {} in {}", run_id, results_dir.display());
+        }
+        Ok(Self {
+            run_id: run_id.to_string(),
+            dir,
+            results_dir: results_dir.to_path_buf(),
+        })
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn case_dir(&self, implementation: &str, test_case: &str) -> Result<PathBuf> {
+        let dir = self.dir.join(implementation).join(test_case);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    pub fn read_manifest(&self) -> Result<RunManifest> {
+        let path = self.dir.join("manifest.json");
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Run {} has no manifest; only `all` runs can be resumed", self.run_id))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn write_manifest(&self, manifest: &RunManifest) -> Result<()> {
+        fs::write(self.dir.join("manifest.json"), serde_json::to_string_pretty(manifest)?)?;
+        Ok(())
+    }
+
+    /// File the container logs and each result the container wrote. The
+    /// logs are appended to, since a resumed run adds one container per case.
+    pub fn record_run(&self, result: &TestResult) -> Result<()> {
+        let impl_dir = self.dir.join(&result.implementation);
+        fs::create_dir_all(&impl_dir)?;
+        for (name, content) in [
+            ("container.log", container_log(&result.stdout, &result.stderr)),
+            ("stdout.log", result.stdout.clone()),
+        ] {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(impl_dir.join(name))?
+                .write_all(content.as_bytes())?;
+        }
+
+        if result.cpu_seconds.is_some() || result.peak_memory.is_some() {
+            let path = impl_dir.join("usage.json");
+            let mut usage: Usage = match fs::read_to_string(&path) {
+                Ok(content) => serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?,
+                Err(_) => Usage::default(),
+            };
+            usage.containers += 1;
+            usage.cpu_seconds += result.cpu_seconds.unwrap_or_default();
+            usage.peak_memory = usage.peak_memory.max(result.peak_memory);
+            usage.execution_time += result.execution_time;
+            usage.cases += result.result_files.len();
+            fs::write(&path, serde_json::to_string_pretty(&usage)?)?;
+        }
+
+        for path in &result.result_files {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let Some(test_case) = result_test_case(&file_name, &result.implementation) else {
+                continue;
+            };
+            let case_dir = self.case_dir(&result.implementation, &test_case)?;
+
+            let target = if file_name.ends_with(".gz") { "result.json.gz" } else { "result.json" };
+            fs::copy(path, case_dir.join(target))
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+
+            if let Some(slice) = log_slice(&result.stdout, &test_case) {
+                fs::write(case_dir.join("container.log"), slice)?;
+            }
+
+            let data: Value = serde_json::from_str(&read_result(path)?)?;
+            if let Some(svg) = elevation_plot(&test_case, &data) {
+                fs::write(case_dir.join("elevation.svg"), svg)?;
+            }
+        }
+
+        Database::open(&self.results_dir)?.record_container(&self.run_id, result)?;
+        openmetrics::write(&self.results_dir, &self.run_id)
+    }
+
+    /// Write `<impl>/versions.json`, what the image declared to the health
+    /// check.
+    pub fn record_versions(&self, implementation: &str, versions: &Versions) -> Result<()> {
+        let impl_dir = self.dir.join(implementation);
+        fs::create_dir_all(&impl_dir)?;
+        fs::write(impl_dir.join("versions.json"), serde_json::to_string_pretty(versions)?)?;
+        Database::open(&self.results_dir)?.record_versions(&self.run_id, implementation, versions)
+    }
+
+    /// Write `verdict.json` for every case the validation covered, and
+    /// update the verdict changelog.
+    pub fn record_validation(&self, summary: &ValidationSummary) -> Result<()> {
+        for case in &summary.cases {
+            let case_dir = self.case_dir(&summary.implementation, &case.test_case)?;
+            fs::write(case_dir.join("verdict.json"), serde_json::to_string_pretty::<CaseVerdict>(case)?)?;
+        }
+        Database::open(&self.results_dir)?.record_verdicts(&self.run_id, &summary.implementation, &summary.cases)?;
+        openmetrics::write(&self.results_dir, &self.run_id)?;
+        changelog::write(&self.results_dir)
+    }
+
+    /// Record that the run has finished, and whether it passed.
+    pub fn finish(&self, passed: bool) -> Result<()> {
+        Database::open(&self.results_dir)?.finish_run(&self.run_id, passed)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    /// The `--test-case` the run was limited to, if any
+    pub test_case: Option<String>,
+    /// Every (implementation, case) pair in execution order
+    pub cases: Vec<PlannedCase>,
+    /// The test data schema version passed to containers
+    #[serde(default)]
+    pub schema_version: Option<String>,
+    /// The optional features enabled for each implementation's containers
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedCase {
+    pub implementation: String,
+    pub test_case: String,
+    pub completed: bool,
+}
+
+impl RunManifest {
+    pub fn new(run_id: &str, test_case: Option<&str>, pairs: Vec<(String, String)>) -> Self {
+        RunManifest {
+            run_id: run_id.to_string(),
+            started_at: Utc::now(),
+            test_case: test_case.map(str::to_string),
+            cases: pairs
+                .into_iter()
+                .map(|(implementation, test_case)| PlannedCase {
+                    implementation,
+                    test_case,
+                    completed: false,
+                })
+                .collect(),
+            schema_version: None,
+            features: BTreeMap::new(),
+        }
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at.into()
+    }
+
+    /// Mark every case `result` wrote a result file for, or left out as
+    /// unsupported, as completed.
+    pub fn record(&mut self, result: &TestResult) {
+        let written = result.result_files.iter().filter_map(|path| {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            result_test_case(&file_name, &result.implementation)
+        });
+        for test_case in written.chain(result.unsupported.iter().cloned()) {
+            for case in &mut self.cases {
+                if case.implementation == result.implementation && case.test_case == test_case {
+                    case.completed = true;
+                }
+            }
+        }
+    }
+
+    /// Pairs not yet completed, in execution order.
+    pub fn remaining(&self) -> Vec<(String, String)> {
+        self.cases
+            .iter()
+            .filter(|case| !case.completed)
+            .map(|case| (case.implementation.clone(), case.test_case.clone()))
+            .collect()
+    }
+}
+
+fn container_log(stdout: &str, stderr: &str) -> String {
+    if stderr.is_empty() {
+        stdout.to_string()
+    } else {
+        format!("{}\n--- stderr ---\n{}", stdout, stderr)
+    }
+}
+
+/// The part of stdout for one case: from its `Processing: <case>.json` line
+/// up to the next `Processing:` line, the convention all implementations use.
+fn log_slice(stdout: &str, test_case: &str) -> Option<String> {
+    let marker = format!("Processing: {}.json", test_case);
+    let mut lines = stdout.lines().skip_while(|line| line.trim() != marker);
+    let first = lines.next()?;
+
+    let mut slice = format!("{}\n", first);
+    for line in lines.take_while(|line| !line.trim_start().starts_with("Processing:")) {
+        slice.push_str(line);
+        slice.push('\n');
+    }
+    Some(slice)
+}
+
+/// SVG elevation-vs-time plot of every window's points, or `None` when the
+/// result has no points to draw.
+fn elevation_plot(test_case: &str, result: &Value) -> Option<String> {
+    let parse = |v: &Value| {
+        v.as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+    };
+
+    let windows: Vec<Vec<(DateTime<Utc>, f64)>> = result
+        .get("visibilityWindows")?
+        .as_array()?
+        .iter()
+        .map(|window| {
+            window
+                .get("points")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|p| Some((parse(p.get("time")?)?, p.get("elevation")?.as_f64()?)))
+                .collect()
+        })
+        .filter(|points: &Vec<_>| !points.is_empty())
+        .collect();
+
+    let first = windows.iter().flatten().map(|(t, _)| *t).min()?;
+    let last = windows.iter().flatten().map(|(t, _)| *t).max()?;
+    let span = ((last - first).num_seconds() as f64).max(1.0);
+
+    let plot_width = PLOT_WIDTH - 2.0 * PLOT_MARGIN;
+    let plot_height = PLOT_HEIGHT - 2.0 * PLOT_MARGIN;
+    let x = |t: DateTime<Utc>| PLOT_MARGIN + (t - first).num_seconds() as f64 / span * plot_width;
+    let y = |elevation: f64| PLOT_MARGIN + (1.0 - elevation.clamp(0.0, 90.0) / 90.0) * plot_height;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{PLOT_WIDTH}" height="{PLOT_HEIGHT}" font-family="sans-serif" font-size="11">"#
+    );
+    let _ = writeln!(svg, r#"  <text x="{PLOT_MARGIN}" y="20">{} - elevation (deg)</text>"#, test_case);
+    for elevation in [0.0, 30.0, 60.0, 90.0] {
+        let grid_y = y(elevation);
+        let _ = writeln!(
+            svg,
+            r##"  <line x1="{PLOT_MARGIN}" x2="{}" y1="{grid_y:.1}" y2="{grid_y:.1}" stroke="#ddd"/><text x="5" y="{:.1}">{elevation}</text>"##,
+            PLOT_MARGIN + plot_width,
+            grid_y + 4.0
+        );
+    }
+    for points in &windows {
+        let coords: Vec<String> = points
+            .iter()
+            .map(|(t, elevation)| format!("{:.1},{:.1}", x(*t), y(*elevation)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r##"  <polyline fill="none" stroke="#1f77b4" stroke-width="1.5" points="{}"/>"##,
+            coords.join(" ")
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r#"  <text x="{PLOT_MARGIN}" y="{}">{}</text><text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        PLOT_HEIGHT - 15.0,
+        first.format("%Y-%m-%d %H:%M:%SZ"),
+        PLOT_MARGIN + plot_width,
+        PLOT_HEIGHT - 15.0,
+        last.format("%Y-%m-%d %H:%M:%SZ")
+    );
+    svg.push_str("</svg>\n");
+
+    Some(svg)
+}
@@ -0,0 +1,345 @@
+//! License and provenance audit.
+//!
+//! For each implementation, gathers the library attribution from README
+//! front-matter ([`LibraryInfo`]), the dependencies declared in its build
+//! manifests, and the `libraryName`/`libraryVersion` its latest results
+//! report. Missing or contradictory declarations become findings; the
+//! whole set can be written out as an SBOM-like JSON document.
+
+use crate::library_info::LibraryInfo;
+use crate::{Implementation, Orchestrator, read_result};
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct Sbom {
+    generated: String,
+    components: Vec<Component>,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+struct Component {
+    #[serde(flatten)]
+    library: LibraryInfo,
+    /// `libraryName`/`libraryVersion` pairs reported by the latest results
+    reported: Vec<ReportedLibrary>,
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+struct ReportedLibrary {
+    name: Option<String>,
+    version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Dependency {
+    name: String,
+    /// Version or version requirement as written, or a git tag
+    version: Option<String>,
+    /// Manifest the dependency was declared in
+    source: String,
+    /// Whether the declaration resolves to exactly one release
+    pinned: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub implementation: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+pub fn run(orchestrator: &Orchestrator) -> Result<Sbom> {
+    let mut components = Vec::new();
+    let mut findings = Vec::new();
+
+    for impl_ in orchestrator.discover_implementations()? {
+        let component = Component {
+            library: LibraryInfo::load(&impl_),
+            reported: reported_libraries(orchestrator, &impl_)?,
+            dependencies: declared_dependencies(&impl_.path),
+        };
+        check(&component, &mut findings);
+        components.push(component);
+    }
+
+    Ok(Sbom {
+        generated: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        components,
+        findings,
+    })
+}
+
+fn check(component: &Component, findings: &mut Vec<Finding>) {
+    let library = &component.library;
+    let mut flag = |severity, message: String| {
+        findings.push(Finding {
+            implementation: library.implementation.clone(),
+            severity,
+            message,
+        })
+    };
+
+    if library.license.is_none() {
+        flag(Severity::Error, "No license declared in README front-matter or manifest".to_string());
+    }
+    if library.library.is_none() {
+        flag(Severity::Warning, "No library name in README front-matter".to_string());
+    }
+    if library.repository.is_none() {
+        flag(Severity::Warning, "No upstream repository declared".to_string());
+    }
+
+    match component.reported.as_slice() {
+        [] => flag(Severity::Warning, "No results to check reported library metadata against".to_string()),
+        [reported] => {
+            let (Some(name), Some(version)) = (&reported.name, &reported.version) else {
+                flag(Severity::Error, "Results do not report libraryName and libraryVersion".to_string());
+                return;
+            };
+
+            // Report names may carry a suffix, e.g. "sat_code (Bill Gray)"
+            let key = name.split([' ', '(']).next().unwrap_or(name).to_lowercase();
+            let Some(dependency) = component.dependencies.iter().find(|d| d.name.to_lowercase() == key) else {
+                flag(Severity::Warning, format!("Reported library '{}' is not declared in any manifest", name));
+                return;
+            };
+
+            if !dependency.pinned {
+                flag(
+                    Severity::Warning,
+                    format!(
+                        "'{}' is not pinned in {} ({})",
+                        dependency.name,
+                        dependency.source,
+                        dependency.version.as_deref().unwrap_or("any version")
+                    ),
+                );
+            }
+            if let Some(declared) = &dependency.version
+                && version_matches(declared, version) == Some(false)
+            {
+                flag(
+                    Severity::Error,
+                    format!(
+                        "Results report {} {} but {} declares {}",
+                        name, version, dependency.source, declared
+                    ),
+                );
+            }
+        }
+        reported => flag(
+            Severity::Error,
+            format!(
+                "Results disagree on the library: {}",
+                reported
+                    .iter()
+                    .map(|r| format!(
+                        "{} {}",
+                        r.name.as_deref().unwrap_or("?"),
+                        r.version.as_deref().unwrap_or("?")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+    }
+}
+
+/// Whether a reported version satisfies a declared one, or `None` when the
+/// declaration can't be compared (git branches, complex ranges).
+fn version_matches(declared: &str, reported: &str) -> Option<bool> {
+    let base = declared.trim_start_matches(['=', '^', '~', 'v']);
+    if base.is_empty() || !base.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    // "2.0" covers "2.0.x" releases; an exact declaration must match exactly
+    Some(reported == base || reported.starts_with(&format!("{}.", base)))
+}
+
+/// Distinct library name/version pairs across the latest results.
+fn reported_libraries(orchestrator: &Orchestrator, impl_: &Implementation) -> Result<Vec<ReportedLibrary>> {
+    let mut reported = BTreeSet::new();
+    for path in orchestrator.collect_results(&impl_.name)? {
+        let result: Value = serde_json::from_str(&read_result(&path)?)?;
+        let field = |name: &str| {
+            result
+                .pointer(&format!("/metadata/{}", name))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        reported.insert(ReportedLibrary {
+            name: field("libraryName"),
+            version: field("libraryVersion"),
+        });
+    }
+    Ok(reported.into_iter().collect())
+}
+
+/// Dependencies from every manifest format the implementations use.
+fn declared_dependencies(dir: &Path) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return dependencies;
+    };
+
+    let mut files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    files.sort();
+
+    for path in files {
+        let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let parsed = match file_name.as_str() {
+            "requirements.txt" => requirements_txt(&content),
+            "package.json" => package_json(&content),
+            "Cargo.toml" => cargo_toml(&content),
+            "CMakeLists.txt" => cmake_fetch_content(&content),
+            _ if file_name.ends_with(".csproj") => csproj(&content),
+            _ => continue,
+        };
+        dependencies.extend(parsed.into_iter().map(|(name, version, pinned)| Dependency {
+            name,
+            version,
+            source: file_name.clone(),
+            pinned,
+        }));
+    }
+
+    dependencies
+}
+
+type Declared = (String, Option<String>, bool);
+
+fn requirements_txt(content: &str) -> Vec<Declared> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .map(|line| match line.split_once("==") {
+            Some((name, version)) => (name.trim().to_string(), Some(version.trim().to_string()), true),
+            None => {
+                let name_end = line.find(['<', '>', '~', '!', '=', ' ']).unwrap_or(line.len());
+                let requirement = line[name_end..].trim();
+                (
+                    line[..name_end].to_string(),
+                    (!requirement.is_empty()).then(|| requirement.to_string()),
+                    false,
+                )
+            }
+        })
+        .collect()
+}
+
+fn package_json(content: &str) -> Vec<Declared> {
+    let Ok(package) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    package
+        .get("dependencies")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, version)| {
+            let version = version.as_str().unwrap_or_default().to_string();
+            let pinned = version.chars().next().is_some_and(|c| c.is_ascii_digit())
+                && !version.contains(['x', '*', ' ', '|']);
+            (name.clone(), Some(version), pinned)
+        })
+        .collect()
+}
+
+fn cargo_toml(content: &str) -> Vec<Declared> {
+    let mut dependencies = Vec::new();
+    let mut in_dependencies = false;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_dependencies = line == "[dependencies]";
+            continue;
+        }
+        let Some((name, spec)) = line.split_once('=').filter(|_| in_dependencies) else {
+            continue;
+        };
+
+        let spec = spec.trim();
+        let version = if spec.starts_with('{') {
+            spec.split("version")
+                .nth(1)
+                .and_then(|rest| rest.split('"').nth(1))
+                .map(str::to_string)
+        } else {
+            Some(spec.trim_matches('"').to_string())
+        };
+        // Cargo requirements are caret ranges unless written as "=x.y.z"
+        let pinned = version.as_deref().is_some_and(|v| v.starts_with('='));
+        dependencies.push((name.trim().to_string(), version, pinned));
+    }
+
+    dependencies
+}
+
+fn csproj(content: &str) -> Vec<Declared> {
+    let attribute = |tag: &str, name: &str| {
+        tag.split(&format!("{}=\"", name))
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .map(str::to_string)
+    };
+
+    content
+        .split("<PackageReference")
+        .skip(1)
+        .filter_map(|tag| {
+            let tag = tag.split('>').next()?;
+            let version = attribute(tag, "Version");
+            // NuGet resolves a bare version to exactly that release when it
+            // exists; floating versions and ranges can drift
+            let pinned = version.as_deref().is_some_and(|v| !v.contains(['*', ',', '(']));
+            Some((attribute(tag, "Include")?, version, pinned))
+        })
+        .collect()
+}
+
+/// `FetchContent_Declare` blocks; the git tag serves as the version.
+fn cmake_fetch_content(content: &str) -> Vec<Declared> {
+    content
+        .split("FetchContent_Declare(")
+        .skip(1)
+        .filter_map(|block| {
+            let block = block.split(')').next()?;
+            let mut words = block.split_whitespace();
+            let name = words.next()?.to_string();
+
+            let words: Vec<&str> = words.collect();
+            let tag = words
+                .iter()
+                .position(|w| *w == "GIT_TAG")
+                .and_then(|i| words.get(i + 1))
+                .map(|t| t.to_string());
+            // Branch names move; tags and commit hashes don't
+            let pinned = tag
+                .as_deref()
+                .is_some_and(|t| t.starts_with('v') || t.chars().all(|c| c.is_ascii_hexdigit()));
+            Some((name, tag, pinned))
+        })
+        .collect()
+}
@@ -0,0 +1,197 @@
+//! Regressions against a baseline run, for `validate --baseline`.
+//!
+//! The baseline is a run ID under `results/`, or any run directory (e.g. one
+//! copied from CI), laid out as in [`artifacts`](crate::artifacts). Each
+//! implementation's latest results are compared with the baseline's for the
+//! same cases, flagging:
+//!
+//! - cases that matched the reference in the baseline and no longer do
+//! - baseline windows no current window overlaps
+//! - execution times that grew by more than a threshold percentage
+//!
+//! Baseline verdicts come from the run's `verdict.json`, or are judged
+//! against the reference results as `report` does when it has none.
+
+use crate::events::Verdict;
+use crate::history;
+use crate::report::Judge;
+use crate::{Orchestrator, ValidationSummary, VisibilityResult, read_result, result_test_case};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct Baseline {
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Regression {
+    pub implementation: String,
+    pub test_case: String,
+    #[serde(flatten)]
+    pub kind: Kind,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Kind {
+    /// Matched the reference in the baseline, but not now
+    NewlyFailing,
+    /// Baseline windows with no overlapping window now
+    MissingWindows { windows: Vec<Window> },
+    /// Execution time up by more than the threshold
+    Slower { baseline: f64, current: f64, percent: f64 },
+}
+
+#[derive(Debug, Serialize)]
+pub struct Window {
+    pub start: String,
+    pub end: String,
+}
+
+impl Baseline {
+    /// `run` as a directory, or as a run ID in `results_dir`.
+    pub fn open(results_dir: &Path, run: &str) -> Result<Self> {
+        let dir = if Path::new(run).is_dir() {
+            PathBuf::from(run)
+        } else {
+            results_dir.join(run)
+        };
+        if !dir.is_dir() {
+            bail!("No baseline run {}: not a run ID in {} or a directory", run, results_dir.display());
+        }
+        Ok(Baseline { dir })
+    }
+
+    /// The baseline's result for `test_case`, if it has one.
+    fn result(&self, implementation: &str, test_case: &str) -> Result<Option<VisibilityResult>> {
+        let case_dir = self.dir.join(implementation).join(test_case);
+        let Some(path) = ["result.json", "result.json.gz"].iter().map(|name| case_dir.join(name)).find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let content = read_result(&path)?;
+        Ok(Some(serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?))
+    }
+
+    fn verdict(&self, implementation: &str, result: &VisibilityResult, judge: &Judge) -> Result<Verdict> {
+        let path = self.dir.join(implementation).join(&result.test_case).join("verdict.json");
+        if path.is_file() {
+            let case: history::Case = serde_json::from_str(&fs::read_to_string(&path)?)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            return Ok(case.verdict);
+        }
+        Ok(judge.judge(result)?.verdict)
+    }
+}
+
+/// Regressions of `summary`'s implementation against `baseline`, with
+/// execution times flagged beyond `threshold` percent.
+pub fn compare(
+    orchestrator: &Orchestrator,
+    summary: &ValidationSummary,
+    baseline: &Baseline,
+    threshold: f64,
+) -> Result<Vec<Regression>> {
+    let implementation = &summary.implementation;
+    let judge = Judge::new(orchestrator)?;
+    let mut regressions = Vec::new();
+    for path in orchestrator.collect_results(implementation)? {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let Some(test_case) = result_test_case(&file_name, implementation) else {
+            continue;
+        };
+        let Some(before) = baseline.result(implementation, &test_case)? else {
+            continue;
+        };
+        let now: VisibilityResult = serde_json::from_str(&read_result(&path)?)?;
+        let mut flag = |kind| {
+            regressions.push(Regression {
+                implementation: implementation.clone(),
+                test_case: test_case.clone(),
+                kind,
+            })
+        };
+
+        let verdict = summary.cases.iter().find(|case| case.test_case == test_case).map(|case| case.verdict);
+        if verdict == Some(Verdict::Mismatch) && baseline.verdict(implementation, &before, &judge)? == Verdict::Match {
+            flag(Kind::NewlyFailing);
+        }
+
+        let current = spans(&now.visibility_windows);
+        let missing: Vec<Window> = before
+            .visibility_windows
+            .iter()
+            .filter(|window| {
+                span(window).is_some_and(|(start, end)| !current.iter().any(|(other_start, other_end)| *other_start <= end && start <= *other_end))
+            })
+            .map(|window| Window {
+                start: window["start"].as_str().unwrap_or_default().to_string(),
+                end: window["end"].as_str().unwrap_or_default().to_string(),
+            })
+            .collect();
+        if !missing.is_empty() {
+            flag(Kind::MissingWindows { windows: missing });
+        }
+
+        if let (Some(baseline), Some(current)) = (before.execution_time, now.execution_time)
+            && baseline > 0.0
+        {
+            let percent = (current - baseline) / baseline * 100.0;
+            if percent > threshold {
+                flag(Kind::Slower {
+                    baseline,
+                    current,
+                    percent,
+                });
+            }
+        }
+    }
+    Ok(regressions)
+}
+
+/// Print `regressions` against `baseline`.
+pub fn print(regressions: &[Regression], baseline: &Baseline) {
+    println!("{}", format!("Regressions against {}:", baseline.dir.display()).bold());
+    if regressions.is_empty() {
+        println!("  {} None", "✓".green());
+    }
+    for regression in regressions {
+        let what = match &regression.kind {
+            Kind::NewlyFailing => "matched in the baseline, now mismatches".to_string(),
+            Kind::MissingWindows { windows } => {
+                let spans: Vec<String> = windows.iter().map(|window| format!("{} .. {}", window.start, window.end)).collect();
+                format!("{} window(s) missing: {}", windows.len(), spans.join(", "))
+            }
+            Kind::Slower {
+                baseline,
+                current,
+                percent,
+            } => format!("{:.3}s, was {:.3}s ({:+.0}%)", current, baseline, percent),
+        };
+        println!(
+            "  {} {} {} - {}",
+            "✗".red(),
+            regression.implementation.bright_white(),
+            regression.test_case.bright_white(),
+            what
+        );
+    }
+}
+
+fn spans(windows: &[Value]) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    windows.iter().filter_map(span).collect()
+}
+
+fn span(window: &Value) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let parse = |field: &str| {
+        DateTime::parse_from_rfc3339(window[field].as_str()?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    Some((parse("start")?, parse("end")?))
+}
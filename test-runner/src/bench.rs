@@ -0,0 +1,202 @@
+//! Benchmark mode.
+//!
+//! Runs each implementation's container several times back to back and
+//! reports wall-clock statistics. With `--pin-cpus`, measurements are meant
+//! to be publishable: every container is pinned to the same dedicated cores
+//! (`docker run --cpuset-cpus`), only one container runs at a time, and the
+//! host must be quiet before each measurement.
+
+use crate::runtime::ContainerRuntime;
+use crate::{Implementation, Orchestrator};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+/// How long to sample `/proc/stat` when checking that pinned cores are idle.
+const IDLE_SAMPLE: Duration = Duration::from_secs(1);
+
+/// Highest busy fraction a pinned core may show before a measurement.
+const MAX_CORE_BUSY: f64 = 0.05;
+
+#[derive(Debug, Serialize)]
+pub struct BenchStats {
+    pub implementation: String,
+    /// Container wall-clock times in seconds, one per measured iteration
+    pub times: Vec<f64>,
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl BenchStats {
+    pub fn new(implementation: &str, times: Vec<f64>) -> Self {
+        let mut sorted = times.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let variance = sorted.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+
+        BenchStats {
+            implementation: implementation.to_string(),
+            min: sorted[0],
+            median,
+            mean,
+            stddev: variance.sqrt(),
+            times,
+        }
+    }
+}
+
+/// Fairness controls for pinned runs.
+pub struct Pinning {
+    /// Cores in `--cpuset-cpus` syntax, e.g. "3" or "2-3"
+    pub cpuset: String,
+    /// Highest 1-minute load average per online CPU tolerated before measuring
+    pub max_load: f64,
+}
+
+impl Pinning {
+    /// Pin to `cpuset`, or by default to the last online core, which is
+    /// least likely to be handling interrupts.
+    pub fn new(cpuset: Option<String>, max_load: f64) -> Result<Self> {
+        let cpuset = match cpuset {
+            Some(cpuset) => cpuset,
+            None => {
+                let cores = cpu_list(&fs::read_to_string("/sys/devices/system/cpu/online")?)?;
+                match cores.as_slice() {
+                    [] | [_] => bail!("Pinning needs at least two online CPUs to leave one dedicated"),
+                    [.., last] => last.to_string(),
+                }
+            }
+        };
+        cpu_list(&cpuset)?;
+        Ok(Pinning { cpuset, max_load })
+    }
+
+    /// Fail unless the host is quiet enough to measure on.
+    pub fn check_host(&self, runtime: &dyn ContainerRuntime, implementations: &[Implementation]) -> Result<()> {
+        let running: Vec<&str> = runtime
+            .running_images()?
+            .into_iter()
+            .filter_map(|running| implementations.iter().find(|impl_| impl_.image_name == running))
+            .map(|impl_| impl_.image_name.as_str())
+            .collect();
+        if !running.is_empty() {
+            bail!("Other test containers are running ({}); wait for them to finish", running.join(", "));
+        }
+
+        let online = cpu_list(&fs::read_to_string("/sys/devices/system/cpu/online")?)?.len();
+        let load: f64 = fs::read_to_string("/proc/loadavg")?
+            .split_whitespace()
+            .next()
+            .context("Empty /proc/loadavg")?
+            .parse()?;
+        let per_cpu = load / online as f64;
+        if per_cpu > self.max_load {
+            bail!(
+                "Host load is {:.2} per CPU (limit {:.2}); stop other work or raise --max-load",
+                per_cpu,
+                self.max_load
+            );
+        }
+
+        for (core, busy) in core_busy(&cpu_list(&self.cpuset)?)? {
+            if busy > MAX_CORE_BUSY {
+                bail!("CPU {} is {:.0}% busy; choose other cores with --cpuset", core, busy * 100.0);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run `iterations` measured container runs per implementation, after
+/// `warmup` unmeasured ones, one container at a time.
+pub fn run(
+    orchestrator: &Orchestrator,
+    implementations: &[Implementation],
+    cases: Option<&[String]>,
+    iterations: usize,
+    warmup: usize,
+    pinning: Option<&Pinning>,
+) -> Result<Vec<BenchStats>> {
+    let cpuset = pinning.map(|p| p.cpuset.as_str());
+    let mut stats = Vec::new();
+
+    for impl_ in implementations {
+        let mut times = Vec::new();
+        for iteration in 0..warmup + iterations {
+            if let Some(pinning) = pinning {
+                pinning.check_host(orchestrator.runtime, implementations)?;
+            }
+
+            let result = orchestrator.run_container(impl_, cases, cpuset)?;
+            if let Some(e) = orchestrator.killed_error(impl_, &result, cases) {
+                return Err(e.into());
+            }
+            if !result.success {
+                bail!("{} failed during benchmarking:\n{}", impl_.name, result.stderr);
+            }
+            if iteration >= warmup {
+                times.push(result.execution_time);
+            }
+        }
+        stats.push(BenchStats::new(&impl_.name, times));
+    }
+
+    Ok(stats)
+}
+
+/// Parse a Linux CPU list ("0-3,6") into core numbers.
+fn cpu_list(list: &str) -> Result<Vec<usize>> {
+    let mut cores = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        let parse = |s: &str| s.parse::<usize>().with_context(|| format!("Invalid CPU list: {}", list.trim()));
+        match part.split_once('-') {
+            Some((first, last)) => cores.extend(parse(first)?..=parse(last)?),
+            None => cores.push(parse(part)?),
+        }
+    }
+    Ok(cores)
+}
+
+/// Busy fraction of each core over [`IDLE_SAMPLE`], from `/proc/stat`.
+fn core_busy(cores: &[usize]) -> Result<Vec<(usize, f64)>> {
+    let before = fs::read_to_string("/proc/stat")?;
+    thread::sleep(IDLE_SAMPLE);
+    let after = fs::read_to_string("/proc/stat")?;
+
+    cores
+        .iter()
+        .map(|&core| {
+            let (busy_0, total_0) = core_ticks(&before, core)?;
+            let (busy_1, total_1) = core_ticks(&after, core)?;
+            let total = (total_1 - total_0).max(1) as f64;
+            Ok((core, (busy_1 - busy_0) as f64 / total))
+        })
+        .collect()
+}
+
+/// (busy, total) jiffies for one `cpuN` line of `/proc/stat`.
+fn core_ticks(stat: &str, core: usize) -> Result<(u64, u64)> {
+    let label = format!("cpu{}", core);
+    let line = stat
+        .lines()
+        .find(|line| line.split_whitespace().next() == Some(label.as_str()))
+        .with_context(|| format!("CPU {} is not online", core))?;
+
+    let ticks: Vec<u64> = line.split_whitespace().skip(1).filter_map(|t| t.parse().ok()).collect();
+    let total: u64 = ticks.iter().sum();
+    // idle and iowait are the 4th and 5th columns
+    let idle = ticks.get(3).copied().unwrap_or(0) + ticks.get(4).copied().unwrap_or(0);
+    Ok((total - idle, total))
+}
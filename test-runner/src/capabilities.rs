@@ -0,0 +1,228 @@
+//! Optional schema features and which implementations support them.
+//!
+//! An implementation declares the optional features it handles in its
+//! README front-matter (see [`library_info`](crate::library_info)):
+//!
+//! ```text
+//! features: optical, time-format, earth-model, isl, trajectory
+//! ```
+//!
+//! Before a run, each case that uses a feature the implementation doesn't
+//! declare is either downgraded, when the feature only adds output fields
+//! (the field is removed and the rest of the case still validates), or
+//! left out, when it changes the geometry. Left-out cases are reported as
+//! unsupported rather than failed.
+//!
+//! Containers are told the schema version and the features they declared
+//! through [`SCHEMA_VERSION_VAR`] and [`FEATURES_VAR`].
+
+use crate::library_info::front_matter;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the version of `test-data/schema.json`
+pub const SCHEMA_VERSION_VAR: &str = "VISIBILITY_SCHEMA_VERSION";
+/// Environment variable holding the comma-separated features enabled for
+/// the container, e.g. `optical,isl`
+pub const FEATURES_VAR: &str = "VISIBILITY_FEATURES";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    /// `optical`: illumination conditions per point
+    Optical,
+    /// `timeFormat`: unix or GPS point timestamps
+    TimeFormat,
+    /// `earthModel`: WGS-84 instead of WGS-72 constants
+    EarthModel,
+    /// `observerSatellite`: inter-satellite links
+    Isl,
+    /// `observerTrajectory`: moving ground observers
+    Trajectory,
+}
+
+const FEATURES: [Feature; 5] = [
+    Feature::Optical,
+    Feature::TimeFormat,
+    Feature::EarthModel,
+    Feature::Isl,
+    Feature::Trajectory,
+];
+
+impl Feature {
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Optical => "optical",
+            Feature::TimeFormat => "time-format",
+            Feature::EarthModel => "earth-model",
+            Feature::Isl => "isl",
+            Feature::Trajectory => "trajectory",
+        }
+    }
+
+    /// The case field that turns the feature on.
+    fn field(self) -> &'static str {
+        match self {
+            Feature::Optical => "optical",
+            Feature::TimeFormat => "timeFormat",
+            Feature::EarthModel => "earthModel",
+            Feature::Isl => "observerSatellite",
+            Feature::Trajectory => "observerTrajectory",
+        }
+    }
+
+    fn used_by(self, case: &Value) -> bool {
+        match &case[self.field()] {
+            Value::Null | Value::Bool(false) => false,
+            Value::String(s) => !matches!(s.as_str(), "rfc3339" | "wgs72"),
+            _ => true,
+        }
+    }
+
+    /// Whether dropping the feature leaves a case that still validates
+    /// against the reference, i.e. the feature only adds output.
+    fn downgradable(self) -> bool {
+        matches!(self, Feature::Optical | Feature::TimeFormat)
+    }
+}
+
+/// Features an implementation declares, from the `features` front-matter
+/// line of its README. Unknown names are ignored.
+pub fn declared(impl_path: &Path) -> BTreeSet<Feature> {
+    let Ok(readme) = fs::read_to_string(impl_path.join("README.md")) else {
+        return BTreeSet::new();
+    };
+    front_matter(&readme)
+        .into_iter()
+        .filter(|(key, _)| *key == "features")
+        .flat_map(|(_, value)| value.split(',').map(str::trim))
+        .filter_map(|name| FEATURES.into_iter().find(|feature| feature.name() == name))
+        .collect()
+}
+
+/// The test data schema version, if `schema.json` declares one. It's kept
+/// in `$comment` (`"version 1.0.0"`), which validators ignore.
+pub fn schema_version(test_data_dir: &Path) -> Result<Option<String>> {
+    let path = test_data_dir.join("schema.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let schema: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(schema["$comment"]
+        .as_str()
+        .and_then(|comment| comment.strip_prefix("version "))
+        .map(|version| version.trim().to_string()))
+}
+
+/// How a case runs for an implementation.
+#[derive(Debug)]
+pub enum Plan {
+    /// As is
+    Run,
+    /// With these features removed
+    Downgraded(Vec<Feature>),
+    /// Not at all, for lack of these features
+    Unsupported(Vec<Feature>),
+}
+
+/// Plan `case` for an implementation supporting `supported`, removing
+/// downgraded features from it.
+pub fn plan(case: &mut Value, supported: &BTreeSet<Feature>) -> Plan {
+    let missing: Vec<Feature> = FEATURES
+        .into_iter()
+        .filter(|feature| feature.used_by(case) && !supported.contains(feature))
+        .collect();
+
+    if missing.is_empty() {
+        return Plan::Run;
+    }
+    let blocking: Vec<Feature> = missing.iter().copied().filter(|feature| !feature.downgradable()).collect();
+    if !blocking.is_empty() {
+        return Plan::Unsupported(blocking);
+    }
+    if let Some(fields) = case.as_object_mut() {
+        for feature in &missing {
+            fields.remove(feature.field());
+        }
+    }
+    Plan::Downgraded(missing)
+}
+
+/// The cases of `cases_dir` as planned for an implementation.
+pub struct Staged {
+    /// Cases to mount for the implementation, or `None` when every case
+    /// runs as is
+    pub cases_dir: Option<PathBuf>,
+    pub downgraded: Vec<(String, Vec<Feature>)>,
+    pub unsupported: Vec<(String, Vec<Feature>)>,
+}
+
+/// Plan every case in `cases_dir` for an implementation supporting
+/// `supported`. Unless all of them run as is, the cases it can run,
+/// downgraded as needed, are written to `<staging_dir>/cases`.
+pub fn stage(cases_dir: &Path, staging_dir: &Path, supported: &BTreeSet<Feature>) -> Result<Staged> {
+    let planned = plan_all(cases_dir, supported)?;
+    let mut staged = Staged {
+        cases_dir: None,
+        downgraded: Vec::new(),
+        unsupported: Vec::new(),
+    };
+    if planned.iter().all(|(_, _, plan)| matches!(plan, Plan::Run)) {
+        return Ok(staged);
+    }
+
+    let staged_dir = staging_dir.join("cases");
+    if staged_dir.exists() {
+        fs::remove_dir_all(&staged_dir).with_context(|| format!("Failed to clear {}", staged_dir.display()))?;
+    }
+    fs::create_dir_all(&staged_dir).with_context(|| format!("Failed to create {}", staged_dir.display()))?;
+    for (path, case, plan) in planned {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        match plan {
+            Plan::Unsupported(features) => {
+                staged.unsupported.push((name, features));
+                continue;
+            }
+            Plan::Downgraded(features) => staged.downgraded.push((name, features)),
+            Plan::Run => {}
+        }
+        fs::write(staged_dir.join(path.file_name().unwrap_or_default()), serde_json::to_string_pretty(&case)?)?;
+    }
+    staged.cases_dir = Some(staged_dir);
+    Ok(staged)
+}
+
+/// Cases in `cases_dir` an implementation supporting `supported` can't
+/// run, with the features each lacks.
+pub fn unsupported(cases_dir: &Path, supported: &BTreeSet<Feature>) -> Result<Vec<(String, Vec<Feature>)>> {
+    Ok(plan_all(cases_dir, supported)?
+        .into_iter()
+        .filter_map(|(path, _, plan)| match plan {
+            Plan::Unsupported(features) => Some((path.file_stem()?.to_string_lossy().to_string(), features)),
+            _ => None,
+        })
+        .collect())
+}
+
+fn plan_all(cases_dir: &Path, supported: &BTreeSet<Feature>) -> Result<Vec<(PathBuf, Value, Plan)>> {
+    let mut planned = Vec::new();
+    for entry in fs::read_dir(cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let mut case: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let plan = plan(&mut case, supported);
+        planned.push((path, case, plan));
+    }
+    planned.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(planned)
+}
+
+pub fn names<'a>(features: impl IntoIterator<Item = &'a Feature>) -> String {
+    features.into_iter().map(|feature| feature.name()).collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,521 @@
+//! Catalog of the test cases, for `describe-cases`, and the short listing
+//! of `list-tests`.
+//!
+//! One entry per case file, once TLE set references are resolved: the
+//! satellite and its orbit (see [`orbit`](crate::orbit)), the observer with
+//! a map thumbnail, the time window, the reference's window count and pass
+//! length, and the case's difficulty and tags. The output has no timestamp,
+//! so regenerating it after the cases change is the only thing that changes
+//! it, and `--check` can fail CI when the committed catalog is stale.
+//!
+//! The HTML thumbnail is an inline SVG graticule with the observer's
+//! position (or a moving observer's track) on it. Markdown links to the
+//! position on OpenStreetMap instead.
+
+use crate::orbit::Elements;
+use crate::report::{self, ReportFormat, STYLE, THEME_SCRIPT, escape};
+use crate::{Orchestrator, REFERENCE_IMPLEMENTATION, case_names, case_tags};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+
+/// Thumbnail size in pixels, two degrees of longitude or latitude per pixel
+const MAP_WIDTH: f64 = 180.0;
+const MAP_HEIGHT: f64 = 90.0;
+
+struct Entry {
+    name: String,
+    description: String,
+    satellite: String,
+    orbit: Option<Elements>,
+    observer: Observer,
+    /// Start and end as written, and the span in hours
+    start: String,
+    end: String,
+    hours: Option<f64>,
+    step: Option<f64>,
+    min_elevation: Option<f64>,
+    reference: Option<Passes>,
+    difficulty: Option<String>,
+    tags: Vec<String>,
+}
+
+enum Observer {
+    /// A ground station: name, latitude, longitude, altitude in meters
+    Fixed { name: Option<String>, latitude: f64, longitude: f64, altitude: f64 },
+    /// A ship or aircraft, as (latitude, longitude) waypoints
+    Moving { waypoints: Vec<(f64, f64)> },
+    /// Another satellite, for inter-satellite links
+    Satellite { name: String },
+    Unknown,
+}
+
+/// The reference result's windows.
+struct Passes {
+    count: usize,
+    /// Minutes
+    mean: f64,
+    longest: f64,
+}
+
+/// A case as `list-tests` shows it.
+#[derive(Debug, Serialize)]
+pub struct Listing {
+    pub name: String,
+    pub satellite: String,
+    /// Station name or coordinates, "Moving", or the observing satellite
+    pub observer: String,
+    /// Where a fixed observer is, or a moving one starts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    pub start: String,
+    pub end: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hours: Option<f64>,
+    /// Seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+    pub tags: Vec<String>,
+}
+
+/// The catalog of every case in `format`.
+pub fn generate(orchestrator: &Orchestrator, format: ReportFormat) -> Result<String> {
+    let entries = entries(orchestrator)?;
+    Ok(match format {
+        ReportFormat::Markdown => markdown(&entries),
+        ReportFormat::Html => html(&entries),
+    })
+}
+
+/// Every case, in name order.
+pub fn list(orchestrator: &Orchestrator) -> Result<Vec<Listing>> {
+    Ok(entries(orchestrator)?
+        .into_iter()
+        .map(|entry| {
+            let position = position(&entry.observer);
+            Listing {
+                observer: observer_name(&entry.observer),
+                latitude: position.map(|(latitude, _)| latitude),
+                longitude: position.map(|(_, longitude)| longitude),
+                name: entry.name,
+                satellite: entry.satellite,
+                start: entry.start,
+                end: entry.end,
+                hours: entry.hours,
+                step: entry.step,
+                tags: entry.tags,
+            }
+        })
+        .collect())
+}
+
+pub fn print_list(listings: &[Listing]) {
+    if listings.is_empty() {
+        println!("  {} No test cases", "○".dimmed());
+        return;
+    }
+    let rows: Vec<[String; 6]> = listings
+        .iter()
+        .map(|listing| {
+            [
+                listing.name.clone(),
+                listing.satellite.clone(),
+                listing.observer.clone(),
+                hours(listing.hours),
+                listing.step.map_or("-".to_string(), |step| format!("{} s", step)),
+                listing.tags.join(", "),
+            ]
+        })
+        .collect();
+    let headers = ["Test case", "Satellite", "Observer", "Duration", "Step", "Tags"];
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([headers[column].len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |cells: [&str; 6]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(headers).bold());
+    for row in &rows {
+        println!("{}", line([&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]]));
+    }
+    println!();
+    println!("{} test case(s)", listings.len().to_string().bright_white());
+}
+
+fn entries(orchestrator: &Orchestrator) -> Result<Vec<Entry>> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let reference_dir = orchestrator.test_data_dir.join("reference-results");
+    let mut entries = Vec::new();
+    for name in case_names(&cases_dir)? {
+        let path = cases_dir.join(format!("{}.json", name));
+        let case: Value = serde_json::from_str(&fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let reference = fs::read_to_string(reference_dir.join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, name)))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+        entries.push(entry(name, &case, reference.as_ref()));
+    }
+    Ok(entries)
+}
+
+fn entry(name: String, case: &Value, reference: Option<&Value>) -> Entry {
+    let text = |pointer: &str| case.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+    let window = &case["timeWindow"];
+    let time = |field: &str| DateTime::parse_from_rfc3339(window[field].as_str()?).ok().map(|time| time.with_timezone(&Utc));
+    let hours = time("start").zip(time("end")).map(|(start, end)| (end - start).num_seconds() as f64 / 3600.0);
+
+    let observer = if let Some(observer) = case["observer"].as_object() {
+        Observer::Fixed {
+            name: observer.get("name").and_then(Value::as_str).map(str::to_string),
+            latitude: observer.get("latitude").and_then(Value::as_f64).unwrap_or_default(),
+            longitude: observer.get("longitude").and_then(Value::as_f64).unwrap_or_default(),
+            altitude: observer.get("altitude").and_then(Value::as_f64).unwrap_or_default(),
+        }
+    } else if let Some(waypoints) = case["observerTrajectory"].as_array() {
+        Observer::Moving {
+            waypoints: waypoints
+                .iter()
+                .filter_map(|waypoint| Some((waypoint["latitude"].as_f64()?, waypoint["longitude"].as_f64()?)))
+                .collect(),
+        }
+    } else if case["observerSatellite"].is_object() {
+        let satellite = &case["observerSatellite"];
+        Observer::Satellite {
+            name: satellite["name"]
+                .as_str()
+                .or_else(|| satellite["tle"][0].as_str())
+                .unwrap_or("unnamed")
+                .trim()
+                .to_string(),
+        }
+    } else {
+        Observer::Unknown
+    };
+
+    Entry {
+        description: text("/description").unwrap_or_default(),
+        satellite: text("/satellite/name")
+            .or_else(|| text("/satellite/tle/0").map(|line| line.trim().to_string()))
+            .unwrap_or_else(|| "-".to_string()),
+        orbit: Elements::of_case(case),
+        observer,
+        start: window["start"].as_str().unwrap_or("-").to_string(),
+        end: window["end"].as_str().unwrap_or("-").to_string(),
+        hours,
+        step: window["step"].as_f64(),
+        min_elevation: case["minElevation"].as_f64(),
+        reference: reference.map(passes),
+        difficulty: text("/metadata/difficulty"),
+        tags: case_tags(case),
+        name,
+    }
+}
+
+fn passes(reference: &Value) -> Passes {
+    let minutes: Vec<f64> = reference["visibilityWindows"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|window| {
+            window["duration"].as_f64().or_else(|| {
+                let time = |field: &str| DateTime::parse_from_rfc3339(window[field].as_str()?).ok();
+                Some((time("end")? - time("start")?).num_seconds() as f64)
+            })
+        })
+        .map(|seconds| seconds / 60.0)
+        .collect();
+    Passes {
+        count: minutes.len(),
+        mean: if minutes.is_empty() { 0.0 } else { minutes.iter().sum::<f64>() / minutes.len() as f64 },
+        longest: minutes.iter().copied().fold(0.0, f64::max),
+    }
+}
+
+fn markdown(entries: &[Entry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Test Cases");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "<!-- Generated by `visibility-test-runner describe-cases`. Edit the case files in test-data/cases/ and regenerate. -->"
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Case | Satellite | Regime | Observer | Time window | Reference passes | Difficulty |");
+    let _ = writeln!(out, "|------|-----------|--------|----------|-------------|------------------|------------|");
+    for entry in entries {
+        let _ = writeln!(
+            out,
+            "| [{0}](#{0}) | {1} | {2} | {3} | {4} | {5} | {6} |",
+            entry.name,
+            entry.satellite,
+            regime(entry),
+            observer_name(&entry.observer),
+            hours(entry.hours),
+            pass_summary(entry.reference.as_ref()),
+            entry.difficulty.as_deref().unwrap_or("-")
+        );
+    }
+
+    for entry in entries {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## {}", entry.name);
+        let _ = writeln!(out);
+        if !entry.description.is_empty() {
+            let _ = writeln!(out, "{}", entry.description);
+            let _ = writeln!(out);
+        }
+        for (label, value) in details(entry) {
+            let _ = writeln!(out, "- **{}:** {}", label, value);
+        }
+        if let Some((latitude, longitude)) = position(&entry.observer) {
+            let _ = writeln!(
+                out,
+                "- **Map:** [OpenStreetMap](https://www.openstreetmap.org/?mlat={0:.4}&mlon={1:.4}#map=6/{0:.4}/{1:.4})",
+                latitude, longitude
+            );
+        }
+    }
+    out
+}
+
+fn html(entries: &[Entry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>Test Cases</title>");
+    let _ = writeln!(out, "<style>\n{}\n</style>", STYLE);
+    let _ = writeln!(out, "<script>\n{}\n</script>", THEME_SCRIPT);
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<button id=\"theme-toggle\" type=\"button\">Toggle dark mode</button>");
+    let _ = writeln!(out, "<h1>Test Cases</h1>");
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Case</th><th>Map</th><th>Satellite</th><th>Regime</th><th>Observer</th><th>Time window</th><th>Reference passes</th><th>Difficulty</th></tr>"
+    );
+    for entry in entries {
+        let _ = writeln!(
+            out,
+            "<tr><td><a href=\"#{0}\">{0}</a></td><td>{1}</td><td>{2}</td><td>{3}</td><td>{4}</td><td>{5}</td><td>{6}</td><td>{7}</td></tr>",
+            escape(&entry.name),
+            map(&entry.observer),
+            escape(&entry.satellite),
+            regime(entry),
+            escape(&observer_name(&entry.observer)),
+            hours(entry.hours),
+            pass_summary(entry.reference.as_ref()),
+            escape(entry.difficulty.as_deref().unwrap_or("-"))
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    for entry in entries {
+        let _ = writeln!(out, "<h2 id=\"{0}\">{0}</h2>", escape(&entry.name));
+        if !entry.description.is_empty() {
+            let _ = writeln!(out, "<p>{}</p>", escape(&entry.description));
+        }
+        out.push_str(&map(&entry.observer));
+        let _ = writeln!(out, "<ul>");
+        for (label, value) in details(entry) {
+            let _ = writeln!(out, "<li><strong>{}:</strong> {}</li>", label, escape(&value));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
+
+/// Labelled details of `entry`, in the order both formats list them.
+fn details(entry: &Entry) -> Vec<(&'static str, String)> {
+    let mut details = Vec::new();
+    details.push((
+        "Satellite",
+        match &entry.orbit {
+            Some(orbit) => format!("{}, {}. {}", entry.satellite, orbit.regime(), report::orbit_label(orbit)),
+            None => entry.satellite.clone(),
+        },
+    ));
+    details.push((
+        "Observer",
+        match &entry.observer {
+            Observer::Fixed {
+                name,
+                latitude,
+                longitude,
+                altitude,
+            } => format!(
+                "{}{}, {}, {:.0} m",
+                name.as_deref().map_or(String::new(), |name| format!("{}, ", name)),
+                coordinate(*latitude, 'N', 'S'),
+                coordinate(*longitude, 'E', 'W'),
+                altitude
+            ),
+            Observer::Moving { waypoints } => format!("Moving, {} waypoint(s)", waypoints.len()),
+            Observer::Satellite { name } => format!("Satellite {}", name),
+            Observer::Unknown => "-".to_string(),
+        },
+    ));
+    details.push((
+        "Time window",
+        format!(
+            "{} to {} ({}){}",
+            entry.start,
+            entry.end,
+            hours(entry.hours),
+            entry.step.map_or(String::new(), |step| format!(", {} s step", step))
+        ),
+    ));
+    if let Some(min_elevation) = entry.min_elevation {
+        details.push(("Minimum elevation", format!("{}°", min_elevation)));
+    }
+    details.push((
+        "Reference",
+        match &entry.reference {
+            Some(passes) if passes.count > 0 => format!(
+                "{} window(s), {:.1} min on average, longest {:.1} min",
+                passes.count, passes.mean, passes.longest
+            ),
+            Some(_) => "no windows".to_string(),
+            None => "none".to_string(),
+        },
+    ));
+    details.push(("Difficulty", entry.difficulty.clone().unwrap_or_else(|| "-".to_string())));
+    if !entry.tags.is_empty() {
+        details.push(("Tags", entry.tags.join(", ")));
+    }
+    details
+}
+
+fn regime(entry: &Entry) -> String {
+    entry.orbit.map_or("-".to_string(), |orbit| orbit.regime().to_string())
+}
+
+fn observer_name(observer: &Observer) -> String {
+    match observer {
+        Observer::Fixed {
+            name: Some(name), ..
+        } => name.clone(),
+        Observer::Fixed {
+            latitude, longitude, ..
+        } => format!("{}, {}", coordinate(*latitude, 'N', 'S'), coordinate(*longitude, 'E', 'W')),
+        Observer::Moving { .. } => "Moving".to_string(),
+        Observer::Satellite { name } => format!("Satellite {}", name),
+        Observer::Unknown => "-".to_string(),
+    }
+}
+
+/// Where the observer is, or starts, for the map link.
+fn position(observer: &Observer) -> Option<(f64, f64)> {
+    match observer {
+        Observer::Fixed {
+            latitude, longitude, ..
+        } => Some((*latitude, *longitude)),
+        Observer::Moving { waypoints } => waypoints.first().copied(),
+        _ => None,
+    }
+}
+
+/// e.g. `40.7128°N`
+fn coordinate(degrees: f64, positive: char, negative: char) -> String {
+    format!("{:.4}°{}", degrees.abs(), if degrees < 0.0 { negative } else { positive })
+}
+
+fn hours(hours: Option<f64>) -> String {
+    match hours {
+        Some(hours) if hours <= 0.0 => "end before start".to_string(),
+        Some(hours) => format!("{} h", (hours * 10.0).round() / 10.0),
+        None => "-".to_string(),
+    }
+}
+
+/// e.g. `5, 5.3 min avg`
+fn pass_summary(passes: Option<&Passes>) -> String {
+    match passes {
+        Some(passes) if passes.count > 0 => format!("{}, {:.1} min avg", passes.count, passes.mean),
+        Some(_) => "0".to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Equirectangular SVG thumbnail: a 30° graticule with the equator and
+/// prime meridian drawn heavier, and the observer's position or track.
+fn map(observer: &Observer) -> String {
+    let x = |longitude: f64| (longitude + 180.0) / 360.0 * MAP_WIDTH;
+    let y = |latitude: f64| (90.0 - latitude) / 180.0 * MAP_HEIGHT;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        MAP_WIDTH, MAP_HEIGHT
+    );
+    let _ = write!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"currentColor\" stroke-opacity=\"0.5\"/>",
+        MAP_WIDTH, MAP_HEIGHT
+    );
+    for longitude in (-150..=150).step_by(30) {
+        let opacity = if longitude == 0 { 0.5 } else { 0.2 };
+        let _ = write!(
+            svg,
+            "<line x1=\"{0:.1}\" y1=\"0\" x2=\"{0:.1}\" y2=\"{1}\" stroke=\"currentColor\" stroke-opacity=\"{2}\"/>",
+            x(longitude as f64),
+            MAP_HEIGHT,
+            opacity
+        );
+    }
+    for latitude in (-60..=60).step_by(30) {
+        let opacity = if latitude == 0 { 0.5 } else { 0.2 };
+        let _ = write!(
+            svg,
+            "<line x1=\"0\" y1=\"{0:.1}\" x2=\"{1}\" y2=\"{0:.1}\" stroke=\"currentColor\" stroke-opacity=\"{2}\"/>",
+            y(latitude as f64),
+            MAP_WIDTH,
+            opacity
+        );
+    }
+    match observer {
+        Observer::Fixed {
+            latitude, longitude, ..
+        } => {
+            let _ = write!(svg, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#d9534f\"/>", x(*longitude), y(*latitude));
+        }
+        Observer::Moving { waypoints } => {
+            let points: Vec<String> = waypoints
+                .iter()
+                .map(|(latitude, longitude)| format!("{:.1},{:.1}", x(*longitude), y(*latitude)))
+                .collect();
+            let _ = write!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"#d9534f\" stroke-width=\"2\"/>",
+                points.join(" ")
+            );
+        }
+        _ => {}
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
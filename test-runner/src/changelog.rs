@@ -0,0 +1,119 @@
+//! `results/changes.md`: a changelog of verdict changes.
+//!
+//! Whenever a run's validation is recorded in the results database, the
+//! changelog is regenerated from it: for each implementation, every case
+//! whose verdict or score (as in [`gate`](crate::gate)) changed since its
+//! previous validated run, with the implementation's version before and
+//! after, oldest first. The database only grows, so entries are only ever
+//! appended, and the file reads as an audit log of the comparison:
+//!
+//! ```markdown
+//! ## python-sgp4
+//!
+//! | Run | Test case | Before | After | Version |
+//! |-----|-----------|--------|-------|---------|
+//! | 20251026_160238 | 003_gps_denver | match (1.00) | mismatch (0.50) | 2.23 → 2.24 |
+//! ```
+
+use crate::database::{Database, VerdictRecord};
+use crate::gate;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// A case's verdict changing from one validated run to the next.
+struct Change<'a> {
+    before: &'a VerdictRecord,
+    after: &'a VerdictRecord,
+}
+
+/// Regenerate `<results_dir>/changes.md` from the results database.
+pub fn write(results_dir: &Path) -> Result<()> {
+    let records = Database::open(results_dir)?.verdict_history()?;
+    let path = results_dir.join("changes.md");
+    fs::write(&path, render(&changes(&records))).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Changes by implementation. `records` are ordered by implementation and
+/// case, oldest run first.
+fn changes(records: &[VerdictRecord]) -> BTreeMap<&str, Vec<Change<'_>>> {
+    let mut changes: BTreeMap<&str, Vec<Change>> = BTreeMap::new();
+    for pair in records.windows(2) {
+        let (before, after) = (&pair[0], &pair[1]);
+        if before.implementation != after.implementation || before.test_case != after.test_case {
+            continue;
+        }
+        let changed = before.verdict != after.verdict
+            || match (score(before), score(after)) {
+                (Some(before), Some(after)) => (before - after).abs() > 1e-9,
+                (before, after) => before.is_some() != after.is_some(),
+            };
+        if changed {
+            changes.entry(&after.implementation).or_default().push(Change { before, after });
+        }
+    }
+    for entries in changes.values_mut() {
+        entries.sort_by(|a, b| (&a.after.run_id, &a.after.test_case).cmp(&(&b.after.run_id, &b.after.test_case)));
+    }
+    changes
+}
+
+fn render(changes: &BTreeMap<&str, Vec<Change>>) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# Verdict Changes\n");
+    let _ = writeln!(
+        md,
+        "Generated from `history.db` by the test runner. Each entry is a case whose verdict or score changed since the implementation's previous validated run.\n"
+    );
+    if changes.is_empty() {
+        let _ = writeln!(md, "No verdict changes recorded yet.");
+        return md;
+    }
+    for (implementation, entries) in changes {
+        let _ = writeln!(md, "## {}\n", implementation);
+        let _ = writeln!(md, "| Run | Test case | Before | After | Version |");
+        let _ = writeln!(md, "|-----|-----------|--------|-------|---------|");
+        for change in entries {
+            let _ = writeln!(
+                md,
+                "| {}{} | {} | {} | {} | {} |",
+                change.after.run_id,
+                change.after.started_at.as_deref().map(|at| format!(" ({})", &at[..at.len().min(10)])).unwrap_or_default(),
+                change.after.test_case,
+                describe(change.before),
+                describe(change.after),
+                versions(change.before, change.after)
+            );
+        }
+        md.push('\n');
+    }
+    md
+}
+
+fn score(record: &VerdictRecord) -> Option<f64> {
+    gate::score(record.verdict, record.result_windows, record.reference_windows, record.windows_off)
+}
+
+/// E.g. "mismatch (0.50)"
+fn describe(record: &VerdictRecord) -> String {
+    let verdict = serde_json::to_value(record.verdict)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    match score(record) {
+        Some(score) => format!("{} ({:.2})", verdict, score),
+        None => verdict,
+    }
+}
+
+/// E.g. "2.23 → 2.24", or just "2.24" if unchanged.
+fn versions(before: &VerdictRecord, after: &VerdictRecord) -> String {
+    let version = |record: &VerdictRecord| record.version.clone().unwrap_or_else(|| "unknown".to_string());
+    if before.version == after.version {
+        version(after)
+    } else {
+        format!("{} → {}", version(before), version(after))
+    }
+}
@@ -0,0 +1,96 @@
+//! `clean`: removing built images and clearing the results directory.
+//!
+//! `--images` removes every image tagged `visibility-test/*`, whether or not
+//! its implementation still exists, along with the dangling images left
+//! behind when a rebuild took over an image's tag. Those are recognized by
+//! the base image label every build adds, so other projects' dangling
+//! images are left alone. An image still used by a container can't be
+//! removed; that is reported and the rest are removed anyway.
+//!
+//! `--results` empties the results directory: result files, run
+//! directories, the results database and history, the quarantine, and
+//! staged cases. `--all` does both.
+
+use crate::{BASE_IMAGES_LABEL, Orchestrator};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+
+#[derive(Debug, Serialize)]
+pub struct RemovedImage {
+    /// `repository:tag`, or the ID of a dangling image
+    pub image: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Remove the built images, returning what was attempted.
+pub fn images(orchestrator: &Orchestrator) -> Result<Vec<RemovedImage>> {
+    let runtime = orchestrator.runtime;
+    let mut targets: Vec<String> = runtime
+        .images(&["reference=visibility-test/*"])?
+        .into_iter()
+        .filter_map(|(_, name)| name)
+        .collect();
+    let label = format!("label={}", BASE_IMAGES_LABEL);
+    targets.extend(
+        runtime
+            .images(&["dangling=true", &label])?
+            .into_iter()
+            .filter(|(_, name)| name.is_none())
+            .map(|(id, _)| id),
+    );
+    // An image can be listed more than once, and not next to itself
+    targets.sort();
+    targets.dedup();
+
+    let mut removed = Vec::new();
+    for image in targets {
+        match runtime.remove_image(&image) {
+            Ok(()) => {
+                println!("  {} Removed {}", "✓".green(), image.bright_white());
+                removed.push(RemovedImage {
+                    image,
+                    success: true,
+                    error: None,
+                });
+            }
+            Err(e) => {
+                eprintln!("  {} Error removing {}: {}", "✗".red(), image.bright_white(), e.to_string().red());
+                removed.push(RemovedImage {
+                    image,
+                    success: false,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+    if removed.is_empty() {
+        println!("  {} No images to remove", "○".dimmed());
+    }
+    Ok(removed)
+}
+
+/// Empty the results directory, returning the number of entries removed.
+pub fn results(orchestrator: &Orchestrator) -> Result<usize> {
+    let dir = &orchestrator.results_dir;
+    if !dir.exists() {
+        println!("  {} No results directory", "○".dimmed());
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to remove {}", path.display()))?;
+        removed += 1;
+    }
+    println!("  {} Removed {} entries from {}", "✓".green(), removed, dir.display().to_string().bright_white());
+    Ok(removed)
+}
@@ -0,0 +1,115 @@
+//! docker-compose file generation.
+//!
+//! Emits one service per discovered implementation, running every test case
+//! with the standard `/test-data` and `/results` mounts, under the `full`
+//! profile. For each test tier (the `metadata.difficulty` of the cases) it
+//! also emits one service per implementation and case under a profile named
+//! after the tier, so `docker compose --profile basic up` runs just the basic
+//! cases. Every service has a profile, so plain `docker compose up` starts
+//! nothing.
+//!
+//! Services carry the `--cpus` and `--memory` limits, if any, as orchestrated
+//! containers do.
+//!
+//! Paths are relative to the project root, where the file is meant to live.
+//! When cases reference TLE sets, the materialized cases directory is
+//! mounted over `/test-data/cases`, as in orchestrated runs.
+
+use crate::Implementation;
+use crate::runtime::Limits;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Profile of the services that run every test case.
+const FULL_PROFILE: &str = "full";
+
+/// Test case names grouped by tier, from `<test_data_dir>/cases/*.json`.
+/// Cases without a difficulty are left out of the tier profiles.
+pub fn test_case_tiers(test_data_dir: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let cases_dir = test_data_dir.join("cases");
+    let mut tiers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for entry in fs::read_dir(&cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let case: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let name = case.get("name").and_then(Value::as_str);
+        let tier = case.pointer("/metadata/difficulty").and_then(Value::as_str);
+        if let (Some(name), Some(tier)) = (name, tier) {
+            tiers.entry(tier.to_string()).or_default().push(name.to_string());
+        }
+    }
+
+    for cases in tiers.values_mut() {
+        cases.sort();
+    }
+    Ok(tiers)
+}
+
+pub fn render(implementations: &[Implementation], tiers: &BTreeMap<String, Vec<String>>, materialized: bool, limits: Limits) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Generated by `visibility-test-runner generate-compose`; do not edit by hand.");
+    let _ = writeln!(out, "#");
+    let _ = writeln!(out, "#   docker compose --profile {} build  # build all images", FULL_PROFILE);
+    let _ = writeln!(out, "#   docker compose --profile {} up  # run every case on every implementation", FULL_PROFILE);
+    for tier in tiers.keys() {
+        let _ = writeln!(out, "#   docker compose --profile {} up  # run only {} cases", tier, tier);
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "services:");
+
+    for impl_ in implementations {
+        let _ = writeln!(out, "  {}:", impl_.name);
+        let _ = writeln!(out, "    build:");
+        let _ = writeln!(out, "      context: ./implementations/{}", impl_.name);
+        let _ = writeln!(out, "      dockerfile: Dockerfile");
+        service_body(&mut out, impl_, &impl_.name, materialized, limits);
+        let _ = writeln!(out, "    profiles: [\"{}\"]", FULL_PROFILE);
+        let _ = writeln!(out, "    command: []  # Run all test cases");
+        let _ = writeln!(out);
+    }
+
+    for (tier, cases) in tiers {
+        for impl_ in implementations {
+            for case in cases {
+                let service = format!("{}--{}", impl_.name, case);
+                let _ = writeln!(out, "  {}:", service);
+                service_body(&mut out, impl_, &service, materialized, limits);
+                let _ = writeln!(out, "    pull_policy: never  # Built by the {} service", impl_.name);
+                let _ = writeln!(out, "    profiles: [\"{}\"]", tier);
+                let _ = writeln!(out, "    command: [\"{}\"]", case);
+                let _ = writeln!(out);
+            }
+        }
+    }
+
+    let _ = writeln!(out, "networks:");
+    let _ = writeln!(out, "  default:");
+    let _ = writeln!(out, "    name: visibility-test-network");
+    out
+}
+
+fn service_body(out: &mut String, impl_: &Implementation, service: &str, materialized: bool, limits: Limits) {
+    let _ = writeln!(out, "    image: {}", impl_.image_name);
+    let _ = writeln!(out, "    container_name: visibility-test-{}", service);
+    let _ = writeln!(out, "    volumes:");
+    let _ = writeln!(out, "      - ./test-data:/test-data:ro");
+    if materialized {
+        let _ = writeln!(out, "      - ./results/.materialized/cases:/test-data/cases:ro");
+    }
+    let _ = writeln!(out, "      - ./results:/results");
+    if let Some(cpus) = limits.cpus {
+        let _ = writeln!(out, "    cpus: {}", cpus);
+    }
+    if let Some(memory) = limits.memory {
+        let _ = writeln!(out, "    mem_limit: {}", memory);
+    }
+}
@@ -0,0 +1,194 @@
+//! Cross-case consistency checks.
+//!
+//! Two cases that differ only in their time window describe the same sky:
+//! when one window lies inside the other, an implementation's windows for
+//! the inner case must be the outer case's windows clipped to the inner
+//! span. Single-case validation against the reference only compares counts
+//! per case, so time-handling bugs (epoch offsets, window clipping, step
+//! alignment) can pass it while breaking this relation.
+//!
+//! Related cases are found automatically by comparing every field except
+//! `name`, `description`, `timeWindow`, `metadata`, and `expectedWindows`.
+
+use crate::{Orchestrator, read_result, result_test_case};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+
+/// Fields that may differ between related cases.
+const IGNORED_FIELDS: [&str; 5] = ["name", "description", "timeWindow", "metadata", "expectedWindows"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Inconsistency {
+    /// Case whose window lies inside the other's
+    pub inner: String,
+    pub outer: String,
+    pub message: String,
+}
+
+struct Case {
+    name: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step: f64,
+    /// Every field that has to match for cases to be related
+    inputs: Value,
+}
+
+/// Check every nested pair of related cases that `impl_name` has results for.
+pub fn check(orchestrator: &Orchestrator, impl_name: &str) -> Result<Vec<Inconsistency>> {
+    let cases = load_cases(orchestrator)?;
+
+    let mut windows = HashMap::new();
+    for path in orchestrator.collect_results(impl_name)? {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if let Some(test_case) = result_test_case(&file_name, impl_name) {
+            let result: Value = serde_json::from_str(&read_result(&path)?)?;
+            windows.insert(test_case, parse_windows(&result));
+        }
+    }
+
+    let mut issues = Vec::new();
+    for inner in &cases {
+        for outer in &cases {
+            let nested = inner.name != outer.name
+                && inner.inputs == outer.inputs
+                && outer.start <= inner.start
+                && inner.end <= outer.end
+                && (inner.start, inner.end) != (outer.start, outer.end);
+            if !nested {
+                continue;
+            }
+            let (Some(inner_windows), Some(outer_windows)) = (windows.get(&inner.name), windows.get(&outer.name))
+            else {
+                continue;
+            };
+
+            if let Some(message) = compare(inner, inner_windows, outer, outer_windows) {
+                issues.push(Inconsistency {
+                    inner: inner.name.clone(),
+                    outer: outer.name.clone(),
+                    message,
+                });
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Why `inner_windows` aren't `outer_windows` clipped to the inner span, or
+/// `None` when they agree. Edges may differ by the coarser of the two steps.
+fn compare(
+    inner: &Case,
+    inner_windows: &[(DateTime<Utc>, DateTime<Utc>)],
+    outer: &Case,
+    outer_windows: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Option<String> {
+    let tolerance = inner.step.max(outer.step);
+    let seconds = |a: DateTime<Utc>, b: DateTime<Utc>| (a - b).num_milliseconds().abs() as f64 / 1000.0;
+    // A pass that only grazes the inner span may fall between samples of
+    // either case, so slivers at the edges don't count on either side
+    let sliver = |(start, end): &(DateTime<Utc>, DateTime<Utc>)| {
+        seconds(*start, *end) < tolerance
+            && (seconds(*start, inner.start) <= tolerance || seconds(*end, inner.end) <= tolerance)
+    };
+
+    let clipped: Vec<_> = outer_windows
+        .iter()
+        .filter(|(start, end)| *end > inner.start && *start < inner.end)
+        .map(|(start, end)| ((*start).max(inner.start), (*end).min(inner.end)))
+        .filter(|window| !sliver(window))
+        .collect();
+    let inner_windows: Vec<_> = inner_windows.iter().filter(|window| !sliver(window)).collect();
+
+    if clipped.len() != inner_windows.len() {
+        return Some(format!(
+            "{} has {} window(s) within {}'s span, but {} has {}",
+            outer.name,
+            clipped.len(),
+            inner.name,
+            inner.name,
+            inner_windows.len()
+        ));
+    }
+
+    for (i, (expected, actual)) in clipped.iter().zip(inner_windows).enumerate() {
+        for (edge, expected, actual) in [("start", expected.0, actual.0), ("end", expected.1, actual.1)] {
+            let difference = seconds(expected, actual);
+            if difference > tolerance {
+                return Some(format!(
+                    "Window {} {}s differ by {:.0}s ({} vs {} in {})",
+                    i + 1,
+                    edge,
+                    difference,
+                    actual.format("%H:%M:%S"),
+                    expected.format("%H:%M:%S"),
+                    outer.name
+                ));
+            }
+        }
+    }
+    None
+}
+
+fn load_cases(orchestrator: &Orchestrator) -> Result<Vec<Case>> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let mut cases = Vec::new();
+
+    for entry in fs::read_dir(&cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let mut case: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let window = &case["timeWindow"];
+        let time = |field: &str| parse_time(&window[field]);
+        let (Some(name), Some(start), Some(end), Some(step)) = (
+            case["name"].as_str().map(str::to_string),
+            time("start"),
+            time("end"),
+            window["step"].as_f64(),
+        ) else {
+            continue;
+        };
+
+        if let Some(fields) = case.as_object_mut() {
+            for field in IGNORED_FIELDS {
+                fields.remove(field);
+            }
+        }
+        cases.push(Case {
+            name,
+            start,
+            end,
+            step,
+            inputs: case,
+        });
+    }
+
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+fn parse_windows(result: &Value) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut windows: Vec<_> = result["visibilityWindows"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|window| Some((parse_time(&window["start"])?, parse_time(&window["end"])?)))
+        .collect();
+    windows.sort();
+    windows
+}
+
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
@@ -0,0 +1,141 @@
+//! Output contract enforcement.
+//!
+//! Every container shares the `results/` mount, and may only write its own
+//! result files there: `<impl>_<case>[_<timestamp>].json[.gz]` for the cases
+//! it was given. After each run, entries in `results/` that appeared or
+//! changed while the container ran are checked against that contract.
+//! Anything else, such as results for cases it wasn't given, files named
+//! for another implementation (or overwriting one of theirs), or unrelated
+//! files, is moved to `results/.quarantine/<impl>/` and reported, so a
+//! misbehaving implementation can't pollute what validation and the
+//! dashboard read.
+//!
+//! Files named for an implementation whose container is running at the
+//! same time (`run --parallel`) are left alone, since they are presumably
+//! its own.
+
+use crate::result_test_case;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// Directories in `results/` the orchestrator itself creates while
+/// containers run.
+const ORCHESTRATOR_DIRS: &[&str] = &[".heartbeat", ".materialized", ".payload", ".quarantine", ".screening", ".stream", ".stress", ".sweep"];
+
+/// Modification time and size of every top-level entry in `results/`.
+pub struct Snapshot(BTreeMap<OsString, (Option<SystemTime>, u64)>);
+
+impl Snapshot {
+    pub fn take(results_dir: &Path) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        for entry in fs::read_dir(results_dir).with_context(|| format!("Failed to read {}", results_dir.display()))? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            entries.insert(entry.file_name(), (metadata.modified().ok(), metadata.len()));
+        }
+        Ok(Snapshot(entries))
+    }
+}
+
+/// What a container was allowed to write.
+pub struct Allowed<'a> {
+    pub implementation: &'a str,
+    /// Cases it was given
+    pub cases: &'a [String],
+    /// Every implementation, to tell whose a file claims to be
+    pub implementations: &'a [String],
+    /// Implementations with a container running alongside it
+    pub concurrent: &'a [String],
+}
+
+/// Quarantine every entry that changed since `before` and breaks the
+/// contract, returning the original paths.
+pub fn enforce(results_dir: &Path, before: &Snapshot, allowed: &Allowed) -> Result<Vec<PathBuf>> {
+    let after = Snapshot::take(results_dir)?;
+    let mut quarantined = Vec::new();
+    for (name, state) in &after.0 {
+        let previous = before.0.get(name);
+        let path = results_dir.join(name);
+        if previous == Some(state) {
+            continue;
+        }
+        let name = name.to_string_lossy();
+        if path.is_dir() && (previous.is_some() || ORCHESTRATOR_DIRS.contains(&name.as_ref())) {
+            // Run directories and the orchestrator's own staging areas
+            continue;
+        }
+        if path.is_file() && allowed_file(&name, allowed) {
+            continue;
+        }
+
+        let target_dir = results_dir.join(".quarantine").join(allowed.implementation);
+        fs::create_dir_all(&target_dir).with_context(|| format!("Failed to create {}", target_dir.display()))?;
+        let mut target = target_dir.join(name.as_ref());
+        let mut copy = 1;
+        while target.exists() {
+            target = target_dir.join(format!("{}.{}", name, copy));
+            copy += 1;
+        }
+        fs::rename(&path, &target).with_context(|| format!("Failed to quarantine {}", path.display()))?;
+        quarantined.push(path);
+    }
+    Ok(quarantined)
+}
+
+fn allowed_file(name: &str, allowed: &Allowed) -> bool {
+    // The longest matching name, so `foo_bar_001.json` is foo_bar's rather
+    // than foo's
+    let owner = allowed
+        .implementations
+        .iter()
+        .filter_map(|implementation| Some((implementation, result_test_case(name, implementation)?)))
+        .max_by_key(|(implementation, _)| implementation.len());
+    match owner {
+        // Another container of the same implementation may have been given
+        // the case instead
+        Some((owner, case)) if owner == allowed.implementation => {
+            allowed.cases.contains(&case) || allowed.concurrent.contains(owner)
+        }
+        Some((owner, _)) => allowed.concurrent.contains(owner),
+        None => false,
+    }
+}
+
+/// Containers running at once (`run --parallel`), and the implementations
+/// each has had running alongside it.
+#[derive(Default)]
+pub struct Running {
+    next_id: AtomicUsize,
+    containers: Mutex<Vec<(usize, String, BTreeSet<String>)>>,
+}
+
+impl Running {
+    /// Start tracking a container of `implementation`, returning its ID.
+    pub fn start(&self, implementation: &str) -> usize {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut containers = self.containers.lock().unwrap();
+        let mut alongside = BTreeSet::new();
+        for (_, other, other_alongside) in containers.iter_mut() {
+            other_alongside.insert(implementation.to_string());
+            alongside.insert(other.clone());
+        }
+        containers.push((id, implementation.to_string(), alongside));
+        id
+    }
+
+    /// Stop tracking container `id`, returning the implementations that ran
+    /// alongside it at any point.
+    pub fn finish(&self, id: usize) -> Vec<String> {
+        let mut containers = self.containers.lock().unwrap();
+        let Some(index) = containers.iter().position(|(other, _, _)| *other == id) else {
+            return Vec::new();
+        };
+        containers.remove(index).2.into_iter().collect()
+    }
+}
@@ -0,0 +1,508 @@
+//! Long-running daemon exposing a local control socket.
+//!
+//! Editor integrations and the web dashboard connect to the Unix socket and
+//! exchange one JSON object per line:
+//!
+//! ```text
+//! -> {"command":"run","implementation":"rust-sgp4","test_case":"001_iss_nyc"}
+//! <- {"ok":true,"data":{"started":true}}
+//! ```
+//!
+//! Supported commands: `list`, `run`, `status`, `results`.
+//!
+//! With `--schedule` the daemon also runs the full suite on a cron schedule,
+//! pruning old results, appending to `results/history.jsonl`, and invoking a
+//! notification command after each run.
+
+use crate::artifacts::RunArtifacts;
+use crate::error;
+use crate::events::Verdict;
+use crate::flaky;
+use crate::schedule::CronSchedule;
+use crate::{Implementation, Orchestrator, TestResult, ValidationSummary, read_result, record_artifacts};
+use anyhow::{Context, Result};
+use chrono::Local;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Request {
+    List,
+    Run {
+        implementation: Option<String>,
+        test_case: Option<String>,
+        #[serde(default)]
+        build: bool,
+    },
+    Status,
+    Results {
+        implementation: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Response {
+    fn ok(data: Value) -> Self {
+        Self {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+struct RunStatus {
+    running: bool,
+    /// Artifacts directory name under `results/`
+    run_id: Option<String>,
+    started_at: Option<u64>,
+    finished_at: Option<u64>,
+    /// Implementation currently executing, if any
+    current: Option<String>,
+    results: Vec<TestResult>,
+    validation: Vec<ValidationSummary>,
+    /// Builds, runs, and validations that failed outright
+    errors: Vec<StageError>,
+    /// RFC 3339 time of the next scheduled full-suite run
+    next_scheduled_run: Option<String>,
+}
+
+/// A stage that failed for an implementation, as in `run` and `all`'s
+/// `errors`.
+#[derive(Debug, Clone, Serialize)]
+struct StageError {
+    implementation: String,
+    /// `build`, `run`, or `validate`
+    stage: &'static str,
+    kind: &'static str,
+    error: String,
+}
+
+impl StageError {
+    fn new(implementation: &str, stage: &'static str, e: &anyhow::Error) -> Self {
+        eprintln!("  {} Error in {} for {}: {}", "✗".red(), stage, implementation.bright_white(), e.to_string().red());
+        Self {
+            implementation: implementation.to_string(),
+            stage,
+            kind: error::kind(e),
+            error: e.to_string(),
+        }
+    }
+}
+
+/// Marks the daemon idle again when dropped, even while unwinding from a
+/// panic, so one failed run doesn't block every later one.
+struct Running<'a>(&'a Daemon);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let mut status = self.0.lock_status();
+        status.running = false;
+        status.current = None;
+        status.finished_at = Some(unix_now());
+    }
+}
+
+/// Unattended full-suite runs for daemon mode.
+pub struct ScheduleOptions {
+    pub cron: CronSchedule,
+    /// Result files to keep per (implementation, test case) after each run
+    pub keep: Option<usize>,
+    /// Shell command run after each scheduled run, with the summary on stdin
+    pub notify: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryEntry<'a> {
+    schedule: &'a str,
+    run_id: Option<&'a str>,
+    started_at: Option<u64>,
+    finished_at: Option<u64>,
+    passed: bool,
+    runs: Vec<RunRecord<'a>>,
+    validation: &'a [ValidationSummary],
+    errors: &'a [StageError],
+}
+
+#[derive(Debug, Serialize)]
+struct RunRecord<'a> {
+    implementation: &'a str,
+    success: bool,
+    execution_time: f64,
+    /// Image the implementation ran from, to tell code changes from flakiness
+    image_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory: Option<u64>,
+}
+
+pub struct Daemon {
+    orchestrator: Arc<Orchestrator>,
+    status: Arc<Mutex<RunStatus>>,
+    schedule: Option<Arc<ScheduleOptions>>,
+}
+
+impl Daemon {
+    pub fn new(orchestrator: Orchestrator, schedule: Option<ScheduleOptions>) -> Self {
+        Self {
+            orchestrator: Arc::new(orchestrator),
+            status: Arc::new(Mutex::new(RunStatus::default())),
+            schedule: schedule.map(Arc::new),
+        }
+    }
+
+    pub fn serve(&self, socket: &Path) -> Result<()> {
+        // A socket file left behind by a previous daemon would make bind fail
+        if socket.exists() {
+            fs::remove_file(socket)
+                .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+        }
+
+        let listener = UnixListener::bind(socket)
+            .with_context(|| format!("Failed to bind control socket {}", socket.display()))?;
+
+        println!(
+            "{} listening on {}",
+            "Daemon".bold().bright_blue(),
+            socket.display().to_string().bright_white()
+        );
+
+        if let Some(schedule) = &self.schedule {
+            println!(
+                "  {} Scheduled full-suite runs: {}",
+                "●".bright_cyan(),
+                schedule.cron.expression().bright_white()
+            );
+            let daemon = self.handle();
+            thread::spawn(move || daemon.run_scheduler());
+        }
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let daemon = self.handle();
+                    thread::spawn(move || {
+                        if let Err(e) = daemon.serve_client(stream) {
+                            eprintln!("  {} Client error: {}", "✗".red(), e.to_string().red());
+                        }
+                    });
+                }
+                Err(e) => eprintln!("  {} Connection failed: {}", "✗".red(), e.to_string().red()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&self) -> Self {
+        Self {
+            orchestrator: Arc::clone(&self.orchestrator),
+            status: Arc::clone(&self.status),
+            schedule: self.schedule.clone(),
+        }
+    }
+
+    fn serve_client(&self, stream: UnixStream) -> Result<()> {
+        let mut writer = stream.try_clone()?;
+        let reader = BufReader::new(stream);
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => self.dispatch(request),
+                Err(e) => Response::error(format!("Invalid request: {}", e)),
+            };
+
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn dispatch(&self, request: Request) -> Response {
+        let result = match request {
+            Request::List => self.list(),
+            Request::Run {
+                implementation,
+                test_case,
+                build,
+            } => self.trigger_run(implementation, test_case, build),
+            Request::Status => Ok(json!(*self.lock_status())),
+            Request::Results { implementation } => self.results(&implementation),
+        };
+
+        match result {
+            Ok(data) => Response::ok(data),
+            Err(e) => Response::error(e.to_string()),
+        }
+    }
+
+    fn list(&self) -> Result<Value> {
+        let implementations = self.orchestrator.discover_implementations()?;
+        Ok(json!(
+            implementations
+                .iter()
+                .map(|i| json!({ "name": i.name, "image": i.image_name }))
+                .collect::<Vec<_>>()
+        ))
+    }
+
+    fn trigger_run(
+        &self,
+        implementation: Option<String>,
+        test_case: Option<String>,
+        build: bool,
+    ) -> Result<Value> {
+        let impls_to_run = self
+            .orchestrator
+            .select_implementations(implementation.as_deref())?;
+
+        self.begin_run()?;
+
+        let daemon = self.handle();
+        thread::spawn(move || {
+            let passed = daemon.execute(&impls_to_run, test_case.as_ref().map(std::slice::from_ref), build, false);
+            daemon.finish_run(passed);
+        });
+
+        Ok(json!({ "started": true }))
+    }
+
+    /// Mark the daemon busy, refusing if another run is active.
+    fn begin_run(&self) -> Result<()> {
+        let mut status = self.lock_status();
+        if status.running {
+            anyhow::bail!("A run is already in progress");
+        }
+        *status = RunStatus {
+            running: true,
+            started_at: Some(unix_now()),
+            next_scheduled_run: status.next_scheduled_run.take(),
+            ..RunStatus::default()
+        };
+        Ok(())
+    }
+
+    /// Build, run, and optionally validate `impls`, returning whether every
+    /// one of them built and ran successfully.
+    fn execute(&self, impls: &[Implementation], cases: Option<&[String]>, build: bool, validate: bool) -> bool {
+        let _running = Running(self);
+        let artifacts = match RunArtifacts::create(&self.orchestrator.results_dir, &self.orchestrator.host()) {
+            Ok(artifacts) => {
+                self.lock_status().run_id = Some(artifacts.run_id().to_string());
+                Some(artifacts)
+            }
+            Err(e) => {
+                eprintln!("  {} Error creating run artifacts: {}", "⚠".yellow(), e.to_string().yellow());
+                None
+            }
+        };
+
+        for impl_ in impls {
+            self.lock_status().current = Some(impl_.name.clone());
+
+            if build && let Err(e) = self.orchestrator.build_image(impl_) {
+                self.lock_status().errors.push(StageError::new(&impl_.name, "build", &e));
+                continue;
+            }
+
+            match self.orchestrator.run_tests(impl_, cases) {
+                Ok(result) => {
+                    if let Some(artifacts) = &artifacts {
+                        record_artifacts(artifacts.record_run(&result), &impl_.name);
+                    }
+                    self.lock_status().results.push(result);
+                }
+                Err(e) => self.lock_status().errors.push(StageError::new(&impl_.name, "run", &e)),
+            }
+        }
+
+        if validate {
+            // Their results would be a previous run's
+            let failed: Vec<String> = self.lock_status().errors.iter().map(|e| e.implementation.clone()).collect();
+            for impl_ in impls.iter().filter(|impl_| !failed.contains(&impl_.name)) {
+                match self.orchestrator.validate_results(&impl_.name) {
+                    Ok(summary) => {
+                        if let Some(artifacts) = &artifacts {
+                            record_artifacts(artifacts.record_validation(&summary), &impl_.name);
+                        }
+                        self.lock_status().validation.push(summary);
+                    }
+                    Err(e) => self.lock_status().errors.push(StageError::new(&impl_.name, "validate", &e)),
+                }
+            }
+        }
+
+        let status = self.lock_status();
+        status.errors.is_empty() && status.results.iter().all(|r| r.success)
+    }
+
+    /// Record in the results database that the current run has finished.
+    fn finish_run(&self, passed: bool) {
+        let Some(run_id) = self.lock_status().run_id.clone() else {
+            return;
+        };
+        let artifacts = RunArtifacts::open(&self.orchestrator.results_dir, &run_id);
+        record_artifacts(artifacts.and_then(|artifacts| artifacts.finish(passed)), "the run");
+    }
+
+    fn run_scheduler(&self) {
+        let Some(schedule) = self.schedule.clone() else {
+            return;
+        };
+
+        loop {
+            let Some(next) = schedule.cron.next_after(Local::now()) else {
+                eprintln!("  {} Schedule '{}' never fires; scheduler stopped", "⚠".yellow(), schedule.cron.expression());
+                return;
+            };
+            self.lock_status().next_scheduled_run = Some(next.to_rfc3339());
+
+            let wait = (next - Local::now()).to_std().unwrap_or_default();
+            thread::sleep(wait);
+
+            // A panicking run mustn't take the scheduler down with it
+            match panic::catch_unwind(AssertUnwindSafe(|| self.run_scheduled(&schedule))) {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("  {} Scheduled run failed: {}", "✗".red(), e.to_string().red()),
+                Err(_) => eprintln!("  {} Scheduled run panicked", "✗".red()),
+            }
+        }
+    }
+
+    fn run_scheduled(&self, schedule: &ScheduleOptions) -> Result<()> {
+        println!("\n{}", "Scheduled full-suite run".bold().bright_magenta());
+        let implementations = self.orchestrator.discover_implementations()?;
+
+        self.begin_run()?;
+        let ran = self.execute(&implementations, None, true, true);
+
+        if let Some(keep) = schedule.keep {
+            let removed = self.orchestrator.prune_results(keep)?;
+            println!("  {} Pruned {} old result file(s)", "✓".green(), removed.len());
+        }
+
+        let status = self.lock_status().clone();
+        // Quarantined pairs' and expected mismatches don't fail the run
+        let quarantine = flaky::read_quarantine(&self.orchestrator.test_data_dir)?;
+        let validated = |v: &ValidationSummary| {
+            v.cases.iter().all(|case| {
+                matches!(case.verdict, Verdict::Match | Verdict::Unsupported)
+                    || case.expected_failure
+                    || flaky::is_quarantined(&quarantine, &v.implementation, &case.test_case)
+            })
+        };
+        let entry = HistoryEntry {
+            schedule: schedule.cron.expression(),
+            run_id: status.run_id.as_deref(),
+            started_at: status.started_at,
+            finished_at: status.finished_at,
+            passed: ran && status.validation.iter().all(validated),
+            runs: status
+                .results
+                .iter()
+                .map(|r| RunRecord {
+                    implementation: &r.implementation,
+                    success: r.success,
+                    execution_time: r.execution_time,
+                    image_id: r.image_id.as_deref(),
+                    cpu_seconds: r.cpu_seconds,
+                    peak_memory: r.peak_memory,
+                })
+                .collect(),
+            validation: &status.validation,
+            errors: &status.errors,
+        };
+        let line = serde_json::to_string(&entry)?;
+        self.finish_run(entry.passed);
+
+        let history_file = self.orchestrator.results_dir.join("history.jsonl");
+        let mut history = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&history_file)
+            .with_context(|| format!("Failed to open {}", history_file.display()))?;
+        writeln!(history, "{}", line)?;
+
+        if let Some(command) = &schedule.notify {
+            notify(command, &line, entry.passed)?;
+        }
+
+        Ok(())
+    }
+
+    fn results(&self, implementation: &str) -> Result<Value> {
+        let mut results = Vec::new();
+        for path in self.orchestrator.collect_results(implementation)? {
+            let content = read_result(&path)?;
+            results.push(serde_json::from_str::<Value>(&content)?);
+        }
+        Ok(Value::Array(results))
+    }
+
+    fn lock_status(&self) -> std::sync::MutexGuard<'_, RunStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run the notification command with the run summary JSON on stdin.
+fn notify(command: &str, summary: &str, passed: bool) -> Result<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .env("VISIBILITY_RUN_PASSED", passed.to_string())
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run notification command '{}'", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", summary)?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("Notification command exited with {}", status);
+    }
+
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
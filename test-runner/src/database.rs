@@ -0,0 +1,918 @@
+//! The results database, `results/history.db`.
+//!
+//! Alongside its artifact directory, every `run`, `all`, and daemon run
+//! records what it produced in an SQLite database, so trends can be queried
+//! without walking timestamped JSON files:
+//!
+//! - `runs` - one row per run ID, with the host and profile it ran on, and
+//!   once it has finished, whether it passed
+//! - `containers` - one row per test container, with its exit status, the
+//!   image it ran from, and resource usage (a resumed run adds one per
+//!   remaining case)
+//! - `results` - one row per (run, implementation, case) result file
+//! - `verdicts` - one row per (run, implementation, case) validation verdict
+//! - `versions` - what each implementation declared to the health check
+//!
+//! [`RunArtifacts`](crate::artifacts::RunArtifacts) writes it as it files
+//! each run; `history` reads it back, `flaky` and `publish` go through each
+//! run's verdicts (see [`history`](crate::history)), `export-failures` flattens every
+//! mismatch into one JSON line per failure for outside analysis, and the
+//! verdict changelog (see [`changelog`](crate::changelog)) is generated from
+//! it.
+
+use crate::events::Verdict;
+use crate::health::Versions;
+use crate::history::{self, Entry};
+use crate::profiles::Host;
+use crate::{CaseVerdict, TestResult, VisibilityResult, read_result, result_test_case};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, Row, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for another process's write lock, such as the daemon's
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_id TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    hostname TEXT NOT NULL,
+    profile TEXT,
+    finished_at TEXT,
+    passed INTEGER
+);
+CREATE TABLE IF NOT EXISTS containers (
+    run_id TEXT NOT NULL,
+    implementation TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    timed_out INTEGER NOT NULL,
+    execution_time REAL NOT NULL,
+    cpu_seconds REAL,
+    peak_memory INTEGER,
+    image_id TEXT
+);
+CREATE TABLE IF NOT EXISTS results (
+    run_id TEXT NOT NULL,
+    implementation TEXT NOT NULL,
+    test_case TEXT NOT NULL,
+    version TEXT,
+    windows INTEGER,
+    execution_time REAL,
+    PRIMARY KEY (run_id, implementation, test_case)
+);
+CREATE TABLE IF NOT EXISTS verdicts (
+    run_id TEXT NOT NULL,
+    implementation TEXT NOT NULL,
+    test_case TEXT NOT NULL,
+    verdict TEXT NOT NULL,
+    result_windows INTEGER NOT NULL,
+    reference_windows INTEGER,
+    warnings INTEGER NOT NULL,
+    deltas TEXT NOT NULL,
+    PRIMARY KEY (run_id, implementation, test_case)
+);
+CREATE TABLE IF NOT EXISTS versions (
+    run_id TEXT NOT NULL,
+    implementation TEXT NOT NULL,
+    version TEXT NOT NULL,
+    library_name TEXT,
+    library_version TEXT,
+    platform TEXT,
+    PRIMARY KEY (run_id, implementation)
+);
+";
+
+/// Columns added to tables after their first release, as (table, column,
+/// type), added to databases created before them.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("runs", "finished_at", "TEXT"),
+    ("runs", "passed", "INTEGER"),
+    ("containers", "image_id", "TEXT"),
+];
+
+pub struct Database {
+    connection: Connection,
+}
+
+/// A run and what it recorded, for `history`.
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub started_at: String,
+    pub hostname: String,
+    pub profile: Option<String>,
+    pub implementations: usize,
+    /// Containers that exited unsuccessfully
+    pub failed_containers: usize,
+    pub results: usize,
+    pub matched: usize,
+    /// Cases with a match or mismatch verdict
+    pub validated: usize,
+}
+
+/// One (run, implementation, case) row, for `history`.
+#[derive(Debug, Serialize)]
+pub struct CaseHistory {
+    pub run_id: String,
+    pub implementation: String,
+    pub test_case: String,
+    pub version: Option<String>,
+    /// Windows in the result file, or the one the verdict covered
+    pub windows: Option<usize>,
+    pub execution_time: Option<f64>,
+    pub verdict: Option<String>,
+    pub reference_windows: Option<usize>,
+}
+
+/// One way a validated case disagreed with the reference, for
+/// `export-failures`. A mismatch is a `window_count` failure when the window
+/// counts differ, and one failure per field of a paired window outside
+/// tolerance.
+#[derive(Debug, Clone, Serialize)]
+pub struct Failure {
+    pub run_id: String,
+    pub started_at: Option<String>,
+    pub hostname: Option<String>,
+    pub profile: Option<String>,
+    pub implementation: String,
+    pub test_case: String,
+    /// `window_count`, `timing`, or `elevation`
+    pub category: &'static str,
+    /// The window field, for `timing` and `elevation`
+    pub field: Option<String>,
+    /// 1-based window, for `timing` and `elevation`
+    pub window: Option<usize>,
+    /// Result minus reference: windows, seconds, or degrees
+    pub magnitude: f64,
+    pub unit: &'static str,
+    pub tolerance: Option<f64>,
+    /// The implementation's version, from the health check or its result
+    pub version: Option<String>,
+    pub library_name: Option<String>,
+    pub library_version: Option<String>,
+}
+
+/// A [`FieldDelta`](crate::tolerance::FieldDelta) as stored in `verdicts.deltas`.
+#[derive(Deserialize)]
+struct StoredDelta {
+    window: usize,
+    field: String,
+    delta: f64,
+    tolerance: f64,
+}
+
+/// One recorded verdict, for the changelog.
+#[derive(Debug)]
+pub struct VerdictRecord {
+    pub run_id: String,
+    pub started_at: Option<String>,
+    pub implementation: String,
+    pub test_case: String,
+    pub verdict: Verdict,
+    pub result_windows: usize,
+    pub reference_windows: Option<usize>,
+    /// Windows with a field outside tolerance
+    pub windows_off: usize,
+    /// Declared to the health check, or else in the result
+    pub version: Option<String>,
+}
+
+/// One container of a run, for its [metrics](crate::openmetrics).
+#[derive(Debug)]
+pub struct ContainerRecord {
+    pub implementation: String,
+    pub success: bool,
+    pub timed_out: bool,
+    pub execution_time: f64,
+    pub cpu_seconds: Option<f64>,
+    pub peak_memory: Option<u64>,
+}
+
+/// What `history` is limited to.
+#[derive(Debug, Default)]
+pub struct Filter<'a> {
+    pub implementation: Option<&'a str>,
+    pub test_case: Option<&'a str>,
+    /// Most recent runs to include
+    pub last: usize,
+}
+
+impl Database {
+    /// Open `results/history.db`, creating it if needed.
+    pub fn open(results_dir: &Path) -> Result<Self> {
+        let path = results_dir.join("history.db");
+        let connection = Connection::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("Failed to set up {}", path.display()))?;
+        for (table, column, kind) in ADDED_COLUMNS {
+            let present: bool = connection.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?) WHERE name = ?",
+                [table, column],
+                |row| row.get(0),
+            )?;
+            if !present {
+                connection
+                    .execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {kind}"))
+                    .with_context(|| format!("Failed to add {}.{} to {}", table, column, path.display()))?;
+            }
+        }
+        Ok(Database { connection })
+    }
+
+    pub fn record_run(&self, run_id: &str, host: &Host) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR IGNORE INTO runs (run_id, started_at, hostname, profile) VALUES (?, ?, ?, ?)",
+            params![run_id, Utc::now().to_rfc3339(), host.hostname, host.profile],
+        )?;
+        Ok(())
+    }
+
+    /// Record that `run_id` has finished, and whether it passed.
+    pub fn finish_run(&self, run_id: &str, passed: bool) -> Result<()> {
+        self.connection.execute(
+            "UPDATE runs SET finished_at = ?, passed = ? WHERE run_id = ?",
+            params![Utc::now().to_rfc3339(), passed, run_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record a container and every result file it wrote.
+    pub fn record_container(&self, run_id: &str, result: &TestResult) -> Result<()> {
+        // Rolled back if dropped before the commit
+        let transaction = self.connection.unchecked_transaction()?;
+        self.insert_container(run_id, result)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn insert_container(&self, run_id: &str, result: &TestResult) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO containers (run_id, implementation, success, timed_out, execution_time, cpu_seconds, peak_memory, image_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                run_id,
+                result.implementation,
+                result.success,
+                result.timed_out,
+                result.execution_time,
+                result.cpu_seconds,
+                result.peak_memory,
+                result.image_id,
+            ],
+        )?;
+        for path in &result.result_files {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let Some(test_case) = result_test_case(&file_name, &result.implementation) else {
+                continue;
+            };
+            let parsed = read_result(path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<VisibilityResult>(&content).ok());
+            self.connection.execute(
+                "INSERT OR REPLACE INTO results (run_id, implementation, test_case, version, windows, execution_time)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                params![
+                    run_id,
+                    result.implementation,
+                    test_case,
+                    parsed.as_ref().map(|parsed| &parsed.version),
+                    parsed.as_ref().map(|parsed| parsed.visibility_windows.len()),
+                    parsed.as_ref().and_then(|parsed| parsed.execution_time),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn record_versions(&self, run_id: &str, implementation: &str, versions: &Versions) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO versions (run_id, implementation, version, library_name, library_version, platform)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                run_id,
+                implementation,
+                versions.version,
+                versions.library_name,
+                versions.library_version,
+                versions.platform,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_verdicts(&self, run_id: &str, implementation: &str, cases: &[CaseVerdict]) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        for case in cases {
+            transaction.execute(
+                "INSERT OR REPLACE INTO verdicts
+                 (run_id, implementation, test_case, verdict, result_windows, reference_windows, warnings, deltas)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    run_id,
+                    implementation,
+                    case.test_case,
+                    verdict_name(case)?,
+                    case.result_windows,
+                    case.reference_windows,
+                    case.warnings,
+                    serde_json::to_string(&case.deltas)?,
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The last `filter.last` runs, newest first, with what each recorded
+    /// for the filter's implementation and case.
+    pub fn runs(&self, filter: &Filter) -> Result<Vec<RunSummary>> {
+        let (conditions, params) = filter.conditions();
+        let mut statement = self.connection.prepare(
+            &format!(
+                "SELECT runs.run_id, runs.started_at, runs.hostname, runs.profile,
+                    (SELECT COUNT(DISTINCT implementation) FROM containers WHERE run_id = runs.run_id AND {conditions_impl}),
+                    (SELECT COUNT(*) FROM containers WHERE run_id = runs.run_id AND success = 0 AND {conditions_impl}),
+                    (SELECT COUNT(*) FROM results WHERE run_id = runs.run_id AND {conditions}),
+                    (SELECT COUNT(*) FROM verdicts WHERE run_id = runs.run_id AND verdict = 'match' AND {conditions}),
+                    (SELECT COUNT(*) FROM verdicts WHERE run_id = runs.run_id AND verdict IN ('match', 'mismatch') AND {conditions})
+                 FROM runs ORDER BY runs.run_id DESC LIMIT ?",
+                conditions_impl = filter.implementation_condition(),
+            ),
+        )?;
+        let params = [
+            filter.implementation_params(),
+            filter.implementation_params(),
+            params.clone(),
+            params.clone(),
+            params,
+            vec![Value::Integer(filter.last as i64)],
+        ]
+        .concat();
+        let runs = statement
+            .query_map(params_from_iter(params), |row| {
+                Ok(RunSummary {
+                    run_id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    hostname: row.get(2)?,
+                    profile: row.get(3)?,
+                    implementations: count(row, 4)?,
+                    failed_containers: count(row, 5)?,
+                    results: count(row, 6)?,
+                    matched: count(row, 7)?,
+                    validated: count(row, 8)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(runs)
+    }
+
+    /// Every (implementation, case) the filter selects in its last
+    /// `filter.last` runs, newest run first.
+    pub fn cases(&self, filter: &Filter) -> Result<Vec<CaseHistory>> {
+        let (conditions, params) = filter.conditions();
+        // A case may have a result without a verdict or, when unsupported,
+        // a verdict without a result
+        let mut statement = self.connection.prepare(
+            &format!(
+                "WITH recent AS (SELECT run_id FROM runs ORDER BY run_id DESC LIMIT ?),
+                 pairs AS (
+                     SELECT run_id, implementation, test_case FROM results WHERE {conditions}
+                     UNION
+                     SELECT run_id, implementation, test_case FROM verdicts WHERE {conditions}
+                 )
+                 SELECT pairs.run_id, pairs.implementation, pairs.test_case,
+                     results.version,
+                     CASE WHEN verdicts.verdict = 'unsupported' THEN NULL
+                         ELSE COALESCE(results.windows, verdicts.result_windows) END,
+                     results.execution_time,
+                     verdicts.verdict, verdicts.reference_windows
+                 FROM pairs
+                 JOIN recent USING (run_id)
+                 LEFT JOIN results USING (run_id, implementation, test_case)
+                 LEFT JOIN verdicts USING (run_id, implementation, test_case)
+                 ORDER BY pairs.run_id DESC, pairs.implementation, pairs.test_case"
+            ),
+        )?;
+        let params = [vec![Value::Integer(filter.last as i64)], params.clone(), params].concat();
+        let cases = statement
+            .query_map(params_from_iter(params), |row| {
+                Ok(CaseHistory {
+                    run_id: row.get(0)?,
+                    implementation: row.get(1)?,
+                    test_case: row.get(2)?,
+                    version: row.get(3)?,
+                    windows: row.get(4)?,
+                    execution_time: row.get(5)?,
+                    verdict: row.get(6)?,
+                    reference_windows: row.get(7)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(cases)
+    }
+}
+
+impl Database {
+    /// When `run_id` started, as RFC 3339.
+    pub fn started_at(&self, run_id: &str) -> Result<Option<String>> {
+        let started = self
+            .connection
+            .query_row("SELECT started_at FROM runs WHERE run_id = ?", [run_id], |row| row.get(0))
+            .optional()?;
+        Ok(started)
+    }
+
+    /// Every container `run_id` recorded, in order.
+    pub fn containers(&self, run_id: &str) -> Result<Vec<ContainerRecord>> {
+        let mut statement = self.connection.prepare(
+            "SELECT implementation, success, timed_out, execution_time, cpu_seconds, peak_memory
+             FROM containers WHERE run_id = ? ORDER BY rowid",
+        )?;
+        let containers = statement
+            .query_map([run_id], |row| {
+                Ok(ContainerRecord {
+                    implementation: row.get(0)?,
+                    success: row.get(1)?,
+                    timed_out: row.get(2)?,
+                    execution_time: row.get(3)?,
+                    cpu_seconds: row.get(4)?,
+                    peak_memory: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(containers)
+    }
+
+    /// The last `last` runs (all if `None`), oldest first, with each
+    /// implementation's containers and verdicts.
+    pub fn history(&self, last: Option<usize>) -> Result<Vec<Entry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT run_id, started_at, finished_at, passed FROM
+                 (SELECT * FROM runs ORDER BY run_id DESC LIMIT ?)
+             ORDER BY run_id",
+        )?;
+        let last = last.map_or(-1, |last| last as i64);
+        let runs: Vec<(String, String, Option<String>, Option<bool>)> = statement
+            .query_map([last], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        // A resumed run has several containers per implementation
+        let mut containers = self.connection.prepare(
+            "SELECT implementation, MIN(success), SUM(execution_time), MAX(image_id), SUM(cpu_seconds), MAX(peak_memory)
+             FROM containers WHERE run_id = ? GROUP BY implementation ORDER BY MIN(rowid)",
+        )?;
+        let mut verdicts = self.connection.prepare(
+            "SELECT implementation, test_case, verdict, result_windows, reference_windows, deltas
+             FROM verdicts WHERE run_id = ? ORDER BY implementation, test_case",
+        )?;
+        let unix = |time: Option<String>| {
+            time.and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+                .map(|time| time.timestamp().max(0) as u64)
+        };
+        let mut entries = Vec::new();
+        for (run_id, started_at, finished_at, passed) in runs {
+            let runs: Vec<history::Run> = containers
+                .query_map([&run_id], |row| {
+                    Ok(history::Run {
+                        implementation: row.get(0)?,
+                        success: row.get(1)?,
+                        execution_time: row.get(2)?,
+                        image_id: row.get(3)?,
+                        cpu_seconds: row.get(4)?,
+                        peak_memory: row.get(5)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut validation: BTreeMap<String, history::Validation> = BTreeMap::new();
+            let mut rows = verdicts.query([&run_id])?;
+            while let Some(row) = rows.next()? {
+                let implementation: String = row.get(0)?;
+                let verdict: Verdict =
+                    serde_json::from_value(row.get::<_, String>(2)?.into()).context("Failed to parse recorded verdict")?;
+                let deltas: Vec<history::Delta> =
+                    serde_json::from_str(&row.get::<_, String>(5)?).context("Failed to parse recorded deltas")?;
+                let summary = validation.entry(implementation.clone()).or_insert_with(|| history::Validation {
+                    implementation,
+                    matched: 0,
+                    total: 0,
+                    cases: Vec::new(),
+                });
+                // As `validate` counts them: every result, but not unsupported cases
+                summary.matched += usize::from(verdict == Verdict::Match);
+                summary.total += usize::from(verdict != Verdict::Unsupported);
+                summary.cases.push(history::Case {
+                    test_case: row.get(1)?,
+                    verdict,
+                    result_windows: row.get(3)?,
+                    reference_windows: row.get(4)?,
+                    deltas,
+                });
+            }
+            let validation: Vec<history::Validation> = validation.into_values().collect();
+
+            // Runs recorded before outcomes were, or that never finished
+            let passed = passed.unwrap_or_else(|| {
+                runs.iter().all(|run| run.success)
+                    && validation.iter().flat_map(|summary| &summary.cases).all(|case| case.verdict != Verdict::Mismatch)
+            });
+            entries.push(Entry {
+                run_id: Some(run_id),
+                started_at: unix(Some(started_at)),
+                finished_at: unix(finished_at),
+                passed,
+                runs,
+                validation,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Every recorded verdict, by implementation and case, oldest run first.
+    pub fn verdict_history(&self) -> Result<Vec<VerdictRecord>> {
+        let mut statement = self.connection.prepare(
+            "SELECT verdicts.run_id, runs.started_at, verdicts.implementation, verdicts.test_case,
+                 verdicts.verdict, verdicts.result_windows, verdicts.reference_windows, verdicts.deltas,
+                 COALESCE(versions.version, results.version)
+             FROM verdicts
+             LEFT JOIN runs USING (run_id)
+             LEFT JOIN versions USING (run_id, implementation)
+             LEFT JOIN results USING (run_id, implementation, test_case)
+             ORDER BY verdicts.implementation, verdicts.test_case, verdicts.run_id",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            let deltas: Vec<StoredDelta> =
+                serde_json::from_str(&row.get::<_, String>(7)?).context("Failed to parse recorded deltas")?;
+            let off: BTreeSet<usize> = deltas.iter().map(|delta| delta.window).collect();
+            records.push(VerdictRecord {
+                run_id: row.get(0)?,
+                started_at: row.get(1)?,
+                implementation: row.get(2)?,
+                test_case: row.get(3)?,
+                verdict: serde_json::from_value(row.get::<_, String>(4)?.into()).context("Failed to parse recorded verdict")?,
+                result_windows: count(row, 5)?,
+                reference_windows: row.get(6)?,
+                windows_off: off.len(),
+                version: row.get(8)?,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Every recorded mismatch, oldest run first.
+    pub fn failures(&self) -> Result<Vec<Failure>> {
+        let mut statement = self.connection.prepare(
+            "SELECT verdicts.run_id, runs.started_at, runs.hostname, runs.profile,
+                 verdicts.implementation, verdicts.test_case,
+                 verdicts.result_windows, verdicts.reference_windows, verdicts.deltas,
+                 COALESCE(versions.version, results.version), versions.library_name, versions.library_version
+             FROM verdicts
+             LEFT JOIN runs USING (run_id)
+             LEFT JOIN versions USING (run_id, implementation)
+             LEFT JOIN results USING (run_id, implementation, test_case)
+             WHERE verdicts.verdict = 'mismatch'
+             ORDER BY verdicts.run_id, verdicts.implementation, verdicts.test_case",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut failures = Vec::new();
+        while let Some(row) = rows.next()? {
+            let template = Failure {
+                run_id: row.get(0)?,
+                started_at: row.get(1)?,
+                hostname: row.get(2)?,
+                profile: row.get(3)?,
+                implementation: row.get(4)?,
+                test_case: row.get(5)?,
+                category: "",
+                field: None,
+                window: None,
+                magnitude: 0.0,
+                unit: "",
+                tolerance: None,
+                version: row.get(9)?,
+                library_name: row.get(10)?,
+                library_version: row.get(11)?,
+            };
+            let failure = |category, field, window, magnitude, unit, tolerance| Failure {
+                category,
+                field,
+                window,
+                magnitude,
+                unit,
+                tolerance,
+                ..template.clone()
+            };
+            let result_windows = count(row, 6)?;
+            if let Some(reference_windows) = row.get::<_, Option<usize>>(7)?
+                && reference_windows != result_windows
+            {
+                failures.push(failure(
+                    "window_count",
+                    None,
+                    None,
+                    result_windows as f64 - reference_windows as f64,
+                    "windows",
+                    None,
+                ));
+            }
+            let deltas: Vec<StoredDelta> =
+                serde_json::from_str(&row.get::<_, String>(8)?).context("Failed to parse recorded deltas")?;
+            for delta in deltas {
+                let (category, unit) = if delta.field == "maxElevation" { ("elevation", "deg") } else { ("timing", "s") };
+                failures.push(failure(
+                    category,
+                    Some(delta.field),
+                    Some(delta.window),
+                    delta.delta,
+                    unit,
+                    Some(delta.tolerance),
+                ));
+            }
+        }
+        Ok(failures)
+    }
+}
+
+impl Filter<'_> {
+    /// SQL condition on `implementation` and `test_case` columns, and its
+    /// parameters.
+    fn conditions(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec!["1".to_string()];
+        let mut params = Vec::new();
+        if let Some(implementation) = self.implementation {
+            conditions.push("implementation = ?".to_string());
+            params.push(Value::Text(implementation.to_string()));
+        }
+        if let Some(test_case) = self.test_case {
+            conditions.push("test_case = ?".to_string());
+            params.push(Value::Text(test_case.to_string()));
+        }
+        (conditions.join(" AND "), params)
+    }
+
+    fn implementation_condition(&self) -> &'static str {
+        if self.implementation.is_some() { "implementation = ?" } else { "1" }
+    }
+
+    fn implementation_params(&self) -> Vec<Value> {
+        self.implementation.map(|implementation| Value::Text(implementation.to_string())).into_iter().collect()
+    }
+}
+
+fn count(row: &Row, index: usize) -> rusqlite::Result<usize> {
+    row.get(index)
+}
+
+/// The verdict as serialized, e.g. `no_reference`.
+fn verdict_name(case: &CaseVerdict) -> Result<String> {
+    Ok(serde_json::to_value(case.verdict)?.as_str().unwrap_or_default().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Limits;
+    use crate::tolerance::FieldDelta;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A fresh results directory, removed when dropped.
+    struct ResultsDir(PathBuf);
+
+    impl ResultsDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("visibility-test-runner-{}-{}", name, std::process::id()));
+            fs::remove_dir_all(&dir).ok();
+            fs::create_dir_all(&dir).unwrap();
+            ResultsDir(dir)
+        }
+    }
+
+    impl Drop for ResultsDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    fn host() -> Host {
+        Host {
+            profile: Some("rpi4".to_string()),
+            hostname: "pi4".to_string(),
+            arch: "aarch64".to_string(),
+            cpu: None,
+            cpus: 4,
+            watts_per_core: 1.0,
+            limits: Limits::default(),
+        }
+    }
+
+    fn container(implementation: &str, success: bool, result_files: Vec<PathBuf>) -> TestResult {
+        TestResult {
+            implementation: implementation.to_string(),
+            success,
+            execution_time: 1.5,
+            stdout: String::new(),
+            stderr: String::new(),
+            result_files,
+            unsupported: Vec::new(),
+            cpu_seconds: Some(1.25),
+            peak_memory: Some(64 << 20),
+            image_id: Some("sha256:abc".to_string()),
+            timed_out: false,
+            hung: false,
+            quarantined: Vec::new(),
+        }
+    }
+
+    fn verdict(test_case: &str, verdict: Verdict, windows: (usize, Option<usize>), deltas: Vec<FieldDelta>) -> CaseVerdict {
+        CaseVerdict {
+            test_case: test_case.to_string(),
+            verdict,
+            result_windows: windows.0,
+            reference_windows: windows.1,
+            warnings: 0,
+            regime: None,
+            deltas,
+            unmatched: Vec::new(),
+            expected_failure: false,
+        }
+    }
+
+    fn delta(window: usize, field: &'static str, delta: f64) -> FieldDelta {
+        FieldDelta {
+            window,
+            reference_window: window,
+            field,
+            delta,
+            tolerance: 60.0,
+        }
+    }
+
+    #[test]
+    fn runs_round_trip_into_history() {
+        let results = ResultsDir::new("db-history");
+        let result_file = results.0.join("rust-sgp4_001_iss_nyc.json");
+        fs::write(
+            &result_file,
+            r#"{"testCase": "001_iss_nyc", "implementation": "rust-sgp4", "version": "0.3.0", "visibilityWindows": [{}, {}], "executionTime": 0.5}"#,
+        )
+        .unwrap();
+
+        let db = Database::open(&results.0).unwrap();
+        db.record_run("20261015_030000", &host()).unwrap();
+        db.record_container("20261015_030000", &container("rust-sgp4", true, vec![result_file])).unwrap();
+        // A resumed run adds a failing container for the same implementation
+        db.record_container("20261015_030000", &container("rust-sgp4", false, Vec::new())).unwrap();
+        db.record_verdicts(
+            "20261015_030000",
+            "rust-sgp4",
+            &[
+                verdict("001_iss_nyc", Verdict::Match, (2, Some(2)), Vec::new()),
+                verdict("002_starlink_sf", Verdict::Mismatch, (3, Some(3)), vec![delta(2, "start", 90.0)]),
+                verdict("t_isl", Verdict::Unsupported, (0, None), Vec::new()),
+            ],
+        )
+        .unwrap();
+        db.finish_run("20261015_030000", false).unwrap();
+        db.record_run("20261016_030000", &host()).unwrap();
+
+        // Reopening migrates nothing and keeps everything
+        let db = Database::open(&results.0).unwrap();
+        let history = db.history(None).unwrap();
+        assert_eq!(history.len(), 2);
+        let entry = &history[0];
+        assert_eq!(entry.run_id.as_deref(), Some("20261015_030000"));
+        assert!(!entry.passed);
+        assert!(entry.finished_at.is_some());
+        assert_eq!(entry.runs.len(), 1);
+        let run = &entry.runs[0];
+        assert!(!run.success);
+        assert_eq!(run.execution_time, 3.0);
+        assert_eq!(run.cpu_seconds, Some(2.5));
+        assert_eq!(run.peak_memory, Some(64 << 20));
+        assert_eq!(run.image_id.as_deref(), Some("sha256:abc"));
+        let validation = &entry.validation[0];
+        assert_eq!((validation.matched, validation.total), (1, 2));
+        assert_eq!(validation.cases[1].deltas[0].field, "start");
+
+        // Not finished, so judged by what it recorded: nothing failed
+        assert_eq!(history[1].finished_at, None);
+        assert!(history[1].passed);
+        assert_eq!(db.history(Some(1)).unwrap()[0].run_id.as_deref(), Some("20261016_030000"));
+
+        let cases = db
+            .cases(&Filter {
+                implementation: Some("rust-sgp4"),
+                last: 10,
+                ..Filter::default()
+            })
+            .unwrap();
+        let iss = cases.iter().find(|case| case.test_case == "001_iss_nyc").unwrap();
+        assert_eq!(iss.version.as_deref(), Some("0.3.0"));
+        assert_eq!(iss.windows, Some(2));
+        assert_eq!(iss.execution_time, Some(0.5));
+        assert_eq!(iss.verdict.as_deref(), Some("match"));
+        assert_eq!(cases.iter().find(|case| case.test_case == "t_isl").unwrap().windows, None);
+
+        let runs = db.runs(&Filter { last: 10, ..Filter::default() }).unwrap();
+        assert_eq!(runs[0].run_id, "20261016_030000");
+        let run = &runs[1];
+        assert_eq!(run.profile.as_deref(), Some("rpi4"));
+        assert_eq!((run.implementations, run.failed_containers, run.results), (1, 1, 1));
+        assert_eq!((run.matched, run.validated), (1, 2));
+    }
+
+    #[test]
+    fn failures_flatten_mismatches() {
+        let results = ResultsDir::new("db-failures");
+        let db = Database::open(&results.0).unwrap();
+        db.record_run("20261015_030000", &host()).unwrap();
+        db.record_versions(
+            "20261015_030000",
+            "python-sgp4",
+            &Versions {
+                implementation: "python-sgp4".to_string(),
+                version: "1.2.0".to_string(),
+                library_name: Some("sgp4".to_string()),
+                library_version: Some("2.23".to_string()),
+                platform: None,
+            },
+        )
+        .unwrap();
+        db.record_verdicts(
+            "20261015_030000",
+            "python-sgp4",
+            &[
+                verdict("001_iss_nyc", Verdict::Match, (2, Some(2)), Vec::new()),
+                verdict(
+                    "003_gps_denver",
+                    Verdict::Mismatch,
+                    (1, Some(1)),
+                    vec![delta(1, "start", 180.0), delta(1, "maxElevation", -1.2)],
+                ),
+                verdict("005_iss_equator", Verdict::Mismatch, (2, Some(3)), vec![delta(2, "end", 72.0)]),
+            ],
+        )
+        .unwrap();
+
+        let failures = db.failures().unwrap();
+        let summary: Vec<String> = failures
+            .iter()
+            .map(|failure| {
+                let field = failure.field.as_deref().zip(failure.window).map(|(field, window)| format!(" window {} {}", window, field));
+                format!(
+                    "{} {}{}: {}{}",
+                    failure.test_case,
+                    failure.category,
+                    field.unwrap_or_default(),
+                    failure.magnitude,
+                    failure.unit
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "003_gps_denver timing window 1 start: 180s",
+                "003_gps_denver elevation window 1 maxElevation: -1.2deg",
+                "005_iss_equator window_count: -1windows",
+                "005_iss_equator timing window 2 end: 72s",
+            ]
+        );
+        assert!(failures.iter().all(|failure| failure.version.as_deref() == Some("1.2.0")
+            && failure.library_name.as_deref() == Some("sgp4")
+            && failure.hostname.as_deref() == Some("pi4")));
+
+        let records = db.verdict_history().unwrap();
+        assert_eq!(records.len(), 3);
+        let gps = records.iter().find(|record| record.test_case == "003_gps_denver").unwrap();
+        assert_eq!(gps.verdict, Verdict::Mismatch);
+        assert_eq!(gps.windows_off, 1);
+        assert_eq!(gps.version.as_deref(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn containers_and_start_times_read_back() {
+        let results = ResultsDir::new("db-containers");
+        let db = Database::open(&results.0).unwrap();
+        db.record_run("20261015_030000", &host()).unwrap();
+        db.record_container("20261015_030000", &container("go-sgp4", true, Vec::new())).unwrap();
+
+        let containers = db.containers("20261015_030000").unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].implementation, "go-sgp4");
+        assert!(containers[0].success);
+        assert_eq!(containers[0].peak_memory, Some(64 << 20));
+        assert!(db.started_at("20261015_030000").unwrap().is_some());
+        assert!(db.started_at("20261015_040000").unwrap().is_none());
+    }
+}
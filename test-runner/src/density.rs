@@ -0,0 +1,149 @@
+//! Point density of results against each case's time step, for reports.
+//!
+//! Point-level comparisons (`validate --points`, the accuracy ranking) pair
+//! an implementation's points with the reference's by timestamp, so they
+//! quietly cover less of the pass when an implementation samples on a grid
+//! of its own instead of the case's `timeWindow.step`. Each window's points
+//! are counted against the `duration / step + 1` the step implies, the
+//! spacing between consecutive points is compared with the step, and
+//! points are checked against the step grid from `timeWindow.start`. Over
+//! an implementation's latest results, the sampling is then:
+//!
+//! - matching the step: within 10% of the expected count, spaced by the
+//!   step, and nearly all on the grid
+//! - decimated: fewer points than expected, spaced wider than the step
+//! - resampled: anything else, such as a finer or adaptive grid, or points
+//!   shifted off the step grid (e.g. to refined window boundaries)
+//! - without points: windows that carry no points at all
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Share of points, or of the expected count, the sampling may be off by
+/// before it's flagged.
+const COUNT_TOLERANCE: f64 = 0.1;
+/// Relative difference between the median spacing and the step that still
+/// counts as the step
+const SPACING_TOLERANCE: f64 = 0.05;
+/// Seconds a point may be off the step grid and still count as on it
+const GRID_TOLERANCE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    Step,
+    Decimated,
+    Resampled,
+    NoPoints,
+}
+
+impl Sampling {
+    pub fn label(self) -> &'static str {
+        match self {
+            Sampling::Step => "matches step",
+            Sampling::Decimated => "decimated",
+            Sampling::Resampled => "resampled",
+            Sampling::NoPoints => "no points",
+        }
+    }
+
+    /// Whether point-level comparisons are skewed by it
+    pub fn flagged(self) -> bool {
+        self != Sampling::Step
+    }
+}
+
+/// Points of one or more results against their cases' steps.
+#[derive(Debug, Default, Clone)]
+pub struct Density {
+    pub points: usize,
+    /// Points the steps imply for the windows' durations
+    pub expected: usize,
+    /// Points on their case's step grid
+    pub on_grid: usize,
+    /// Spacing of consecutive points within a window, in steps
+    spacings: Vec<f64>,
+}
+
+impl Density {
+    /// Density of `result`'s windows against `case`'s time window. Empty if
+    /// the case has no valid start or step.
+    pub fn of(result: &Value, case: &Value) -> Self {
+        let mut density = Density::default();
+        let start = case["timeWindow"]["start"].as_str().and_then(parse_time);
+        let step = case["timeWindow"]["step"].as_f64().filter(|step| *step > 0.0);
+        let (Some(start), Some(step)) = (start, step) else {
+            return density;
+        };
+
+        for window in result["visibilityWindows"].as_array().into_iter().flatten() {
+            if let Some(duration) = window["duration"].as_f64() {
+                density.expected += (duration / step).floor() as usize + 1;
+            }
+            let times: Vec<DateTime<Utc>> = window["points"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|point| point["time"].as_str().and_then(parse_time))
+                .collect();
+            density.points += times.len();
+            for time in &times {
+                let offset = (*time - start).num_milliseconds() as f64 / 1000.0;
+                let off_grid = offset.rem_euclid(step);
+                if off_grid.min(step - off_grid) <= GRID_TOLERANCE {
+                    density.on_grid += 1;
+                }
+            }
+            for pair in times.windows(2) {
+                density.spacings.push((pair[1] - pair[0]).num_milliseconds() as f64 / 1000.0 / step);
+            }
+        }
+        density
+    }
+
+    pub fn merge(&mut self, other: Density) {
+        self.points += other.points;
+        self.expected += other.expected;
+        self.on_grid += other.on_grid;
+        self.spacings.extend(other.spacings);
+    }
+
+    /// Median spacing of consecutive points, in steps.
+    pub fn median_spacing(&self) -> Option<f64> {
+        if self.spacings.is_empty() {
+            return None;
+        }
+        let mut spacings = self.spacings.clone();
+        spacings.sort_by(f64::total_cmp);
+        Some(spacings[spacings.len() / 2])
+    }
+
+    /// Share of the points on the step grid.
+    pub fn grid_share(&self) -> Option<f64> {
+        (self.points > 0).then(|| self.on_grid as f64 / self.points as f64)
+    }
+
+    /// `None` without windows to judge by.
+    pub fn sampling(&self) -> Option<Sampling> {
+        if self.expected == 0 {
+            return None;
+        }
+        if self.points == 0 {
+            return Some(Sampling::NoPoints);
+        }
+        let coverage = self.points as f64 / self.expected as f64;
+        let spacing = self.median_spacing();
+        let on_step = spacing.is_some_and(|spacing| (spacing - 1.0).abs() <= SPACING_TOLERANCE);
+        let on_grid = self.grid_share().is_some_and(|share| share >= 1.0 - COUNT_TOLERANCE);
+        Some(if on_step && on_grid && (coverage - 1.0).abs() <= COUNT_TOLERANCE {
+            Sampling::Step
+        } else if coverage < 1.0 - COUNT_TOLERANCE && spacing.is_none_or(|spacing| spacing > 1.0 + SPACING_TOLERANCE) {
+            Sampling::Decimated
+        } else {
+            Sampling::Resampled
+        })
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|time| time.with_timezone(&Utc))
+}
@@ -0,0 +1,364 @@
+//! `du`: disk usage of the results directory, without changing anything.
+//!
+//! Every file under `results/` is counted by the space it takes on disk,
+//! and summed three ways:
+//!
+//! - by run: each `results/<run-id>/` directory (recognized by its
+//!   `host.json`), with its age
+//! - by implementation: its flat result files, its subdirectories of runs,
+//!   and its quarantined files
+//! - by artifact type: flat result files (plain and gzip-compressed), the
+//!   result copies, logs, and plots in run directories, the other run
+//!   records, the results database and history, the quarantine, scratch
+//!   directories, and anything else
+//!
+//! Suggestions follow for what could go: result files beyond the newest
+//! per implementation and case (the profile's `keep`, or only the newest,
+//! which is the one read), runs
+//! older than `--older-than` days (the results database keeps their
+//! records), uncompressed result files that would shrink with gzip
+//! (estimated by compressing them in memory), and leftover scratch
+//! directories and quarantined files.
+
+use crate::{Orchestrator, result_test_case, truth};
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime};
+use colored::Colorize;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directories the orchestrator stages files in while it works
+const SCRATCH_DIRS: &[&str] = &[".heartbeat", ".materialized", ".minimize", ".payload", ".screening", ".stream", ".stress", ".sweep"];
+/// Estimated gzip savings worth suggesting compression for
+const COMPRESS_THRESHOLD: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Results,
+    CompressedResults,
+    ResultCopies,
+    Logs,
+    Plots,
+    RunRecords,
+    Database,
+    Quarantine,
+    Scratch,
+    Other,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Results => "result files",
+            Kind::CompressedResults => "compressed result files",
+            Kind::ResultCopies => "result copies in runs",
+            Kind::Logs => "container logs",
+            Kind::Plots => "elevation plots",
+            Kind::RunRecords => "other run records",
+            Kind::Database => "results database and history",
+            Kind::Quarantine => "quarantined files",
+            Kind::Scratch => "scratch directories",
+            Kind::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Run {
+    pub run_id: String,
+    pub bytes: u64,
+    /// Days since the run started, if its ID gives the time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Suggestion {
+    /// `prune`, `remove_old_runs`, `compress`, `remove_scratch`, or
+    /// `review_quarantine`
+    pub action: &'static str,
+    /// Bytes it would free
+    pub bytes: u64,
+    pub detail: String,
+    /// Files or directories it concerns
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskUsage {
+    pub total: u64,
+    /// Newest first
+    pub runs: Vec<Run>,
+    pub implementations: BTreeMap<String, u64>,
+    pub kinds: BTreeMap<Kind, u64>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Space `path` takes on disk, following no links.
+fn disk_size(path: &Path) -> u64 {
+    fs::symlink_metadata(path).map_or(0, |metadata| metadata.blocks() * 512)
+}
+
+/// Bytes on disk of every file under `dir`.
+fn tree_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| disk_size(entry.path()))
+        .sum()
+}
+
+/// Size of `path` gzip-compressed, with fast compression.
+fn compressed_size(path: &Path) -> Option<u64> {
+    struct Counter(u64);
+    impl Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut encoder = GzEncoder::new(Counter(0), Compression::fast());
+    io::copy(&mut fs::File::open(path).ok()?, &mut encoder).ok()?;
+    Some(encoder.finish().ok()?.0)
+}
+
+/// The artifact type of `relative`, a file in a run directory.
+fn run_file_kind(relative: &Path) -> Kind {
+    let name = relative.file_name().unwrap_or_default().to_string_lossy();
+    match name.as_ref() {
+        "result.json" | "result.json.gz" => Kind::ResultCopies,
+        _ if name.ends_with(".log") => Kind::Logs,
+        _ if name.ends_with(".svg") => Kind::Plots,
+        _ => Kind::RunRecords,
+    }
+}
+
+/// Audit `results/`, suggesting removal of runs older than `older_than`
+/// days.
+pub fn audit(orchestrator: &Orchestrator, older_than: u64) -> Result<DiskUsage> {
+    let results_dir = &orchestrator.results_dir;
+    let mut usage = DiskUsage {
+        total: 0,
+        runs: Vec::new(),
+        implementations: BTreeMap::new(),
+        kinds: BTreeMap::new(),
+        suggestions: Vec::new(),
+    };
+    if !results_dir.exists() {
+        return Ok(usage);
+    }
+
+    // Longest first, so `rust` doesn't claim `rust_fast`'s files
+    let mut names: Vec<String> = orchestrator
+        .discover_implementations()?
+        .into_iter()
+        .chain(truth::external_tools(&orchestrator.test_data_dir)?)
+        .map(|impl_| impl_.name)
+        .collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+    let mut uncompressed = Vec::new();
+    let mut scratch = Vec::new();
+    let mut quarantine = None;
+    let mut entries: Vec<_> = fs::read_dir(results_dir)?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+    entries.sort();
+    for path in entries {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if path.is_dir() {
+            let bytes = tree_size(&path);
+            usage.total += bytes;
+            if path.join("host.json").is_file() {
+                for entry in WalkDir::new(&path).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+                    let relative = entry.path().strip_prefix(&path).unwrap_or(entry.path());
+                    let size = disk_size(entry.path());
+                    *usage.kinds.entry(run_file_kind(relative)).or_default() += size;
+                    let mut components = relative.components();
+                    if let (Some(first), Some(_)) = (components.next(), components.next()) {
+                        let implementation = first.as_os_str().to_string_lossy().to_string();
+                        *usage.implementations.entry(implementation).or_default() += size;
+                    }
+                }
+                let started = NaiveDateTime::parse_from_str(&file_name, "%Y%m%d_%H%M%S").ok();
+                let age_days = started.map(|started| (Local::now().naive_local() - started).num_days());
+                usage.runs.push(Run {
+                    run_id: file_name,
+                    bytes,
+                    age_days,
+                });
+            } else if file_name == ".quarantine" {
+                *usage.kinds.entry(Kind::Quarantine).or_default() += bytes;
+                for entry in fs::read_dir(&path)?.filter_map(|entry| entry.ok()) {
+                    let implementation = entry.file_name().to_string_lossy().to_string();
+                    *usage.implementations.entry(implementation).or_default() += tree_size(&entry.path());
+                }
+                quarantine = Some((path, bytes));
+            } else if SCRATCH_DIRS.contains(&file_name.as_str()) {
+                *usage.kinds.entry(Kind::Scratch).or_default() += bytes;
+                scratch.push((path, bytes));
+            } else {
+                *usage.kinds.entry(Kind::Other).or_default() += bytes;
+            }
+            continue;
+        }
+
+        let bytes = disk_size(&path);
+        usage.total += bytes;
+        if let Some(name) = names.iter().find(|name| result_test_case(&file_name, name).is_some()) {
+            *usage.implementations.entry(name.clone()).or_default() += bytes;
+            if file_name.ends_with(".gz") {
+                *usage.kinds.entry(Kind::CompressedResults).or_default() += bytes;
+            } else {
+                *usage.kinds.entry(Kind::Results).or_default() += bytes;
+                uncompressed.push((path, bytes));
+            }
+        } else if file_name.starts_with("history.") || file_name == "changes.md" {
+            *usage.kinds.entry(Kind::Database).or_default() += bytes;
+        } else {
+            *usage.kinds.entry(Kind::Other).or_default() += bytes;
+        }
+    }
+    usage.runs.sort_by(|a, b| b.run_id.cmp(&a.run_id));
+
+    // The profile's `keep`, or only what's read
+    let keep = orchestrator.profile.as_ref().and_then(|profile| profile.keep).unwrap_or(1);
+    let stale = orchestrator.stale_results(keep)?;
+    if !stale.is_empty() {
+        usage.suggestions.push(Suggestion {
+            action: "prune",
+            bytes: stale.iter().map(|path| disk_size(path)).sum(),
+            detail: format!(
+                "{} result file(s) are older than the newest {} of their implementation and case: `prune --keep {}`",
+                stale.len(),
+                keep,
+                keep
+            ),
+            paths: stale.clone(),
+        });
+    }
+
+    let old: Vec<&Run> = usage.runs.iter().filter(|run| run.age_days.is_some_and(|age| age >= older_than as i64)).collect();
+    if !old.is_empty() {
+        usage.suggestions.push(Suggestion {
+            action: "remove_old_runs",
+            bytes: old.iter().map(|run| run.bytes).sum(),
+            detail: format!(
+                "{} run(s) are {} days old or older; their directories can go, and the results database keeps their records",
+                old.len(),
+                older_than
+            ),
+            paths: old.iter().map(|run| results_dir.join(&run.run_id)).collect(),
+        });
+    }
+
+    // Files `prune` would take anyway aren't worth compressing
+    let compressible: Vec<(PathBuf, u64)> = uncompressed
+        .into_iter()
+        .filter(|(path, _)| !stale.contains(path))
+        .filter_map(|(path, bytes)| {
+            let saved = bytes.saturating_sub(compressed_size(&path)?);
+            (saved > 0).then_some((path, saved))
+        })
+        .collect();
+    let saved: u64 = compressible.iter().map(|(_, saved)| saved).sum();
+    if saved >= COMPRESS_THRESHOLD {
+        usage.suggestions.push(Suggestion {
+            action: "compress",
+            bytes: saved,
+            detail: format!(
+                "{} uncompressed result file(s) would shrink with gzip, which validation and reports read as well: `gzip results/*.json`",
+                compressible.len()
+            ),
+            paths: compressible.into_iter().map(|(path, _)| path).collect(),
+        });
+    }
+
+    scratch.retain(|(_, bytes)| *bytes > 0);
+    if !scratch.is_empty() {
+        usage.suggestions.push(Suggestion {
+            action: "remove_scratch",
+            bytes: scratch.iter().map(|(_, bytes)| bytes).sum(),
+            detail: "Scratch directories are recreated as needed, and can go while nothing runs".to_string(),
+            paths: scratch.into_iter().map(|(path, _)| path).collect(),
+        });
+    }
+
+    if let Some((path, bytes)) = quarantine.filter(|(_, bytes)| *bytes > 0) {
+        usage.suggestions.push(Suggestion {
+            action: "review_quarantine",
+            bytes,
+            detail: "Files written outside the output contract are kept for review, and can go once looked at".to_string(),
+            paths: vec![path],
+        });
+    }
+    Ok(usage)
+}
+
+/// E.g. "1.5 GiB"
+fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+pub fn print(usage: &DiskUsage, results_dir: &Path) {
+    println!(
+        "{} {}: {}",
+        "Disk usage of".bold().bright_blue(),
+        results_dir.display(),
+        size(usage.total).bright_white()
+    );
+
+    println!("\n{}", "By run:".bold());
+    if usage.runs.is_empty() {
+        println!("  {} No runs", "○".dimmed());
+    }
+    for run in &usage.runs {
+        let age = run.age_days.map(|days| format!(" ({} day(s) old)", days)).unwrap_or_default();
+        println!("  {:<20} {:>10}{}", run.run_id, size(run.bytes), age.dimmed());
+    }
+
+    println!("\n{}", "By implementation:".bold());
+    if usage.implementations.is_empty() {
+        println!("  {} No implementation files", "○".dimmed());
+    }
+    let mut implementations: Vec<(&String, &u64)> = usage.implementations.iter().collect();
+    implementations.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    for (name, bytes) in implementations {
+        println!("  {:<20} {:>10}", name, size(*bytes));
+    }
+
+    println!("\n{}", "By artifact type:".bold());
+    let mut kinds: Vec<(&Kind, &u64)> = usage.kinds.iter().filter(|(_, bytes)| **bytes > 0).collect();
+    kinds.sort_by(|a, b| b.1.cmp(a.1));
+    for (kind, bytes) in kinds {
+        println!("  {:<30} {:>10}", kind.label(), size(*bytes));
+    }
+
+    println!("\n{}", "Suggestions:".bold());
+    if usage.suggestions.is_empty() {
+        println!("  {} Nothing to suggest", "✓".green());
+    }
+    for suggestion in &usage.suggestions {
+        println!("  {} {} ({} to free)", "●".bright_cyan(), suggestion.detail, size(suggestion.bytes).bright_white());
+    }
+}
@@ -0,0 +1,405 @@
+//! The Docker runtime, through the Docker Engine API with
+//! [bollard](https://docs.rs/bollard) rather than the `docker` CLI, so it
+//! works on hosts with a daemon but no CLI (e.g. CI containers with
+//! `/var/run/docker.sock` mounted in).
+//!
+//! The daemon is found through `DOCKER_HOST` (`unix://` or `tcp://`), and is
+//! `/var/run/docker.sock` otherwise. Build output is streamed as it arrives,
+//! and exit codes come straight from the daemon rather than through the
+//! CLI's own (125-127 overlap with the implementation's). The rest of the
+//! orchestrator is synchronous, so each call blocks on a Tokio runtime kept
+//! for the purpose.
+
+use crate::error::Error;
+use crate::runtime::{ContainerRuntime, Heartbeat, RunOutput, RunSpec};
+use anyhow::{Context, Result, bail};
+use bollard::container::LogOutput;
+use bollard::errors::Error as ApiError;
+use bollard::models::{ContainerCreateBody, HostConfig};
+use bollard::query_parameters::{
+    BuildImageOptionsBuilder, CreateImageOptionsBuilder, KillContainerOptions, ListContainersOptions,
+    ListImagesOptionsBuilder, LogsOptionsBuilder, RemoveContainerOptionsBuilder, RemoveImageOptions,
+    StartContainerOptions, WaitContainerOptions,
+};
+use chrono::DateTime;
+use colored::Colorize;
+use futures_util::{StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often a run with a heartbeat stops waiting to check it
+const HEARTBEAT_POLL: Duration = Duration::from_secs(1);
+
+pub struct Docker;
+
+impl ContainerRuntime for Docker {
+    fn program(&self) -> &'static str {
+        "docker"
+    }
+
+    fn build(&self, context_dir: &Path, image: &str, label: &str) -> Result<()> {
+        let (key, value) = label.split_once('=').unwrap_or((label, ""));
+        let options = BuildImageOptionsBuilder::new()
+            .t(image)
+            .labels(&HashMap::from([(key, value)]))
+            .rm(true)
+            .forcerm(true)
+            .build();
+        let context = context(context_dir)?;
+        let docker = client()?;
+        block_on(async {
+            let mut build = pin!(docker.build_image(options, None, Some(bollard::body_full(context.into()))));
+            while let Some(info) = build.next().await {
+                let info = info.map_err(|e| api_error("build", e))?;
+                if let Some(error) = info.error_detail.and_then(|detail| detail.message).or(info.error) {
+                    bail!("Build failed: {}", error.trim());
+                }
+                for line in info.stream.iter().flat_map(|stream| stream.lines()).filter(|line| !line.trim().is_empty()) {
+                    println!("    {} {}", format!("{} |", image).dimmed(), line.dimmed());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn image_id(&self, image: &str) -> Result<Option<String>> {
+        let docker = client()?;
+        Ok(block_on(inspect_image(docker, image))?.and_then(|image| image.id))
+    }
+
+    fn image_label(&self, image: &str, label: &str) -> Result<Option<String>> {
+        let docker = client()?;
+        Ok(block_on(inspect_image(docker, image))?
+            .and_then(|image| image.config?.labels?.remove(label))
+            .filter(|value| !value.is_empty()))
+    }
+
+    fn pull(&self, image: &str) -> Result<()> {
+        println!("  {} Pulling {}", "●".bright_cyan(), image);
+        let (name, tag) = split_reference(image);
+        let mut options = CreateImageOptionsBuilder::new().from_image(name);
+        if let Some(tag) = tag {
+            options = options.tag(tag);
+        }
+        let docker = client()?;
+        block_on(async {
+            let mut pull = pin!(docker.create_image(Some(options.build()), None, None));
+            while let Some(progress) = pull.next().await {
+                match progress {
+                    Ok(_) => {}
+                    Err(ApiError::DockerStreamError { error }) => bail!("Pull of {} failed: {}", image, error.trim()),
+                    Err(e) => return Err(api_error("pull", e)),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn images(&self, filters: &[&str]) -> Result<Vec<(String, Option<String>)>> {
+        let mut by_key: HashMap<&str, Vec<&str>> = HashMap::new();
+        for filter in filters {
+            let (key, value) = filter.split_once('=').unwrap_or((filter, ""));
+            by_key.entry(key).or_default().push(value);
+        }
+        let options = ListImagesOptionsBuilder::new().filters(&by_key).build();
+        let docker = client()?;
+        let images = block_on(docker.list_images(Some(options))).map_err(|e| api_error("images", e))?;
+        let mut listed = Vec::new();
+        for image in images {
+            let tags: Vec<String> = image.repo_tags.into_iter().filter(|tag| tag != "<none>:<none>").collect();
+            if tags.is_empty() {
+                listed.push((image.id, None));
+            } else {
+                listed.extend(tags.into_iter().map(|tag| (image.id.clone(), Some(tag))));
+            }
+        }
+        Ok(listed)
+    }
+
+    fn remove_image(&self, image: &str) -> Result<()> {
+        let docker = client()?;
+        block_on(docker.remove_image(image, None::<RemoveImageOptions>, None)).map_err(|e| api_error("rmi", e))?;
+        Ok(())
+    }
+
+    fn running_images(&self) -> Result<Vec<String>> {
+        let docker = client()?;
+        let containers = block_on(docker.list_containers(None::<ListContainersOptions>)).map_err(|e| api_error("ps", e))?;
+        Ok(containers.into_iter().filter_map(|container| container.image).collect())
+    }
+
+    fn run(&self, spec: &RunSpec) -> Result<RunOutput> {
+        let binds = spec
+            .mounts
+            .iter()
+            .map(|(host, container, read_only)| format!("{}:{}{}", host.display(), container, if *read_only { ":ro" } else { "" }))
+            .collect();
+        let config = ContainerCreateBody {
+            image: Some(spec.image.to_string()),
+            cmd: (!spec.args.is_empty()).then(|| spec.args.iter().map(|arg| arg.to_string()).collect()),
+            env: Some(spec.env.clone()),
+            host_config: Some(HostConfig {
+                binds: Some(binds),
+                cpuset_cpus: spec.cpuset.map(str::to_string),
+                nano_cpus: spec.limits.cpus.map(|cpus| (cpus * 1e9) as i64),
+                memory: spec.limits.memory.map(|memory| memory as i64),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let docker = client()?;
+        let id = block_on(docker.create_container(None::<bollard::query_parameters::CreateContainerOptions>, config))
+            .map_err(|e| api_error("create", e))?
+            .id;
+        let output = run_created(docker, &id, spec);
+        // Removed whatever happened, as `run --rm` would
+        let remove = RemoveContainerOptionsBuilder::new().force(true).build();
+        block_on(docker.remove_container(&id, Some(remove))).ok();
+        output
+    }
+
+    fn cgroups(&self, id: &str) -> Vec<PathBuf> {
+        // systemd and cgroupfs drivers
+        vec![
+            Path::new("system.slice").join(format!("docker-{}.scope", id)),
+            Path::new("docker").join(id),
+        ]
+    }
+}
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .expect("Failed to start the Docker API runtime")
+    })
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// The client for the daemon `DOCKER_HOST` names, shared by every call so
+/// connections are reused.
+fn client() -> Result<&'static bollard::Docker> {
+    static CLIENT: OnceLock<bollard::Docker> = OnceLock::new();
+    if let Some(docker) = CLIENT.get() {
+        return Ok(docker);
+    }
+    let _runtime = runtime().enter();
+    let docker = bollard::Docker::connect_with_defaults().map_err(unavailable)?;
+    Ok(CLIENT.get_or_init(|| docker))
+}
+
+fn unavailable(e: ApiError) -> anyhow::Error {
+    Error::DockerUnavailable {
+        runtime: "docker".to_string(),
+        detail: format!("failed to connect to the Docker daemon: {}", e),
+    }
+    .into()
+}
+
+/// The error for a failed `operation`: the daemon's message if it answered,
+/// otherwise that it couldn't be reached.
+fn api_error(operation: &str, e: ApiError) -> anyhow::Error {
+    match e {
+        ApiError::DockerResponseServerError { status_code, message } => {
+            anyhow::anyhow!("Docker API {} failed ({}): {}", operation, status_code, message.trim())
+        }
+        ApiError::HyperLegacyError { .. }
+        | ApiError::IOError { .. }
+        | ApiError::SocketNotFoundError(_)
+        | ApiError::RequestTimeoutError
+        | ApiError::UnsupportedURISchemeError { .. } => unavailable(e),
+        e => anyhow::Error::new(e).context(format!("Docker API {} failed", operation)),
+    }
+}
+
+/// Image details, or `None` if there is no such image.
+async fn inspect_image(docker: &bollard::Docker, image: &str) -> Result<Option<bollard::models::ImageInspect>> {
+    match docker.inspect_image(image).await {
+        Ok(inspect) => Ok(Some(inspect)),
+        Err(ApiError::DockerResponseServerError { status_code: 404, .. }) => Ok(None),
+        Err(e) => Err(api_error("image inspect", e)),
+    }
+}
+
+/// Split an image reference into the name and tag `create_image` takes
+/// separately, leaving a digest reference whole.
+fn split_reference(image: &str) -> (&str, Option<&str>) {
+    match image.rsplit_once(':') {
+        Some((name, tag)) if !image.contains('@') && !tag.contains('/') => (name, Some(tag)),
+        _ => (image, None),
+    }
+}
+
+/// Start container `id`, wait for it to exit, and collect its output.
+fn run_created(docker: &bollard::Docker, id: &str, spec: &RunSpec) -> Result<RunOutput> {
+    if let Some(cidfile) = spec.cidfile {
+        std::fs::write(cidfile, id)?;
+    }
+    block_on(docker.start_container(id, None::<StartContainerOptions>)).map_err(|e| api_error("start", e))?;
+    let (exit_code, timed_out, hung) = wait(docker, id, spec.timeout, spec.heartbeat.as_ref())?;
+    let (stdout, stderr) = block_on(logs(docker, id, false, "all"))?;
+    Ok(RunOutput {
+        exit_code,
+        stdout,
+        stderr,
+        timed_out,
+        hung,
+    })
+}
+
+/// Stdout and stderr of container `id`, from its last `tail` lines (or
+/// `all`), each line prefixed with its time if `timestamps`.
+async fn logs(docker: &bollard::Docker, id: &str, timestamps: bool, tail: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+    let options = LogsOptionsBuilder::new()
+        .stdout(true)
+        .stderr(true)
+        .timestamps(timestamps)
+        .tail(tail)
+        .build();
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let mut logs = pin!(docker.logs(id, Some(options)));
+    while let Some(output) = logs.try_next().await.map_err(|e| api_error("logs", e))? {
+        match output {
+            LogOutput::StdErr { message } => stderr.extend_from_slice(&message),
+            LogOutput::StdOut { message } | LogOutput::Console { message } => stdout.extend_from_slice(&message),
+            LogOutput::StdIn { .. } => {}
+        }
+    }
+    Ok((stdout, stderr))
+}
+
+/// When container `id` last printed, from the timestamp the daemon keeps
+/// with its latest line on either stream.
+fn last_output(docker: &bollard::Docker, id: &str) -> Option<SystemTime> {
+    let (stdout, stderr) = block_on(logs(docker, id, true, "1")).ok()?;
+    [stdout, stderr]
+        .iter()
+        .filter_map(|output| {
+            let line = String::from_utf8_lossy(output).lines().last()?.to_string();
+            let (time, _) = line.split_once(' ').unwrap_or((&line, ""));
+            DateTime::parse_from_rfc3339(time).ok().map(SystemTime::from)
+        })
+        .max()
+}
+
+/// Wait for container `id` to exit, killing it if it runs past `timeout`
+/// or goes silent past its `heartbeat`. Returns its exit code and whether
+/// it was killed for either.
+fn wait(docker: &bollard::Docker, id: &str, timeout: Option<Duration>, heartbeat: Option<&Heartbeat>) -> Result<(Option<i64>, bool, bool)> {
+    let started = SystemTime::now();
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    // One request, answered when the container exits; in between, waiting
+    // stops at the deadline, or with a heartbeat, at the next time to check it
+    let mut waiting = docker.wait_container(id, None::<WaitContainerOptions>).boxed();
+    loop {
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let poll = match heartbeat {
+            Some(_) => Some(remaining.map_or(HEARTBEAT_POLL, |remaining| remaining.min(HEARTBEAT_POLL))),
+            None => remaining,
+        };
+        let waited = match poll {
+            Some(poll) => block_on(async { tokio::time::timeout(poll, waiting.next()).await.ok() }),
+            None => Some(block_on(waiting.next())),
+        };
+        if let Some(waited) = waited {
+            return Ok((exit_code(id, waited)?, false, false));
+        }
+        let timed_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let hung = !timed_out && heartbeat.is_some_and(|heartbeat| heartbeat.silent(started, || last_output(docker, id)));
+        if timed_out || hung {
+            block_on(docker.kill_container(id, None::<KillContainerOptions>)).map_err(|e| api_error("kill", e))?;
+            return Ok((exit_code(id, block_on(waiting.next()))?, timed_out, hung));
+        }
+    }
+}
+
+/// The exit code from a wait response. The client reports a nonzero code as
+/// an error, which is only one if the daemon gave a message with it.
+fn exit_code(id: &str, waited: Option<Result<bollard::models::ContainerWaitResponse, ApiError>>) -> Result<Option<i64>> {
+    match waited.context("Docker API wait returned no response")? {
+        Ok(response) => Ok(Some(response.status_code)),
+        Err(ApiError::DockerContainerWaitError { error, code }) => {
+            if !error.is_empty() {
+                bail!("Waiting for container {} failed: {}", id, error);
+            }
+            Ok(Some(code))
+        }
+        Err(e) => Err(api_error("wait", e)),
+    }
+}
+
+/// `dir` as an uncompressed tar archive, the build context the daemon
+/// expects. Unlike the CLI, `.dockerignore` isn't applied.
+fn context(dir: &Path) -> Result<Vec<u8>> {
+    let mut archive = tar::Builder::new(Vec::new());
+    archive.follow_symlinks(false);
+    archive
+        .append_dir_all(".", dir)
+        .with_context(|| format!("Failed to archive the build context {}", dir.display()))?;
+    Ok(archive.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn split_reference_separates_the_tag() {
+        assert_eq!(split_reference("alpine:3.20"), ("alpine", Some("3.20")));
+        assert_eq!(split_reference("registry:5000/team/image:v1"), ("registry:5000/team/image", Some("v1")));
+        assert_eq!(split_reference("registry:5000/team/image"), ("registry:5000/team/image", None));
+        assert_eq!(split_reference("alpine"), ("alpine", None));
+    }
+
+    #[test]
+    fn split_reference_keeps_digests_whole() {
+        let image = "alpine@sha256:0123456789abcdef";
+        assert_eq!(split_reference(image), (image, None));
+    }
+
+    #[test]
+    fn context_archives_the_directory() {
+        let dir = std::env::temp_dir().join(format!("visibility-test-runner-context-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Dockerfile"), "FROM scratch\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::os::unix::fs::symlink("main.rs", dir.join("src/link.rs")).unwrap();
+
+        let archive = context(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut entries: Vec<(String, tar::EntryType, Option<String>)> = tar::Archive::new(archive.unwrap().as_slice())
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().trim_end_matches('/').to_string();
+                let link = entry.link_name().unwrap().map(|link| link.to_string_lossy().into_owned());
+                (path, entry.header().entry_type(), link)
+            })
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            entries,
+            vec![
+                (".".to_string(), tar::EntryType::Directory, None),
+                ("Dockerfile".to_string(), tar::EntryType::Regular, None),
+                ("src".to_string(), tar::EntryType::Directory, None),
+                ("src/link.rs".to_string(), tar::EntryType::Symlink, Some("main.rs".to_string())),
+                ("src/main.rs".to_string(), tar::EntryType::Regular, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Doppler correction tables.
+//!
+//! For cases whose satellite declares a `downlinkFrequency` (and optionally
+//! an `uplinkFrequency`), in Hz, every sampled point of a pass gets the
+//! frequency to receive on and the frequency to transmit on so the
+//! satellite hears the nominal uplink:
+//!
+//! - RX = downlink × (1 - ṙ/c)
+//! - TX = uplink / (1 - ṙ/c)
+//!
+//! where ṙ is the point's `rangeRate`, positive when receding. Results
+//! without `rangeRate` fall back to the change in `range` between points.
+
+use crate::{Orchestrator, read_result};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+
+/// Speed of light, km/s
+const C: f64 = 299_792.458;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DopplerFormat {
+    /// One row per point with range rate, RX and TX frequencies
+    Csv,
+    /// `<unix time> <RX Hz>` lines, the time-tagged frequency list read by
+    /// file-driven Doppler correction in SDR tools
+    Sdr,
+}
+
+struct Sample {
+    pass: usize,
+    time: DateTime<Utc>,
+    range_rate: f64,
+}
+
+pub fn render(
+    orchestrator: &Orchestrator,
+    impl_name: &str,
+    test_case: &str,
+    pass: Option<usize>,
+    format: DopplerFormat,
+) -> Result<String> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let case_path = cases_dir.join(format!("{}.json", test_case));
+    let case: Value = serde_json::from_str(
+        &fs::read_to_string(&case_path).with_context(|| format!("Failed to read {}", case_path.display()))?,
+    )?;
+    let downlink = case["satellite"]["downlinkFrequency"]
+        .as_f64()
+        .with_context(|| format!("{} declares no satellite.downlinkFrequency", test_case))?;
+    let uplink = case["satellite"]["uplinkFrequency"].as_f64();
+
+    let result: Value = serde_json::from_str(&read_result(&orchestrator.latest_result(impl_name, test_case)?)?)?;
+    let windows = result["visibilityWindows"].as_array().cloned().unwrap_or_default();
+    if let Some(number) = pass
+        && (number == 0 || number > windows.len())
+    {
+        bail!("{} has {} pass(es); there is no pass {}", test_case, windows.len(), number);
+    }
+
+    let mut samples = Vec::new();
+    for (i, window) in windows.iter().enumerate() {
+        if pass.is_some_and(|number| number != i + 1) {
+            continue;
+        }
+        samples.extend(pass_samples(i + 1, window));
+    }
+    if samples.is_empty() {
+        bail!("No sampled points with range data in {} for {}", test_case, impl_name);
+    }
+
+    let mut out = String::new();
+    match format {
+        DopplerFormat::Csv => {
+            out.push_str("pass,time,range_rate_km_s,downlink_hz,rx_hz,rx_shift_hz");
+            if uplink.is_some() {
+                out.push_str(",uplink_hz,tx_hz,tx_shift_hz");
+            }
+            out.push('\n');
+            for sample in &samples {
+                let rx = rx_frequency(downlink, sample.range_rate);
+                write!(
+                    out,
+                    "{},{},{:.4},{:.0},{:.0},{:.0}",
+                    sample.pass,
+                    sample.time.format("%Y-%m-%dT%H:%M:%SZ"),
+                    sample.range_rate,
+                    downlink,
+                    rx,
+                    rx - downlink
+                )?;
+                if let Some(uplink) = uplink {
+                    let tx = tx_frequency(uplink, sample.range_rate);
+                    write!(out, ",{:.0},{:.0},{:.0}", uplink, tx, tx - uplink)?;
+                }
+                out.push('\n');
+            }
+        }
+        DopplerFormat::Sdr => {
+            for sample in &samples {
+                writeln!(out, "{} {:.0}", sample.time.timestamp(), rx_frequency(downlink, sample.range_rate))?;
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Frequency to receive `downlink` on at `range_rate` (km/s, positive
+/// when receding).
+fn rx_frequency(downlink: f64, range_rate: f64) -> f64 {
+    downlink * (1.0 - range_rate / C)
+}
+
+/// Frequency to transmit on for the satellite to hear `uplink` at
+/// `range_rate`.
+fn tx_frequency(uplink: f64, range_rate: f64) -> f64 {
+    uplink / (1.0 - range_rate / C)
+}
+
+/// Time and range rate of every point in a window, with range rates derived
+/// from neighbouring ranges where the result doesn't give them.
+fn pass_samples(pass: usize, window: &Value) -> Vec<Sample> {
+    let points: Vec<(DateTime<Utc>, Option<f64>, Option<f64>)> = window["points"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|point| {
+            let time = DateTime::parse_from_rfc3339(point["time"].as_str()?).ok()?.with_timezone(&Utc);
+            Some((time, point["range"].as_f64(), point["rangeRate"].as_f64()))
+        })
+        .collect();
+
+    let mut samples = Vec::new();
+    for (i, &(time, _, range_rate)) in points.iter().enumerate() {
+        let range_rate = range_rate.or_else(|| {
+            let (before, after) = (&points[i.saturating_sub(1)], points.get(i + 1).unwrap_or(&points[i]));
+            let seconds = (after.0 - before.0).num_milliseconds() as f64 / 1000.0;
+            (seconds > 0.0).then_some((after.1? - before.1?) / seconds)
+        });
+        if let Some(range_rate) = range_rate {
+            samples.push(Sample { pass, time, range_rate });
+        }
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DOWNLINK: f64 = 437_800_000.0;
+    const UPLINK: f64 = 145_900_000.0;
+
+    #[test]
+    fn an_approaching_satellite_is_heard_high_and_sent_to_low() {
+        let rx = rx_frequency(DOWNLINK, -7.0);
+        let tx = tx_frequency(UPLINK, -7.0);
+        assert!(rx > DOWNLINK && tx < UPLINK, "rx {} tx {}", rx, tx);
+        // About 10 kHz at 70 cm for a LEO pass
+        assert!((rx - DOWNLINK - 10_222.0).abs() < 1.0, "{}", rx - DOWNLINK);
+    }
+
+    #[test]
+    fn a_receding_satellite_is_heard_low_and_sent_to_high() {
+        let rx = rx_frequency(DOWNLINK, 7.0);
+        let tx = tx_frequency(UPLINK, 7.0);
+        assert!(rx < DOWNLINK && tx > UPLINK, "rx {} tx {}", rx, tx);
+        // What the satellite receives is the nominal uplink again
+        assert!((tx * (1.0 - 7.0 / C) - UPLINK).abs() < 1e-6);
+    }
+
+    #[test]
+    fn range_rates_come_from_ranges_when_not_given() {
+        let window = json!({ "points": [
+            { "time": "2025-01-01T12:00:00Z", "range": 2000.0 },
+            { "time": "2025-01-01T12:01:00Z", "range": 1400.0 },
+            { "time": "2025-01-01T12:02:00Z", "range": 1100.0, "rangeRate": 0.5 },
+        ] });
+        let rates: Vec<f64> = pass_samples(1, &window).iter().map(|sample| sample.range_rate).collect();
+        // Approaching while the range shrinks; a given rate is kept
+        assert_eq!(rates, [-10.0, -7.5, 0.5]);
+    }
+}
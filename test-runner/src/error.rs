@@ -0,0 +1,205 @@
+//! Failure kinds automation can branch on.
+//!
+//! Errors travel as `anyhow::Error` with context added on the way up, but
+//! the failures worth telling apart are raised as an [`Error`], which can be
+//! recovered from beneath any context. Its kind is reported as `kind` in the
+//! `--json` document, and in the per-implementation `errors` of `run` and
+//! `all`, and a command failing with it exits with its code:
+//!
+//! | Kind                      | Exit code |
+//! |---------------------------|-----------|
+//! | (anything else)           | 1         |
+//! | (bad command-line usage)  | 2         |
+//! | `docker_unavailable`      | 3         |
+//! | `implementation_not_found`| 4         |
+//! | `build_failed`            | 5         |
+//! | `health_check_failed`     | 6         |
+//! | `timeout`                 | 7         |
+//! | `schema_violation`        | 8         |
+//! | `reference_missing`       | 9         |
+//! | `reference_invalid`       | 10        |
+//! | `gate_failed`             | 11        |
+//! | `run_failed`              | 12        |
+//! | `validation_failed`       | 13        |
+//! | `hung`                    | 14        |
+//!
+//! `build`, `run`, `validate`, and `all` carry on past failing builds, runs,
+//! and cases, and then fail with a [`Failed`] for the first stage that
+//! failed, which keeps the command's fields in the `--json` document. When
+//! every container that failed was killed at its timeout or for going
+//! silent, `run` and `all` fail with the first as a `timeout` or `hung`;
+//! any other failed run makes it `run_failed`.
+
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The container runtime can't be run or its daemon can't be reached
+    DockerUnavailable { runtime: String, detail: String },
+    ImplementationNotFound { name: String },
+    BuildFailed { implementation: String, detail: String },
+    /// Images that are missing or don't answer `--version` properly
+    HealthCheckFailed { implementations: Vec<String>, detail: String },
+    /// A container killed at `--timeout`, running one case or all of them
+    Timeout {
+        implementation: String,
+        test_case: Option<String>,
+        seconds: u64,
+    },
+    /// A container killed for going `seconds` without a heartbeat
+    Hung {
+        implementation: String,
+        test_case: Option<String>,
+        seconds: u64,
+    },
+    /// A result or reference file that doesn't parse as a result
+    SchemaViolation { file: PathBuf, detail: String },
+    /// Test cases without a reference result
+    ReferenceMissing { cases: Vec<String> },
+    /// Reference results failing `check-reference`
+    ReferenceInvalid { problems: usize },
+    /// `validate` results falling short of `--min-pass-rate` or `--min-score`
+    GateFailed { shortfalls: Vec<String> },
+    /// Builds that failed in a command building several images
+    BuildsFailed { implementations: Vec<String> },
+    /// Containers that failed, timed out, hung, or couldn't be run
+    RunFailed { implementations: Vec<String> },
+    /// `<implementation>/<case>` results that don't match the reference,
+    /// or implementations whose results couldn't be validated
+    ValidationFailed { mismatches: Vec<String> },
+}
+
+impl Error {
+    /// The kind as reported in JSON, e.g. `build_failed`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::DockerUnavailable { .. } => "docker_unavailable",
+            Error::ImplementationNotFound { .. } => "implementation_not_found",
+            Error::BuildFailed { .. } => "build_failed",
+            Error::HealthCheckFailed { .. } => "health_check_failed",
+            Error::Timeout { .. } => "timeout",
+            Error::Hung { .. } => "hung",
+            Error::SchemaViolation { .. } => "schema_violation",
+            Error::ReferenceMissing { .. } => "reference_missing",
+            Error::ReferenceInvalid { .. } => "reference_invalid",
+            Error::GateFailed { .. } => "gate_failed",
+            Error::BuildsFailed { .. } => "build_failed",
+            Error::RunFailed { .. } => "run_failed",
+            Error::ValidationFailed { .. } => "validation_failed",
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::DockerUnavailable { .. } => 3,
+            Error::ImplementationNotFound { .. } => 4,
+            Error::BuildFailed { .. } | Error::BuildsFailed { .. } => 5,
+            Error::HealthCheckFailed { .. } => 6,
+            Error::Timeout { .. } => 7,
+            Error::SchemaViolation { .. } => 8,
+            Error::ReferenceMissing { .. } => 9,
+            Error::ReferenceInvalid { .. } => 10,
+            Error::GateFailed { .. } => 11,
+            Error::RunFailed { .. } => 12,
+            Error::ValidationFailed { .. } => 13,
+            Error::Hung { .. } => 14,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DockerUnavailable { runtime, detail } => write!(f, "{} is unavailable: {}", runtime, detail),
+            Error::ImplementationNotFound { name } => write!(f, "Implementation not found: {}", name),
+            Error::BuildFailed { implementation, detail } => write!(f, "Build of {} failed: {}", implementation, detail),
+            Error::HealthCheckFailed { implementations, detail } => write!(
+                f,
+                "Health check failed for {} implementation(s); fix them or pass --skip-health-check:\n  {}",
+                implementations.len(),
+                detail
+            ),
+            Error::Timeout {
+                implementation,
+                test_case,
+                seconds,
+            } => match test_case {
+                Some(test_case) => write!(f, "{} timed out after {}s on {}", implementation, seconds, test_case),
+                None => write!(f, "{} timed out after {}s", implementation, seconds),
+            },
+            Error::Hung {
+                implementation,
+                test_case,
+                seconds,
+            } => match test_case {
+                Some(test_case) => write!(f, "{} hung on {}: no heartbeat for {}s", implementation, test_case, seconds),
+                None => write!(f, "{} hung: no heartbeat for {}s", implementation, seconds),
+            },
+            Error::SchemaViolation { file, detail } => write!(f, "{} is not a valid result: {}", file.display(), detail),
+            Error::ReferenceMissing { cases } => write!(f, "No reference result for {}", cases.join(", ")),
+            Error::ReferenceInvalid { problems } => write!(f, "{} problem(s) in the reference results", problems),
+            Error::GateFailed { shortfalls } => write!(f, "Quality gate failed: {}", shortfalls.join("; ")),
+            Error::BuildsFailed { implementations } => {
+                write!(f, "{} build(s) failed: {}", implementations.len(), implementations.join(", "))
+            }
+            Error::RunFailed { implementations } => {
+                write!(f, "{} implementation(s) failed to run: {}", implementations.len(), implementations.join(", "))
+            }
+            Error::ValidationFailed { mismatches } => {
+                const SHOWN: usize = 5;
+                write!(f, "{} result(s) don't match the reference: ", mismatches.len())?;
+                write!(f, "{}", mismatches.iter().take(SHOWN).cloned().collect::<Vec<_>>().join(", "))?;
+                if mismatches.len() > SHOWN {
+                    write!(f, " and {} more", mismatches.len() - SHOWN)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A command that ran to the end but found failures. It fails with
+/// `error`'s kind and exit code, and `--json` still reports its `fields`.
+#[derive(Debug)]
+pub struct Failed {
+    pub error: Error,
+    pub fields: serde_json::Value,
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for Failed {}
+
+/// `fields`, or a [`Failed`] carrying them if there's a `failure`.
+pub fn finish(fields: serde_json::Value, failure: Option<Error>) -> anyhow::Result<serde_json::Value> {
+    match failure {
+        Some(error) => Err(Failed { error, fields }.into()),
+        None => Ok(fields),
+    }
+}
+
+/// `e`'s [`Error`], whether raised as one or as a [`Failed`].
+fn as_error(e: &anyhow::Error) -> Option<&Error> {
+    e.downcast_ref::<Error>().or_else(|| e.downcast_ref::<Failed>().map(|failed| &failed.error))
+}
+
+/// The kind of `e`: its [`Error`]'s, or `error` for anything else.
+pub fn kind(e: &anyhow::Error) -> &'static str {
+    as_error(e).map_or("error", Error::kind)
+}
+
+/// The exit code of a command failing with `e`.
+pub fn exit_code(e: &anyhow::Error) -> u8 {
+    match as_error(e) {
+        Some(e) => e.exit_code(),
+        None if e.downcast_ref::<clap::Error>().is_some() => 2,
+        None => 1,
+    }
+}
@@ -0,0 +1,181 @@
+//! Newline-delimited JSON progress events.
+//!
+//! External UIs (IDE plugins, dashboards) can follow a run in real time by
+//! passing `--events <target>`, where target is one of:
+//!
+//! - `fd:<n>` - an already-open file descriptor (3 or above) inherited from
+//!   the parent, which is duplicated rather than taken over
+//! - `unix:<path>` - a listening Unix socket to connect to
+//! - `<path>` - a file, created or appended to
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::fd::{BorrowedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    BuildStarted {
+        implementation: &'a str,
+    },
+    BuildFinished {
+        implementation: &'a str,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    CaseStarted {
+        implementation: &'a str,
+        /// `None` when the container runs every test case, or several
+        test_case: Option<&'a str>,
+    },
+    CaseFinished {
+        implementation: &'a str,
+        test_case: Option<&'a str>,
+        success: bool,
+        execution_time: f64,
+    },
+    ValidationVerdict {
+        implementation: &'a str,
+        test_case: &'a str,
+        verdict: Verdict,
+        result_windows: usize,
+        reference_windows: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Match,
+    Mismatch,
+    NoReference,
+    /// Not run, for features the implementation doesn't declare
+    Unsupported,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    timestamp: f64,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+/// Destination for progress events. Emitting is a no-op when disabled.
+pub struct EventSink {
+    writer: Option<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl EventSink {
+    pub fn disabled() -> Self {
+        Self { writer: None }
+    }
+
+    pub fn open(target: &str) -> Result<Self> {
+        let writer: Box<dyn Write + Send> = if let Some(fd) = target.strip_prefix("fd:") {
+            let fd: RawFd = fd
+                .parse()
+                .with_context(|| format!("Invalid file descriptor: {}", fd))?;
+            // Standard streams carry the run's own output
+            if fd <= 2 {
+                bail!("File descriptor {} is a standard stream; pass one of 3 or above", fd);
+            }
+            // SAFETY: F_GETFD only reads the descriptor's flags, open or not
+            if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+                bail!("File descriptor {} isn't open", fd);
+            }
+            // SAFETY: the descriptor is open, as checked above, and is only
+            // borrowed long enough to duplicate it, so the original stays
+            // the caller's to close
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) }
+                .try_clone_to_owned()
+                .with_context(|| format!("Failed to duplicate file descriptor {}", fd))?;
+            Box::new(File::from(fd))
+        } else if let Some(path) = target.strip_prefix("unix:") {
+            Box::new(
+                UnixStream::connect(path)
+                    .with_context(|| format!("Failed to connect to event socket {}", path))?,
+            )
+        } else {
+            Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(target)
+                    .with_context(|| format!("Failed to open event file {}", target))?,
+            )
+        };
+
+        Ok(Self {
+            writer: Some(Mutex::new(writer)),
+        })
+    }
+
+    /// Write one event as a single JSON line. Failures are reported on stderr
+    /// but never abort the run - a disconnected UI shouldn't kill the suite.
+    pub fn emit(&self, event: Event) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+
+        let line = match serde_json::to_string(&Envelope {
+            timestamp,
+            event: &event,
+        }) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Failed to serialize progress event: {}", e);
+                return;
+            }
+        };
+
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            eprintln!("Failed to write progress event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::AsRawFd;
+
+    #[test]
+    fn fd_rejects_standard_streams() {
+        for fd in ["fd:0", "fd:1", "fd:2"] {
+            assert!(EventSink::open(fd).is_err(), "{} was accepted", fd);
+        }
+    }
+
+    #[test]
+    fn fd_rejects_closed_descriptors() {
+        assert!(EventSink::open("fd:-1").is_err());
+        assert!(EventSink::open(&format!("fd:{}", i32::MAX)).is_err());
+    }
+
+    #[test]
+    fn fd_is_duplicated_and_left_open() {
+        let path = std::env::temp_dir().join(format!("visibility-test-runner-events-{}.jsonl", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let sink = EventSink::open(&format!("fd:{}", file.as_raw_fd())).unwrap();
+        sink.emit(Event::BuildStarted { implementation: "x" });
+        drop(sink);
+
+        // SAFETY: F_GETFD only reads the descriptor's flags
+        assert_ne!(unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFD) }, -1);
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(written.contains("\"event\":\"build_started\""), "{}", written);
+    }
+}
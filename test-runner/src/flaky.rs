@@ -0,0 +1,178 @@
+//! Flaky-case detection and the quarantine list.
+//!
+//! Every run records its validation verdicts in the results database, with
+//! the image ID each implementation ran from. An (implementation, case)
+//! pair is flaky when its verdict flips between match and mismatch across
+//! runs of the same image, i.e. without any change to the implementation.
+//!
+//! Flaky pairs can be proposed for `test-data/quarantine.json`. Proposals
+//! take no effect until someone reviews them and sets their `status` to
+//! `quarantined`; from then on the pair's mismatches no longer fail
+//! scheduled runs.
+
+use crate::database::Database;
+use crate::events::Verdict;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Verdict history of a pair on one image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlakyStats {
+    /// Runs with a match or mismatch verdict
+    pub runs: usize,
+    pub matches: usize,
+    pub mismatches: usize,
+    /// Changes between match and mismatch from one run to the next
+    pub flips: usize,
+    /// `None` for runs from before the history recorded image IDs
+    pub image_id: Option<String>,
+    pub first_run: Option<String>,
+    pub last_run: Option<String>,
+}
+
+/// Verdicts of a pair on one image, in run order.
+#[derive(Default)]
+struct Sequence {
+    verdicts: Vec<Verdict>,
+    first_run: Option<String>,
+    last_run: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct FlakyPair {
+    pub implementation: String,
+    pub test_case: String,
+    pub stats: FlakyStats,
+}
+
+/// Pairs whose verdict flipped at least `min_flips` times on one image,
+/// most flips first, over the last `last` runs (all if `None`).
+pub fn detect(results_dir: &Path, min_flips: usize, last: Option<usize>) -> Result<(usize, Vec<FlakyPair>)> {
+    let entries = Database::open(results_dir)?.history(last)?;
+
+    // Keyed by (implementation, case, image)
+    let mut sequences: BTreeMap<(String, String, Option<String>), Sequence> = BTreeMap::new();
+    for entry in &entries {
+        for validation in &entry.validation {
+            let image_id = entry.run(&validation.implementation).and_then(|run| run.image_id.clone());
+            for case in &validation.cases {
+                if !matches!(case.verdict, Verdict::Match | Verdict::Mismatch) {
+                    continue;
+                }
+                let key = (validation.implementation.clone(), case.test_case.clone(), image_id.clone());
+                let sequence = sequences.entry(key).or_default();
+                sequence.verdicts.push(case.verdict);
+                if sequence.verdicts.len() == 1 {
+                    sequence.first_run = entry.run_id.clone();
+                }
+                sequence.last_run = entry.run_id.clone();
+            }
+        }
+    }
+
+    let mut flaky: Vec<FlakyPair> = sequences
+        .into_iter()
+        .filter_map(|((implementation, test_case, image_id), sequence)| {
+            let Sequence {
+                verdicts,
+                first_run,
+                last_run,
+            } = sequence;
+            let flips = verdicts.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            let matches = verdicts.iter().filter(|verdict| **verdict == Verdict::Match).count();
+            (flips >= min_flips.max(1)).then(|| FlakyPair {
+                implementation,
+                test_case,
+                stats: FlakyStats {
+                    runs: verdicts.len(),
+                    matches,
+                    mismatches: verdicts.len() - matches,
+                    flips,
+                    image_id,
+                    first_run,
+                    last_run,
+                },
+            })
+        })
+        .collect();
+    flaky.sort_by_key(|pair| Reverse(pair.stats.flips));
+    Ok((entries.len(), flaky))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Suggested by `flaky --propose`; not yet in effect
+    Proposed,
+    /// Mismatches don't fail scheduled runs
+    Quarantined,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineEntry {
+    pub implementation: String,
+    pub test_case: String,
+    pub status: Status,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub proposed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stats: Option<FlakyStats>,
+}
+
+/// `<test_data_dir>/quarantine.json`, empty if there is none.
+pub fn read_quarantine(test_data_dir: &Path) -> Result<Vec<QuarantineEntry>> {
+    let path = test_data_dir.join("quarantine.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&fs::read_to_string(&path)?).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+pub fn is_quarantined(quarantine: &[QuarantineEntry], implementation: &str, test_case: &str) -> bool {
+    quarantine.iter().any(|entry| {
+        entry.status == Status::Quarantined && entry.implementation == implementation && entry.test_case == test_case
+    })
+}
+
+/// Add the pairs not yet listed to the quarantine list as proposals.
+/// Returns how many were added.
+pub fn propose(test_data_dir: &Path, pairs: &[FlakyPair]) -> Result<usize> {
+    let mut quarantine = read_quarantine(test_data_dir)?;
+    let mut added = 0;
+    for pair in pairs {
+        if quarantine
+            .iter()
+            .any(|entry| entry.implementation == pair.implementation && entry.test_case == pair.test_case)
+        {
+            continue;
+        }
+        quarantine.push(QuarantineEntry {
+            implementation: pair.implementation.clone(),
+            test_case: pair.test_case.clone(),
+            status: Status::Proposed,
+            reason: Some(format!(
+                "Verdict flipped {} time(s) in {} run(s) of the same image",
+                pair.stats.flips, pair.stats.runs
+            )),
+            proposed_at: Some(Utc::now()),
+            stats: Some(pair.stats.clone()),
+        });
+        added += 1;
+    }
+
+    if added > 0 {
+        let path = test_data_dir.join("quarantine.json");
+        fs::write(&path, serde_json::to_string_pretty(&quarantine)? + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(added)
+}
@@ -0,0 +1,274 @@
+//! Quality gates for `validate --min-pass-rate` and `--min-score`.
+//!
+//! CI doesn't have to insist that every case of every implementation match
+//! exactly. The gates are computed across the matrix of validated
+//! implementations and cases, leaving out the reference itself, cells
+//! that can't be judged (no reference, or unsupported), and mismatches an
+//! implementation's [`impl.toml`](crate::impl_toml) expects:
+//!
+//! - the pass rate is the share of cells that match
+//! - the score gives partial credit: a cell scores the share of its
+//!   windows with every field within tolerance, or 0 when the window counts
+//!   differ, and the score is the mean over cells
+//!
+//! `validate` fails with `gate_failed` when either falls short. Without
+//! gates, every such cell has to match, and `validate` fails with
+//! `validation_failed` otherwise.
+
+use crate::ValidationSummary;
+use crate::error::Error;
+use crate::events::Verdict;
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gates {
+    pub min_pass_rate: Option<f64>,
+    pub min_score: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    /// Implementation × case cells judged
+    pub cells: usize,
+    pub matched: usize,
+    pub pass_rate: f64,
+    pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_pass_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f64>,
+}
+
+impl Gates {
+    pub fn is_empty(&self) -> bool {
+        self.min_pass_rate.is_none() && self.min_score.is_none()
+    }
+
+    /// Evaluate the gates over `summaries`, leaving out `reference`.
+    pub fn evaluate(&self, summaries: &[ValidationSummary], reference: &str) -> Outcome {
+        let mut cells = 0;
+        let mut matched = 0;
+        let mut total_score = 0.0;
+        for summary in summaries.iter().filter(|summary| summary.implementation != reference) {
+            for case in summary.cases.iter().filter(|case| !case.expected_failure) {
+                let off: BTreeSet<usize> = case.deltas.iter().map(|delta| delta.window).collect();
+                let Some(score) = score(case.verdict, case.result_windows, case.reference_windows, off.len()) else {
+                    continue;
+                };
+                cells += 1;
+                matched += usize::from(case.verdict == Verdict::Match);
+                total_score += score;
+            }
+        }
+        // Nothing to judge falls short of any bar
+        let (pass_rate, score) = if cells == 0 {
+            (0.0, 0.0)
+        } else {
+            (matched as f64 / cells as f64, total_score / cells as f64)
+        };
+        Outcome {
+            cells,
+            matched,
+            pass_rate,
+            score,
+            min_pass_rate: self.min_pass_rate,
+            min_score: self.min_score,
+        }
+    }
+}
+
+/// `<implementation>/<case>` of every cell in `summaries` that doesn't
+/// match, leaving out the same cells as the gates.
+pub fn mismatches(summaries: &[ValidationSummary], reference: &str) -> Vec<String> {
+    summaries
+        .iter()
+        .filter(|summary| summary.implementation != reference)
+        .flat_map(|summary| {
+            summary
+                .cases
+                .iter()
+                .filter(|case| case.verdict == Verdict::Mismatch && !case.expected_failure)
+                .map(|case| format!("{}/{}", summary.implementation, case.test_case))
+        })
+        .collect()
+}
+
+/// A cell's score, given how many of its windows have a field outside
+/// tolerance, or `None` if it can't be judged.
+pub fn score(verdict: Verdict, result_windows: usize, reference_windows: Option<usize>, windows_off: usize) -> Option<f64> {
+    match (verdict, reference_windows) {
+        (Verdict::Match, _) => Some(1.0),
+        (Verdict::Mismatch, Some(windows)) if windows == result_windows && windows > 0 => {
+            Some(windows.saturating_sub(windows_off) as f64 / windows as f64)
+        }
+        (Verdict::Mismatch, _) => Some(0.0),
+        (Verdict::NoReference | Verdict::Unsupported, _) => None,
+    }
+}
+
+impl Outcome {
+    /// The error to fail `validate` with, if a gate falls short.
+    pub fn error(&self) -> Option<Error> {
+        let mut shortfalls = Vec::new();
+        if let Some(min) = self.min_pass_rate
+            && self.pass_rate < min
+        {
+            shortfalls.push(format!("pass rate {:.3} is below {}", self.pass_rate, min));
+        }
+        if let Some(min) = self.min_score
+            && self.score < min
+        {
+            shortfalls.push(format!("score {:.3} is below {}", self.score, min));
+        }
+        (!shortfalls.is_empty()).then_some(Error::GateFailed { shortfalls })
+    }
+}
+
+pub fn print(outcome: &Outcome) {
+    println!("{}", "Quality gates:".bold());
+    let line = |name: &str, value: f64, min: Option<f64>| {
+        let Some(min) = min else {
+            println!("  {} {}: {:.3}", "●".bright_cyan(), name, value);
+            return;
+        };
+        let mark = if value >= min { "✓".green() } else { "✗".red() };
+        println!("  {} {}: {:.3} (minimum {})", mark, name, value, min);
+    };
+    line(&format!("Pass rate ({}/{} cells)", outcome.matched, outcome.cells), outcome.pass_rate, outcome.min_pass_rate);
+    line("Score", outcome.score, outcome.min_score);
+}
+
+/// A fraction between 0 and 1, for `--min-pass-rate` and `--min-score`.
+pub fn parse_fraction(value: &str) -> Result<f64> {
+    let fraction: f64 = value.parse().with_context(|| format!("{:?} is not a number", value))?;
+    if !(0.0..=1.0).contains(&fraction) {
+        bail!("{} is not between 0 and 1", fraction);
+    }
+    Ok(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CaseVerdict;
+    use crate::tolerance::FieldDelta;
+
+    fn case(test_case: &str, verdict: Verdict, windows: (usize, Option<usize>), off: &[usize]) -> CaseVerdict {
+        CaseVerdict {
+            test_case: test_case.to_string(),
+            verdict,
+            result_windows: windows.0,
+            reference_windows: windows.1,
+            warnings: 0,
+            regime: None,
+            // Two fields of each window off, so windows aren't counted twice
+            deltas: off
+                .iter()
+                .flat_map(|&window| {
+                    ["start", "end"].map(|field| FieldDelta {
+                        window,
+                        reference_window: window,
+                        field,
+                        delta: 100.0,
+                        tolerance: 60.0,
+                    })
+                })
+                .collect(),
+            unmatched: Vec::new(),
+            expected_failure: false,
+        }
+    }
+
+    fn summary(implementation: &str, cases: Vec<CaseVerdict>) -> ValidationSummary {
+        ValidationSummary {
+            implementation: implementation.to_string(),
+            matched: cases.iter().filter(|case| case.verdict == Verdict::Match).count(),
+            total: cases.len(),
+            warnings: 0,
+            cases,
+            inconsistencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn score_gives_partial_credit_for_windows_within_tolerance() {
+        assert_eq!(score(Verdict::Match, 4, Some(4), 0), Some(1.0));
+        assert_eq!(score(Verdict::Mismatch, 4, Some(4), 1), Some(0.75));
+        assert_eq!(score(Verdict::Mismatch, 4, Some(4), 4), Some(0.0));
+        assert_eq!(score(Verdict::Mismatch, 3, Some(4), 0), Some(0.0));
+        assert_eq!(score(Verdict::Mismatch, 0, Some(0), 0), Some(0.0));
+        assert_eq!(score(Verdict::NoReference, 4, None, 0), None);
+        assert_eq!(score(Verdict::Unsupported, 0, None, 0), None);
+    }
+
+    #[test]
+    fn evaluate_leaves_out_the_reference_and_unjudged_cells() {
+        let mut expected = case("004", Verdict::Mismatch, (2, Some(2)), &[1, 2]);
+        expected.expected_failure = true;
+        let summaries = [
+            summary("python-skyfield", vec![case("001", Verdict::Mismatch, (1, Some(2)), &[])]),
+            summary(
+                "rust-sgp4",
+                vec![
+                    case("001", Verdict::Match, (2, Some(2)), &[]),
+                    case("002", Verdict::Mismatch, (4, Some(4)), &[3]),
+                    case("003", Verdict::Mismatch, (1, Some(2)), &[]),
+                    expected,
+                    case("005", Verdict::NoReference, (2, None), &[]),
+                    case("006", Verdict::Unsupported, (0, None), &[]),
+                ],
+            ),
+        ];
+        let gates = Gates {
+            min_pass_rate: Some(0.5),
+            min_score: Some(0.5),
+        };
+
+        let outcome = gates.evaluate(&summaries, "python-skyfield");
+        assert_eq!(outcome.cells, 3);
+        assert_eq!(outcome.matched, 1);
+        assert!((outcome.pass_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert!((outcome.score - (1.0 + 0.75 + 0.0) / 3.0).abs() < 1e-9);
+        // Pass rate falls short, the score doesn't
+        match outcome.error() {
+            Some(Error::GateFailed { shortfalls }) => {
+                assert_eq!(shortfalls.len(), 1);
+                assert!(shortfalls[0].starts_with("pass rate"), "{:?}", shortfalls);
+            }
+            other => panic!("expected a gate failure, got {:?}", other),
+        }
+        assert_eq!(mismatches(&summaries, "python-skyfield"), ["rust-sgp4/002", "rust-sgp4/003"]);
+    }
+
+    #[test]
+    fn nothing_to_judge_fails_any_gate() {
+        let summaries = [summary("rust-sgp4", vec![case("005", Verdict::NoReference, (2, None), &[])])];
+        let outcome = Gates {
+            min_pass_rate: Some(0.0),
+            min_score: None,
+        }
+        .evaluate(&summaries, "python-skyfield");
+        assert_eq!(outcome.cells, 0);
+        assert_eq!(outcome.pass_rate, 0.0);
+        assert!(outcome.error().is_none());
+
+        let outcome = Gates {
+            min_pass_rate: None,
+            min_score: Some(0.1),
+        }
+        .evaluate(&summaries, "python-skyfield");
+        assert!(outcome.error().is_some());
+    }
+
+    #[test]
+    fn parse_fraction_takes_0_to_1() {
+        assert_eq!(parse_fraction("0.95").unwrap(), 0.95);
+        assert_eq!(parse_fraction("1").unwrap(), 1.0);
+        assert!(parse_fraction("1.5").is_err());
+        assert!(parse_fraction("-0.1").is_err());
+        assert!(parse_fraction("most").is_err());
+    }
+}
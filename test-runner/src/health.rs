@@ -0,0 +1,122 @@
+//! Pre-run health check of implementation images.
+//!
+//! Before `run` or `all` starts any test containers, each image is run once
+//! with `--version`. An implementation answers with a single JSON object on
+//! stdout and exits 0:
+//!
+//! ```json
+//! {"implementation": "python-sgp4", "version": "1.0.0", "libraryName": "sgp4", "libraryVersion": "2.23", "platform": "Python 3.11.9"}
+//! ```
+//!
+//! A missing image, a non-zero exit, or output that isn't that object aborts
+//! the run before any test container starts, instead of surfacing later as
+//! a failed test. The declared versions are filed with the run as
+//! `results/<run-id>/<impl>/versions.json`.
+
+use crate::error::Error;
+use crate::runtime::RunSpec;
+use crate::{Implementation, Orchestrator};
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+/// What an implementation reports for `--version`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Versions {
+    pub implementation: String,
+    pub version: String,
+    #[serde(default)]
+    pub library_name: Option<String>,
+    #[serde(default)]
+    pub library_version: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+}
+
+/// Run `impl_`'s image with `--version` and parse what it declares.
+pub fn check(orchestrator: &Orchestrator, impl_: &Implementation) -> Result<Versions> {
+    let runtime = orchestrator.runtime;
+    if runtime.image_id(&impl_.image_name)?.is_none() {
+        bail!("Image {} not found; build it with `build -i {}` or `run --build`", impl_.image_name, impl_.name);
+    }
+
+    let output = runtime.run(&RunSpec {
+        image: &impl_.image_name,
+        args: vec!["--version"],
+        ..Default::default()
+    })?;
+    if !output.success() {
+        bail!(
+            "`{} --version` exited with {}; the entrypoint must print its versions as JSON and exit 0: {}",
+            impl_.image_name,
+            output.exit_code.map_or("a signal".to_string(), |code| format!("code {}", code)),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default();
+    let versions: Versions = serde_json::from_str(line).with_context(|| {
+        format!(
+            "`{} --version` printed {:?}, not a JSON object with `implementation` and `version`",
+            impl_.image_name, line
+        )
+    })?;
+    if versions.implementation != impl_.name {
+        eprintln!(
+            "  {} {} reports itself as {}",
+            "⚠".yellow(),
+            impl_.name.bright_white(),
+            versions.implementation
+        );
+    }
+    Ok(versions)
+}
+
+/// Check every implementation, failing with all problems at once. Returns
+/// each implementation's name with what it declared.
+pub fn check_all(orchestrator: &Orchestrator, implementations: &[&Implementation]) -> Result<Vec<(String, Versions)>> {
+    println!("\n{}", "Checking images...".bold().bright_blue());
+    println!("{}", "-".repeat(50).dimmed());
+    let mut checked = Vec::new();
+    let mut failures = Vec::new();
+    let mut failed = Vec::new();
+    for impl_ in implementations {
+        match check(orchestrator, impl_) {
+            Ok(versions) => {
+                let library = match (&versions.library_name, &versions.library_version) {
+                    (Some(name), Some(version)) => format!("{} {}", name, version),
+                    (Some(name), None) => name.clone(),
+                    (None, Some(version)) => version.clone(),
+                    (None, None) => "unknown library".to_string(),
+                };
+                let platform = versions.platform.as_deref().map(|platform| format!(", {}", platform)).unwrap_or_default();
+                println!(
+                    "  {} {} {} ({}{})",
+                    "✓".green(),
+                    impl_.name.bright_white(),
+                    versions.version,
+                    library,
+                    platform
+                );
+                checked.push((impl_.name.clone(), versions));
+            }
+            // No point checking the others
+            Err(e) if matches!(e.downcast_ref::<Error>(), Some(Error::DockerUnavailable { .. })) => return Err(e),
+            Err(e) => {
+                eprintln!("  {} {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                failures.push(format!("{}: {}", impl_.name, e));
+                failed.push(impl_.name.clone());
+            }
+        }
+    }
+    if !failures.is_empty() {
+        return Err(Error::HealthCheckFailed {
+            implementations: failed,
+            detail: failures.join("\n  "),
+        }
+        .into());
+    }
+    Ok(checked)
+}
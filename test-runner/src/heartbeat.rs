@@ -0,0 +1,69 @@
+//! Heartbeats, for telling a hung container from a slow one.
+//!
+//! With `--heartbeat <SECS>` (or `heartbeat` in the profile or the
+//! implementation's [`impl.toml`](crate::impl_toml)), a test container has to
+//! show it's alive at least that often: by touching the file named in
+//! `VISIBILITY_HEARTBEAT`, by printing to stdout or stderr, or by streaming a
+//! record to its [FIFO](crate::stream). One that goes silent for longer is
+//! killed and reported as hung, whereas one killed at `--timeout` while
+//! still beating was only slow.
+//!
+//! The file is `beat` in `results/.heartbeat/<impl>-<pid>-<n>/`, mounted at
+//! `/heartbeat`. The directory is removed once the container exits.
+
+use crate::runtime::{Heartbeat, RunSpec};
+use crate::{Implementation, Orchestrator};
+use anyhow::{Context, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Environment variable holding the heartbeat file's path in the container
+pub const HEARTBEAT_VAR: &str = "VISIBILITY_HEARTBEAT";
+
+/// Numbers heartbeat directories, since containers of one implementation
+/// may run at the same time
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Beacon {
+    dir: PathBuf,
+}
+
+impl Beacon {
+    /// Require heartbeats of `impl_`'s container run by `spec`, if a
+    /// heartbeat interval applies to it.
+    pub fn attach(orchestrator: &Orchestrator, impl_: &Implementation, spec: &mut RunSpec) -> Result<Option<Self>> {
+        let Some(silence) = orchestrator.heartbeat_for(impl_) else {
+            return Ok(None);
+        };
+        let dir = orchestrator.results_dir.join(".heartbeat").join(format!(
+            "{}-{}-{}",
+            impl_.name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        // For containers running as another user to create the file
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777))?;
+
+        spec.mounts.push((dir.clone(), "/heartbeat", false));
+        spec.env.push(format!("{}=/heartbeat/beat", HEARTBEAT_VAR));
+        spec.heartbeat = Some(Heartbeat {
+            file: dir.join("beat"),
+            silence,
+        });
+        Ok(Some(Beacon { dir }))
+    }
+}
+
+impl Drop for Beacon {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+        // Only once no other container beats
+        if let Some(parent) = self.dir.parent() {
+            fs::remove_dir(parent).ok();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! The history of runs, as `flaky` and `publish` go through it.
+//!
+//! Every `run`, `all`, and daemon run is read back from the results
+//! database (see [`Database::history`](crate::database::Database::history)).
+//! [`Case`] is also the shape of each case's `verdict.json`.
+
+use crate::events::Verdict;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug)]
+pub struct Entry {
+    pub run_id: Option<String>,
+    /// Unix seconds
+    pub started_at: Option<u64>,
+    /// `None` for runs that never finished
+    pub finished_at: Option<u64>,
+    pub passed: bool,
+    /// One per implementation, its containers summed up
+    pub runs: Vec<Run>,
+    pub validation: Vec<Validation>,
+}
+
+impl Entry {
+    pub fn started(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.started_at? as i64, 0)
+    }
+
+    pub fn run(&self, implementation: &str) -> Option<&Run> {
+        self.runs.iter().find(|run| run.implementation == implementation)
+    }
+}
+
+#[derive(Debug)]
+pub struct Run {
+    pub implementation: String,
+    /// Every container succeeded
+    pub success: bool,
+    pub execution_time: f64,
+    /// `None` for runs from before the database recorded image IDs, and
+    /// imported results
+    pub image_id: Option<String>,
+    /// `None` where the containers' cgroups couldn't be read
+    pub cpu_seconds: Option<f64>,
+    /// Peak memory in bytes; `None` where the cgroups couldn't be read
+    pub peak_memory: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct Validation {
+    pub implementation: String,
+    pub matched: usize,
+    /// Cases with a result
+    pub total: usize,
+    pub cases: Vec<Case>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Case {
+    pub test_case: String,
+    pub verdict: Verdict,
+    #[serde(default)]
+    pub result_windows: usize,
+    #[serde(default)]
+    pub reference_windows: Option<usize>,
+    #[serde(default)]
+    pub deltas: Vec<Delta>,
+}
+
+/// A window field outside tolerance, as recorded by `validate`.
+#[derive(Debug, Deserialize)]
+pub struct Delta {
+    pub window: usize,
+    pub field: String,
+    pub delta: f64,
+    pub tolerance: f64,
+}
@@ -0,0 +1,167 @@
+//! Optional per-implementation manifest, `impl.toml` next to the Dockerfile.
+//!
+//! ```toml
+//! display_name = "Skyfield"
+//! language = "python"
+//! library = "skyfield"
+//! library_version = "1.49"
+//! # Known to disagree with the reference until the fix is released
+//! expected_failures = ["005_molniya_moscow"]
+//! timeout = 900
+//! heartbeat = 300
+//! stream = "fifo"
+//! ```
+//!
+//! Every key is optional:
+//!
+//! - `display_name` names the implementation in `discover` and reports
+//! - `language` replaces the one the directory name or README front-matter
+//!   gives (see [`languages`](crate::languages))
+//! - `library` and `library_version` attribute the library in reports,
+//!   ahead of the README front-matter (see
+//!   [`library_info`](crate::library_info))
+//! - `expected_failures` lists cases known to mismatch the reference. Their
+//!   mismatches are reported as expected, and left out of the quality gates,
+//!   JUnit failures, and the status of scheduled runs; a listed case that
+//!   matches is pointed out so it can be removed
+//! - `timeout` (seconds) replaces `--timeout` for the implementation's
+//!   containers, for a library that is slow by nature
+//! - `heartbeat` (seconds) replaces `--heartbeat`, the silence after which
+//!   its containers are killed as hung (see [`heartbeat`](crate::heartbeat))
+//! - `stream`, `"stdout"` or `"fifo"`, says the implementation streams its
+//!   results as JSON lines instead of writing result files (see
+//!   [`stream`](crate::stream))
+//!
+//! The file is read with the `toml` crate. An unknown key is an error, so a
+//! typo doesn't go unnoticed.
+
+use crate::stream::Stream;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_failures: Vec<String>,
+    /// Seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<Stream>,
+}
+
+impl Manifest {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+
+    pub fn heartbeat(&self) -> Option<Duration> {
+        self.heartbeat.map(Duration::from_secs)
+    }
+
+    pub fn expects_failure(&self, test_case: &str) -> bool {
+        self.expected_failures.iter().any(|case| case == test_case)
+    }
+}
+
+/// `<impl_path>/impl.toml`, or an empty manifest if there is none.
+pub fn load(impl_path: &Path) -> Result<Manifest> {
+    let path = impl_path.join("impl.toml");
+    if !path.exists() {
+        return Ok(Manifest::default());
+    }
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid {}", path.display()))
+}
+
+fn parse(content: &str) -> Result<Manifest> {
+    let mut manifest: Manifest = toml::from_str(content)?;
+    for (key, seconds) in [("timeout", manifest.timeout), ("heartbeat", manifest.heartbeat)] {
+        if seconds == Some(0) {
+            bail!("{} must be a positive number of seconds", key);
+        }
+    }
+    manifest.language = manifest.language.map(|language| language.to_lowercase());
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_key() {
+        let manifest = parse(
+            r#"
+display_name = "Skyfield"
+language = "Python"
+library = 'skyfield'
+library_version = "1.49"
+# Known to disagree with the reference until the fix is released
+expected_failures = [
+    "005_molniya_moscow", # fixed upstream
+    "012_geo",
+]
+timeout = 900
+heartbeat = 300
+stream = "fifo"
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.display_name.as_deref(), Some("Skyfield"));
+        assert_eq!(manifest.language.as_deref(), Some("python"));
+        assert_eq!(manifest.library.as_deref(), Some("skyfield"));
+        assert_eq!(manifest.library_version.as_deref(), Some("1.49"));
+        assert_eq!(manifest.expected_failures, ["005_molniya_moscow", "012_geo"]);
+        assert_eq!(manifest.timeout(), Some(Duration::from_secs(900)));
+        assert_eq!(manifest.heartbeat(), Some(Duration::from_secs(300)));
+        assert_eq!(manifest.stream, Some(Stream::Fifo));
+        assert!(manifest.expects_failure("012_geo"));
+        assert!(!manifest.expects_failure("001_iss"));
+    }
+
+    #[test]
+    fn every_key_is_optional() {
+        let manifest = parse("# nothing to declare\n").unwrap();
+        assert!(manifest.display_name.is_none());
+        assert!(manifest.expected_failures.is_empty());
+        assert!(manifest.timeout().is_none());
+        assert!(manifest.stream.is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let error = format!("{:#}", parse("timout = 900\n").unwrap_err());
+        assert!(error.contains("timout"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_type() {
+        assert!(parse("timeout = \"900\"\n").is_err());
+        assert!(parse("timeout = -1\n").is_err());
+        assert!(parse("expected_failures = \"001_iss\"\n").is_err());
+        assert!(parse("stream = \"pipe\"\n").is_err());
+        assert!(parse("display_name = [\"Skyfield\"]\n").is_err());
+    }
+
+    #[test]
+    fn rejects_zero_seconds() {
+        let error = parse("heartbeat = 0\n").unwrap_err().to_string();
+        assert!(error.contains("heartbeat"), "{}", error);
+    }
+}
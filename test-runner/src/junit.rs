@@ -0,0 +1,398 @@
+//! JUnit XML output for CI systems.
+//!
+//! With `--format junit`, `run`, `validate`, and `all` also write a JUnit
+//! XML file that Jenkins, GitLab, and most other CI systems display
+//! natively. Each implementation is a `<testsuite>` and each of its test
+//! cases a `<testcase>` (`classname` is the implementation). For `run`, a
+//! case passes when the container wrote a readable result for it; for
+//! `validate`, when it matches the reference. `all` reports validation
+//! verdicts, with run failures taking precedence. Cases without a reference
+//! and unsupported cases are skipped.
+
+use crate::events::Verdict;
+use crate::{TestResult, ValidationSummary, VisibilityResult, read_result, result_test_case};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Console output only
+    Text,
+    /// Console output and a JUnit XML file
+    Junit,
+    /// Console output and a TAP stream (see [`tap`](crate::tap))
+    Tap,
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Passed,
+    Failed { message: String, details: String },
+    /// The implementation couldn't be run or validated at all
+    Errored { message: String, details: String },
+    Skipped { message: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub implementation: String,
+    pub test_case: String,
+    /// Seconds, when the implementation reports it
+    pub time: Option<f64>,
+    pub outcome: Outcome,
+}
+
+/// One case per test case the run covered: the selected `cases`, or every
+/// case.
+pub fn run_cases(result: &TestResult, selected: Option<&[String]>, all_cases: &[String]) -> Vec<Case> {
+    let mut cases = Vec::new();
+    let expected: Vec<&str> = selected.unwrap_or(all_cases).iter().map(String::as_str).collect();
+    for name in expected {
+        let case = |time: Option<f64>, outcome: Outcome| Case {
+            implementation: result.implementation.clone(),
+            test_case: name.to_string(),
+            time,
+            outcome,
+        };
+        if result.unsupported.iter().any(|unsupported| unsupported == name) {
+            cases.push(case(None, skipped_unsupported()));
+            continue;
+        }
+
+        let file = result.result_files.iter().find(|path| {
+            path.file_name()
+                .and_then(|file_name| result_test_case(&file_name.to_string_lossy(), &result.implementation))
+                .is_some_and(|case| case == name)
+        });
+        cases.push(match file {
+            Some(path) => match read_result(path).and_then(|content| Ok(serde_json::from_str::<VisibilityResult>(&content)?)) {
+                Ok(parsed) => case(parsed.execution_time, Outcome::Passed),
+                Err(e) => case(
+                    None,
+                    Outcome::Failed {
+                        message: format!("Unreadable result {}", path.display()),
+                        details: e.to_string(),
+                    },
+                ),
+            },
+            None if result.success => case(
+                None,
+                Outcome::Failed {
+                    message: "No result written".to_string(),
+                    details: result.stderr.clone(),
+                },
+            ),
+            None if result.hung => case(
+                None,
+                Outcome::Failed {
+                    message: format!("Hung: killed after {:.0}s without a heartbeat", result.execution_time),
+                    details: result.stderr.clone(),
+                },
+            ),
+            None if result.timed_out => case(
+                None,
+                Outcome::Failed {
+                    message: format!("Timed out after {:.0}s", result.execution_time),
+                    details: result.stderr.clone(),
+                },
+            ),
+            None => case(
+                None,
+                Outcome::Failed {
+                    message: "Container failed".to_string(),
+                    details: result.stderr.clone(),
+                },
+            ),
+        });
+    }
+    cases
+}
+
+/// One case per validated test case.
+pub fn validation_cases(summary: &ValidationSummary) -> Vec<Case> {
+    summary
+        .cases
+        .iter()
+        .map(|verdict| {
+            let outcome = match verdict.verdict {
+                Verdict::Match => Outcome::Passed,
+                Verdict::NoReference => Outcome::Skipped {
+                    message: "No reference result".to_string(),
+                },
+                Verdict::Unsupported => skipped_unsupported(),
+                Verdict::Mismatch if verdict.expected_failure => Outcome::Skipped {
+                    message: "Expected failure (impl.toml)".to_string(),
+                },
+                Verdict::Mismatch => {
+                    let deltas = verdict.deltas.iter().map(|delta| {
+                        format!(
+                            "{} {}: {:+.1}{} (tolerance ±{}{})\n",
+                            delta.windows(),
+                            delta.field,
+                            delta.delta,
+                            delta.unit(),
+                            delta.tolerance,
+                            delta.unit()
+                        )
+                    });
+                    let unmatched = verdict.unmatched.iter().map(|unmatched| format!("{}\n", unmatched));
+                    Outcome::Failed {
+                        message: match verdict.reference_windows {
+                            Some(reference) if reference != verdict.result_windows => {
+                                format!("{} window(s) vs {} reference", verdict.result_windows, reference)
+                            }
+                            _ => format!("{} field(s) outside tolerance", verdict.deltas.len()),
+                        },
+                        details: deltas.chain(unmatched).collect(),
+                    }
+                }
+            };
+            Case {
+                implementation: summary.implementation.clone(),
+                test_case: verdict.test_case.clone(),
+                time: None,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// A single case standing for an implementation that failed as a whole,
+/// e.g. whose container couldn't start.
+pub fn error_case(implementation: &str, stage: &str, error: &anyhow::Error) -> Case {
+    Case {
+        implementation: implementation.to_string(),
+        test_case: format!("({})", stage),
+        time: None,
+        outcome: Outcome::Errored {
+            message: error.to_string(),
+            details: format!("{:?}", error),
+        },
+    }
+}
+
+fn skipped_unsupported() -> Outcome {
+    Outcome::Skipped {
+        message: "Unsupported: uses features the implementation doesn't declare (see README front-matter `features`)"
+            .to_string(),
+    }
+}
+
+/// Validation verdicts, replaced by the run outcome wherever the run
+/// failed, since validation then only saw older results.
+pub fn merge(run: Vec<Case>, validation: Vec<Case>) -> Vec<Case> {
+    let mut merged = validation;
+    for case in run {
+        let existing = merged
+            .iter_mut()
+            .find(|other| other.implementation == case.implementation && other.test_case == case.test_case);
+        match (existing, &case.outcome) {
+            (Some(existing), Outcome::Failed { .. } | Outcome::Errored { .. }) => *existing = case,
+            (Some(existing), _) => existing.time = existing.time.or(case.time),
+            (None, _) => merged.push(case),
+        }
+    }
+    merged
+}
+
+/// Render `cases` as a JUnit XML document named `name`.
+pub fn render(name: &str, cases: &[Case]) -> String {
+    let mut suites: BTreeMap<&str, Vec<&Case>> = BTreeMap::new();
+    for case in cases {
+        suites.entry(&case.implementation).or_default().push(case);
+    }
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let totals = Counts::of(cases.iter());
+    let _ = writeln!(xml, "<testsuites name=\"{}\" {}>", escape(name), totals.attributes());
+    for (implementation, cases) in &suites {
+        let counts = Counts::of(cases.iter().copied());
+        let _ = writeln!(xml, "  <testsuite name=\"{}\" {}>", escape(implementation), counts.attributes());
+        for case in cases {
+            let time = case.time.map(|time| format!(" time=\"{:.3}\"", time)).unwrap_or_default();
+            let open = format!(
+                "    <testcase classname=\"{}\" name=\"{}\"{}",
+                escape(&case.implementation),
+                escape(&case.test_case),
+                time
+            );
+            match &case.outcome {
+                Outcome::Passed => {
+                    let _ = writeln!(xml, "{}/>", open);
+                }
+                Outcome::Skipped { message } => {
+                    let _ = writeln!(xml, "{}>\n      <skipped message=\"{}\"/>\n    </testcase>", open, escape(message));
+                }
+                Outcome::Failed { message, details } | Outcome::Errored { message, details } => {
+                    let element = if matches!(case.outcome, Outcome::Failed { .. }) { "failure" } else { "error" };
+                    let _ = writeln!(
+                        xml,
+                        "{}>\n      <{} message=\"{}\">{}</{}>\n    </testcase>",
+                        open,
+                        element,
+                        escape(message),
+                        escape(details),
+                        element
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+struct Counts {
+    tests: usize,
+    failures: usize,
+    errors: usize,
+    skipped: usize,
+    time: f64,
+}
+
+impl Counts {
+    fn of<'a>(cases: impl Iterator<Item = &'a Case>) -> Self {
+        let mut counts = Counts {
+            tests: 0,
+            failures: 0,
+            errors: 0,
+            skipped: 0,
+            time: 0.0,
+        };
+        for case in cases {
+            counts.tests += 1;
+            counts.time += case.time.unwrap_or_default();
+            match case.outcome {
+                Outcome::Passed => {}
+                Outcome::Failed { .. } => counts.failures += 1,
+                Outcome::Errored { .. } => counts.errors += 1,
+                Outcome::Skipped { .. } => counts.skipped += 1,
+            }
+        }
+        counts
+    }
+
+    fn attributes(&self) -> String {
+        format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\"",
+            self.tests, self.failures, self.errors, self.skipped, self.time
+        )
+    }
+}
+
+/// Escape for XML text and attributes, dropping control characters XML 1.0
+/// doesn't allow (containers' stderr may contain them).
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn write(path: &Path, name: &str, cases: &[Case]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, render(name, cases)).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_handles_markup_and_control_characters() {
+        assert_eq!(escape("a < b && c > \"d\""), "a &lt; b &amp;&amp; c &gt; &quot;d&quot;");
+        assert_eq!(escape("tab\tline\r\nbell\x07\x1b[0m"), "tab\tline\r\nbell[0m");
+        assert_eq!(escape("&lt;"), "&amp;lt;");
+    }
+
+    #[test]
+    fn render_groups_cases_into_suites() {
+        let cases = [
+            Case {
+                implementation: "python-sgp4".to_string(),
+                test_case: "001_iss_nyc".to_string(),
+                time: Some(1.5),
+                outcome: Outcome::Passed,
+            },
+            Case {
+                implementation: "python-sgp4".to_string(),
+                test_case: "002_<starlink>".to_string(),
+                time: Some(0.25),
+                outcome: Outcome::Failed {
+                    message: "2 window(s) vs 3 reference".to_string(),
+                    details: "reference window 3 has no result window\n".to_string(),
+                },
+            },
+            Case {
+                implementation: "go-sgp4".to_string(),
+                test_case: "t_isl".to_string(),
+                time: None,
+                outcome: Outcome::Skipped {
+                    message: "Unsupported: isl".to_string(),
+                },
+            },
+        ];
+
+        assert_eq!(
+            render("all", &cases),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="all" tests="3" failures="1" errors="0" skipped="1" time="1.750">
+  <testsuite name="go-sgp4" tests="1" failures="0" errors="0" skipped="1" time="0.000">
+    <testcase classname="go-sgp4" name="t_isl">
+      <skipped message="Unsupported: isl"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="python-sgp4" tests="2" failures="1" errors="0" skipped="0" time="1.750">
+    <testcase classname="python-sgp4" name="001_iss_nyc" time="1.500"/>
+    <testcase classname="python-sgp4" name="002_&lt;starlink&gt;" time="0.250">
+      <failure message="2 window(s) vs 3 reference">reference window 3 has no result window
+</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn merge_prefers_run_failures_over_validation() {
+        let validated = |test_case: &str, outcome: Outcome| Case {
+            implementation: "x".to_string(),
+            test_case: test_case.to_string(),
+            time: None,
+            outcome,
+        };
+        let run = vec![
+            Case {
+                time: Some(2.0),
+                ..validated("001", Outcome::Passed)
+            },
+            validated(
+                "002",
+                Outcome::Failed {
+                    message: "Container failed".to_string(),
+                    details: String::new(),
+                },
+            ),
+        ];
+        let validation = vec![validated("001", Outcome::Passed), validated("002", Outcome::Passed)];
+
+        let merged = merge(run, validation);
+        assert_eq!(merged.len(), 2);
+        assert!(matches!(merged[0].outcome, Outcome::Passed));
+        assert_eq!(merged[0].time, Some(2.0));
+        assert!(matches!(merged[1].outcome, Outcome::Failed { .. }));
+    }
+}
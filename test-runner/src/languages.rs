@@ -0,0 +1,152 @@
+//! Language and runtime family of each implementation, for grouped output.
+//!
+//! The language comes from `language` in the implementation's
+//! [`impl.toml`](crate::impl_toml) or README front-matter (see
+//! [`library_info`](crate::library_info)), then from the
+//! `{language}-{library}` directory name, and where the name doesn't follow
+//! the convention, from the base images its Dockerfile builds `FROM`.
+//! Languages compiled ahead of time (to native code or
+//! bytecode) count as compiled, the rest as interpreted; languages not
+//! listed here, and external tools, fall under "other".
+
+use crate::impl_toml::Manifest;
+use crate::library_info::front_matter;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Runtime {
+    Compiled,
+    Interpreted,
+    Other,
+}
+
+impl Runtime {
+    pub fn label(self) -> &'static str {
+        match self {
+            Runtime::Compiled => "Compiled",
+            Runtime::Interpreted => "Interpreted",
+            Runtime::Other => "Other",
+        }
+    }
+}
+
+/// Directory name prefix, display name, and runtime family.
+const LANGUAGES: [(&str, &str, Runtime); 16] = [
+    ("c", "C", Runtime::Compiled),
+    ("cpp", "C++", Runtime::Compiled),
+    ("csharp", "C#", Runtime::Compiled),
+    ("fortran", "Fortran", Runtime::Compiled),
+    ("go", "Go", Runtime::Compiled),
+    ("java", "Java", Runtime::Compiled),
+    ("kotlin", "Kotlin", Runtime::Compiled),
+    ("rust", "Rust", Runtime::Compiled),
+    ("swift", "Swift", Runtime::Compiled),
+    ("zig", "Zig", Runtime::Compiled),
+    ("javascript", "JavaScript", Runtime::Interpreted),
+    ("typescript", "TypeScript", Runtime::Interpreted),
+    ("python", "Python", Runtime::Interpreted),
+    ("ruby", "Ruby", Runtime::Interpreted),
+    ("matlab", "MATLAB", Runtime::Interpreted),
+    ("r", "R", Runtime::Interpreted),
+];
+
+/// Base image name (without registry, tag, or digest) and the language it
+/// builds or runs, for Dockerfiles of implementations whose directory name
+/// doesn't give the language. A C/C++ compiler image could be either, so
+/// `gcc` isn't listed.
+const BASE_IMAGES: [(&str, &str); 13] = [
+    ("python", "python"),
+    ("pypy", "python"),
+    ("node", "javascript"),
+    ("golang", "go"),
+    ("rust", "rust"),
+    ("openjdk", "java"),
+    ("eclipse-temurin", "java"),
+    ("amazoncorretto", "java"),
+    ("maven", "java"),
+    ("gradle", "java"),
+    ("dotnet/sdk", "csharp"),
+    ("ruby", "ruby"),
+    ("swift", "swift"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Language {
+    pub runtime: Runtime,
+    /// Directory name prefix, e.g. `cpp`, or the declared language
+    pub key: String,
+    pub name: String,
+}
+
+impl Language {
+    /// Language of the implementation `name` in `path`, with `manifest`.
+    pub fn detect(name: &str, path: &Path, manifest: &Manifest) -> Self {
+        let declared = manifest.language.clone().or_else(|| {
+            fs::read_to_string(path.join("README.md")).ok().and_then(|readme| {
+                front_matter(&readme)
+                    .into_iter()
+                    .find(|(key, _)| *key == "language")
+                    .map(|(_, value)| value.to_lowercase())
+            })
+        });
+        let prefix = name.split('-').next().unwrap_or_default().to_lowercase();
+        let key = declared
+            .or_else(|| LANGUAGES.iter().any(|(known, _, _)| *known == prefix).then(|| prefix.clone()))
+            .or_else(|| from_dockerfile(path))
+            .unwrap_or(prefix);
+
+        match LANGUAGES.iter().find(|(prefix, _, _)| *prefix == key) {
+            Some((_, name, runtime)) => Language {
+                runtime: *runtime,
+                name: name.to_string(),
+                key,
+            },
+            None => Language {
+                runtime: Runtime::Other,
+                name: key.clone(),
+                key,
+            },
+        }
+    }
+
+    /// Whether `filter` names this language, by key or display name,
+    /// ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        self.key.eq_ignore_ascii_case(filter) || self.name.eq_ignore_ascii_case(filter)
+    }
+}
+
+/// Language of the first base image in `<path>/Dockerfile` listed in
+/// [`BASE_IMAGES`].
+fn from_dockerfile(path: &Path) -> Option<String> {
+    let dockerfile = fs::read_to_string(path.join("Dockerfile")).ok()?;
+    dockerfile.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        if !words.next()?.eq_ignore_ascii_case("FROM") {
+            return None;
+        }
+        // Skip flags such as --platform=linux/amd64
+        let image = words.find(|word| !word.starts_with("--"))?;
+        let image = image.split(['@', ':']).next().unwrap_or(image).to_lowercase();
+        BASE_IMAGES
+            .iter()
+            .find(|(base, _)| image == *base || image.ends_with(&format!("/{}", base)))
+            .map(|(_, language)| language.to_string())
+    })
+}
+
+/// `items` grouped by runtime family, then language, both in order.
+pub fn group<T>(items: impl IntoIterator<Item = (Language, T)>) -> BTreeMap<Runtime, BTreeMap<String, Vec<T>>> {
+    let mut groups: BTreeMap<Runtime, BTreeMap<String, Vec<T>>> = BTreeMap::new();
+    for (language, item) in items {
+        groups
+            .entry(language.runtime)
+            .or_default()
+            .entry(language.name)
+            .or_default()
+            .push(item);
+    }
+    groups
+}
@@ -0,0 +1,206 @@
+//! Implementations ranked by accuracy and speed, for `leaderboard`.
+//!
+//! Each implementation's latest results are compared with the reference
+//! window by window, paired as validation pairs them (see
+//! [`tolerance::pair`]). Accuracy is the mean error of the paired windows'
+//! start and end times; windows either side has and the other doesn't are
+//! counted as unmatched, and rank ahead of the mean error so that missing a
+//! pass can't make an implementation look more accurate. Speed is the mean execution time over
+//! the same results. Efficiency is the CPU time per case of the
+//! implementation's latest measured run (see [`usage`]), with the energy
+//! that works out to. The reference implementation itself isn't ranked.
+//!
+//! [`tolerance::pair`]: crate::tolerance::pair
+//! [`usage`]: crate::usage
+
+use crate::report;
+use crate::result_diff;
+use crate::truth::{self, Reference};
+use crate::usage;
+use crate::{Orchestrator, REFERENCE_IMPLEMENTATION, VisibilityResult, read_result};
+use anyhow::Result;
+use chrono::Utc;
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Ranking {
+    /// Fewest unmatched windows, then smallest mean boundary error
+    Accuracy,
+    /// Smallest mean execution time
+    Speed,
+    /// Least CPU time per case
+    Efficiency,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Standing {
+    pub implementation: String,
+    /// Results with a reference to compare with
+    pub cases: usize,
+    /// Mean absolute error of paired window starts and ends, in seconds
+    pub mean_error: Option<f64>,
+    /// Largest such error, in seconds
+    pub max_error: Option<f64>,
+    /// Windows only the result or only the reference has
+    pub unmatched_windows: usize,
+    /// Mean execution time, in seconds
+    pub mean_time: Option<f64>,
+    /// CPU seconds per case in the latest run whose usage was measured
+    pub cpu_seconds_per_case: Option<f64>,
+    /// Estimated energy per case in that run, in joules
+    pub joules_per_case: Option<f64>,
+}
+
+/// Standings of every implementation with results, ranked by `ranking`.
+pub fn rank(orchestrator: &Orchestrator, reference: &Reference, ranking: Ranking) -> Result<Vec<Standing>> {
+    let mut implementations = orchestrator.discover_implementations()?;
+    implementations.extend(truth::external_tools(&orchestrator.test_data_dir)?);
+
+    let mut standings = Vec::new();
+    for impl_ in implementations {
+        if impl_.name == REFERENCE_IMPLEMENTATION {
+            continue;
+        }
+        let mut cases = 0;
+        let mut errors = Vec::new();
+        let mut unmatched_windows = 0;
+        let mut times = Vec::new();
+        for path in orchestrator.collect_results(&impl_.name)? {
+            let result: VisibilityResult = serde_json::from_str(&read_result(&path)?)?;
+            let Some(reference) = reference.load(&result.test_case)? else {
+                continue;
+            };
+            cases += 1;
+            times.extend(result.execution_time);
+            let pairing = result_diff::pair(&reference.visibility_windows, &result.visibility_windows);
+            unmatched_windows += pairing.removed.len() + pairing.added.len();
+            for (expected, actual) in &pairing.pairs {
+                for error in [actual.start - expected.start, actual.end - expected.end] {
+                    errors.push((error.num_milliseconds() as f64 / 1000.0).abs());
+                }
+            }
+        }
+        if cases == 0 {
+            continue;
+        }
+        let usage = usage::latest(&orchestrator.results_dir, &impl_.name)?;
+        standings.push(Standing {
+            implementation: impl_.name,
+            cases,
+            mean_error: mean(&errors),
+            max_error: errors.iter().copied().reduce(f64::max),
+            unmatched_windows,
+            mean_time: mean(&times),
+            cpu_seconds_per_case: usage.as_ref().and_then(usage::Usage::cpu_seconds_per_case),
+            joules_per_case: usage.and_then(|usage| usage.joules_per_case(orchestrator.watts_per_core)),
+        });
+    }
+
+    // Missing figures sort last
+    let by = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    standings.sort_by(|a, b| {
+        let order = match ranking {
+            Ranking::Accuracy => a.unmatched_windows.cmp(&b.unmatched_windows).then(by(a.mean_error, b.mean_error)),
+            Ranking::Speed => by(a.mean_time, b.mean_time),
+            Ranking::Efficiency => by(a.cpu_seconds_per_case, b.cpu_seconds_per_case),
+        };
+        order.then_with(|| a.implementation.cmp(&b.implementation))
+    });
+    Ok(standings)
+}
+
+pub fn print(standings: &[Standing], reference: &Reference, ranking: Ranking) {
+    let by = match ranking {
+        Ranking::Accuracy => "accuracy",
+        Ranking::Speed => "speed",
+        Ranking::Efficiency => "efficiency",
+    };
+    println!("{}", format!("Leaderboard by {} against {}:", by, reference.name()).bold());
+    if standings.is_empty() {
+        println!("  {} No results with a reference found", "⚠".yellow());
+        return;
+    }
+    println!(
+        "  {:>3}  {:<28} {:>5}  {:>10} {:>10}  {:>9}  {:>9}  {:>9}  {:>11}",
+        "#", "Implementation", "Cases", "Mean error", "Max error", "Unmatched", "Avg time", "CPU/case", "Energy/case"
+    );
+    for (i, standing) in standings.iter().enumerate() {
+        let line = format!(
+            "  {:>3}  {:<28} {:>5}  {:>10} {:>10}  {:>9}  {:>9}  {:>9}  {:>11}",
+            i + 1,
+            standing.implementation,
+            standing.cases,
+            seconds(standing.mean_error, 1),
+            seconds(standing.max_error, 1),
+            standing.unmatched_windows,
+            seconds(standing.mean_time, 3),
+            seconds(standing.cpu_seconds_per_case, 3),
+            report::joules(standing.joules_per_case)
+        );
+        if i == 0 {
+            println!("{}", line.bold());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// The standings as a Markdown table, for committing to the repo.
+pub fn markdown(standings: &[Standing], reference: &Reference, ranking: Ranking) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Leaderboard");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Ranked by {} against {}. Generated {}.",
+        match ranking {
+            Ranking::Accuracy => "accuracy (unmatched windows, then mean window boundary error)",
+            Ranking::Speed => "speed (mean execution time)",
+            Ranking::Efficiency => "efficiency (CPU time per case)",
+        },
+        reference.name(),
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "| # | Implementation | Cases | Mean error | Max error | Unmatched windows | Avg time | CPU/case | Energy/case |"
+    );
+    let _ = writeln!(
+        out,
+        "|---|----------------|-------|------------|-----------|-------------------|----------|----------|-------------|"
+    );
+    for (i, standing) in standings.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            i + 1,
+            standing.implementation,
+            standing.cases,
+            seconds(standing.mean_error, 1),
+            seconds(standing.max_error, 1),
+            standing.unmatched_windows,
+            seconds(standing.mean_time, 3),
+            seconds(standing.cpu_seconds_per_case, 3),
+            report::joules(standing.joules_per_case)
+        );
+    }
+    out
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn seconds(value: Option<f64>, precision: usize) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.precision$}s", value))
+}
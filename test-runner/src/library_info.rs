@@ -0,0 +1,144 @@
+//! Attribution metadata for the library behind each implementation.
+//!
+//! Read from front-matter at the top of the implementation's README.md:
+//!
+//! ```text
+//! ---
+//! library: Skyfield
+//! description: High-precision astronomy library
+//! repository: https://github.com/skyfielders/python-skyfield
+//! license: MIT
+//! ---
+//! ```
+//!
+//! The display name, library, and library version in its
+//! [`impl.toml`](crate::impl_toml) come first. Fields missing there fall
+//! back to the implementation's own manifest (`package.json` or
+//! `Cargo.toml`), where present.
+
+use crate::Implementation;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LibraryInfo {
+    pub implementation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub library: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_version: Option<String>,
+    pub description: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+}
+
+impl LibraryInfo {
+    pub fn load(impl_: &Implementation) -> Self {
+        let mut info = LibraryInfo {
+            implementation: impl_.name.clone(),
+            display_name: impl_.manifest.display_name.clone(),
+            library: impl_.manifest.library.clone(),
+            library_version: impl_.manifest.library_version.clone(),
+            ..Default::default()
+        };
+
+        if let Ok(readme) = fs::read_to_string(impl_.path.join("README.md")) {
+            for (key, value) in front_matter(&readme) {
+                let field = match key {
+                    "library" if info.library.is_some() => continue,
+                    "library" => &mut info.library,
+                    "description" => &mut info.description,
+                    "repository" => &mut info.repository,
+                    "license" => &mut info.license,
+                    _ => continue,
+                };
+                *field = Some(value.to_string());
+            }
+        }
+
+        if let Ok(content) = fs::read_to_string(impl_.path.join("package.json"))
+            && let Ok(package) = serde_json::from_str::<Value>(&content)
+        {
+            let repository = package
+                .get("repository")
+                .and_then(|r| r.as_str().or_else(|| r.get("url")?.as_str()));
+            info.fill(
+                package.get("description").and_then(Value::as_str),
+                repository,
+                package.get("license").and_then(Value::as_str),
+            );
+        }
+
+        if let Ok(content) = fs::read_to_string(impl_.path.join("Cargo.toml")) {
+            let field = |name: &str| cargo_package_field(&content, name);
+            info.fill(
+                field("description").as_deref(),
+                field("repository").as_deref(),
+                field("license").as_deref(),
+            );
+        }
+
+        info
+    }
+
+    /// The display name, or the implementation's directory name.
+    pub fn title(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.implementation)
+    }
+
+    /// The library with its version, when declared.
+    pub fn library_label(&self) -> Option<String> {
+        match (&self.library, &self.library_version) {
+            (Some(library), Some(version)) => Some(format!("{} {}", library, version)),
+            (library, _) => library.clone(),
+        }
+    }
+
+    /// Set each field that is still missing.
+    fn fill(&mut self, description: Option<&str>, repository: Option<&str>, license: Option<&str>) {
+        for (field, value) in [
+            (&mut self.description, description),
+            (&mut self.repository, repository),
+            (&mut self.license, license),
+        ] {
+            if field.is_none() {
+                *field = value.map(str::to_string);
+            }
+        }
+    }
+}
+
+/// `key: value` pairs between the leading `---` lines of a Markdown file.
+pub(crate) fn front_matter(content: &str) -> Vec<(&str, &str)> {
+    let mut lines = content.lines();
+    if lines.next().map(str::trim) != Some("---") {
+        return Vec::new();
+    }
+
+    lines
+        .take_while(|line| line.trim() != "---")
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim().trim_matches('"');
+            (!value.is_empty()).then_some((key.trim(), value))
+        })
+        .collect()
+}
+
+/// A string field of the `[package]` table in a Cargo.toml.
+fn cargo_package_field(content: &str, name: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == name
+        {
+            return Some(value.trim().trim_matches('"').to_string());
+        }
+    }
+    None
+}
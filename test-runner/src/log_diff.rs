@@ -0,0 +1,321 @@
+//! Diffs of an implementation's container stdout between two runs.
+//!
+//! Every run files the containers' stdout as
+//! `results/<run-id>/<impl>/stdout.log`. Comparing two of them shows
+//! behavior changes that don't reach the result JSON, such as new warnings,
+//! retries, or a different code path. Before comparing, lines are masked of
+//! what differs on every run anyway:
+//!
+//! - timestamps: ISO 8601 date-times, times of day, and the
+//!   `YYYYMMDD_HHMMSS` of result file names, as `<timestamp>`
+//! - timings such as `Execution time: 0.012s`, as `<duration>`
+//!
+//! Lines are shown as written, not masked. Each hunk is annotated with the
+//! test case it falls in, from the `Processing: <case>.json` lines all
+//! implementations print. Runs from before stdout was filed separately fall
+//! back to `container.log`, which includes stderr.
+
+use crate::artifacts::RunArtifacts;
+use anyhow::{Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+pub struct LogDiff {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A run of changed lines with their context, as in a unified diff.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    /// The test case being processed where the hunk starts
+    pub test_case: Option<String>,
+    /// 1-based first line and line count in each log
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Lines prefixed with ` `, `-`, or `+`
+    pub lines: Vec<String>,
+}
+
+/// An edit by line index: unchanged (in the new log), deleted from the
+/// old, or inserted from the new.
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Diff `implementation`'s stdout in `run_a` against `run_b`, with `context`
+/// unchanged lines around each change. `mask` hides timestamps and timings.
+pub fn diff(results_dir: &Path, implementation: &str, run_a: &str, run_b: &str, context: usize, mask: bool) -> Result<LogDiff> {
+    let (old, old_log) = read_stdout(results_dir, run_a, implementation)?;
+    let (new, new_log) = read_stdout(results_dir, run_b, implementation)?;
+    let old_lines: Vec<&str> = old_log.lines().collect();
+    let new_lines: Vec<&str> = new_log.lines().collect();
+    let key = |line: &&str| if mask { mask_noise(line) } else { line.to_string() };
+    let edits = edits(
+        &old_lines.iter().map(key).collect::<Vec<_>>(),
+        &new_lines.iter().map(key).collect::<Vec<_>>(),
+    );
+
+    let old_cases = cases(&old_lines);
+    let new_cases = cases(&new_lines);
+    let is_change = |edit: &Edit| !matches!(edit, Edit::Equal(..));
+    let mut hunks = Vec::new();
+    let mut from = 0;
+    while let Some(start) = edits[from..].iter().position(is_change).map(|i| i + from) {
+        // Join changes separated by up to twice the context
+        let mut end = start;
+        loop {
+            while end < edits.len() && is_change(&edits[end]) {
+                end += 1;
+            }
+            match edits[end..].iter().position(is_change) {
+                Some(gap) if gap <= 2 * context => end += gap,
+                _ => break,
+            }
+        }
+        let first = start.saturating_sub(context);
+        let last = (end + context).min(edits.len());
+
+        let old_before = edits[..first].iter().filter(|edit| !matches!(edit, Edit::Insert(_))).count();
+        let new_before = edits[..first].iter().filter(|edit| !matches!(edit, Edit::Delete(_))).count();
+        let mut hunk = Hunk {
+            test_case: match edits[start] {
+                Edit::Delete(x) => old_cases[x].clone(),
+                Edit::Insert(y) | Edit::Equal(y) => new_cases[y].clone(),
+            },
+            old_start: old_before,
+            old_lines: 0,
+            new_start: new_before,
+            new_lines: 0,
+            lines: Vec::new(),
+        };
+        for edit in &edits[first..last] {
+            match *edit {
+                Edit::Equal(y) => {
+                    hunk.lines.push(format!(" {}", new_lines[y]));
+                    hunk.old_lines += 1;
+                    hunk.new_lines += 1;
+                }
+                Edit::Delete(x) => {
+                    hunk.lines.push(format!("-{}", old_lines[x]));
+                    hunk.old_lines += 1;
+                }
+                Edit::Insert(y) => {
+                    hunk.lines.push(format!("+{}", new_lines[y]));
+                    hunk.new_lines += 1;
+                }
+            }
+        }
+        // Empty ranges start at the line before, as in unified diffs
+        if hunk.old_lines > 0 {
+            hunk.old_start += 1;
+        }
+        if hunk.new_lines > 0 {
+            hunk.new_start += 1;
+        }
+        hunks.push(hunk);
+        from = last;
+    }
+
+    Ok(LogDiff { old, new, hunks })
+}
+
+/// Print `diff` as a colored unified diff.
+pub fn print(diff: &LogDiff) {
+    println!("{}", format!("--- {}", diff.old.display()).bold());
+    println!("{}", format!("+++ {}", diff.new.display()).bold());
+    if diff.hunks.is_empty() {
+        println!("{} No differences", "✓".green());
+        return;
+    }
+    for hunk in &diff.hunks {
+        let header = format!(
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+        );
+        println!("{} {}", header.cyan(), hunk.test_case.as_deref().unwrap_or_default().bright_white());
+        for line in &hunk.lines {
+            match line.chars().next() {
+                Some('-') => println!("{}", line.red()),
+                Some('+') => println!("{}", line.green()),
+                _ => println!("{}", line.dimmed()),
+            }
+        }
+    }
+}
+
+fn read_stdout(results_dir: &Path, run_id: &str, implementation: &str) -> Result<(PathBuf, String)> {
+    let artifacts = RunArtifacts::open(results_dir, run_id)?;
+    let dir = artifacts.dir().join(implementation);
+    for name in ["stdout.log", "container.log"] {
+        let path = dir.join(name);
+        if let Ok(content) = fs::read_to_string(&path) {
+            return Ok((path, content));
+        }
+    }
+    bail!("Run {} has no output from {}", run_id, implementation)
+}
+
+/// The test case each line falls in.
+fn cases(lines: &[&str]) -> Vec<Option<String>> {
+    let mut current = None;
+    lines
+        .iter()
+        .map(|line| {
+            if let Some(file) = line.trim().strip_prefix("Processing: ") {
+                current = Some(file.strip_suffix(".json").unwrap_or(file).to_string());
+            }
+            current.clone()
+        })
+        .collect()
+}
+
+/// Shortest edit script from `a` to `b` (Myers' algorithm).
+fn edits(a: &[String], b: &[String]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // The furthest x on each diagonal k = x - y before each step
+    let mut trace = Vec::new();
+    'search: for d in 0..=n + m {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) { k + 1 } else { k - 1 };
+        let previous_x = v[index(previous_k)];
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(y as usize));
+        }
+        if d > 0 {
+            if x == previous_x {
+                y -= 1;
+                edits.push(Edit::Insert(y as usize));
+            } else {
+                x -= 1;
+                edits.push(Edit::Delete(x as usize));
+            }
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+/// `line` with timestamps and timings replaced by placeholders.
+fn mask_noise(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut masked = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let previous = i.checked_sub(1).map(|j| bytes[j]);
+        // Result file names put `_` before their timestamp
+        if !previous.is_some_and(|b| b.is_ascii_alphanumeric())
+            && let Some(len) = timestamp(&bytes[i..])
+        {
+            masked.extend_from_slice(b"<timestamp>");
+            i += len;
+            continue;
+        }
+        if !previous.is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.')
+            && let Some(len) = duration(&bytes[i..])
+        {
+            masked.extend_from_slice(b"<duration>");
+            i += len;
+            continue;
+        }
+        masked.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&masked).into_owned()
+}
+
+/// Whether `s` starts with `pattern`, where `d` matches any digit.
+fn shape(s: &[u8], pattern: &[u8]) -> bool {
+    s.len() >= pattern.len()
+        && s.iter()
+            .zip(pattern)
+            .all(|(&b, &p)| if p == b'd' { b.is_ascii_digit() } else { b == p })
+}
+
+fn digits(s: &[u8]) -> usize {
+    s.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// Length of the timestamp `s` starts with, if any.
+fn timestamp(s: &[u8]) -> Option<usize> {
+    if shape(s, b"dddddddd_dddddd") {
+        return Some(15);
+    }
+    let mut len = if shape(s, b"dddd-dd-ddTdd:dd:dd") || shape(s, b"dddd-dd-dd dd:dd:dd") {
+        19
+    } else if shape(s, b"dd:dd:dd") {
+        8
+    } else {
+        return None;
+    };
+    if s.get(len) == Some(&b'.') && digits(&s[len + 1..]) > 0 {
+        len += 1 + digits(&s[len + 1..]);
+    }
+    match s.get(len) {
+        Some(b'Z') => len += 1,
+        Some(b'+' | b'-') if shape(&s[len + 1..], b"dd:dd") => len += 6,
+        _ => {}
+    }
+    Some(len)
+}
+
+/// Length of the timing, such as `0.012s` or `15 ms`, `s` starts with, if any.
+fn duration(s: &[u8]) -> Option<usize> {
+    let mut len = digits(s);
+    if len == 0 {
+        return None;
+    }
+    if s.get(len) == Some(&b'.') && digits(&s[len + 1..]) > 0 {
+        len += 1 + digits(&s[len + 1..]);
+    }
+    if s.get(len) == Some(&b' ') {
+        len += 1;
+    }
+    let unit = ["ms", "µs", "us", "ns", "s"]
+        .iter()
+        .find(|unit| s[len..].starts_with(unit.as_bytes()))?;
+    len += unit.len();
+    if s.get(len).is_some_and(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(len)
+}
@@ -1,17 +1,145 @@
+mod accuracy;
+mod artifacts;
+mod audit;
+mod baseline;
+mod bench;
+mod capabilities;
+mod catalog;
+mod changelog;
+mod clean;
+mod compose;
+mod consistency;
+mod contract;
+mod daemon;
+mod database;
+mod density;
+mod disk_usage;
+mod docker_api;
+mod doppler;
+mod error;
+mod events;
+mod flaky;
+mod gate;
+mod health;
+mod heartbeat;
+mod history;
+mod impl_toml;
+mod junit;
+mod languages;
+mod leaderboard;
+mod library_info;
+mod log_diff;
+mod merge;
+mod minimize;
+mod openmetrics;
+mod orbit;
+mod output;
+mod passes;
+mod payload;
+mod profiles;
+mod progress;
+mod publish;
+mod reference_check;
+mod report;
+mod result_diff;
+mod result_import;
+mod runtime;
+mod scaffold;
+mod schedule;
+mod schema;
+mod screening;
+mod show;
+mod sites;
+mod stream;
+mod stress;
+mod sweep;
+mod tap;
+mod time_display;
+mod tle_sets;
+mod tolerance;
+mod track;
+mod truth;
+mod usage;
+mod watch;
+
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use colored::Colorize;
+use artifacts::{RunArtifacts, RunManifest};
+use bench::BenchStats;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use colored::{ColoredString, Colorize};
+use events::{Event, EventSink, Verdict};
+use flate2::read::GzDecoder;
+use languages::Language;
+use output::JsonOutput;
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::Instant;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use truth::Reference;
 use walkdir::WalkDir;
 
+/// Implementation whose results serve as the validation reference.
+const REFERENCE_IMPLEMENTATION: &str = "python-skyfield";
+
 #[derive(Parser)]
 #[command(name = "visibility-test-runner")]
 #[command(about = "Docker-based test orchestrator for satellite visibility implementations")]
 struct Cli {
+    /// Stream newline-delimited JSON progress events to fd:<n>, unix:<path>, or a file
+    #[arg(long, global = true)]
+    events: Option<String>,
+
+    /// Print a JSON document describing the outcome on stdout, and the usual output on stderr
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Print progress line by line instead of drawing progress bars (the default when stdout isn't a terminal)
+    #[arg(long, global = true)]
+    no_progress: bool,
+
+    /// Tag runs with this profile from test-data/profiles.json, and take its defaults
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Container runtime (default: the profile's, or docker)
+    #[arg(long, global = true, value_enum)]
+    runtime: Option<runtime::RuntimeKind>,
+
+    /// Power drawn by one busy CPU core in watts, for energy estimates (default: the profile's, or 5.0)
+    #[arg(long, global = true)]
+    watts_per_core: Option<f64>,
+
+    /// Mount only the selected case files into each container, not all of test-data
+    #[arg(long, global = true)]
+    isolate_cases: bool,
+
+    /// Kill test containers still running after this many seconds
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Kill test containers as hung after this many seconds without a heartbeat or output
+    #[arg(long, global = true, value_name = "SECS")]
+    heartbeat: Option<u64>,
+
+    /// CPUs each test container may use, e.g. 1.5 (default: the profile's, or unlimited)
+    #[arg(long, global = true)]
+    cpus: Option<f64>,
+
+    /// Memory each test container may use, e.g. 512m or 2g (default: the profile's, or unlimited)
+    #[arg(long, global = true, value_parser = runtime::parse_memory)]
+    memory: Option<u64>,
+
+    /// Only run cases of this difficulty tier (metadata.difficulty); repeat for several (default: the profile's, or all)
+    #[arg(long = "tier", global = true, value_name = "TIER")]
+    tiers: Vec<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -21,11 +149,37 @@ enum Commands {
     /// Discover all implementations
     Discover,
 
+    /// Create a skeleton implementation to fill in
+    Init {
+        /// Library name; the directory is implementations/<language>-<name>
+        name: String,
+
+        /// Language of the skeleton
+        #[arg(short, long, value_enum)]
+        language: scaffold::Template,
+    },
+
+    /// List the test cases: satellite, observer, time window duration, and step
+    ListTests,
+
     /// Build Docker images for implementations
     Build {
         /// Specific implementation to build (optional)
         #[arg(short, long)]
         implementation: Option<String>,
+
+        /// Only implementations in this language, e.g. python or C++; repeat
+        /// or separate with commas for several, e.g. java,kotlin
+        #[arg(long, value_delimiter = ',', conflicts_with = "implementation")]
+        language: Vec<String>,
+
+        /// Pull each Dockerfile's base images and rebuild only images whose base changed
+        #[arg(long)]
+        check_base_updates: bool,
+
+        /// Number of images to build concurrently
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
     },
 
     /// Run tests for implementations
@@ -34,13 +188,51 @@ enum Commands {
         #[arg(short, long)]
         implementation: Option<String>,
 
-        /// Specific test case to run (optional)
+        /// Only implementations in this language, e.g. python or C++; repeat
+        /// or separate with commas for several, e.g. java,kotlin
+        #[arg(long, value_delimiter = ',', conflicts_with = "implementation")]
+        language: Vec<String>,
+
+        /// Test case to run, or a glob pattern like 'iss-*'; repeat for several (default: all)
         #[arg(short, long)]
-        test_case: Option<String>,
+        test_case: Vec<String>,
+
+        /// Run only cases with this tag, e.g. leo; repeat to allow several
+        #[arg(long)]
+        tag: Vec<String>,
+
+        /// Leave out cases with this tag, e.g. deep-space; repeat for several
+        #[arg(long)]
+        exclude_tag: Vec<String>,
 
         /// Build images before running
         #[arg(short, long)]
         build: bool,
+
+        /// Number of containers to run concurrently (default: the profile's, or 1)
+        #[arg(long)]
+        parallel: Option<usize>,
+
+        /// Stop at the first build or run failure, starting no more containers
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Don't run each image with `--version` before the tests
+        #[arg(long)]
+        skip_health_check: bool,
+
+        /// `junit` also writes a JUnit XML file for CI systems, `tap` a TAP stream (default: the profile's, or text)
+        #[arg(short, long, value_enum)]
+        format: Option<junit::Format>,
+
+        /// Where to write the JUnit XML file or TAP stream (default: results/junit.xml or results/results.tap)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// After the run, watch the implementations and test data, and
+        /// rebuild and re-run what a change affects until interrupted
+        #[arg(long)]
+        watch: bool,
     },
 
     /// Validate results against reference
@@ -48,6 +240,148 @@ enum Commands {
         /// Implementation to validate
         #[arg(short, long)]
         implementation: Option<String>,
+
+        /// Only implementations in this language, e.g. python or C++; repeat
+        /// or separate with commas for several, e.g. java,kotlin
+        #[arg(long, value_delimiter = ',', conflicts_with = "implementation")]
+        language: Vec<String>,
+
+        /// Validate against third-party truth data (STK or GMAT CSV exports,
+        /// one `<test-case>.csv` per case) instead of the reference results
+        #[arg(long)]
+        truth: Option<PathBuf>,
+
+        /// Allowed difference in window start, end, and duration, in seconds,
+        /// for every case (default: the case's, or its orbit regime's)
+        #[arg(long)]
+        time_tolerance: Option<f64>,
+
+        /// Allowed difference in maximum elevation, in degrees, for every
+        /// case (default: the case's, or its orbit regime's)
+        #[arg(long)]
+        elevation_tolerance: Option<f64>,
+
+        /// Compare sampled points by timestamp and report RMS/max azimuth,
+        /// elevation, and range errors instead of checking windows
+        #[arg(long)]
+        points: bool,
+
+        /// Flag regressions against this earlier run (a run ID or run directory)
+        #[arg(long, conflicts_with = "points")]
+        baseline: Option<String>,
+
+        /// Execution time growth, in percent, that counts as a regression
+        #[arg(long, default_value_t = 20.0, requires = "baseline")]
+        regression_threshold: f64,
+
+        /// Fail unless at least this share (0-1) of implementation × case
+        /// cells match the reference
+        #[arg(long, value_parser = gate::parse_fraction, conflicts_with = "points")]
+        min_pass_rate: Option<f64>,
+
+        /// Fail unless the mean per-cell score (0-1), with partial credit for
+        /// windows within tolerance, reaches this
+        #[arg(long, value_parser = gate::parse_fraction, conflicts_with = "points")]
+        min_score: Option<f64>,
+
+        /// `junit` also writes a JUnit XML file for CI systems, `tap` a TAP stream (default: the profile's, or text)
+        #[arg(short, long, value_enum)]
+        format: Option<junit::Format>,
+
+        /// Where to write the JUnit XML file or TAP stream (default: results/junit.xml or results/results.tap)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the visibility windows of an implementation's latest results
+    Show {
+        /// Implementation whose results to show
+        #[arg(short, long)]
+        implementation: String,
+
+        /// Specific test case to show (optional)
+        #[arg(short, long)]
+        test_case: Option<String>,
+
+        /// Also show times in this zone: local, an offset like +05:30, or an IANA name like America/New_York
+        #[arg(long)]
+        tz: Option<time_display::Zone>,
+    },
+
+    /// Export an implementation's latest windows as a gpredict/AMSAT-style pass table
+    Passes {
+        /// Implementation whose results to export
+        #[arg(short, long)]
+        implementation: String,
+
+        /// Specific test case to export (optional)
+        #[arg(short, long)]
+        test_case: Option<String>,
+
+        /// Show times in this zone instead of UTC: local, an offset like +05:30, or an IANA name
+        #[arg(long)]
+        tz: Option<time_display::Zone>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export one pass as a time-tagged rotctld command script
+    Track {
+        /// Implementation whose results to use
+        #[arg(short, long)]
+        implementation: String,
+
+        /// Test case containing the pass
+        #[arg(short, long)]
+        test_case: String,
+
+        /// Pass number within the test case, starting at 1
+        #[arg(short, long, default_value_t = 1)]
+        pass: usize,
+
+        /// Seconds before AOS to move the rotator to the AOS position
+        #[arg(long, default_value_t = 60)]
+        lead: u32,
+
+        /// How to handle passes that cross north
+        #[arg(long, value_enum, default_value = "continuous")]
+        unwrap: track::Unwrap,
+
+        /// Lowest azimuth the rotator can reach
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        az_min: f64,
+
+        /// Highest azimuth the rotator can reach (e.g. 450 for an overlap rotator)
+        #[arg(long, default_value_t = 360.0)]
+        az_max: f64,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export a Doppler correction table for a case with a declared downlink frequency
+    Doppler {
+        /// Implementation whose results to use
+        #[arg(short, long)]
+        implementation: String,
+
+        /// Test case to export
+        #[arg(short, long)]
+        test_case: String,
+
+        /// Only this pass, starting at 1 (default: every pass)
+        #[arg(short, long)]
+        pass: Option<usize>,
+
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: doppler::DopplerFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Run complete test suite (build + run + validate)
@@ -55,6 +389,361 @@ enum Commands {
         /// Specific test case to run (optional)
         #[arg(short, long)]
         test_case: Option<String>,
+
+        /// Run only cases with this tag, e.g. leo; repeat to allow several
+        #[arg(long)]
+        tag: Vec<String>,
+
+        /// Leave out cases with this tag, e.g. deep-space; repeat for several
+        #[arg(long)]
+        exclude_tag: Vec<String>,
+
+        /// Continue an interrupted run from its first incomplete (implementation, case) pair
+        #[arg(long, value_name = "RUN_ID", conflicts_with_all = ["test_case", "tag", "exclude_tag"])]
+        resume: Option<String>,
+
+        /// Stop at the first build failure, run failure, or validation mismatch
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Don't run each image with `--version` before the tests
+        #[arg(long)]
+        skip_health_check: bool,
+
+        /// `junit` also writes a JUnit XML file for CI systems, `tap` a TAP stream (default: the profile's, or text)
+        #[arg(short, long, value_enum)]
+        format: Option<junit::Format>,
+
+        /// Where to write the JUnit XML file or TAP stream (default: results/junit.xml or results/results.tap)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Narrow a failing test case to the smallest time window that still disagrees with the reference
+    Minimize {
+        /// Implementation that disagrees with the reference
+        #[arg(short, long)]
+        implementation: String,
+
+        /// Test case to minimize
+        #[arg(short, long)]
+        test_case: String,
+
+        /// Name of the new test case (default: <test-case>_min)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Time repeated container runs for each implementation
+    Bench {
+        /// Specific implementation to benchmark (optional)
+        #[arg(short, long)]
+        implementation: Option<String>,
+
+        /// Specific test case to run (optional)
+        #[arg(short, long)]
+        test_case: Option<String>,
+
+        /// Measured runs per implementation
+        #[arg(short = 'n', long, default_value = "5")]
+        iterations: usize,
+
+        /// Unmeasured runs before measuring, to warm caches
+        #[arg(long, default_value = "1")]
+        warmup: usize,
+
+        /// Pin containers to dedicated cores and refuse to measure on a busy host
+        #[arg(long)]
+        pin_cpus: bool,
+
+        /// Cores to pin to, in cpuset syntax (default: the last online CPU)
+        #[arg(long, requires = "pin_cpus")]
+        cpuset: Option<String>,
+
+        /// Highest 1-minute load average per CPU tolerated before each run
+        #[arg(long, default_value = "0.1", requires = "pin_cpus")]
+        max_load: f64,
+
+        /// Time screening the whole catalog of a scenario in test-data/screening instead
+        #[arg(long, conflicts_with = "test_case")]
+        screening: Option<String>,
+
+        /// Satellites of the scenario to check against the reference (default: the scenario's)
+        #[arg(long, requires = "screening")]
+        sample: Option<usize>,
+
+        /// Also write the timings as JSON to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Run long windows and catalog scenarios under tight memory limits and report which implementations run out of memory
+    Stress {
+        /// Specific implementation to stress (optional)
+        #[arg(short, long)]
+        implementation: Option<String>,
+
+        /// Memory limit to try, e.g. 128m; repeat for several (default: 512m, 256m, 128m, and 64m)
+        #[arg(long = "limit", value_parser = runtime::parse_memory)]
+        limits: Vec<u64>,
+
+        /// Case to stretch into a long window, or a glob pattern; repeat for several (default: all)
+        #[arg(short, long)]
+        test_case: Vec<String>,
+
+        /// Length of the long windows in days
+        #[arg(long, default_value = "7")]
+        days: u32,
+
+        /// Screening scenario to run; repeat for several (default: every one whose TLE set is present)
+        #[arg(long)]
+        screening: Vec<String>,
+
+        /// Also write the attempts as JSON to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Run a test case over a grid of elevation masks, time steps, and observer latitudes and report how each implementation's windows respond
+    Sweep {
+        /// Base test case to vary
+        test_case: String,
+
+        /// Specific implementation to sweep (optional)
+        #[arg(short, long)]
+        implementation: Option<String>,
+
+        /// Minimum elevation to try in degrees; repeat for several
+        #[arg(long = "min-elevation", allow_negative_numbers = true)]
+        min_elevations: Vec<f64>,
+
+        /// Time step to try in seconds; repeat for several
+        #[arg(long = "step")]
+        steps: Vec<f64>,
+
+        /// Observer latitude to try in degrees; repeat for several
+        #[arg(long = "latitude", allow_negative_numbers = true)]
+        latitudes: Vec<f64>,
+
+        /// Also write the surfaces as JSON to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Import STK/GMAT exports from test-data/external as pseudo-implementation results
+    Import {
+        /// Tool to import (a directory under test-data/external)
+        #[arg(short, long)]
+        implementation: Option<String>,
+    },
+
+    /// Import result files produced outside the orchestrator
+    ImportResults {
+        /// Directory, .tar or .tar.gz archive, or file of result files
+        source: PathBuf,
+
+        /// Implementation the results are from, replacing the one they name
+        #[arg(short, long)]
+        implementation: Option<String>,
+    },
+
+    /// Write a comparison report with library attribution
+    Report {
+        #[arg(short, long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rank implementations by accuracy against the reference and by speed
+    Leaderboard {
+        /// What to rank by
+        #[arg(long, value_enum, default_value = "accuracy")]
+        by: leaderboard::Ranking,
+
+        /// Rank against third-party truth data (STK or GMAT CSV exports)
+        /// instead of the reference results
+        #[arg(long)]
+        truth: Option<PathBuf>,
+
+        /// Also write the leaderboard as a Markdown table to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a catalog of the test cases: satellite, orbit, observer, time window, difficulty
+    DescribeCases {
+        #[arg(short, long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Don't write; fail if the output file isn't what would be written
+        #[arg(long, requires = "output")]
+        check: bool,
+    },
+
+    /// Check the reference results: one per test case, schema-valid, and internally consistent
+    CheckReference,
+
+    /// Write a report comparing the latest runs of each hardware profile side by side
+    Merge {
+        /// Results directories to merge, e.g. copied from each host (default: ../results)
+        results: Vec<PathBuf>,
+
+        #[arg(short, long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate a static results site from the history of runs
+    Publish {
+        /// Directory to write the site to (default: results/site)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only publish the last N runs
+        #[arg(long)]
+        last: Option<usize>,
+    },
+
+    /// Find cases whose verdict flip-flops across runs of the same image
+    Flaky {
+        /// Fewest verdict flips for a pair to count as flaky
+        #[arg(long, default_value_t = 2)]
+        min_flips: usize,
+
+        /// Only look at the last N runs
+        #[arg(long)]
+        last: Option<usize>,
+
+        /// Propose flaky pairs not yet listed for test-data/quarantine.json
+        #[arg(long)]
+        propose: bool,
+    },
+
+    /// Diff an implementation's container stdout between two runs
+    LogDiff {
+        /// Implementation whose output to compare
+        implementation: String,
+
+        /// Run ID of the earlier run
+        run_a: String,
+
+        /// Run ID of the later run
+        run_b: String,
+
+        /// Unchanged lines shown around each change
+        #[arg(short = 'U', long, default_value_t = 3)]
+        context: usize,
+
+        /// Compare lines as written, without masking timestamps and timings
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Diff two result files window by window, ignoring timestamps and timings
+    Diff {
+        /// The earlier result file
+        old: PathBuf,
+
+        /// The later result file
+        new: PathBuf,
+    },
+
+    /// Query past runs from the results database
+    History {
+        /// List this implementation's cases across runs
+        #[arg(short, long)]
+        implementation: Option<String>,
+
+        /// List this test case across runs
+        #[arg(short, long)]
+        test_case: Option<String>,
+
+        /// How many of the most recent runs to include
+        #[arg(long, default_value_t = 10)]
+        last: usize,
+    },
+
+    /// Export every recorded validation failure as JSON lines
+    ExportFailures {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check declared library names, versions and licenses across implementations
+    Audit {
+        /// Also write an SBOM-like JSON document of the implementation set to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate a docker-compose file for all discovered implementations
+    GenerateCompose {
+        /// Write to this file instead of stdout (e.g. ../docker-compose.yml)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Run as a long-lived daemon controlled over a Unix socket
+    Daemon {
+        /// Path of the control socket
+        #[arg(short, long, default_value = "/tmp/visibility-test-runner.sock")]
+        socket: PathBuf,
+
+        /// Cron expression for unattended full-suite runs (e.g. "0 3 * * *")
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// After each scheduled run, keep only the newest N results per implementation/test case
+        #[arg(long, requires = "schedule")]
+        keep: Option<usize>,
+
+        /// Shell command to run after each scheduled run (summary JSON on stdin)
+        #[arg(long, requires = "schedule")]
+        notify: Option<String>,
+    },
+
+    /// Delete all but the newest result files per implementation and test case
+    Prune {
+        /// Result files to keep per implementation and test case (default: the profile's `keep`)
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        keep: Option<u64>,
+
+        /// List what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Summarize disk usage of the results directory, with suggestions of what could go
+    Du {
+        /// Suggest removing runs at least this many days old
+        #[arg(long, default_value = "30")]
+        older_than: u64,
+    },
+
+    /// Remove built images and clear the results directory
+    Clean {
+        /// Remove the visibility-test/* images and the dangling images earlier builds left
+        #[arg(long)]
+        images: bool,
+
+        /// Remove everything in the results directory
+        #[arg(long)]
+        results: bool,
+
+        /// Both --images and --results
+        #[arg(long)]
+        all: bool,
     },
 }
 
@@ -63,15 +752,74 @@ struct Implementation {
     name: String,
     path: PathBuf,
     image_name: String,
+    /// `impl.toml`, empty if there is none
+    manifest: impl_toml::Manifest,
+    language: Language,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 struct TestResult {
     implementation: String,
     success: bool,
     execution_time: f64,
     stdout: String,
     stderr: String,
+    /// Result files the container wrote during this run
+    #[serde(skip)]
+    result_files: Vec<PathBuf>,
+    /// Cases left out for features the implementation doesn't declare
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unsupported: Vec<String>,
+    /// CPU time of the container, where its cgroup could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_seconds: Option<f64>,
+    /// Peak memory of the container in bytes, where its cgroup could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory: Option<u64>,
+    /// Image the container ran from, where the runtime could tell
+    #[serde(skip)]
+    image_id: Option<String>,
+    /// Killed for running past `--timeout`
+    timed_out: bool,
+    /// Killed for going silent past `--heartbeat`
+    hung: bool,
+    /// Files written outside the output contract, moved to `results/.quarantine`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    quarantined: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ValidationSummary {
+    implementation: String,
+    matched: usize,
+    total: usize,
+    /// Non-fatal warnings reported across the implementation's results
+    warnings: usize,
+    cases: Vec<CaseVerdict>,
+    /// Nested cases whose windows don't agree with each other
+    inconsistencies: Vec<consistency::Inconsistency>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CaseVerdict {
+    test_case: String,
+    verdict: Verdict,
+    result_windows: usize,
+    reference_windows: Option<usize>,
+    warnings: usize,
+    /// Orbit regime the case's default tolerances came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regime: Option<orbit::Regime>,
+    /// Fields outside tolerance, of windows paired with the reference's by
+    /// nearest start time
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    deltas: Vec<tolerance::FieldDelta>,
+    /// Windows of either side left without a pair
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unmatched: Vec<tolerance::Unmatched>,
+    /// A mismatch the implementation's `impl.toml` expects
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    expected_failure: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -85,16 +833,81 @@ struct VisibilityResult {
     visibility_windows: Vec<serde_json::Value>,
     #[serde(rename = "executionTime")]
     execution_time: Option<f64>,
+    #[serde(default)]
+    warnings: Vec<ResultWarning>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultWarning {
+    code: String,
+    message: String,
 }
 
 struct Orchestrator {
     implementations_dir: PathBuf,
     test_data_dir: PathBuf,
     results_dir: PathBuf,
+    events: EventSink,
+    progress: progress::Progress,
+    /// Profile runs are tagged with and take their defaults from
+    profile: Option<profiles::Profile>,
+    runtime: &'static dyn runtime::ContainerRuntime,
+    watts_per_core: f64,
+    /// Give each container only its selected cases (see [`payload`])
+    isolate_cases: bool,
+    /// How long a test container may run before it is killed
+    timeout: Option<Duration>,
+    /// How long a test container may go without a heartbeat before it is
+    /// killed as hung (see [`heartbeat`])
+    heartbeat: Option<Duration>,
+    limits: runtime::Limits,
+    /// Difficulty tiers to run; every case when empty
+    tiers: Vec<String>,
+    /// Test containers currently running, for output contract checks
+    running: contract::Running,
+}
+
+/// Global options from the command line. Those not given fall back to the
+/// profile's, then to defaults.
+struct Settings<'a> {
+    profile: Option<&'a str>,
+    runtime: Option<runtime::RuntimeKind>,
+    watts_per_core: Option<f64>,
+    isolate_cases: bool,
+    timeout: Option<Duration>,
+    heartbeat: Option<Duration>,
+    limits: runtime::Limits,
+    tiers: Vec<String>,
+    /// Draw progress bars when stdout is a terminal
+    progress: bool,
+}
+
+impl TestResult {
+    /// Why the container was killed, if it was: " (hung)" or " (timed out)"
+    fn killed_note(&self) -> &'static str {
+        if self.hung {
+            " (hung)"
+        } else if self.timed_out {
+            " (timed out)"
+        } else {
+            ""
+        }
+    }
 }
 
 impl Orchestrator {
-    fn new() -> Result<Self> {
+    fn new(events: EventSink, settings: Settings) -> Result<Self> {
+        let Settings {
+            profile,
+            runtime,
+            watts_per_core,
+            isolate_cases,
+            timeout,
+            heartbeat,
+            limits,
+            tiers,
+            progress,
+        } = settings;
         let project_root = std::env::current_dir()
             .context("Failed to get current directory")?
             .parent()
@@ -107,13 +920,90 @@ impl Orchestrator {
 
         fs::create_dir_all(&results_dir)?;
 
+        let profile = profile.map(|name| profiles::find(&test_data_dir, name)).transpose()?;
+        let watts_per_core = watts_per_core
+            .or(profile.as_ref().and_then(|profile| profile.watts_per_core))
+            .unwrap_or(usage::DEFAULT_WATTS_PER_CORE);
+        let runtime = runtime
+            .or(profile.as_ref().and_then(|profile| profile.runtime))
+            .unwrap_or(runtime::RuntimeKind::Docker)
+            .runtime();
+        let limits = runtime::Limits {
+            cpus: limits.cpus.or(profile.as_ref().and_then(|profile| profile.cpus)),
+            memory: match (limits.memory, profile.as_ref().and_then(|profile| profile.memory.as_deref())) {
+                (Some(memory), _) => Some(memory),
+                (None, Some(memory)) => Some(
+                    runtime::parse_memory(memory)
+                        .with_context(|| format!("Invalid memory in profile {}", profile.as_ref().map_or("", |profile| &profile.name)))?,
+                ),
+                (None, None) => None,
+            },
+        };
+        if limits.cpus.is_some_and(|cpus| cpus <= 0.0) {
+            anyhow::bail!("--cpus must be positive");
+        }
+        let isolate_cases = isolate_cases || profile.as_ref().is_some_and(|profile| profile.isolate_cases);
+        let timeout = timeout.or(profile.as_ref().and_then(|profile| profile.timeout).map(Duration::from_secs));
+        let heartbeat = heartbeat.or(profile.as_ref().and_then(|profile| profile.heartbeat).map(Duration::from_secs));
+        let tiers = match (tiers.is_empty(), &profile) {
+            (true, Some(profile)) => profile.tiers.clone(),
+            _ => tiers,
+        };
+        if !tiers.is_empty() {
+            let known = compose::test_case_tiers(&test_data_dir)?;
+            if let Some(unknown) = tiers.iter().find(|tier| !known.contains_key(*tier)) {
+                let names: Vec<&str> = known.keys().map(String::as_str).collect();
+                anyhow::bail!("Unknown tier: {} (tiers: {})", unknown, names.join(", "));
+            }
+        }
+
         Ok(Self {
             implementations_dir,
             test_data_dir,
             results_dir,
+            events,
+            progress: progress::Progress::new(progress),
+            profile,
+            runtime,
+            watts_per_core,
+            isolate_cases,
+            timeout,
+            heartbeat,
+            limits,
+            tiers,
+            running: contract::Running::default(),
         })
     }
 
+    fn print_profile(&self) {
+        if let Some(profile) = &self.profile {
+            let description = profile.description.as_deref().map(|text| format!(" ({})", text)).unwrap_or_default();
+            println!("Profile: {}{}", profile.name.bright_white(), description.dimmed());
+        }
+        self.print_limits();
+        if !self.tiers.is_empty() {
+            println!("Tiers: {}", self.tiers.join(", ").bright_white());
+        }
+    }
+
+    /// `format`, or the profile's, or text.
+    fn format(&self, format: Option<junit::Format>) -> junit::Format {
+        format
+            .or(self.profile.as_ref().and_then(|profile| profile.format))
+            .unwrap_or(junit::Format::Text)
+    }
+
+    fn print_limits(&self) {
+        if !self.limits.is_empty() {
+            println!("Limits: {} per container", self.limits.describe().bright_white());
+        }
+    }
+
+    /// This host, as recorded with each run.
+    fn host(&self) -> profiles::Host {
+        profiles::Host::current(self.profile.as_ref(), self.watts_per_core, self.limits)
+    }
+
     fn discover_implementations(&self) -> Result<Vec<Implementation>> {
         let mut implementations = Vec::new();
 
@@ -126,19 +1016,21 @@ impl Orchestrator {
             .into_iter()
             .filter_map(|e| e.ok())
         {
-            if entry.file_name() == "Dockerfile" {
-                if let Some(parent) = entry.path().parent() {
-                    if let Some(name) = parent.file_name() {
-                        let impl_name = name.to_string_lossy().to_string();
-                        let image_name = format!("visibility-test/{}:latest", impl_name);
-
-                        implementations.push(Implementation {
-                            name: impl_name,
-                            path: parent.to_path_buf(),
-                            image_name,
-                        });
-                    }
-                }
+            if entry.file_name() == "Dockerfile"
+                && let Some(parent) = entry.path().parent()
+                && let Some(name) = parent.file_name()
+            {
+                let impl_name = name.to_string_lossy().to_string();
+                let image_name = format!("visibility-test/{}:latest", impl_name);
+                let manifest = impl_toml::load(parent)?;
+
+                implementations.push(Implementation {
+                    language: Language::detect(&impl_name, parent, &manifest),
+                    manifest,
+                    name: impl_name,
+                    path: parent.to_path_buf(),
+                    image_name,
+                });
             }
         }
 
@@ -146,58 +1038,537 @@ impl Orchestrator {
         Ok(implementations)
     }
 
-    fn build_image(&self, impl_: &Implementation) -> Result<()> {
-        println!("Building {}...", impl_.name.bright_cyan());
-
-        let output = Command::new("docker")
-            .args(["build", "-t", &impl_.image_name, "."])
-            .current_dir(&impl_.path)
-            .output()
-            .context("Failed to execute docker build")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Build failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+    /// Test case names, from `<test_data_dir>/cases/*.json`, in run order,
+    /// narrowed to the selected tiers.
+    fn test_cases(&self) -> Result<Vec<String>> {
+        let mut cases = case_names(&self.test_data_dir.join("cases"))?;
+        if !self.tiers.is_empty() {
+            cases.retain(|name| self.case_tier(name).is_some_and(|tier| self.tiers.contains(&tier)));
         }
-
-        println!("  {} Built {}", "✓".green(), impl_.image_name.bright_white());
-        Ok(())
+        Ok(cases)
     }
 
-    fn run_tests(&self, impl_: &Implementation, test_case: Option<&str>) -> Result<TestResult> {
-        println!("Running tests for {}...", impl_.name.bright_cyan());
-
-        let start = Instant::now();
-
-        let mut cmd = Command::new("docker");
-        cmd.args([
-            "run",
-            "--rm",
-            "-v",
-            &format!("{}:/test-data:ro", self.test_data_dir.display()),
-            "-v",
-            &format!("{}:/results", self.results_dir.display()),
-            &impl_.image_name,
-        ]);
-
-        if let Some(tc) = test_case {
-            cmd.arg(tc);
+    /// The cases matching any of `patterns`, names or globs (see
+    /// [`glob_match`]), in run order. Each pattern must match a case, and
+    /// the selected tiers don't apply.
+    fn resolve_cases(&self, patterns: &[String]) -> Result<Vec<String>> {
+        let names = case_names(&self.test_data_dir.join("cases"))?;
+        if let Some(pattern) = patterns.iter().find(|pattern| !names.iter().any(|name| glob_match(pattern, name))) {
+            anyhow::bail!("No test case matches {}; list-tests shows them", pattern);
         }
+        Ok(names
+            .into_iter()
+            .filter(|name| patterns.iter().any(|pattern| glob_match(pattern, name)))
+            .collect())
+    }
 
-        let output = cmd.output().context("Failed to execute docker run")?;
+    /// `cases` with any of `tags`, when given, and none of `exclude` (see
+    /// [`case_tags`]), ignoring case. Fails if that leaves none.
+    fn filter_tags(&self, cases: Vec<String>, tags: &[String], exclude: &[String]) -> Result<Vec<String>> {
+        let cases_dir = self.test_data_dir.join("cases");
+        let has_any = |own: &[String], tags: &[String]| tags.iter().any(|tag| own.iter().any(|own| own.eq_ignore_ascii_case(tag)));
+        let mut selected = Vec::new();
+        for name in cases {
+            let path = cases_dir.join(format!("{}.json", name));
+            let case: Value = serde_json::from_str(&fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            let own = case_tags(&case);
+            if (tags.is_empty() || has_any(&own, tags)) && !has_any(&own, exclude) {
+                selected.push(name);
+            }
+        }
+        if selected.is_empty() {
+            anyhow::bail!("No test case left after filtering by tag; list-tests shows each case's tags");
+        }
+        Ok(selected)
+    }
 
-        let execution_time = start.elapsed().as_secs_f64();
-        let success = output.status.success();
+    /// Write `cases` as JUnit XML to `output`, or `results/junit.xml`, or
+    /// as TAP to `output`, or `results/results.tap`, as `format` asks.
+    fn write_junit(&self, format: junit::Format, output: Option<PathBuf>, command: &str, cases: &[junit::Case]) -> Result<()> {
+        let path = match format {
+            junit::Format::Text => return Ok(()),
+            junit::Format::Junit => {
+                let path = output.unwrap_or_else(|| self.results_dir.join("junit.xml"));
+                junit::write(&path, &format!("visibility-test-runner {}", command), cases)?;
+                path
+            }
+            junit::Format::Tap => {
+                let path = output.unwrap_or_else(|| self.results_dir.join("results.tap"));
+                tap::write(&path, cases)?;
+                path
+            }
+        };
+        println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+        Ok(())
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    /// Discovered implementations, narrowed to `name` when given.
+    fn select_implementations(&self, name: Option<&str>) -> Result<Vec<Implementation>> {
+        let implementations = self.discover_implementations()?;
+
+        Ok(match name {
+            Some(name) => {
+                let selected: Vec<Implementation> = implementations.into_iter().filter(|i| i.name == name).collect();
+                if selected.is_empty() {
+                    return Err(error::Error::ImplementationNotFound { name: name.to_string() }.into());
+                }
+                selected
+            }
+            None => implementations,
+        })
+    }
+
+    /// `implementations` in any of `languages`, when given (see
+    /// [`Language::matches`]). Fails if that leaves none.
+    fn filter_languages(&self, implementations: Vec<Implementation>, languages: &[String]) -> Result<Vec<Implementation>> {
+        if languages.is_empty() {
+            return Ok(implementations);
+        }
+        let mut known: Vec<&str> = implementations.iter().map(|impl_| impl_.language.key.as_str()).collect();
+        known.sort_unstable();
+        known.dedup();
+        let known = known.join(", ");
+        let selected: Vec<Implementation> = implementations
+            .into_iter()
+            .filter(|impl_| languages.iter().any(|language| impl_.language.matches(language)))
+            .collect();
+        if selected.is_empty() {
+            anyhow::bail!("No implementation in {}; found languages: {}", languages.join(", "), known);
+        }
+        Ok(selected)
+    }
+
+    fn build_image(&self, impl_: &Implementation) -> Result<()> {
+        if !self.progress.is_active() {
+            println!("Building {}...", impl_.name.bright_cyan());
+        }
+        self.events.emit(Event::BuildStarted {
+            implementation: &impl_.name,
+        });
+
+        let task = self.progress.task(&impl_.name, "building", None);
+        // An unreachable runtime is reported as such, not as a failed build
+        let result = self.build_labelled(impl_).map_err(|e| match e.downcast_ref::<error::Error>() {
+            Some(_) => e,
+            None => error::Error::BuildFailed {
+                implementation: impl_.name.clone(),
+                detail: format!("{:#}", e),
+            }
+            .into(),
+        });
+        drop(task);
+        self.events.emit(Event::BuildFinished {
+            implementation: &impl_.name,
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result?;
+
+        self.progress.println(format!("  {} Built {}", "✓".green(), impl_.image_name.bright_white()));
+        Ok(())
+    }
+
+    /// Build images on up to `jobs` threads. Every line of progress names
+    /// its implementation, so the interleaved output stays readable. Returns
+    /// the failed builds in the order given.
+    fn build_images<'a>(&self, implementations: &'a [Implementation], jobs: usize) -> Vec<(&'a Implementation, anyhow::Error)> {
+        let results = parallel_map(implementations, jobs, |impl_| {
+            self.build_image(impl_).inspect_err(|e| {
+                self.progress.eprintln(format!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()))
+            })
+        });
+        implementations
+            .iter()
+            .zip(results)
+            .filter_map(|(impl_, result)| Some((impl_, result.err()?)))
+            .collect()
+    }
+
+    fn build_labelled(&self, impl_: &Implementation) -> Result<()> {
+        // Record the base images the build starts from, so a later
+        // `--check-base-updates` can tell whether they have moved on
+        let fingerprint = self.base_image_fingerprint(impl_, false)?;
+        let label = format!("{}={}", BASE_IMAGES_LABEL, fingerprint);
+        self.runtime.build(&impl_.path, &impl_.image_name, &label)
+    }
+
+    /// `image@id` for every base image in the implementation's Dockerfile.
+    /// Bases missing locally are pulled; with `pull`, every base is pulled
+    /// first so the fingerprint reflects the registry's current images.
+    fn base_image_fingerprint(&self, impl_: &Implementation, pull: bool) -> Result<String> {
+        let dockerfile = impl_.path.join("Dockerfile");
+        let content = fs::read_to_string(&dockerfile)
+            .with_context(|| format!("Failed to read {}", dockerfile.display()))?;
+
+        let mut fingerprint = Vec::new();
+        for image in dockerfile_base_images(&content) {
+            if pull || self.runtime.image_id(&image)?.is_none() {
+                self.runtime.pull(&image)?;
+            }
+            let id = self.runtime.image_id(&image)?
+                .with_context(|| format!("Base image {} not found after pull", image))?;
+            fingerprint.push(format!("{}@{}", image, id));
+        }
+
+        Ok(fingerprint.join(";"))
+    }
+
+    /// Rebuild the image only if it is missing or any base image changed
+    /// since it was built. Returns whether a build happened.
+    fn rebuild_if_base_updated(&self, impl_: &Implementation) -> Result<bool> {
+        println!("Checking base images for {}...", impl_.name.bright_cyan());
+
+        let current = self.base_image_fingerprint(impl_, true)?;
+        let built_from = self.runtime.image_label(&impl_.image_name, BASE_IMAGES_LABEL)?;
+
+        if built_from.as_deref() == Some(current.as_str()) {
+            println!("  {} {} is up to date", "✓".green(), impl_.image_name.bright_white());
+            return Ok(false);
+        }
+
+        match built_from {
+            None => println!("  {} No base image record on {}", "●".bright_cyan(), impl_.image_name),
+            Some(_) => println!("  {} Base image updated", "●".bright_cyan()),
+        }
+        self.build_image(impl_)?;
+        Ok(true)
+    }
+
+    /// Cases with TLE set references resolved, staged under
+    /// `results/.materialized/cases`, or `None` when no case needs it.
+    fn materialize_cases(&self) -> Result<Option<PathBuf>> {
+        tle_sets::materialize(&self.test_data_dir, &self.results_dir.join(".materialized"))
+    }
+
+    /// The cases `impl_` runs: materialized, then planned against the
+    /// features it declares and staged under
+    /// `results/.materialized/implementations/<name>` if any needs
+    /// downgrading or leaving out. Cases outside the selected `cases`, or
+    /// without a selection, outside the selected tiers, are left out too. A
+    /// single case is passed to the container instead.
+    fn stage_cases(
+        &self,
+        impl_: &Implementation,
+        features: &BTreeSet<capabilities::Feature>,
+        cases: Option<&[String]>,
+    ) -> Result<capabilities::Staged> {
+        let materialized = self.materialize_cases()?;
+        let cases_dir = materialized.clone().unwrap_or_else(|| self.test_data_dir.join("cases"));
+        let staging_dir = self.results_dir.join(".materialized").join("implementations").join(&impl_.name);
+        let mut staged = capabilities::stage(&cases_dir, &staging_dir, features)?;
+        if (cases.is_none() && self.tiers.is_empty()) || single_case(cases).is_some() {
+            staged.cases_dir = staged.cases_dir.or(materialized);
+            return Ok(staged);
+        }
+
+        let excluded: BTreeSet<String> = case_names(&cases_dir)?
+            .into_iter()
+            .filter(|name| match cases {
+                Some(cases) => !cases.contains(name),
+                None => !self.case_tier(name).is_some_and(|tier| self.tiers.contains(&tier)),
+            })
+            .collect();
+        staged.downgraded.retain(|(name, _)| !excluded.contains(name));
+        staged.unsupported.retain(|(name, _)| !excluded.contains(name));
+        let staged_dir = staging_dir.join("cases");
+        match &staged.cases_dir {
+            // Already a copy of our own
+            Some(dir) => {
+                for name in &excluded {
+                    fs::remove_file(dir.join(format!("{}.json", name))).ok();
+                }
+            }
+            None => {
+                if staged_dir.exists() {
+                    fs::remove_dir_all(&staged_dir).with_context(|| format!("Failed to clear {}", staged_dir.display()))?;
+                }
+                fs::create_dir_all(&staged_dir).with_context(|| format!("Failed to create {}", staged_dir.display()))?;
+                for name in case_names(&cases_dir)?.iter().filter(|name| !excluded.contains(*name)) {
+                    let file = format!("{}.json", name);
+                    fs::copy(cases_dir.join(&file), staged_dir.join(&file))
+                        .with_context(|| format!("Failed to copy {}", file))?;
+                }
+            }
+        }
+        staged.cases_dir = Some(staged_dir);
+        Ok(staged)
+    }
+
+    /// The difficulty tier (`metadata.difficulty`) of case `name`.
+    fn case_tier(&self, name: &str) -> Option<String> {
+        let path = self.test_data_dir.join("cases").join(format!("{}.json", name));
+        let case: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        Some(case.pointer("/metadata/difficulty")?.as_str()?.to_string())
+    }
+
+    /// Run `impl_` on the selected `cases`, or every case.
+    fn run_tests(&self, impl_: &Implementation, cases: Option<&[String]>) -> Result<TestResult> {
+        self.run_container(impl_, cases, None)
+    }
+
+    /// How long `impl_`'s containers may run: its `impl.toml` timeout, or
+    /// `--timeout`.
+    fn timeout_for(&self, impl_: &Implementation) -> Option<Duration> {
+        impl_.manifest.timeout().or(self.timeout)
+    }
+
+    /// How long `impl_`'s containers may go without a heartbeat: its
+    /// `impl.toml` heartbeat, or `--heartbeat`.
+    fn heartbeat_for(&self, impl_: &Implementation) -> Option<Duration> {
+        impl_.manifest.heartbeat().or(self.heartbeat)
+    }
+
+    /// The error for `result` of `impl_`, if it was killed at its timeout or
+    /// for going silent.
+    fn killed_error(&self, impl_: &Implementation, result: &TestResult, cases: Option<&[String]>) -> Option<error::Error> {
+        let implementation = result.implementation.clone();
+        let test_case = single_case(cases).map(str::to_string);
+        if result.hung {
+            Some(error::Error::Hung {
+                implementation,
+                test_case,
+                seconds: self.heartbeat_for(impl_).unwrap_or_default().as_secs(),
+            })
+        } else {
+            result.timed_out.then(|| error::Error::Timeout {
+                implementation,
+                test_case,
+                seconds: self.timeout_for(impl_).unwrap_or_default().as_secs(),
+            })
+        }
+    }
+
+    /// Run each implementation on up to `parallel` containers at a time.
+    /// Results are in the order given. With `fail_fast`, no container starts
+    /// after one fails, and the implementations left are `None`.
+    fn run_parallel(
+        &self,
+        implementations: &[Implementation],
+        cases: Option<&[String]>,
+        parallel: usize,
+        fail_fast: bool,
+    ) -> Result<Vec<Option<Result<TestResult>>>> {
+        // Stage the cases once up front, so concurrent runs find them in place
+        self.materialize_cases()?;
+        let failed = AtomicBool::new(false);
+        Ok(parallel_map(implementations, parallel, |impl_| {
+            if fail_fast && failed.load(Ordering::Relaxed) {
+                return None;
+            }
+            let outcome = self.run_tests(impl_, cases);
+            if !outcome.as_ref().is_ok_and(|result| result.success) {
+                failed.store(true, Ordering::Relaxed);
+            }
+            Some(outcome)
+        }))
+    }
+
+    /// Run an implementation's container on the selected `cases`, or every
+    /// case, optionally pinned to `cpuset` cores.
+    fn run_container(&self, impl_: &Implementation, cases: Option<&[String]>, cpuset: Option<&str>) -> Result<TestResult> {
+        if !self.progress.is_active() {
+            match cases {
+                Some(cases) => println!("Running tests for {} ({})...", impl_.name.bright_cyan(), cases.join(", ")),
+                None => println!("Running tests for {}...", impl_.name.bright_cyan()),
+            }
+        }
+        let test_case = single_case(cases);
+        let features = capabilities::declared(&impl_.path);
+        let staged = self.stage_cases(impl_, &features, cases)?;
+        let in_run = |name: &str| cases.is_none_or(|cases| cases.iter().any(|case| case == name));
+        for (name, features) in staged.downgraded.iter().filter(|(name, _)| in_run(name)) {
+            self.progress.println(format!("  {} {} runs without {}", "●".bright_cyan(), name, capabilities::names(features)));
+        }
+        let unsupported: Vec<String> = staged
+            .unsupported
+            .iter()
+            .filter(|(name, _)| in_run(name))
+            .map(|(name, features)| {
+                self.progress.println(format!("  {} {} unsupported (needs {})", "○".dimmed(), name, capabilities::names(features)));
+                name.clone()
+            })
+            .collect();
+        if cases.is_some_and(|cases| cases.iter().all(|case| unsupported.contains(case))) {
+            return Ok(TestResult {
+                implementation: impl_.name.clone(),
+                success: true,
+                execution_time: 0.0,
+                stdout: String::new(),
+                stderr: String::new(),
+                result_files: Vec::new(),
+                unsupported,
+                cpu_seconds: None,
+                peak_memory: None,
+                image_id: None,
+                timed_out: false,
+                hung: false,
+                quarantined: Vec::new(),
+            });
+        }
+
+        self.events.emit(Event::CaseStarted {
+            implementation: &impl_.name,
+            test_case,
+        });
+
+        let start = Instant::now();
+        let started_at = SystemTime::now();
+
+        let cases_dir = staged.cases_dir.clone().unwrap_or_else(|| self.test_data_dir.join("cases"));
+        let payload = self
+            .isolate_cases
+            .then(|| payload::Payload::create(&self.results_dir, &impl_.name, &cases_dir, cases))
+            .transpose()?;
+        let mut mounts = match &payload {
+            Some(payload) => payload.mounts(&self.test_data_dir),
+            None => {
+                let mut mounts = vec![(self.test_data_dir.clone(), "/test-data", true)];
+                if let Some(cases) = &staged.cases_dir {
+                    mounts.push((cases.clone(), "/test-data/cases", true));
+                }
+                mounts
+            }
+        };
+        mounts.push((self.results_dir.clone(), "/results", false));
+        let mut env = Vec::new();
+        if let Some(version) = capabilities::schema_version(&self.test_data_dir)? {
+            env.push(format!("{}={}", capabilities::SCHEMA_VERSION_VAR, version));
+        }
+        let enabled: Vec<&str> = features.iter().map(|feature| feature.name()).collect();
+        env.push(format!("{}={}", capabilities::FEATURES_VAR, enabled.join(",")));
+        // Unique per container, since `all --jobs` runs several at once
+        let cidfile = std::env::temp_dir().join(format!(
+            "visibility-test-runner-{}-{}-{}.cid",
+            std::process::id(),
+            impl_.name,
+            test_case.unwrap_or("all")
+        ));
+        fs::remove_file(&cidfile).ok();
+        let mut spec = runtime::RunSpec {
+            image: &impl_.image_name,
+            args: test_case.into_iter().collect(),
+            mounts,
+            env,
+            cpuset,
+            cidfile: Some(&cidfile),
+            timeout: self.timeout_for(impl_),
+            heartbeat: None,
+            limits: self.limits,
+        };
+        let beacon = heartbeat::Beacon::attach(self, impl_, &mut spec)?;
+        let monitored = beacon.is_some();
+
+        let selected = match cases {
+            Some(cases) => cases.iter().filter(|case| !unsupported.contains(case)).cloned().collect(),
+            None => case_names(&cases_dir)?,
+        };
+        let implementations: Vec<String> = self.discover_implementations()?.into_iter().map(|impl_| impl_.name).collect();
+        let before = contract::Snapshot::take(&self.results_dir)?;
+        let container = self.running.start(&impl_.name);
+
+        let meter = usage::Meter::start(self.runtime, cidfile.clone());
+        // Cases left out as unsupported count as done
+        let task = self.progress.task(&impl_.name, "running", Some(unsupported.len() + selected.len()));
+        task.set_position(unsupported.len());
+        let output = if self.progress.is_active() {
+            // Follow the result files as the container writes them
+            let done = AtomicBool::new(false);
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    while !done.load(Ordering::Relaxed) {
+                        if let Ok(files) = self.results_since(&impl_.name, started_at) {
+                            let cases: BTreeSet<String> = files
+                                .iter()
+                                .filter_map(|path| result_test_case(&path.file_name()?.to_string_lossy(), &impl_.name))
+                                .filter(|case| selected.contains(case))
+                                .collect();
+                            task.set_position(unsupported.len() + cases.len());
+                        }
+                        thread::sleep(Duration::from_millis(250));
+                    }
+                });
+                let output = stream::run(self, impl_, spec, &self.results_dir);
+                done.store(true, Ordering::Relaxed);
+                output
+            })
+        } else {
+            stream::run(self, impl_, spec, &self.results_dir)
+        };
+        drop(task);
+        drop(beacon);
+        let reading = meter.finish();
+        let concurrent = self.running.finish(container);
+        let (output, unfinished) = output?;
+        for case in &unfinished {
+            self.progress.println(format!(
+                "  {} {} streamed windows of {} without finishing it",
+                "⚠".yellow(),
+                impl_.name.bright_white(),
+                case
+            ));
+        }
+
+        let quarantined = contract::enforce(
+            &self.results_dir,
+            &before,
+            &contract::Allowed {
+                implementation: &impl_.name,
+                cases: &selected,
+                implementations: &implementations,
+                concurrent: &concurrent,
+            },
+        )?;
+        for path in &quarantined {
+            self.progress.println(format!(
+                "  {} {} wrote {} outside its output contract; moved to {}",
+                "⚠".yellow(),
+                impl_.name.bright_white(),
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                Path::new(".quarantine").join(&impl_.name).display()
+            ));
+        }
+
+        let execution_time = start.elapsed().as_secs_f64();
+        let success = output.success();
+        self.events.emit(Event::CaseFinished {
+            implementation: &impl_.name,
+            test_case,
+            success,
+            execution_time,
+        });
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
         if success {
-            println!("  {} Tests completed in {}", "✓".green(), format!("{:.2}s", execution_time).bright_white());
+            self.progress.println(format!(
+                "  {} Tests completed for {} in {}{}",
+                "✓".green(),
+                impl_.name.bright_white(),
+                format!("{:.2}s", execution_time).bright_white(),
+                self.usage_note(reading.cpu_seconds, reading.peak_memory)
+            ));
+        } else if output.hung {
+            self.progress.println(format!(
+                "  {} Tests hung for {}: no heartbeat for {}s, killed after {}",
+                "✗".red(),
+                impl_.name.bright_white(),
+                self.heartbeat_for(impl_).unwrap_or_default().as_secs(),
+                format!("{:.0}s", execution_time).bright_white()
+            ));
+        } else if output.timed_out {
+            self.progress.println(format!(
+                "  {} Tests timed out for {} after {}{}",
+                "✗".red(),
+                impl_.name.bright_white(),
+                format!("{:.0}s", execution_time).bright_white(),
+                // Beating to the end, so slow rather than hung
+                if monitored { ", still alive" } else { "" }
+            ));
         } else {
-            println!("  {} Tests failed", "✗".red());
+            let status = output.exit_code.map_or("killed".to_string(), |code| format!("exit {}", code));
+            self.progress.println(format!("  {} Tests failed for {} ({})", "✗".red(), impl_.name.bright_white(), status));
         }
 
         Ok(TestResult {
@@ -206,13 +1577,20 @@ impl Orchestrator {
             execution_time,
             stdout,
             stderr,
+            result_files: self.results_since(&impl_.name, started_at)?,
+            unsupported,
+            cpu_seconds: reading.cpu_seconds,
+            peak_memory: reading.peak_memory,
+            image_id: self.runtime.image_id(&impl_.image_name).ok().flatten(),
+            timed_out: output.timed_out,
+            hung: output.hung,
+            quarantined,
         })
     }
 
-    fn collect_results(&self, impl_: &Implementation) -> Result<Vec<PathBuf>> {
+    fn collect_results(&self, impl_name: &str) -> Result<Vec<PathBuf>> {
         use std::collections::HashMap;
 
-        let pattern = format!("{}_", impl_.name);
         let mut results_by_test_case: HashMap<String, PathBuf> = HashMap::new();
 
         if !self.results_dir.exists() {
@@ -223,54 +1601,22 @@ impl Orchestrator {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_file() {
-                if let Some(name) = path.file_name() {
-                    let name_str = name.to_string_lossy();
-                    if name_str.starts_with(&pattern) && name_str.ends_with(".json") {
-                        // Parse filename formats:
-                        // Old: {impl}_{testcase}.json
-                        // New: {impl}_{testcase}_{timestamp}.json
-                        if let Some(rest) = name_str.strip_prefix(&pattern) {
-                            let rest = rest.strip_suffix(".json").unwrap();
-
-                            // Try to parse as new format (with timestamp)
-                            // Timestamp format: YYYYMMDD_HHMMSS
-                            let test_case = if rest.contains('_') {
-                                // Check if last part looks like a timestamp
-                                let parts: Vec<&str> = rest.split('_').collect();
-                                if parts.len() >= 2 {
-                                    let last_part = parts[parts.len() - 1];
-                                    let second_last = parts[parts.len() - 2];
-                                    // If last two parts look like timestamp (YYYYMMDD_HHMMSS)
-                                    if last_part.len() == 6 && second_last.len() == 8
-                                       && last_part.chars().all(|c| c.is_numeric())
-                                       && second_last.chars().all(|c| c.is_numeric()) {
-                                        // New format with timestamp - extract test case
-                                        parts[..parts.len() - 2].join("_")
-                                    } else {
-                                        // Old format without timestamp
-                                        rest.to_string()
-                                    }
-                                } else {
-                                    rest.to_string()
-                                }
-                            } else {
-                                rest.to_string()
-                            };
-
-                            // Keep only the most recent result for each test case
-                            results_by_test_case
-                                .entry(test_case.clone())
-                                .and_modify(|existing| {
-                                    // Keep the newer file (lexicographically later)
-                                    if name_str > existing.file_name().unwrap().to_string_lossy() {
-                                        *existing = path.clone();
-                                    }
-                                })
-                                .or_insert_with(|| path.clone());
+            if path.is_file()
+                && let Some(name) = path.file_name()
+                && let Some(test_case) = result_test_case(&name.to_string_lossy(), impl_name)
+            {
+                let name_str = name.to_string_lossy();
+
+                // Keep only the most recent result for each test case
+                results_by_test_case
+                    .entry(test_case)
+                    .and_modify(|existing| {
+                        // Keep the newer file (lexicographically later)
+                        if name_str > existing.file_name().unwrap().to_string_lossy() {
+                            *existing = path.clone();
                         }
-                    }
-                }
+                    })
+                    .or_insert_with(|| path.clone());
             }
         }
 
@@ -279,68 +1625,302 @@ impl Orchestrator {
         Ok(results)
     }
 
-    fn validate_results(&self, impl_name: &str) -> Result<()> {
-        println!("Validating results for {}...", impl_name.bright_cyan());
+    /// The latest result file of `impl_name` for `test_case`.
+    fn latest_result(&self, impl_name: &str, test_case: &str) -> Result<PathBuf> {
+        self.collect_results(impl_name)?
+            .into_iter()
+            .find(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                result_test_case(&name, impl_name).as_deref() == Some(test_case)
+            })
+            .with_context(|| format!("No results for {} on {}", impl_name, test_case))
+    }
+
+    /// `" (cpu 1.23s ≈ 6.15 J, peak 45.2 MiB)"` for a container's CPU time
+    /// and memory, as far as measured.
+    fn usage_note(&self, cpu_seconds: Option<f64>, peak_memory: Option<u64>) -> String {
+        let cpu = cpu_seconds
+            .map(|seconds| format!("cpu {:.2}s ≈ {:.2} J", seconds, usage::joules(seconds, self.watts_per_core)));
+        let memory = peak_memory.map(|bytes| format!("peak {}", usage::format_memory(bytes)));
+        let parts: Vec<String> = cpu.into_iter().chain(memory).collect();
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!(" ({})", parts.join(", ")).dimmed().to_string()
+    }
+
+    /// Result files for `impl_name` modified at or after `since`, i.e. written
+    /// by a run that started then.
+    fn results_since(&self, impl_name: &str, since: SystemTime) -> Result<Vec<PathBuf>> {
+        // Compare at whole seconds; some filesystems store coarse mtimes
+        let since = UNIX_EPOCH
+            + Duration::from_secs(since.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
 
-        let reference_dir = self.test_data_dir.join("reference-results");
-        let results = self.collect_results(&Implementation {
-            name: impl_name.to_string(),
-            path: PathBuf::new(),
-            image_name: String::new(),
-        })?;
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.results_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_file()
+                && result_test_case(&entry.file_name().to_string_lossy(), impl_name).is_some()
+                && entry.metadata()?.modified()? >= since
+            {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Result files beyond the newest `keep` per (implementation, test
+    /// case), oldest first. Only files named as results of a discovered
+    /// implementation or imported tool are considered, each as the one with
+    /// the longest matching name, so `rust` never claims `rust_fast`'s.
+    fn stale_results(&self, keep: usize) -> Result<Vec<PathBuf>> {
+        use std::collections::HashMap;
+
+        let mut names: Vec<String> = self
+            .discover_implementations()?
+            .into_iter()
+            .chain(truth::external_tools(&self.test_data_dir)?)
+            .map(|impl_| impl_.name)
+            .collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+        let mut files_by_pair: HashMap<(&str, String), Vec<PathBuf>> = HashMap::new();
+        for entry in fs::read_dir(&self.results_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if let Some((name, test_case)) = names
+                .iter()
+                .find_map(|name| Some((name.as_str(), result_test_case(&file_name, name)?)))
+            {
+                files_by_pair.entry((name, test_case)).or_default().push(path);
+            }
+        }
 
-        if results.is_empty() {
+        let mut stale = Vec::new();
+        for mut files in files_by_pair.into_values() {
+            // Newest last, matching collect_results' lexicographic ordering
+            files.sort();
+            let excess = files.len().saturating_sub(keep);
+            stale.extend(files.into_iter().take(excess));
+        }
+        stale.sort();
+        Ok(stale)
+    }
+
+    /// Delete all but the newest `keep` result files per (implementation, test case).
+    fn prune_results(&self, keep: usize) -> Result<Vec<PathBuf>> {
+        anyhow::ensure!(keep > 0, "Keeping 0 result files would delete every result; keep at least 1");
+        let stale = self.stale_results(keep)?;
+        for path in &stale {
+            fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(stale)
+    }
+
+    /// Prune after a run when the profile sets `keep`. A failure is only
+    /// reported; the run itself succeeded.
+    fn apply_retention(&self) {
+        let Some(keep) = self.profile.as_ref().and_then(|profile| profile.keep) else {
+            return;
+        };
+        match self.prune_results(keep) {
+            Ok(removed) if !removed.is_empty() => println!(
+                "  {} Pruned {} old result file(s), keeping the newest {} per case",
+                "✓".green(),
+                removed.len(),
+                keep
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("  {} Error pruning results: {}", "⚠".yellow(), e.to_string().yellow()),
+        }
+    }
+
+    fn validate_results(&self, impl_name: &str) -> Result<ValidationSummary> {
+        self.validate_against(
+            impl_name,
+            &Reference::Skyfield(self.test_data_dir.join("reference-results")),
+            &tolerance::Overrides::default(),
+        )
+    }
+
+    fn validate_against(&self, impl_name: &str, reference: &Reference, overrides: &tolerance::Overrides) -> Result<ValidationSummary> {
+        println!("Validating results for {} against {}...", impl_name.bright_cyan(), reference.name());
+
+        let results = self.collect_results(impl_name)?;
+        let cases_dir = self.materialize_cases()?.unwrap_or_else(|| self.test_data_dir.join("cases"));
+
+        // Cases left out of runs for lack of a feature, unless an earlier
+        // run produced results for them anyway
+        let mut unsupported = Vec::new();
+        let impl_ = self.discover_implementations()?.into_iter().find(|i| i.name == impl_name);
+        let manifest = impl_.as_ref().map(|impl_| impl_.manifest.clone()).unwrap_or_default();
+        if let Some(impl_) = impl_ {
+            let has_result = |case: &str| {
+                results.iter().any(|path| {
+                    result_test_case(&path.file_name().unwrap_or_default().to_string_lossy(), impl_name).as_deref() == Some(case)
+                })
+            };
+            unsupported = capabilities::unsupported(&cases_dir, &capabilities::declared(&impl_.path))?;
+            unsupported.retain(|(case, _)| !has_result(case));
+        }
+
+        if results.is_empty() && unsupported.is_empty() {
             println!("  {} No results found for {}", "⚠".yellow(), impl_name);
-            return Ok(());
+            return Ok(ValidationSummary {
+                implementation: impl_name.to_string(),
+                matched: 0,
+                total: 0,
+                warnings: 0,
+                cases: Vec::new(),
+                inconsistencies: Vec::new(),
+            });
         }
 
         let mut match_count = 0;
+        let mut expected_count = 0;
+        let mut warning_count = 0;
+        let mut cases = Vec::new();
         let total_count = results.len();
 
         for result_file in &results {
-            let result_data: VisibilityResult =
-                serde_json::from_str(&fs::read_to_string(result_file)?)?;
-
-            // Find corresponding reference file
-            let ref_file_name = format!(
-                "python-skyfield_{}.json",
-                result_data.test_case
-            );
-            let ref_file = reference_dir.join(&ref_file_name);
-
-            if !ref_file.exists() {
+            let result_data = parse_result(result_file)?;
+            warning_count += result_data.warnings.len();
+            let (tolerances, regime) = tolerance::for_case(&cases_dir, &result_data.test_case, overrides);
+
+            let Some(ref_data) = reference.load(&result_data.test_case)? else {
+                cases.push(CaseVerdict {
+                    test_case: result_data.test_case.clone(),
+                    verdict: Verdict::NoReference,
+                    result_windows: result_data.visibility_windows.len(),
+                    reference_windows: None,
+                    warnings: result_data.warnings.len(),
+                    regime,
+                    deltas: Vec::new(),
+                    unmatched: Vec::new(),
+                    expected_failure: false,
+                });
+                self.events.emit(Event::ValidationVerdict {
+                    implementation: impl_name,
+                    test_case: &result_data.test_case,
+                    verdict: Verdict::NoReference,
+                    result_windows: result_data.visibility_windows.len(),
+                    reference_windows: None,
+                });
                 println!(
                     "  {} No reference file for test case: {}",
                     "⚠".yellow(),
                     result_data.test_case.bright_white()
                 );
+                print_warnings(&result_data.warnings);
                 continue;
-            }
-
-            let ref_data: VisibilityResult =
-                serde_json::from_str(&fs::read_to_string(&ref_file)?)?;
+            };
 
-            // Compare window counts
             let result_windows = result_data.visibility_windows.len();
             let ref_windows = ref_data.visibility_windows.len();
-
-            if result_windows == ref_windows {
+            let comparison = tolerance::compare(&result_data.visibility_windows, &ref_data.visibility_windows, &tolerances);
+            let verdict = if comparison.is_match() { Verdict::Match } else { Verdict::Mismatch };
+            let listed = manifest.expects_failure(&result_data.test_case);
+            let expected_failure = listed && verdict == Verdict::Mismatch;
+            expected_count += usize::from(expected_failure);
+            cases.push(CaseVerdict {
+                test_case: result_data.test_case.clone(),
+                verdict,
+                result_windows,
+                reference_windows: Some(ref_windows),
+                warnings: result_data.warnings.len(),
+                regime,
+                deltas: comparison.deltas.clone(),
+                unmatched: comparison.unmatched.clone(),
+                expected_failure,
+            });
+            self.events.emit(Event::ValidationVerdict {
+                implementation: impl_name,
+                test_case: &result_data.test_case,
+                verdict,
+                result_windows,
+                reference_windows: Some(ref_windows),
+            });
+
+            let mark = if expected_failure { "✗".yellow() } else { "✗".red() };
+            let expected = if expected_failure { " (expected failure)".dimmed().to_string() } else { String::new() };
+            if verdict == Verdict::Match {
                 println!(
-                    "  {} {} - {} window(s)",
+                    "  {} {} - {} window(s){}",
                     "✓".green(),
                     result_data.test_case.bright_white(),
-                    result_windows
+                    result_windows,
+                    if listed { " (expected to fail; remove it from impl.toml)".yellow().to_string() } else { String::new() }
                 );
                 match_count += 1;
             } else {
-                println!(
-                    "  {} {} - {} window(s) vs {} reference",
-                    "✗".red(),
-                    result_data.test_case.bright_white(),
-                    result_windows.to_string().yellow(),
-                    ref_windows.to_string().green()
-                );
+                if result_windows == ref_windows {
+                    println!(
+                        "  {} {} - {} window(s), {} field(s) outside tolerance{}",
+                        mark,
+                        result_data.test_case.bright_white(),
+                        result_windows,
+                        comparison.deltas.len().to_string().yellow(),
+                        expected
+                    );
+                } else {
+                    println!(
+                        "  {} {} - {} window(s) vs {} reference, {} field(s) outside tolerance in paired windows{}",
+                        mark,
+                        result_data.test_case.bright_white(),
+                        result_windows.to_string().yellow(),
+                        ref_windows.to_string().green(),
+                        comparison.deltas.len().to_string().yellow(),
+                        expected
+                    );
+                }
+                for delta in &comparison.deltas {
+                    println!(
+                        "      {} {}: {:+.1}{} (tolerance ±{}{})",
+                        delta.windows(),
+                        delta.field,
+                        delta.delta,
+                        delta.unit(),
+                        delta.tolerance,
+                        delta.unit()
+                    );
+                }
+                for unmatched in &comparison.unmatched {
+                    println!("      {}", unmatched);
+                }
             }
+            print_warnings(&result_data.warnings);
+        }
+
+        for (case, features) in &unsupported {
+            cases.push(CaseVerdict {
+                test_case: case.clone(),
+                verdict: Verdict::Unsupported,
+                result_windows: 0,
+                reference_windows: None,
+                warnings: 0,
+                regime: None,
+                deltas: Vec::new(),
+                unmatched: Vec::new(),
+                expected_failure: false,
+            });
+            self.events.emit(Event::ValidationVerdict {
+                implementation: impl_name,
+                test_case: case,
+                verdict: Verdict::Unsupported,
+                result_windows: 0,
+                reference_windows: None,
+            });
+            println!(
+                "  {} {} - unsupported (needs {})",
+                "○".dimmed(),
+                case.bright_white(),
+                capabilities::names(features)
+            );
         }
 
         println!();
@@ -350,118 +1930,895 @@ impl Orchestrator {
         } else {
             println!("{}", validation_msg.yellow());
         }
+        if expected_count > 0 {
+            println!("{}", format!("Expected failures: {} mismatch(es) listed in impl.toml", expected_count).dimmed());
+        }
+        if !unsupported.is_empty() {
+            println!("{}", format!("Unsupported: {} test case(s) not run", unsupported.len()).dimmed());
+        }
+        if warning_count > 0 {
+            println!("{}", format!("Warnings: {} across all results", warning_count).yellow());
+        }
 
-        Ok(())
+        let inconsistencies = consistency::check(self, impl_name)?;
+        for issue in &inconsistencies {
+            println!(
+                "  {} {} inside {}: {}",
+                "✗".red(),
+                issue.inner.bright_white(),
+                issue.outer.bright_white(),
+                issue.message
+            );
+        }
+        if !inconsistencies.is_empty() {
+            println!(
+                "{}",
+                format!("Consistency: {} nested case pair(s) disagree", inconsistencies.len()).yellow()
+            );
+        }
+
+        Ok(ValidationSummary {
+            implementation: impl_name.to_string(),
+            matched: match_count,
+            total: total_count,
+            warnings: warning_count,
+            cases,
+            inconsistencies,
+        })
     }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let orchestrator = Orchestrator::new()?;
+/// Print the non-fatal warnings an implementation attached to a result.
+fn print_warnings(warnings: &[ResultWarning]) {
+    for warning in warnings {
+        println!("    {} [{}] {}", "⚠".yellow(), warning.code.dimmed(), warning.message);
+    }
+}
 
-    match cli.command {
-        Commands::Discover => {
-            let implementations = orchestrator.discover_implementations()?;
-            println!("{} {} implementation(s):", "Discovered".bold().bright_blue(), implementations.len().to_string().bright_white());
-            for impl_ in &implementations {
-                println!("  {} {}", "●".bright_cyan(), impl_.name.bright_white());
-            }
+/// Read and parse the result file at `path`, failing with a schema
+/// violation if it isn't a result.
+pub(crate) fn parse_result(path: &Path) -> Result<VisibilityResult> {
+    serde_json::from_str(&read_result(path)?).map_err(|e| {
+        error::Error::SchemaViolation {
+            file: path.to_path_buf(),
+            detail: e.to_string(),
         }
+        .into()
+    })
+}
 
-        Commands::Build { implementation } => {
-            let implementations = orchestrator.discover_implementations()?;
+/// Read a result file, decompressing `.json.gz` results.
+pub(crate) fn read_result(path: &Path) -> Result<String> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut content = String::new();
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        GzDecoder::new(file)
+            .read_to_string(&mut content)
+            .with_context(|| format!("Failed to decompress {}", path.display()))?;
+    } else {
+        BufReader::new(file).read_to_string(&mut content)?;
+    }
 
-            if let Some(name) = implementation {
-                let impl_ = implementations
-                    .iter()
-                    .find(|i| i.name == name)
-                    .context("Implementation not found")?;
-                orchestrator.build_image(impl_)?;
-            } else {
-                for impl_ in &implementations {
-                    if let Err(e) = orchestrator.build_image(impl_) {
-                        eprintln!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
-                    }
-                }
+    Ok(content)
+}
+
+/// The case of a selection of exactly one, which is passed to the container
+/// by name rather than staged.
+fn single_case(cases: Option<&[String]>) -> Option<&str> {
+    match cases {
+        Some([case]) => Some(case),
+        _ => None,
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of
+/// characters and `?` any one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    // Where the last `*` was, and the name position it's matched up to
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
             }
+            _ => match star {
+                // Let the `*` take one more character
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
         }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
 
-        Commands::Run {
-            implementation,
-            test_case,
-            build,
-        } => {
-            let implementations = orchestrator.discover_implementations()?;
+/// A case's `tags`, followed by its descriptive `metadata.tags`.
+fn case_tags(case: &Value) -> Vec<String> {
+    let mut tags: Vec<String> = ["/tags", "/metadata/tags"]
+        .iter()
+        .filter_map(|pointer| case.pointer(pointer).and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    let mut seen = BTreeSet::new();
+    tags.retain(|tag| seen.insert(tag.to_lowercase()));
+    tags
+}
 
-            let impls_to_run: Vec<_> = if let Some(name) = implementation {
-                implementations
-                    .iter()
-                    .filter(|i| i.name == name)
-                    .cloned()
-                    .collect()
-            } else {
-                implementations
-            };
+/// Names of the `*.json` cases in `cases_dir`, sorted.
+fn case_names(cases_dir: &Path) -> Result<Vec<String>> {
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && let Some(stem) = path.file_stem()
+        {
+            cases.push(stem.to_string_lossy().to_string());
+        }
+    }
+    cases.sort();
+    Ok(cases)
+}
 
-            if build {
-                println!("\n{}", "Building images...".bold().bright_blue());
-                println!("{}", "=".repeat(50).dimmed());
-                for impl_ in &impls_to_run {
-                    if let Err(e) = orchestrator.build_image(impl_) {
-                        eprintln!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
-                    }
-                }
-                println!();
-            }
+/// Extract the test case name from a result file name belonging to `impl_name`.
+///
+/// Parses filename formats (either may carry a `.gz` suffix):
+/// Old: {impl}_{testcase}.json
+/// New: {impl}_{testcase}_{timestamp}.json
+pub(crate) fn result_test_case(file_name: &str, impl_name: &str) -> Option<String> {
+    let rest = file_name.strip_prefix(impl_name)?.strip_prefix('_')?;
+    let rest = rest
+        .strip_suffix(".json.gz")
+        .or_else(|| rest.strip_suffix(".json"))?;
+
+    // Try to parse as new format (with timestamp)
+    // Timestamp format: YYYYMMDD_HHMMSS
+    let parts: Vec<&str> = rest.split('_').collect();
+    if parts.len() > 2 {
+        let last_part = parts[parts.len() - 1];
+        let second_last = parts[parts.len() - 2];
+        // If last two parts look like timestamp (YYYYMMDD_HHMMSS)
+        if last_part.len() == 6
+            && second_last.len() == 8
+            && last_part.chars().all(|c| c.is_numeric())
+            && second_last.chars().all(|c| c.is_numeric())
+        {
+            return Some(parts[..parts.len() - 2].join("_"));
+        }
+    }
 
-            println!("\n{}", "Running tests...".bold().bright_blue());
-            println!("{}", "=".repeat(50).dimmed());
-            let mut results = Vec::new();
-            for impl_ in &impls_to_run {
-                match orchestrator.run_tests(impl_, test_case.as_deref()) {
-                    Ok(result) => results.push(result),
-                    Err(e) => eprintln!("  {} Error running {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()),
-                }
-            }
+    Some(rest.to_string())
+}
 
-            println!();
-            println!("{}", "Summary:".bold().bright_green());
-            println!("{}", "=".repeat(50).dimmed());
-            for result in &results {
-                if result.success {
-                    println!(
-                        "{} {} - {}",
-                        "✓".green(),
-                        result.implementation.bright_white(),
-                        format!("{:.2}s", result.execution_time).bright_white()
-                    );
-                } else {
-                    println!(
-                        "{} {} - {}",
-                        "✗".red(),
-                        result.implementation.bright_white(),
-                        format!("{:.2}s", result.execution_time).dimmed()
-                    );
-                }
-            }
+/// Image label recording the base image IDs an implementation was built from.
+const BASE_IMAGES_LABEL: &str = "org.visibility-test.base-images";
+
+/// Base images named by `FROM` lines, skipping earlier build stages and
+/// `scratch`.
+fn dockerfile_base_images(content: &str) -> Vec<String> {
+    let mut stages = Vec::new();
+    let mut images = Vec::new();
+
+    for line in content.lines() {
+        let mut words = line.split_whitespace();
+        if !words.next().is_some_and(|w| w.eq_ignore_ascii_case("FROM")) {
+            continue;
         }
+        let mut words = words.skip_while(|w| w.starts_with("--"));
+        let Some(image) = words.next() else {
+            continue;
+        };
 
-        Commands::Validate { implementation } => {
-            if let Some(name) = implementation {
-                orchestrator.validate_results(&name)?;
-            } else {
-                let implementations = orchestrator.discover_implementations()?;
-                for impl_ in &implementations {
-                    orchestrator.validate_results(&impl_.name)?;
-                    println!();
+        if image != "scratch" && !stages.contains(&image.to_lowercase()) && !images.iter().any(|i| i == image) {
+            images.push(image.to_string());
+        }
+        if words.next().is_some_and(|w| w.eq_ignore_ascii_case("AS"))
+            && let Some(alias) = words.next()
+        {
+            stages.push(alias.to_lowercase());
+        }
+    }
+
+    images
+}
+
+/// `f` applied to every item on up to `threads` threads, each taking the
+/// next unstarted item. Results are in the order of `items`.
+fn parallel_map<T: Sync, R: Send>(items: &[T], threads: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results.lock().unwrap().push((index, result));
                 }
-            }
+            });
         }
+    });
 
-        Commands::All { test_case } => {
-            let implementations = orchestrator.discover_implementations()?;
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
 
-            println!("\n{}", "Satellite Visibility Test Suite".bold().bright_magenta());
+/// Report a failure to file run artifacts without failing the run itself.
+fn record_artifacts(result: Result<()>, impl_name: &str) {
+    if let Err(e) = result {
+        eprintln!("  {} Error saving artifacts for {}: {}", "⚠".yellow(), impl_name.bright_white(), e.to_string().yellow());
+    }
+}
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(error::exit_code(&e))
+        }
+    }
+}
+
+fn try_main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let command = matches.subcommand_name().unwrap_or_default().to_string();
+    let json = cli.json.then(JsonOutput::redirect).transpose()?;
+
+    let outcome = (|| {
+        let events = match &cli.events {
+            Some(target) => EventSink::open(target)?,
+            None => EventSink::disabled(),
+        };
+        let settings = Settings {
+            profile: cli.profile.as_deref(),
+            runtime: cli.runtime,
+            watts_per_core: cli.watts_per_core,
+            isolate_cases: cli.isolate_cases,
+            timeout: cli.timeout.map(Duration::from_secs),
+            heartbeat: cli.heartbeat.map(Duration::from_secs),
+            limits: runtime::Limits {
+                cpus: cli.cpus,
+                memory: cli.memory,
+            },
+            tiers: cli.tiers.clone(),
+            progress: !cli.no_progress && !cli.json,
+        };
+        execute(Orchestrator::new(events, settings)?, cli.command, json.is_some())
+    })();
+    if let Some(json) = json {
+        json.write(&command, &outcome)?;
+    }
+    outcome.map(|_| ())
+}
+
+/// Write `content` to `output`, or print it; with `--json`, carry it in
+/// the returned fields instead of printing it.
+fn export(content: String, output: Option<PathBuf>, json: bool) -> Result<Value> {
+    match output {
+        Some(path) => {
+            fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            Ok(json!({ "output": path }))
+        }
+        None if json => Ok(json!({ "content": content })),
+        None => {
+            print!("{}", content);
+            Ok(json!({}))
+        }
+    }
+}
+
+/// What a `run` or `all` that ran to the end fails with: the first stage
+/// that failed, of builds, runs (timeouts and imports included), and
+/// validation against the reference (see [`gate::mismatches`]). Runs that
+/// failed only by being `killed` fail with the first kill, as a timeout or
+/// hang.
+fn stage_failure(errors: &[Value], results: &[TestResult], killed: Vec<error::Error>, summaries: &[ValidationSummary]) -> Option<error::Error> {
+    let failed = |stages: &[&str]| -> Vec<String> {
+        errors
+            .iter()
+            .filter(|e| e["stage"].as_str().is_some_and(|stage| stages.contains(&stage)))
+            .filter_map(|e| e["implementation"].as_str().map(str::to_string))
+            .collect()
+    };
+
+    let builds = failed(&["build"]);
+    if !builds.is_empty() {
+        return Some(error::Error::BuildsFailed { implementations: builds });
+    }
+    let mut runs = failed(&["run", "import"]);
+    runs.extend(results.iter().filter(|result| !result.success).map(|result| result.implementation.clone()));
+    runs.sort();
+    runs.dedup();
+    if !runs.is_empty() {
+        // Containers killed at their timeout or for going silent, and
+        // nothing else, end with the first one's own kind
+        let killed_implementations: Vec<&str> = killed
+            .iter()
+            .filter_map(|e| match e {
+                error::Error::Timeout { implementation, .. } | error::Error::Hung { implementation, .. } => Some(implementation.as_str()),
+                _ => None,
+            })
+            .collect();
+        if runs.iter().all(|name| killed_implementations.contains(&name.as_str())) {
+            return killed.into_iter().next();
+        }
+        return Some(error::Error::RunFailed { implementations: runs });
+    }
+    let mut mismatches = gate::mismatches(summaries, REFERENCE_IMPLEMENTATION);
+    mismatches.extend(failed(&["validate"]));
+    (!mismatches.is_empty()).then_some(error::Error::ValidationFailed { mismatches })
+}
+
+/// Note the implementations `--fail-fast` left out after the first failure.
+fn print_fail_fast(skipped: &[impl AsRef<str>]) {
+    let names: Vec<&str> = skipped.iter().map(AsRef::as_ref).collect();
+    println!("{} Stopped at the first failure (--fail-fast); skipped: {}", "⚠".yellow(), names.join(", "));
+}
+
+/// Run `command`, returning its outcome as JSON fields for `--json`.
+fn execute(orchestrator: Orchestrator, command: Commands, json: bool) -> Result<Value> {
+    Ok(match command {
+        Commands::Discover => {
+            let implementations = orchestrator.discover_implementations()?;
+            println!("{} {} implementation(s):", "Discovered".bold().bright_blue(), implementations.len().to_string().bright_white());
+            for impl_ in &implementations {
+                let display_name = impl_.manifest.display_name.as_deref().map(|name| format!(" ({})", name)).unwrap_or_default();
+                println!(
+                    "  {} {}{} {}",
+                    "●".bright_cyan(),
+                    impl_.name.bright_white(),
+                    display_name.dimmed(),
+                    format!("[{}]", impl_.language.name).dimmed()
+                );
+            }
+            json!({
+                "implementations": implementations
+                    .iter()
+                    .map(|impl_| json!({ "name": impl_.name, "path": impl_.path, "image": impl_.image_name, "language": impl_.language.key, "manifest": impl_.manifest }))
+                    .collect::<Vec<_>>()
+            })
+        }
+
+        Commands::Init { name, language } => {
+            let scaffold = scaffold::create(&orchestrator.implementations_dir, &name, language)?;
+            println!("{} {} in {}", "Created".bold().bright_blue(), scaffold.name.bright_white(), scaffold.path.display());
+            for file in &scaffold.files {
+                println!("  {} {}", "●".bright_cyan(), file);
+            }
+            println!();
+            println!("Next: write `calculate` in {} and fill in the README front-matter, then", scaffold.entrypoint);
+            println!("  visibility-test-runner build -i {}", scaffold.name);
+            println!("  visibility-test-runner run -i {}", scaffold.name);
+            println!("  visibility-test-runner validate -i {}", scaffold.name);
+            json!({ "implementation": scaffold.name, "path": scaffold.path, "files": scaffold.files })
+        }
+
+        Commands::ListTests => {
+            let listings = catalog::list(&orchestrator)?;
+            catalog::print_list(&listings);
+            json!({ "cases": listings })
+        }
+
+        Commands::Build {
+            implementation,
+            language,
+            check_base_updates: true,
+            ..
+        } => {
+            let implementations = orchestrator.filter_languages(orchestrator.select_implementations(implementation.as_deref())?, &language)?;
+            anyhow::ensure!(!implementations.is_empty(), "No implementations found");
+
+            let mut rebuilt = 0;
+            let mut builds = Vec::new();
+            for impl_ in &implementations {
+                match orchestrator.rebuild_if_base_updated(impl_) {
+                    Ok(updated) => {
+                        rebuilt += usize::from(updated);
+                        builds.push(json!({ "implementation": impl_.name, "success": true, "rebuilt": updated }));
+                    }
+                    Err(e) => {
+                        eprintln!("  {} Error checking {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                        builds.push(json!({ "implementation": impl_.name, "success": false, "error": e.to_string() }));
+                    }
+                }
+            }
+            println!();
+            println!("Rebuilt {} of {} image(s)", rebuilt.to_string().bright_white(), implementations.len());
+            let failed: Vec<String> = builds
+                .iter()
+                .filter(|build| build["success"] == false)
+                .filter_map(|build| build["implementation"].as_str().map(str::to_string))
+                .collect();
+            error::finish(
+                json!({ "builds": builds }),
+                (!failed.is_empty()).then_some(error::Error::BuildsFailed { implementations: failed }),
+            )?
+        }
+
+        Commands::Build {
+            implementation,
+            language,
+            jobs,
+            ..
+        } => {
+            let implementations = orchestrator.filter_languages(orchestrator.discover_implementations()?, &language)?;
+
+            if let Some(name) = implementation {
+                let impl_ = implementations
+                    .iter()
+                    .find(|i| i.name == name)
+                    .ok_or(error::Error::ImplementationNotFound { name: name.clone() })?;
+                orchestrator.build_image(impl_)?;
+                json!({ "builds": [{ "implementation": impl_.name, "success": true }] })
+            } else {
+                orchestrator.progress.start_suite("images", implementations.len());
+                let failures = orchestrator.build_images(&implementations, jobs);
+                orchestrator.progress.finish_suite();
+                println!();
+                if failures.is_empty() {
+                    println!("{}", format!("Built {} image(s)", implementations.len()).green().bold());
+                } else {
+                    println!(
+                        "{}",
+                        format!("{} of {} build(s) failed:", failures.len(), implementations.len()).yellow()
+                    );
+                    for (impl_, _) in &failures {
+                        println!("  {} {}", "✗".red(), impl_.name.bright_white());
+                    }
+                }
+                let builds: Vec<Value> = implementations
+                    .iter()
+                    .map(|impl_| match failures.iter().find(|(failed, _)| failed.name == impl_.name) {
+                        Some((_, e)) => json!({ "implementation": impl_.name, "success": false, "error": e.to_string() }),
+                        None => json!({ "implementation": impl_.name, "success": true }),
+                    })
+                    .collect();
+                let failed: Vec<String> = failures.iter().map(|(impl_, _)| impl_.name.clone()).collect();
+                error::finish(
+                    json!({ "builds": builds }),
+                    (!failed.is_empty()).then_some(error::Error::BuildsFailed { implementations: failed }),
+                )?
+            }
+        }
+
+        Commands::Run {
+            implementation,
+            language,
+            test_case,
+            tag,
+            exclude_tag,
+            build,
+            parallel,
+            fail_fast,
+            skip_health_check,
+            format,
+            output,
+            watch,
+        } => {
+            anyhow::ensure!(!(watch && json), "--watch runs until interrupted, so it can't be combined with --json");
+            let impls_to_run = orchestrator.filter_languages(orchestrator.select_implementations(implementation.as_deref())?, &language)?;
+            let mut cases = (!test_case.is_empty()).then(|| orchestrator.resolve_cases(&test_case)).transpose()?;
+            if !tag.is_empty() || !exclude_tag.is_empty() {
+                let candidates = match cases {
+                    Some(cases) => cases,
+                    None => orchestrator.test_cases()?,
+                };
+                cases = Some(orchestrator.filter_tags(candidates, &tag, &exclude_tag)?);
+            }
+            let parallel = parallel.or(orchestrator.profile.as_ref().and_then(|profile| profile.parallel)).unwrap_or(1);
+            let format = orchestrator.format(format);
+
+            let mut errors = Vec::new();
+            let mut killed = Vec::new();
+            if build {
+                println!("\n{}", "Building images...".bold().bright_blue());
+                println!("{}", "=".repeat(50).dimmed());
+                orchestrator.progress.start_suite("images", impls_to_run.len());
+                for impl_ in &impls_to_run {
+                    if let Err(e) = orchestrator.build_image(impl_) {
+                        orchestrator.progress.eprintln(format!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()));
+                        errors.push(json!({ "implementation": impl_.name, "stage": "build", "kind": error::kind(&e), "error": e.to_string() }));
+                        if fail_fast {
+                            break;
+                        }
+                    }
+                }
+                orchestrator.progress.finish_suite();
+                println!();
+                if fail_fast && !errors.is_empty() {
+                    let skipped: Vec<&str> = impls_to_run.iter().map(|impl_| impl_.name.as_str()).collect();
+                    print_fail_fast(&skipped);
+                    return error::finish(json!({ "errors": errors, "skipped": skipped }), stage_failure(&errors, &[], Vec::new(), &[]));
+                }
+            }
+
+            let versions = if skip_health_check {
+                Vec::new()
+            } else {
+                health::check_all(&orchestrator, &impls_to_run.iter().collect::<Vec<_>>())?
+            };
+
+            println!("\n{}", "Running tests...".bold().bright_blue());
+            println!("{}", "=".repeat(50).dimmed());
+            let artifacts = RunArtifacts::create(&orchestrator.results_dir, &orchestrator.host())?;
+            for (name, declared) in &versions {
+                record_artifacts(artifacts.record_versions(name, declared), name);
+            }
+            println!("Run ID: {}", artifacts.run_id().bright_white());
+            orchestrator.print_profile();
+            let all_cases = orchestrator.test_cases()?;
+            let mut results = Vec::new();
+            let mut junit_cases = Vec::new();
+            let cases_per_run = cases.as_ref().map_or(all_cases.len(), Vec::len);
+            orchestrator.progress.start_suite("cases", impls_to_run.len() * cases_per_run);
+            let outcomes = orchestrator.run_parallel(&impls_to_run, cases.as_deref(), parallel, fail_fast)?;
+            orchestrator.progress.finish_suite();
+            let mut skipped = Vec::new();
+            for (impl_, outcome) in impls_to_run.iter().zip(outcomes) {
+                let Some(outcome) = outcome else {
+                    skipped.push(impl_.name.as_str());
+                    continue;
+                };
+                match outcome {
+                    Ok(result) => {
+                        if let Some(e) = orchestrator.killed_error(impl_, &result, cases.as_deref()) {
+                            errors.push(json!({ "implementation": impl_.name, "stage": "run", "kind": e.kind(), "error": e.to_string() }));
+                            killed.push(e);
+                        }
+                        record_artifacts(artifacts.record_run(&result), &impl_.name);
+                        junit_cases.extend(junit::run_cases(&result, cases.as_deref(), &all_cases));
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        eprintln!("  {} Error running {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                        errors.push(json!({ "implementation": impl_.name, "stage": "run", "kind": error::kind(&e), "error": e.to_string() }));
+                        junit_cases.push(junit::error_case(&impl_.name, "run", &e));
+                    }
+                }
+            }
+
+            println!();
+            println!("{}", "Summary:".bold().bright_green());
+            println!("{}", "=".repeat(50).dimmed());
+            for result in &results {
+                if result.success {
+                    println!(
+                        "{} {} - {}{}",
+                        "✓".green(),
+                        result.implementation.bright_white(),
+                        format!("{:.2}s", result.execution_time).bright_white(),
+                        orchestrator.usage_note(result.cpu_seconds, result.peak_memory)
+                    );
+                } else {
+                    println!(
+                        "{} {} - {}{}",
+                        "✗".red(),
+                        result.implementation.bright_white(),
+                        format!("{:.2}s", result.execution_time).dimmed(),
+                        result.killed_note()
+                    );
+                }
+                if !result.unsupported.is_empty() {
+                    println!("  {} unsupported: {}", "○".dimmed(), result.unsupported.join(", "));
+                }
+                if !result.quarantined.is_empty() {
+                    let names: Vec<_> = result
+                        .quarantined
+                        .iter()
+                        .map(|path| path.file_name().unwrap_or_default().to_string_lossy())
+                        .collect();
+                    println!("  {} quarantined: {}", "⚠".yellow(), names.join(", "));
+                }
+            }
+            if !skipped.is_empty() {
+                print_fail_fast(&skipped);
+            }
+            println!("Artifacts: {}", artifacts.dir().display().to_string().bright_white());
+            orchestrator.apply_retention();
+            orchestrator.write_junit(format, output, "run", &junit_cases)?;
+            if watch {
+                watch::watch(&orchestrator, &impls_to_run, cases.as_deref(), parallel);
+            }
+            let failure = stage_failure(&errors, &results, killed, &[]);
+            record_artifacts(artifacts.finish(failure.is_none()), "the run");
+            error::finish(
+                json!({
+                    "run_id": artifacts.run_id(),
+                    "profile": orchestrator.profile.as_ref().map(|profile| &profile.name),
+                    "artifacts": artifacts.dir(),
+                    "versions": versions.iter().map(|(_, declared)| declared).collect::<Vec<_>>(),
+                    "results": results,
+                    "errors": errors,
+                    "skipped": skipped,
+                }),
+                failure,
+            )?
+        }
+
+        Commands::Validate {
+            implementation,
+            language,
+            truth,
+            time_tolerance,
+            elevation_tolerance,
+            points,
+            baseline,
+            regression_threshold,
+            min_pass_rate,
+            min_score,
+            format,
+            output,
+        } => {
+            // A profile's junit or tap format doesn't apply to point comparisons
+            let format = if points { format.unwrap_or(junit::Format::Text) } else { orchestrator.format(format) };
+            if points && format != junit::Format::Text {
+                anyhow::bail!("--format junit and tap report window verdicts; they can't be combined with --points");
+            }
+            let overrides = tolerance::Overrides {
+                time: time_tolerance,
+                elevation: elevation_tolerance,
+            };
+            let reference = match truth {
+                Some(dir) => {
+                    truth::check_dir(&dir)?;
+                    Reference::Truth(dir)
+                }
+                None => Reference::Skyfield(orchestrator.test_data_dir.join("reference-results")),
+            };
+            if points {
+                let names: Vec<String> = match implementation {
+                    Some(name) => vec![name],
+                    None => {
+                        let mut implementations = orchestrator.discover_implementations()?;
+                        implementations.extend(truth::external_tools(&orchestrator.test_data_dir)?);
+                        orchestrator
+                            .filter_languages(implementations, &language)?
+                            .into_iter()
+                            .map(|impl_| impl_.name)
+                            .collect()
+                    }
+                };
+                let mut accuracies = Vec::new();
+                let mut measured = Vec::new();
+                for name in &names {
+                    let accuracy = accuracy::measure(&orchestrator, name, &reference)?;
+                    accuracy::print(&accuracy, &reference);
+                    println!();
+                    measured.push(accuracy.to_json());
+                    // The reference trivially matches itself
+                    if *name != reference.name() {
+                        accuracies.push(accuracy);
+                    }
+                }
+                if accuracies.len() > 1 {
+                    accuracy::print_ranking(&accuracies);
+                }
+                json!({ "reference": reference.name(), "accuracy": measured })
+            } else {
+                let baseline = baseline
+                    .map(|run| baseline::Baseline::open(&orchestrator.results_dir, &run))
+                    .transpose()?;
+                let mut summaries = Vec::new();
+                if let Some(name) = implementation {
+                    summaries.push(orchestrator.validate_against(&name, &reference, &overrides)?);
+                } else {
+                    let mut implementations = orchestrator.discover_implementations()?;
+                    implementations.extend(truth::external_tools(&orchestrator.test_data_dir)?);
+                    for impl_ in &orchestrator.filter_languages(implementations, &language)? {
+                        summaries.push(orchestrator.validate_against(&impl_.name, &reference, &overrides)?);
+                        println!();
+                    }
+                }
+                let mut regressions = Vec::new();
+                if let Some(baseline) = &baseline {
+                    for summary in &summaries {
+                        regressions.extend(baseline::compare(&orchestrator, summary, baseline, regression_threshold)?);
+                    }
+                    baseline::print(&regressions, baseline);
+                }
+                let junit_cases: Vec<junit::Case> = summaries.iter().flat_map(junit::validation_cases).collect();
+                orchestrator.write_junit(format, output, "validate", &junit_cases)?;
+                let gates = gate::Gates { min_pass_rate, min_score };
+                let outcome = (!gates.is_empty()).then(|| gates.evaluate(&summaries, &reference.name()));
+                // The gates decide when given; otherwise every case has to match
+                let failure = match &outcome {
+                    Some(outcome) => {
+                        println!();
+                        gate::print(outcome);
+                        outcome.error()
+                    }
+                    None => {
+                        let mismatches = gate::mismatches(&summaries, &reference.name());
+                        (!mismatches.is_empty()).then_some(error::Error::ValidationFailed { mismatches })
+                    }
+                };
+                error::finish(
+                    json!({ "reference": reference.name(), "summaries": summaries, "regressions": regressions, "gates": outcome }),
+                    failure,
+                )?
+            }
+        }
+
+        Commands::Show {
+            implementation,
+            test_case,
+            tz,
+        } => {
+            let results = show::print(&orchestrator, &implementation, test_case.as_deref(), tz.as_ref())?;
+            json!({ "results": results })
+        }
+
+        Commands::Track {
+            implementation,
+            test_case,
+            pass,
+            lead,
+            unwrap,
+            az_min,
+            az_max,
+            output,
+        } => {
+            let options = track::Options {
+                pass,
+                lead,
+                unwrap,
+                az_min,
+                az_max,
+            };
+            let content = track::render(&orchestrator, &implementation, &test_case, &options)?;
+            export(content, output, json)?
+        }
+
+        Commands::Doppler {
+            implementation,
+            test_case,
+            pass,
+            format,
+            output,
+        } => {
+            let content = doppler::render(&orchestrator, &implementation, &test_case, pass, format)?;
+            export(content, output, json)?
+        }
+
+        Commands::Passes {
+            implementation,
+            test_case,
+            tz,
+            output,
+        } => {
+            let content = passes::render(&orchestrator, &implementation, test_case.as_deref(), tz.as_ref())?;
+            export(content, output, json)?
+        }
+
+        Commands::All {
+            test_case,
+            tag,
+            exclude_tag,
+            resume,
+            fail_fast,
+            skip_health_check,
+            format,
+            output,
+        } => {
+            let format = orchestrator.format(format);
+            let mut implementations = orchestrator.discover_implementations()?;
+            // Tags narrow --test-case as they do for `run`
+            let selection = if !tag.is_empty() || !exclude_tag.is_empty() {
+                let candidates = match &test_case {
+                    Some(case) => vec![case.clone()],
+                    None => orchestrator.test_cases()?,
+                };
+                Some(orchestrator.filter_tags(candidates, &tag, &exclude_tag)?)
+            } else {
+                test_case.clone().map(|case| vec![case])
+            };
+
+            let (artifacts, mut manifest) = match &resume {
+                Some(run_id) => {
+                    let artifacts = RunArtifacts::open(&orchestrator.results_dir, run_id)?;
+                    let manifest = artifacts.read_manifest()?;
+                    implementations.retain(|impl_| manifest.cases.iter().any(|c| c.implementation == impl_.name));
+                    if let Some(missing) = manifest
+                        .cases
+                        .iter()
+                        .find(|c| !implementations.iter().any(|impl_| impl_.name == c.implementation))
+                    {
+                        anyhow::bail!("Run {} includes {}, which no longer exists", run_id, missing.implementation);
+                    }
+                    (artifacts, manifest)
+                }
+                None => {
+                    let artifacts = RunArtifacts::create(&orchestrator.results_dir, &orchestrator.host())?;
+                    let cases = match &selection {
+                        Some(cases) => cases.clone(),
+                        None => orchestrator.test_cases()?,
+                    };
+                    let pairs = implementations
+                        .iter()
+                        .flat_map(|impl_| cases.iter().map(|case| (impl_.name.clone(), case.clone())))
+                        .collect();
+                    let mut manifest = RunManifest::new(artifacts.run_id(), test_case.as_deref(), pairs);
+                    manifest.schema_version = capabilities::schema_version(&orchestrator.test_data_dir)?;
+                    manifest.features = implementations
+                        .iter()
+                        .map(|impl_| {
+                            let features = capabilities::declared(&impl_.path);
+                            (impl_.name.clone(), features.iter().map(|feature| feature.name().to_string()).collect())
+                        })
+                        .collect();
+                    artifacts.write_manifest(&manifest)?;
+                    (artifacts, manifest)
+                }
+            };
+
+            // Results an interrupted container wrote before it was stopped
+            if resume.is_some() {
+                for impl_ in &implementations {
+                    let result_files: Vec<PathBuf> = orchestrator
+                        .results_since(&impl_.name, manifest.started_at())?
+                        .into_iter()
+                        .filter(|path| {
+                            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                            result_test_case(&file_name, &impl_.name).is_some_and(|case| {
+                                manifest.remaining().iter().any(|(i, c)| *i == impl_.name && *c == case)
+                            })
+                        })
+                        .collect();
+                    if result_files.is_empty() {
+                        continue;
+                    }
+                    let recovered = TestResult {
+                        implementation: impl_.name.clone(),
+                        success: true,
+                        execution_time: 0.0,
+                        stdout: String::new(),
+                        stderr: String::new(),
+                        result_files,
+                        unsupported: Vec::new(),
+                        cpu_seconds: None,
+                        peak_memory: None,
+                        image_id: None,
+                        timed_out: false,
+                        hung: false,
+                        quarantined: Vec::new(),
+                    };
+                    record_artifacts(artifacts.record_run(&recovered), &impl_.name);
+                    manifest.record(&recovered);
+                }
+                artifacts.write_manifest(&manifest)?;
+            }
+
+            // A fresh run starts one container per implementation; a resumed
+            // one, one per remaining case
+            let jobs: Vec<(&Implementation, Option<Vec<String>>)> = match &resume {
+                Some(_) => manifest
+                    .remaining()
+                    .into_iter()
+                    .filter_map(|(name, case)| Some((implementations.iter().find(|i| i.name == name)?, Some(vec![case]))))
+                    .collect(),
+                None => implementations.iter().map(|impl_| (impl_, selection.clone())).collect(),
+            };
+
+            println!("\n{}", "Satellite Visibility Test Suite".bold().bright_magenta());
             println!("{}", "=".repeat(50).dimmed());
             println!("Discovered {} implementation(s)", implementations.len().to_string().bright_white());
             for impl_ in &implementations {
@@ -471,31 +2828,128 @@ fn main() -> Result<()> {
 
             println!("\n{}", "Building images...".bold().bright_blue());
             println!("{}", "-".repeat(50).dimmed());
-            for impl_ in &implementations {
+            let mut errors = Vec::new();
+            let mut killed = Vec::new();
+            let to_build: Vec<&Implementation> =
+                implementations.iter().filter(|impl_| jobs.iter().any(|(job, _)| job.name == impl_.name)).collect();
+            orchestrator.progress.start_suite("images", to_build.len());
+            for impl_ in to_build {
                 if let Err(e) = orchestrator.build_image(impl_) {
-                    eprintln!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                    orchestrator.progress.eprintln(format!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()));
+                    errors.push(json!({ "implementation": impl_.name, "stage": "build", "kind": error::kind(&e), "error": e.to_string() }));
+                    if fail_fast {
+                        break;
+                    }
                 }
             }
+            orchestrator.progress.finish_suite();
             println!();
+            // With --fail-fast, set at the first failure; later stages then
+            // skip the implementations they'd get to
+            let mut stopped = fail_fast && !errors.is_empty();
+            let mut skipped = Vec::new();
+
+            let versions = if skip_health_check || stopped {
+                Vec::new()
+            } else {
+                let checked: Vec<&Implementation> = implementations
+                    .iter()
+                    .filter(|impl_| jobs.iter().any(|(job, _)| job.name == impl_.name))
+                    .collect();
+                health::check_all(&orchestrator, &checked)?
+            };
+            for (name, declared) in &versions {
+                record_artifacts(artifacts.record_versions(name, declared), name);
+            }
 
             println!("\n{}", "Running tests...".bold().bright_blue());
             println!("{}", "-".repeat(50).dimmed());
+            match &resume {
+                Some(_) => println!(
+                    "Resuming run {}: {} of {} case(s) remaining",
+                    artifacts.run_id().bright_white(),
+                    jobs.len(),
+                    manifest.cases.len()
+                ),
+                None => println!("Run ID: {}", artifacts.run_id().bright_white()),
+            }
+            orchestrator.print_profile();
+            let all_cases = orchestrator.test_cases()?;
             let mut results = Vec::new();
-            for impl_ in &implementations {
-                match orchestrator.run_tests(impl_, test_case.as_deref()) {
-                    Ok(result) => results.push(result),
-                    Err(e) => eprintln!("  {} Error running {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()),
+            let mut run_cases = Vec::new();
+            let total_cases: usize = jobs.iter().map(|(_, cases)| cases.as_ref().map_or(all_cases.len(), Vec::len)).sum();
+            orchestrator.progress.start_suite("cases", total_cases);
+            for (impl_, cases) in &jobs {
+                if stopped {
+                    skipped.push(impl_.name.clone());
+                    continue;
+                }
+                let cases = cases.as_deref();
+                match orchestrator.run_tests(impl_, cases) {
+                    Ok(result) => {
+                        if let Some(e) = orchestrator.killed_error(impl_, &result, cases) {
+                            errors.push(json!({ "implementation": impl_.name, "stage": "run", "kind": e.kind(), "error": e.to_string() }));
+                            killed.push(e);
+                        }
+                        record_artifacts(artifacts.record_run(&result), &impl_.name);
+                        manifest.record(&result);
+                        record_artifacts(artifacts.write_manifest(&manifest), &impl_.name);
+                        run_cases.extend(junit::run_cases(&result, cases, &all_cases));
+                        stopped = fail_fast && !result.success;
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        orchestrator.progress.eprintln(format!("  {} Error running {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()));
+                        errors.push(json!({ "implementation": impl_.name, "stage": "run", "kind": error::kind(&e), "error": e.to_string() }));
+                        run_cases.push(junit::error_case(&impl_.name, "run", &e));
+                        stopped = fail_fast;
+                    }
                 }
             }
+            orchestrator.progress.finish_suite();
             println!();
 
+            for tool in truth::external_tools(&orchestrator.test_data_dir)? {
+                if stopped {
+                    skipped.push(tool.name.clone());
+                    continue;
+                }
+                match truth::import(&orchestrator, &tool) {
+                    Ok(_) => implementations.push(tool),
+                    Err(e) => {
+                        eprintln!("  {} Error importing {}: {}", "✗".red(), tool.name.bright_white(), e.to_string().red());
+                        errors.push(json!({ "implementation": tool.name, "stage": "import", "kind": error::kind(&e), "error": e.to_string() }));
+                        stopped = fail_fast;
+                    }
+                }
+            }
+
             println!("\n{}", "Validating results...".bold().bright_blue());
             println!("{}", "-".repeat(50).dimmed());
+            let mut summaries = Vec::new();
+            let mut validation_cases = Vec::new();
             for impl_ in &implementations {
-                if let Err(e) = orchestrator.validate_results(&impl_.name) {
-                    eprintln!("  {} Error validating {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                if stopped {
+                    skipped.push(impl_.name.clone());
+                    continue;
+                }
+                match orchestrator.validate_results(&impl_.name) {
+                    Ok(summary) => {
+                        record_artifacts(artifacts.record_validation(&summary), &impl_.name);
+                        validation_cases.extend(junit::validation_cases(&summary));
+                        stopped = fail_fast && !gate::mismatches(std::slice::from_ref(&summary), REFERENCE_IMPLEMENTATION).is_empty();
+                        summaries.push(summary);
+                    }
+                    Err(e) => {
+                        eprintln!("  {} Error validating {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                        errors.push(json!({ "implementation": impl_.name, "stage": "validate", "kind": error::kind(&e), "error": e.to_string() }));
+                        validation_cases.push(junit::error_case(&impl_.name, "validate", &e));
+                        stopped = fail_fast;
+                    }
                 }
             }
+            skipped.sort();
+            skipped.dedup();
 
             println!();
             println!("{}", "Final Summary:".bold().bright_green());
@@ -503,22 +2957,786 @@ fn main() -> Result<()> {
             for result in &results {
                 if result.success {
                     println!(
-                        "{} {} - {}",
+                        "{} {} - {}{}",
                         "✓".green(),
                         result.implementation.bright_white(),
-                        format!("{:.2}s", result.execution_time).bright_white()
+                        format!("{:.2}s", result.execution_time).bright_white(),
+                        orchestrator.usage_note(result.cpu_seconds, result.peak_memory)
                     );
                 } else {
                     println!(
-                        "{} {} - {}",
+                        "{} {} - {}{}",
                         "✗".red(),
                         result.implementation.bright_white(),
-                        format!("{:.2}s", result.execution_time).dimmed()
+                        format!("{:.2}s", result.execution_time).dimmed(),
+                        result.killed_note()
+                    );
+                }
+                if !result.unsupported.is_empty() {
+                    println!("  {} unsupported: {}", "○".dimmed(), result.unsupported.join(", "));
+                }
+                if !result.quarantined.is_empty() {
+                    let names: Vec<_> = result
+                        .quarantined
+                        .iter()
+                        .map(|path| path.file_name().unwrap_or_default().to_string_lossy())
+                        .collect();
+                    println!("  {} quarantined: {}", "⚠".yellow(), names.join(", "));
+                }
+            }
+            if !skipped.is_empty() {
+                print_fail_fast(&skipped);
+            }
+            println!("Artifacts: {}", artifacts.dir().display().to_string().bright_white());
+            orchestrator.apply_retention();
+            orchestrator.write_junit(format, output, "all", &junit::merge(run_cases, validation_cases))?;
+            let failure = stage_failure(&errors, &results, killed, &summaries);
+            record_artifacts(artifacts.finish(failure.is_none()), "the run");
+            error::finish(
+                json!({
+                    "run_id": artifacts.run_id(),
+                    "profile": orchestrator.profile.as_ref().map(|profile| &profile.name),
+                    "artifacts": artifacts.dir(),
+                    "versions": versions.iter().map(|(_, declared)| declared).collect::<Vec<_>>(),
+                    "results": results,
+                    "summaries": summaries,
+                    "errors": errors,
+                    "skipped": skipped,
+                }),
+                failure,
+            )?
+        }
+
+        Commands::Minimize {
+            implementation,
+            test_case,
+            name,
+        } => {
+            let name = name.unwrap_or_else(|| format!("{}_min", test_case));
+            let case_path = orchestrator.test_data_dir.join("cases").join(format!("{}.json", name));
+            let reference_path = orchestrator
+                .test_data_dir
+                .join("reference-results")
+                .join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, name));
+            if case_path.exists() {
+                anyhow::bail!("{} already exists; choose another --name", case_path.display());
+            }
+
+            let implementations = orchestrator.discover_implementations()?;
+            let find = |name: &str| {
+                implementations
+                    .iter()
+                    .find(|impl_| impl_.name == name)
+                    .ok_or_else(|| error::Error::ImplementationNotFound { name: name.to_string() })
+            };
+            let impl_ = find(&implementation)?;
+            let reference = find(REFERENCE_IMPLEMENTATION)?;
+            if impl_.name == reference.name {
+                anyhow::bail!("{} is the reference; pick another implementation", REFERENCE_IMPLEMENTATION);
+            }
+
+            println!("\n{}", "Minimizing...".bold().bright_blue());
+            println!("{}", "=".repeat(50).dimmed());
+            let minimized = minimize::run(&orchestrator, impl_, reference, &test_case, &name)?;
+
+            fs::write(&case_path, serde_json::to_string_pretty(&minimized.case)? + "\n")
+                .with_context(|| format!("Failed to write {}", case_path.display()))?;
+            fs::write(&reference_path, &minimized.reference_result)
+                .with_context(|| format!("Failed to write {}", reference_path.display()))?;
+
+            println!();
+            println!(
+                "{} {} .. {}: {} window(s) vs reference {}",
+                "✓".green(),
+                minimized.start,
+                minimized.end,
+                minimized.result_windows,
+                minimized.reference_windows
+            );
+            println!("Wrote {}", case_path.display().to_string().bright_white());
+            println!("Wrote {}", reference_path.display().to_string().bright_white());
+            json!({
+                "test_case": name,
+                "start": minimized.start,
+                "end": minimized.end,
+                "result_windows": minimized.result_windows,
+                "reference_windows": minimized.reference_windows,
+                "case_file": case_path,
+                "reference_file": reference_path,
+            })
+        }
+
+        Commands::Bench {
+            implementation,
+            test_case,
+            iterations,
+            warmup,
+            pin_cpus,
+            cpuset,
+            max_load,
+            screening,
+            sample,
+            output,
+        } => {
+            if iterations == 0 {
+                anyhow::bail!("--iterations must be at least 1");
+            }
+            let impls_to_bench = orchestrator.select_implementations(implementation.as_deref())?;
+            let pinning = pin_cpus.then(|| bench::Pinning::new(cpuset, max_load)).transpose()?;
+
+            println!("\n{}", "Benchmarking...".bold().bright_blue());
+            println!("{}", "=".repeat(50).dimmed());
+            match &pinning {
+                Some(pinning) => println!("Pinned to CPU(s) {}", pinning.cpuset.bright_white()),
+                None => println!("{}", "Unpinned: timings include scheduler noise".dimmed()),
+            }
+            orchestrator.print_limits();
+
+            if let Some(name) = screening {
+                let scenario = screening::Scenario::load(&orchestrator, &name, sample)?;
+                println!(
+                    "Screening scenario {}: {} satellites from TLE set {}, {} checked against the reference",
+                    scenario.name.bright_white(),
+                    scenario.cases.len(),
+                    scenario.tle_set,
+                    scenario.sample.len()
+                );
+                if let Some(description) = &scenario.description {
+                    println!("{}", description.dimmed());
+                }
+                let stats = screening::run(&orchestrator, &impls_to_bench, &scenario, iterations, warmup, pinning.as_ref())?;
+
+                println!();
+                println!("{}", "Summary:".bold().bright_green());
+                println!("{}", "=".repeat(50).dimmed());
+                for s in &stats {
+                    println!(
+                        "{} - {} (median {:.3}s, min {:.3}s, n={}), {} of {} screened, {} window(s)",
+                        s.timing.implementation.bright_white(),
+                        format!("{:.1} satellites/s", s.throughput).bright_white(),
+                        s.timing.median,
+                        s.timing.min,
+                        s.timing.times.len(),
+                        s.screened,
+                        scenario.cases.len(),
+                        s.windows
                     );
+                    match &s.sample {
+                        None => println!("  {} Reference", "●".bright_cyan()),
+                        Some(check) => {
+                            let mark = if check.matched == check.checked && check.missing.is_empty() { "✓".green() } else { "✗".red() };
+                            println!("  {} Sample: {}/{} match", mark, check.matched, check.checked);
+                            for case in &check.mismatched {
+                                println!("    {} {} mismatches", "✗".red(), case);
+                            }
+                            for case in &check.missing {
+                                println!("    {} {} has no result", "✗".red(), case);
+                            }
+                        }
+                    }
                 }
+
+                if let Some(path) = &output {
+                    fs::write(path, serde_json::to_string_pretty(&stats)?)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+                }
+                return Ok(json!({
+                    "pinned_cpus": pinning.as_ref().map(|pinning| &pinning.cpuset),
+                    "scenario": scenario.name,
+                    "satellites": scenario.cases.len(),
+                    "sample": scenario.sample,
+                    "stats": stats,
+                }));
             }
+            let stats = bench::run(
+                &orchestrator,
+                &impls_to_bench,
+                test_case.as_ref().map(std::slice::from_ref),
+                iterations,
+                warmup,
+                pinning.as_ref(),
+            )?;
+
+            println!();
+            println!("{}", "Summary:".bold().bright_green());
+            println!("{}", "=".repeat(50).dimmed());
+            let print_stats = |indent: usize, label: ColoredString, s: &BenchStats| {
+                println!(
+                    "{}{} - median {} (min {:.3}s, mean {:.3}s, stddev {:.3}s, n={})",
+                    "  ".repeat(indent),
+                    label,
+                    format!("{:.3}s", s.median).bright_white(),
+                    s.min,
+                    s.mean,
+                    s.stddev,
+                    s.times.len()
+                );
+            };
+            // Per runtime family and language, over the pooled times of
+            // their implementations
+            let pooled = |label: &str, members: &[&BenchStats]| {
+                BenchStats::new(label, members.iter().flat_map(|s| s.times.iter().copied()).collect())
+            };
+            let by_language = languages::group(impls_to_bench.iter().zip(&stats).map(|(impl_, s)| (impl_.language.clone(), s)));
+            for (runtime, by_language) in &by_language {
+                let family: Vec<&BenchStats> = by_language.values().flatten().copied().collect();
+                print_stats(0, runtime.label().bold(), &pooled(runtime.label(), &family));
+                for (language, members) in by_language {
+                    print_stats(1, language.bold(), &pooled(language, members));
+                    for s in members {
+                        print_stats(2, s.implementation.bright_white(), s);
+                    }
+                }
+            }
+
+            if let Some(path) = &output {
+                fs::write(path, serde_json::to_string_pretty(&stats)?)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            }
+            json!({ "pinned_cpus": pinning.as_ref().map(|pinning| &pinning.cpuset), "stats": stats })
+        }
+
+        Commands::Stress {
+            implementation,
+            limits,
+            test_case,
+            days,
+            screening,
+            output,
+        } => {
+            anyhow::ensure!(days > 0, "--days must be at least 1");
+            let mut limits = if limits.is_empty() { vec![512 << 20, 256 << 20, 128 << 20, 64 << 20] } else { limits };
+            // Loosest first
+            limits.sort_unstable_by(|a, b| b.cmp(a));
+            limits.dedup();
+            let implementations = orchestrator.select_implementations(implementation.as_deref())?;
+
+            println!("\n{}", "Stress testing memory...".bold().bright_blue());
+            println!("{}", "=".repeat(50).dimmed());
+            let mut workloads = vec![stress::long_window(&orchestrator, &test_case, days)?];
+            workloads.extend(stress::catalogs(&orchestrator, &screening)?);
+            for workload in &workloads {
+                println!("  {} {}: {}", "●".bright_cyan(), workload.name.bright_white(), workload.description);
+            }
+            println!();
+            let attempts = stress::run(&orchestrator, &implementations, &workloads, &limits)?;
+
+            println!();
+            println!("{}", "Summary:".bold().bright_green());
+            println!("{}", "=".repeat(50).dimmed());
+            stress::print(&attempts, &implementations, &workloads);
+
+            if let Some(path) = &output {
+                fs::write(path, serde_json::to_string_pretty(&attempts)?).with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            }
+            json!({
+                "limits": limits,
+                "workloads": workloads.iter().map(|workload| json!({ "name": workload.name, "description": workload.description })).collect::<Vec<_>>(),
+                "attempts": attempts,
+            })
         }
+
+        Commands::Sweep {
+            test_case,
+            implementation,
+            min_elevations,
+            steps,
+            latitudes,
+            output,
+        } => {
+            let grid = sweep::Grid {
+                min_elevations,
+                steps,
+                latitudes,
+            };
+            let implementations = orchestrator.select_implementations(implementation.as_deref())?;
+            let sweep = sweep::prepare(&orchestrator, &test_case, &grid)?;
+
+            println!("\n{}", format!("Sweeping {} over {} variant(s)...", sweep.base, sweep.variants.len()).bold().bright_blue());
+            println!("{}", "=".repeat(50).dimmed());
+            let surfaces = sweep::run(&orchestrator, &implementations, &sweep)?;
+
+            println!();
+            println!("{}", "Surfaces:".bold().bright_green());
+            println!("{}", "=".repeat(50).dimmed());
+            sweep::print(&sweep, &surfaces);
+
+            let result = json!({ "base": sweep.base, "variants": sweep.variants, "surfaces": surfaces });
+            if let Some(path) = &output {
+                fs::write(path, serde_json::to_string_pretty(&result)?).with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            }
+            result
+        }
+
+        Commands::ImportResults { source, implementation } => {
+            let import = result_import::import(&orchestrator, &source, implementation.as_deref())?;
+            for imported in &import.imported {
+                println!(
+                    "  {} Imported {} {} from {}",
+                    "✓".green(),
+                    imported.implementation.bright_white(),
+                    imported.test_case,
+                    imported.source.display()
+                );
+            }
+            for rejected in &import.rejected {
+                println!("  {} Rejected {}: {}", "✗".red(), rejected.source.display(), rejected.reason);
+            }
+            match &import.run_id {
+                Some(run_id) => println!("Imported {} result(s) as run {}", import.imported.len(), run_id.bright_white()),
+                None => println!("{} No results imported from {}", "⚠".yellow(), source.display()),
+            }
+            let failure = import.failure();
+            error::finish(serde_json::to_value(&import)?, failure)?
+        }
+
+        Commands::Import { implementation } => {
+            let tools: Vec<_> = truth::external_tools(&orchestrator.test_data_dir)?
+                .into_iter()
+                .filter(|tool| implementation.as_ref().is_none_or(|name| &tool.name == name))
+                .collect();
+            if tools.is_empty() {
+                println!(
+                    "{} No external tools found in {}",
+                    "⚠".yellow(),
+                    orchestrator.test_data_dir.join("external").display()
+                );
+            }
+            let mut imported = Vec::new();
+            for tool in &tools {
+                let written = truth::import(&orchestrator, tool)?;
+                println!("  {} Imported {} result(s) for {}", "✓".green(), written.len(), tool.name.bright_white());
+                imported.push(json!({ "implementation": tool.name, "results": written }));
+            }
+            json!({ "imported": imported })
+        }
+
+        Commands::Report { format, output } => {
+            let content = report::generate(&orchestrator, format)?;
+            export(content, output, json)?
+        }
+
+        Commands::Leaderboard { by, truth, output } => {
+            let reference = match truth {
+                Some(dir) => {
+                    truth::check_dir(&dir)?;
+                    Reference::Truth(dir)
+                }
+                None => Reference::Skyfield(orchestrator.test_data_dir.join("reference-results")),
+            };
+            let standings = leaderboard::rank(&orchestrator, &reference, by)?;
+            leaderboard::print(&standings, &reference, by);
+            if let Some(path) = &output {
+                fs::write(path, leaderboard::markdown(&standings, &reference, by))
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            }
+            json!({ "standings": standings, "output": output })
+        }
+
+        Commands::DescribeCases { format, output, check } => {
+            let content = catalog::generate(&orchestrator, format)?;
+            match output {
+                Some(path) if check => {
+                    let current = fs::read_to_string(&path).unwrap_or_default();
+                    if current != content {
+                        anyhow::bail!(
+                            "{} is out of date with the test cases; regenerate it with describe-cases --output {}",
+                            path.display(),
+                            path.display()
+                        );
+                    }
+                    println!("{} {} is up to date", "✓".green(), path.display().to_string().bright_white());
+                    json!({ "output": path, "up_to_date": true })
+                }
+                output => export(content, output, json)?,
+            }
+        }
+
+        Commands::CheckReference => {
+            let check = reference_check::run(&orchestrator)?;
+            reference_check::print(&check);
+            if let Some(e) = check.error() {
+                return Err(e.into());
+            }
+            serde_json::to_value(&check)?
+        }
+
+        Commands::Merge { results, format, output } => {
+            let results = if results.is_empty() { vec![orchestrator.results_dir.clone()] } else { results };
+            let content = merge::generate(&orchestrator, &results, format)?;
+            export(content, output, json)?
+        }
+
+        Commands::Publish { output, last } => {
+            let site_dir = output.unwrap_or_else(|| orchestrator.results_dir.join("site"));
+            let published = publish::publish(&orchestrator, &site_dir, last)?;
+            println!(
+                "{} Published {} run(s) as {} page(s) in {}",
+                "✓".green(),
+                published.runs,
+                published.pages,
+                site_dir.display().to_string().bright_white()
+            );
+            json!({ "output": site_dir, "runs": published.runs, "pages": published.pages })
+        }
+
+        Commands::Flaky { min_flips, last, propose } => {
+            let (runs, pairs) = flaky::detect(&orchestrator.results_dir, min_flips, last)?;
+            let quarantine = flaky::read_quarantine(&orchestrator.test_data_dir)?;
+
+            println!("{}", format!("Flaky cases in {} run(s):", runs).bold());
+            for pair in &pairs {
+                let stats = &pair.stats;
+                let listed = quarantine
+                    .iter()
+                    .find(|entry| entry.implementation == pair.implementation && entry.test_case == pair.test_case)
+                    .map(|entry| match entry.status {
+                        flaky::Status::Proposed => " [proposed]",
+                        flaky::Status::Quarantined => " [quarantined]",
+                    })
+                    .unwrap_or_default();
+                println!(
+                    "  {} {} / {}{} - {} match, {} mismatch over {} run(s), {} flip(s)",
+                    "~".yellow(),
+                    pair.implementation.bright_white(),
+                    pair.test_case.bright_white(),
+                    listed.dimmed(),
+                    stats.matches,
+                    stats.mismatches,
+                    stats.runs,
+                    stats.flips.to_string().yellow()
+                );
+                let image = stats.image_id.as_deref().map_or("unknown".to_string(), |id| {
+                    id.trim_start_matches("sha256:").chars().take(12).collect()
+                });
+                println!(
+                    "      image {}, runs {} to {}",
+                    image,
+                    stats.first_run.as_deref().unwrap_or("-"),
+                    stats.last_run.as_deref().unwrap_or("-")
+                );
+            }
+            if pairs.is_empty() {
+                println!("  {} No flaky cases", "✓".green());
+            }
+
+            let mut proposed = None;
+            if propose {
+                let added = flaky::propose(&orchestrator.test_data_dir, &pairs)?;
+                println!(
+                    "{} Proposed {} pair(s) for quarantine in {}",
+                    "✓".green(),
+                    added,
+                    orchestrator.test_data_dir.join("quarantine.json").display().to_string().bright_white()
+                );
+                proposed = Some(added);
+            }
+            let flaky: Vec<Value> = pairs
+                .iter()
+                .map(|pair| {
+                    let status = quarantine
+                        .iter()
+                        .find(|entry| entry.implementation == pair.implementation && entry.test_case == pair.test_case)
+                        .map(|entry| entry.status);
+                    json!({
+                        "implementation": pair.implementation,
+                        "test_case": pair.test_case,
+                        "quarantine": status,
+                        "stats": pair.stats,
+                    })
+                })
+                .collect();
+            json!({ "runs": runs, "flaky": flaky, "proposed": proposed })
+        }
+
+        Commands::LogDiff {
+            implementation,
+            run_a,
+            run_b,
+            context,
+            raw,
+        } => {
+            let diff = log_diff::diff(&orchestrator.results_dir, &implementation, &run_a, &run_b, context, !raw)?;
+            log_diff::print(&diff);
+            json!(diff)
+        }
+
+        Commands::Diff { old, new } => {
+            let diff = result_diff::diff(&old, &new)?;
+            result_diff::print(&diff);
+            json!(diff)
+        }
+
+        Commands::History {
+            implementation,
+            test_case,
+            last,
+        } => {
+            let database = database::Database::open(&orchestrator.results_dir)?;
+            let filter = database::Filter {
+                implementation: implementation.as_deref(),
+                test_case: test_case.as_deref(),
+                last,
+            };
+            if implementation.is_none() && test_case.is_none() {
+                let runs = database.runs(&filter)?;
+                println!("{}", format!("Last {} run(s):", runs.len()).bold());
+                for run in &runs {
+                    let profile = run.profile.as_deref().map(|profile| format!(" [{}]", profile)).unwrap_or_default();
+                    let verdicts = if run.validated > 0 {
+                        format!(", {}/{} matched", run.matched, run.validated)
+                    } else {
+                        String::new()
+                    };
+                    let failed = if run.failed_containers > 0 {
+                        format!(", {} failed", run.failed_containers).red().to_string()
+                    } else {
+                        String::new()
+                    };
+                    println!(
+                        "  {} {}{} - {} implementation(s), {} result(s){}{}",
+                        run.run_id.bright_white(),
+                        run.hostname,
+                        profile.dimmed(),
+                        run.implementations,
+                        run.results,
+                        verdicts,
+                        failed
+                    );
+                }
+                json!({ "runs": runs })
+            } else {
+                let cases = database.cases(&filter)?;
+                println!("{}", format!("{} case result(s) in the last {} run(s):", cases.len(), last).bold());
+                for case in &cases {
+                    let verdict = match case.verdict.as_deref() {
+                        Some("match") => "✓".green(),
+                        Some("mismatch") => "✗".red(),
+                        Some(_) => "○".dimmed(),
+                        None => "-".dimmed(),
+                    };
+                    let windows = match (case.windows, case.reference_windows) {
+                        (Some(windows), Some(reference)) => format!("{} window(s) vs {} reference", windows, reference),
+                        (Some(windows), None) => format!("{} window(s)", windows),
+                        (None, _) => case.verdict.clone().unwrap_or_else(|| "no result".to_string()),
+                    };
+                    let time = case.execution_time.map(|time| format!(", {:.3}s", time)).unwrap_or_default();
+                    println!(
+                        "  {} {} {} {} - {}{}",
+                        verdict,
+                        case.run_id.dimmed(),
+                        case.implementation.bright_white(),
+                        case.test_case,
+                        windows,
+                        time
+                    );
+                }
+                json!({ "cases": cases })
+            }
+        }
+
+        Commands::ExportFailures { output } => {
+            let failures = database::Database::open(&orchestrator.results_dir)?.failures()?;
+            let mut content = String::new();
+            for failure in &failures {
+                content.push_str(&serde_json::to_string(failure)?);
+                content.push('\n');
+            }
+            let mut exported = export(content, output, json)?;
+            exported["failures"] = json!(failures.len());
+            exported
+        }
+
+        Commands::Audit { output } => {
+            let sbom = audit::run(&orchestrator)?;
+
+            println!("{}", "License and provenance audit:".bold());
+            for finding in &sbom.findings {
+                let severity = match finding.severity {
+                    audit::Severity::Error => "error".red(),
+                    audit::Severity::Warning => "warning".yellow(),
+                };
+                println!("  {} [{}] {}", severity, finding.implementation.bright_white(), finding.message);
+            }
+            if sbom.findings.is_empty() {
+                println!("  {} No findings", "✓".green());
+            }
+
+            if let Some(path) = output {
+                fs::write(&path, serde_json::to_string_pretty(&sbom)?)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                println!("{} Wrote {}", "✓".green(), path.display().to_string().bright_white());
+            }
+            serde_json::to_value(&sbom)?
+        }
+
+        Commands::GenerateCompose { output } => {
+            let implementations = orchestrator.discover_implementations()?;
+            let tiers = compose::test_case_tiers(&orchestrator.test_data_dir)?;
+            let materialized = orchestrator.materialize_cases()?.is_some();
+            let content = compose::render(&implementations, &tiers, materialized, orchestrator.limits);
+
+            match output {
+                Some(path) => {
+                    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!(
+                        "{} Wrote {} with {} implementation(s) and {} tier profile(s)",
+                        "✓".green(),
+                        path.display().to_string().bright_white(),
+                        implementations.len(),
+                        tiers.len()
+                    );
+                    json!({ "output": path, "implementations": implementations.len(), "tiers": tiers.len() })
+                }
+                None if json => json!({ "content": content }),
+                None => {
+                    print!("{}", content);
+                    json!({})
+                }
+            }
+        }
+
+        Commands::Daemon {
+            socket,
+            schedule,
+            keep,
+            notify,
+        } => {
+            if json {
+                anyhow::bail!("The daemon answers in JSON on its control socket; --json doesn't apply");
+            }
+            let schedule = schedule
+                .map(|expr| -> Result<_> {
+                    Ok(daemon::ScheduleOptions {
+                        cron: schedule::CronSchedule::parse(&expr)?,
+                        keep: keep.or(orchestrator.profile.as_ref().and_then(|profile| profile.keep)),
+                        notify,
+                    })
+                })
+                .transpose()?;
+            daemon::Daemon::new(orchestrator, schedule).serve(&socket)?;
+            json!({})
+        }
+
+        Commands::Prune { keep, dry_run } => {
+            let keep = keep
+                .map(|keep| keep as usize)
+                .or(orchestrator.profile.as_ref().and_then(|profile| profile.keep))
+                .context("Pass --keep, or a --profile that sets keep")?;
+            let stale = if dry_run { orchestrator.stale_results(keep)? } else { orchestrator.prune_results(keep)? };
+            for path in &stale {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                if dry_run {
+                    println!("  {} Would remove {}", "○".dimmed(), name);
+                } else {
+                    println!("  {} Removed {}", "✓".green(), name);
+                }
+            }
+            let verb = if dry_run { "Would remove" } else { "Removed" };
+            println!("{} {} old result file(s), keeping the newest {} per implementation and test case", verb, stale.len(), keep);
+            json!({ "keep": keep, "dry_run": dry_run, "removed": stale })
+        }
+
+        Commands::Du { older_than } => {
+            let usage = disk_usage::audit(&orchestrator, older_than)?;
+            disk_usage::print(&usage, &orchestrator.results_dir);
+            serde_json::to_value(&usage)?
+        }
+
+        Commands::Clean { images, results, all } => {
+            anyhow::ensure!(images || results || all, "Pass --images, --results, or --all");
+            let mut outcome = json!({});
+            if images || all {
+                println!("{}", "Removing images...".bold().bright_blue());
+                outcome["images"] = serde_json::to_value(clean::images(&orchestrator)?)?;
+            }
+            if results || all {
+                println!("{}", "Clearing results...".bold().bright_blue());
+                outcome["results_removed"] = clean::results(&orchestrator)?.into();
+            }
+            outcome
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_star_matches_at_either_end() {
+        assert!(glob_match("iss_*", "iss_equator"));
+        assert!(glob_match("*_equator", "iss_equator"));
+        assert!(glob_match("*equator*", "iss_equator_pass"));
+        assert!(glob_match("iss_*", "iss_"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("iss_*", "geo_iss_equator"));
+        assert!(!glob_match("*_equator", "iss_equator_pass"));
+    }
+
+    #[test]
+    fn glob_matches_several_stars() {
+        assert!(glob_match("*_*_*", "005_molniya_moscow"));
+        assert!(glob_match("0*m*w", "005_molniya_moscow"));
+        assert!(glob_match("**molniya**", "005_molniya_moscow"));
+        // The first `*` must give back what it took for the rest to match
+        assert!(glob_match("*a*a", "banana"));
+        assert!(!glob_match("*_*_*_*", "005_molniya_moscow"));
+        assert!(!glob_match("*molniya*tokyo*", "005_molniya_moscow"));
     }
 
-    Ok(())
+    #[test]
+    fn glob_question_mark_matches_one_character() {
+        assert!(glob_match("00?_geo", "001_geo"));
+        assert!(glob_match("???", "abc"));
+        assert!(glob_match("?*", "a"));
+        assert!(!glob_match("???", "ab"));
+        assert!(!glob_match("???", "abcd"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn glob_without_wildcards_matches_exactly() {
+        assert!(glob_match("001_iss", "001_iss"));
+        assert!(!glob_match("001_iss", "001_is"));
+        assert!(!glob_match("001_iss", "001_iss_2"));
+        assert!(!glob_match("001_ISS", "001_iss"));
+        assert!(!glob_match("", "001_iss"));
+    }
+
+    fn timeout(implementation: &str) -> error::Error {
+        error::Error::Timeout {
+            implementation: implementation.to_string(),
+            test_case: None,
+            seconds: 60,
+        }
+    }
+
+    fn run_error(implementation: &str, kind: &str) -> Value {
+        json!({ "implementation": implementation, "stage": "run", "kind": kind, "error": "" })
+    }
+
+    #[test]
+    fn runs_killed_and_nothing_else_fail_as_the_first_kill() {
+        let errors = [run_error("rust", "timeout"), run_error("java", "hung")];
+        let hung = error::Error::Hung {
+            implementation: "java".to_string(),
+            test_case: None,
+            seconds: 30,
+        };
+        let failure = stage_failure(&errors, &[], vec![timeout("rust"), hung], &[]).unwrap();
+        assert_eq!((failure.kind(), failure.exit_code()), ("timeout", 7));
+    }
+
+    #[test]
+    fn other_run_failures_fail_as_runs() {
+        let errors = [run_error("rust", "timeout"), run_error("java", "other")];
+        let failure = stage_failure(&errors, &[], vec![timeout("rust")], &[]).unwrap();
+        assert_eq!((failure.kind(), failure.exit_code()), ("run_failed", 12));
+    }
 }
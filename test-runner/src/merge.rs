@@ -0,0 +1,322 @@
+//! Reports comparing results from several hosts.
+//!
+//! Each host runs the suite with its own `--profile` (see [`profiles`]),
+//! then its `results/` directory is copied to one machine, and `merge`
+//! reads them all:
+//!
+//! ```text
+//! visibility-test-runner merge ../results ~/pi-results
+//! ```
+//!
+//! For every profile and implementation it takes the latest run in the run
+//! artifacts (`results/<run-id>/<impl>/`), so it needs no flat result files
+//! from the other hosts. Verdicts come from the run's `verdict.json` where
+//! it validated, and are otherwise judged against this tree's reference as
+//! `report` does. Runs without a profile are labelled by hostname; runs
+//! from before hosts were recorded are skipped.
+//!
+//! [`profiles`]: crate::profiles
+
+use crate::events::Verdict;
+use crate::history;
+use crate::profiles::Host;
+use crate::report::{self, ReportFormat, STYLE, THEME_SCRIPT, agreement_class, bar_chart, escape, joules, memory, seconds};
+use crate::usage::Usage;
+use crate::{Orchestrator, VisibilityResult, read_result};
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One implementation's latest run under one profile.
+struct Entry {
+    host: Host,
+    run_id: String,
+    /// The results directory the run was found in
+    source: PathBuf,
+    cases: usize,
+    matched: usize,
+    with_reference: usize,
+    times: Vec<f64>,
+    usage: Option<Usage>,
+}
+
+impl Entry {
+    fn average_time(&self) -> Option<f64> {
+        (!self.times.is_empty()).then(|| self.times.iter().sum::<f64>() / self.times.len() as f64)
+    }
+
+    fn joules_per_case(&self) -> Option<f64> {
+        self.usage.as_ref()?.joules_per_case(self.host.watts_per_core)
+    }
+}
+
+/// Entries by implementation, then by profile label.
+type Entries = BTreeMap<String, BTreeMap<String, Entry>>;
+
+pub fn generate(orchestrator: &Orchestrator, results_dirs: &[PathBuf], format: ReportFormat) -> Result<String> {
+    let judge = report::Judge::new(orchestrator)?;
+    let mut entries = Entries::new();
+    for results_dir in results_dirs {
+        collect(results_dir, &judge, &mut entries)
+            .with_context(|| format!("Failed to read runs in {}", results_dir.display()))?;
+    }
+    if entries.is_empty() {
+        bail!("No runs with a recorded host found; runs record one in results/<run-id>/host.json");
+    }
+
+    Ok(match format {
+        ReportFormat::Markdown => markdown(&entries),
+        ReportFormat::Html => html(&entries),
+    })
+}
+
+/// Add the runs in `results_dir` that are newer than those seen so far.
+fn collect(results_dir: &Path, judge: &report::Judge, entries: &mut Entries) -> Result<()> {
+    let mut run_dirs: Vec<PathBuf> = fs::read_dir(results_dir)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.join("host.json").is_file())
+        .collect();
+    run_dirs.sort();
+
+    for run_dir in run_dirs {
+        let host_file = run_dir.join("host.json");
+        let host: Host = serde_json::from_str(&fs::read_to_string(&host_file)?)
+            .with_context(|| format!("Failed to parse {}", host_file.display()))?;
+        let run_id = run_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        for impl_dir in subdirs(&run_dir)? {
+            let implementation = impl_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let previous = entries.get(&implementation).and_then(|by_label| by_label.get(host.label()));
+            if previous.is_some_and(|previous| previous.run_id >= run_id) {
+                continue;
+            }
+            let Some(entry) = read_entry(&impl_dir, judge, &host, &run_id, results_dir)? else {
+                continue;
+            };
+            entries.entry(implementation).or_default().insert(host.label().to_string(), entry);
+        }
+    }
+    Ok(())
+}
+
+/// The implementation's results in one run, if it wrote any.
+fn read_entry(impl_dir: &Path, judge: &report::Judge, host: &Host, run_id: &str, source: &Path) -> Result<Option<Entry>> {
+    let mut entry = Entry {
+        host: host.clone(),
+        run_id: run_id.to_string(),
+        source: source.to_path_buf(),
+        cases: 0,
+        matched: 0,
+        with_reference: 0,
+        times: Vec::new(),
+        usage: None,
+    };
+
+    for case_dir in subdirs(impl_dir)? {
+        let Some(result_file) = ["result.json", "result.json.gz"].iter().map(|name| case_dir.join(name)).find(|path| path.is_file())
+        else {
+            continue;
+        };
+        let result: VisibilityResult = serde_json::from_str(&read_result(&result_file)?)
+            .with_context(|| format!("Failed to parse {}", result_file.display()))?;
+        entry.cases += 1;
+        entry.times.extend(result.execution_time);
+
+        let verdict_file = case_dir.join("verdict.json");
+        let verdict = if verdict_file.is_file() {
+            let case: history::Case = serde_json::from_str(&fs::read_to_string(&verdict_file)?)
+                .with_context(|| format!("Failed to parse {}", verdict_file.display()))?;
+            case.verdict
+        } else {
+            judge.judge(&result)?.verdict
+        };
+        match verdict {
+            Verdict::Match => {
+                entry.matched += 1;
+                entry.with_reference += 1;
+            }
+            Verdict::Mismatch => entry.with_reference += 1,
+            Verdict::NoReference | Verdict::Unsupported => {}
+        }
+    }
+    if entry.cases == 0 {
+        return Ok(None);
+    }
+
+    let usage_file = impl_dir.join("usage.json");
+    if usage_file.is_file() {
+        entry.usage = Some(
+            serde_json::from_str(&fs::read_to_string(&usage_file)?)
+                .with_context(|| format!("Failed to parse {}", usage_file.display()))?,
+        );
+    }
+    Ok(Some(entry))
+}
+
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// The latest entry per profile label, for the hosts table.
+fn hosts(entries: &Entries) -> BTreeMap<&str, &Entry> {
+    let mut hosts: BTreeMap<&str, &Entry> = BTreeMap::new();
+    for (label, entry) in entries.values().flatten() {
+        let latest = hosts.entry(label).or_insert(entry);
+        if entry.run_id > latest.run_id {
+            *latest = entry;
+        }
+    }
+    hosts
+}
+
+/// Average time relative to the fastest profile for the implementation.
+fn relative(entry: &Entry, by_label: &BTreeMap<String, Entry>) -> String {
+    let fastest = by_label.values().filter_map(Entry::average_time).fold(f64::INFINITY, f64::min);
+    match entry.average_time() {
+        Some(time) if fastest > 0.0 && fastest.is_finite() => format!("×{:.1}", time / fastest),
+        _ => "-".to_string(),
+    }
+}
+
+fn markdown(entries: &Entries) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Cross-Host Comparison Report");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Generated {}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Hosts");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Profile | Host | Arch | CPU | Cores | W/core | Latest run |");
+    let _ = writeln!(out, "|---------|------|------|-----|-------|--------|------------|");
+    for (label, entry) in hosts(entries) {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {:.1} | {} ({}) |",
+            label,
+            entry.host.hostname,
+            entry.host.arch,
+            entry.host.cpu.as_deref().unwrap_or("-"),
+            entry.host.cpus,
+            entry.host.watts_per_core,
+            entry.run_id,
+            entry.source.display()
+        );
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Implementations");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "| Implementation | Profile | Cases | Matching reference | Avg time | Relative | CPU time | Energy/case | Peak memory |"
+    );
+    let _ = writeln!(
+        out,
+        "|----------------|---------|-------|--------------------|----------|----------|----------|-------------|-------------|"
+    );
+    for (implementation, by_label) in entries {
+        for (i, (label, entry)) in by_label.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {}/{} | {} | {} | {} | {} | {} |",
+                if i == 0 { implementation.as_str() } else { "" },
+                label,
+                entry.cases,
+                entry.matched,
+                entry.with_reference,
+                seconds(entry.average_time()),
+                relative(entry, by_label),
+                seconds(entry.usage.as_ref().map(|usage| usage.cpu_seconds)),
+                joules(entry.joules_per_case()),
+                memory(entry.usage.as_ref().and_then(|usage| usage.peak_memory))
+            );
+        }
+    }
+    out
+}
+
+fn html(entries: &Entries) -> String {
+    let mut body = String::new();
+    let _ = writeln!(body, "<h2>Hosts</h2>");
+    let _ = writeln!(body, "<table>");
+    let _ = writeln!(
+        body,
+        "<tr><th>Profile</th><th>Host</th><th>Arch</th><th>CPU</th><th>Cores</th><th>W/core</th><th>Latest run</th></tr>"
+    );
+    for (label, entry) in hosts(entries) {
+        let _ = writeln!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td title=\"{}\">{}</td></tr>",
+            escape(label),
+            escape(&entry.host.hostname),
+            escape(&entry.host.arch),
+            escape(entry.host.cpu.as_deref().unwrap_or("-")),
+            entry.host.cpus,
+            entry.host.watts_per_core,
+            escape(&entry.source.display().to_string()),
+            escape(&entry.run_id)
+        );
+    }
+    let _ = writeln!(body, "</table>");
+
+    let _ = writeln!(body, "<h2>Implementations</h2>");
+    let _ = writeln!(body, "<table>");
+    let _ = writeln!(
+        body,
+        "<tr><th>Implementation</th><th>Profile</th><th>Cases</th><th>Matching reference</th><th>Avg time</th><th>Relative</th><th>CPU time</th><th>Energy/case</th><th>Peak memory</th></tr>"
+    );
+    for (implementation, by_label) in entries {
+        for (i, (label, entry)) in by_label.iter().enumerate() {
+            let name = if i == 0 {
+                format!("<td rowspan=\"{}\">{}</td>", by_label.len(), escape(implementation))
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                body,
+                "<tr>{}<td>{}</td><td>{}</td><td class=\"{}\">{}/{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                name,
+                escape(label),
+                entry.cases,
+                agreement_class(entry.matched, entry.with_reference),
+                entry.matched,
+                entry.with_reference,
+                seconds(entry.average_time()),
+                relative(entry, by_label),
+                seconds(entry.usage.as_ref().map(|usage| usage.cpu_seconds)),
+                joules(entry.joules_per_case()),
+                memory(entry.usage.as_ref().and_then(|usage| usage.peak_memory))
+            );
+        }
+    }
+    let _ = writeln!(body, "</table>");
+
+    let labels: Vec<(String, f64)> = entries
+        .iter()
+        .flat_map(|(implementation, by_label)| {
+            by_label
+                .iter()
+                .filter_map(move |(label, entry)| Some((format!("{} ({})", implementation, label), entry.average_time()?)))
+        })
+        .collect();
+    if !labels.is_empty() {
+        let _ = writeln!(body, "<h2>Execution Time</h2>");
+        let bars: Vec<(&str, f64)> = labels.iter().map(|(label, time)| (label.as_str(), *time)).collect();
+        body.push_str(&bar_chart(&bars));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Cross-Host Comparison Report</title>\n<style>\n{}\n</style>\n<script>\n{}\n</script>\n</head>\n<body>\n<button id=\"theme-toggle\" type=\"button\">Toggle dark mode</button>\n<h1>Cross-Host Comparison Report</h1>\n<p>Generated {}</p>\n{}</body>\n</html>\n",
+        STYLE,
+        THEME_SCRIPT,
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        body
+    )
+}
@@ -0,0 +1,277 @@
+//! Failure minimization.
+//!
+//! Narrows a test case on which an implementation disagrees with the
+//! reference (different visibility window counts, the first check of
+//! validation) to the smallest time window that still disagrees, and emits
+//! it as a new focused test case.
+//!
+//! Each probe runs the implementation and the reference on a candidate
+//! window, in a scratch workspace under `results/.minimize/` so probe
+//! results never mix with real ones. The start is moved as late as possible
+//! by binary search over time steps, then the end as early as possible.
+
+use crate::events::EventSink;
+use crate::progress::Progress;
+use crate::{Implementation, Orchestrator, VisibilityResult, read_result};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use colored::Colorize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub struct Minimized {
+    /// The focused test case
+    pub case: Value,
+    /// The focused window, as RFC 3339 times
+    pub start: String,
+    pub end: String,
+    pub result_windows: usize,
+    pub reference_windows: usize,
+    /// The reference's result for the focused case
+    pub reference_result: String,
+}
+
+/// Window counts of the implementation and the reference for one window.
+struct Probe {
+    result_windows: usize,
+    reference_windows: usize,
+    reference_result: String,
+}
+
+impl Probe {
+    fn disagrees(&self) -> bool {
+        self.result_windows != self.reference_windows
+    }
+}
+
+struct Workspace<'a> {
+    orchestrator: Orchestrator,
+    implementation: &'a Implementation,
+    reference: &'a Implementation,
+    /// The original case with TLE set references resolved
+    case: Value,
+    name: String,
+}
+
+impl Workspace<'_> {
+    /// The case renamed and narrowed to `start..end`.
+    fn narrowed(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Value {
+        let mut case = self.case.clone();
+        case["name"] = Value::from(self.name.as_str());
+        case["timeWindow"]["start"] = Value::from(format_time(start));
+        case["timeWindow"]["end"] = Value::from(format_time(end));
+        // Expectations were written for the original window
+        if let Some(case) = case.as_object_mut() {
+            case.remove("expectedWindows");
+        }
+        case
+    }
+
+    fn probe(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Probe> {
+        let case = self.narrowed(start, end);
+        let cases_dir = self.orchestrator.test_data_dir.join("cases");
+        fs::write(cases_dir.join(format!("{}.json", self.name)), serde_json::to_string_pretty(&case)?)?;
+
+        let (result_windows, _) = self.run(self.implementation)?;
+        let (reference_windows, reference_result) = self.run(self.reference)?;
+        let probe = Probe {
+            result_windows,
+            reference_windows,
+            reference_result,
+        };
+
+        let verdict = if probe.disagrees() { "disagrees".red() } else { "agrees".green() };
+        println!(
+            "  {} .. {}: {} window(s) vs reference {} - {}",
+            format_time(start),
+            format_time(end),
+            probe.result_windows,
+            probe.reference_windows,
+            verdict
+        );
+        Ok(probe)
+    }
+
+    /// Run one implementation on the probe case; its window count and raw result.
+    fn run(&self, impl_: &Implementation) -> Result<(usize, String)> {
+        let cases = Some(std::slice::from_ref(&self.name));
+        let result = self.orchestrator.run_tests(impl_, cases)?;
+        if let Some(e) = self.orchestrator.killed_error(impl_, &result, cases) {
+            return Err(e.into());
+        }
+        if !result.success {
+            bail!("{} failed on a probe case:\n{}", impl_.name, result.stderr);
+        }
+        let path = result
+            .result_files
+            .last()
+            .with_context(|| format!("{} wrote no result for the probe case", impl_.name))?;
+
+        let content = read_result(path)?;
+        let parsed: VisibilityResult = serde_json::from_str(&content)?;
+        // Only the newest probe result matters
+        for path in &result.result_files {
+            fs::remove_file(path)?;
+        }
+        Ok((parsed.visibility_windows.len(), content))
+    }
+}
+
+pub fn run(
+    orchestrator: &Orchestrator,
+    implementation: &Implementation,
+    reference: &Implementation,
+    test_case: &str,
+    name: &str,
+) -> Result<Minimized> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let case_path = cases_dir.join(format!("{}.json", test_case));
+    let case: Value = serde_json::from_str(
+        &fs::read_to_string(&case_path).with_context(|| format!("Failed to read {}", case_path.display()))?,
+    )?;
+
+    let window = &case["timeWindow"];
+    let parse = |field: &str| {
+        window[field]
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .with_context(|| format!("{} has no valid timeWindow.{}", test_case, field))
+    };
+    let (start, end) = (parse("start")?, parse("end")?);
+    let step_ms = window["step"]
+        .as_f64()
+        .map(|s| (s * 1000.0).round() as i64)
+        .filter(|&ms| ms > 0)
+        .with_context(|| format!("{} has no valid timeWindow.step", test_case))?;
+    let step = Duration::milliseconds(step_ms);
+    let steps = (end - start).num_milliseconds() / step_ms;
+
+    let workspace = Workspace {
+        orchestrator: scratch_orchestrator(orchestrator)?,
+        implementation,
+        reference,
+        case,
+        name: name.to_string(),
+    };
+
+    println!("Checking that {} disagrees with {} on {}...", implementation.name, reference.name, test_case);
+    let mut best = workspace.probe(start, end)?;
+    if !best.disagrees() {
+        bail!("{} agrees with {} on {}; nothing to minimize", implementation.name, reference.name, test_case);
+    }
+
+    // Latest start that still disagrees: invariant lo disagrees, hi doesn't
+    // (or is past the last step)
+    println!("Moving the start...");
+    let (mut lo, mut hi) = (0, steps);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        let probe = workspace.probe(start + step * mid as i32, end)?;
+        if probe.disagrees() {
+            lo = mid;
+            best = probe;
+        } else {
+            hi = mid;
+        }
+    }
+    let new_start = start + step * lo as i32;
+
+    // Earliest end that still disagrees, at least one step after the start
+    println!("Moving the end...");
+    let span = steps - lo;
+    let (mut lo, mut hi) = (0, span);
+    let mut best_end = end;
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        let candidate = new_start + step * mid as i32;
+        let probe = workspace.probe(new_start, candidate)?;
+        if probe.disagrees() {
+            hi = mid;
+            best = probe;
+            best_end = candidate;
+        } else {
+            lo = mid;
+        }
+    }
+
+    let mut case = workspace.narrowed(new_start, best_end);
+    case["description"] = Value::from(format!(
+        "Minimized from {}: {} reports {} window(s), {} reports {}",
+        test_case, implementation.name, best.result_windows, reference.name, best.reference_windows
+    ));
+    if let Some(tags) = case.pointer_mut("/metadata/tags").and_then(Value::as_array_mut) {
+        tags.push(Value::from("minimized"));
+    }
+
+    fs::remove_dir_all(scratch_dir(orchestrator)).ok();
+
+    Ok(Minimized {
+        case,
+        start: format_time(new_start),
+        end: format_time(best_end),
+        result_windows: best.result_windows,
+        reference_windows: best.reference_windows,
+        reference_result: best.reference_result,
+    })
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn scratch_dir(orchestrator: &Orchestrator) -> PathBuf {
+    orchestrator.results_dir.join(".minimize")
+}
+
+/// An orchestrator over a fresh copy of the test data without any cases,
+/// writing results to its own directory.
+fn scratch_orchestrator(orchestrator: &Orchestrator) -> Result<Orchestrator> {
+    let dir = scratch_dir(orchestrator);
+    fs::remove_dir_all(&dir).ok();
+
+    let test_data_dir = dir.join("test-data");
+    copy_dir(&orchestrator.test_data_dir, &test_data_dir, &["cases", "reference-results"])?;
+    fs::create_dir_all(test_data_dir.join("cases"))?;
+    let results_dir = dir.join("results");
+    fs::create_dir_all(&results_dir)?;
+
+    Ok(Orchestrator {
+        implementations_dir: orchestrator.implementations_dir.clone(),
+        test_data_dir,
+        results_dir,
+        events: EventSink::disabled(),
+        // Probes print one line each; bars would flicker past
+        progress: Progress::new(false),
+        profile: orchestrator.profile.clone(),
+        runtime: orchestrator.runtime,
+        watts_per_core: orchestrator.watts_per_core,
+        isolate_cases: orchestrator.isolate_cases,
+        timeout: orchestrator.timeout,
+        heartbeat: orchestrator.heartbeat,
+        limits: orchestrator.limits,
+        // The scratch test data holds only the probe cases
+        tiers: Vec::new(),
+        running: Default::default(),
+    })
+}
+
+/// Copy a directory tree, leaving out the top-level entries in `skip`.
+/// Containers can't follow symlinks out of a mount, so this is a real copy.
+fn copy_dir(from: &Path, to: &Path, skip: &[&str]) -> Result<()> {
+    let entries = WalkDir::new(from).into_iter().filter_entry(|entry| {
+        entry.depth() != 1 || !skip.iter().any(|name| entry.file_name() == *name)
+    });
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target).with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+//! OpenMetrics summary of a run, `results/<run-id>/metrics.txt`.
+//!
+//! Rewritten whenever [`RunArtifacts`](crate::artifacts::RunArtifacts) files
+//! a container or a validation, so it always covers the run so far. Every
+//! sample carries the run's start time as its timestamp, so a scraper or
+//! `promtool tsdb create-blocks-from openmetrics` can ingest old runs where
+//! they happened. Per implementation:
+//!
+//! - `visibility_containers` - containers by `status` (`succeeded`,
+//!   `failed`, or `timed_out`)
+//! - `visibility_container_seconds` - wall-clock time of its containers
+//! - `visibility_cpu_seconds`, `visibility_peak_memory_bytes`, and
+//!   `visibility_energy_joules` - where the containers' cgroups could be
+//!   read (see [`usage`](crate::usage))
+//! - `visibility_case_execution_seconds` - the `executionTime` each result
+//!   reports, by `test_case`
+//! - `visibility_cases` - validated cases by `verdict`
+//! - `visibility_pass_rate` and `visibility_score` - as the
+//!   [quality gates](crate::gate) compute them, once a case can be judged
+//!
+//! `visibility_run_info` labels the run with its ID and the host it ran on.
+
+use crate::database::Database;
+use crate::events::Verdict;
+use crate::gate;
+use crate::profiles::Host;
+use crate::read_result;
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Samples of one metric family: labels and value.
+type Samples = Vec<(Vec<(&'static str, String)>, f64)>;
+
+/// Verdicts of one implementation's cases in the run.
+#[derive(Default)]
+struct Verdicts {
+    /// By verdict as serialized, e.g. `no_reference`
+    counts: BTreeMap<String, usize>,
+    cells: usize,
+    matched: usize,
+    total_score: f64,
+}
+
+/// Write `results/<run_id>/metrics.txt` from what the run has recorded.
+pub fn write(results_dir: &Path, run_id: &str) -> Result<()> {
+    let run_dir = results_dir.join(run_id);
+    let host_path = run_dir.join("host.json");
+    let host: Host = serde_json::from_str(&fs::read_to_string(&host_path).with_context(|| format!("Failed to read {}", host_path.display()))?)
+        .with_context(|| format!("Failed to parse {}", host_path.display()))?;
+    let database = Database::open(results_dir)?;
+    let timestamp = database
+        .started_at(run_id)?
+        .and_then(|started| DateTime::parse_from_rfc3339(&started).ok())
+        .map(|started| started.timestamp());
+
+    let mut containers: Samples = Vec::new();
+    let mut container_seconds: BTreeMap<String, f64> = BTreeMap::new();
+    let mut cpu_seconds: BTreeMap<String, f64> = BTreeMap::new();
+    let mut peak_memory: BTreeMap<String, u64> = BTreeMap::new();
+    let mut statuses: BTreeMap<(String, &'static str), usize> = BTreeMap::new();
+    for container in database.containers(run_id)? {
+        let status = if container.timed_out {
+            "timed_out"
+        } else if container.success {
+            "succeeded"
+        } else {
+            "failed"
+        };
+        *statuses.entry((container.implementation.clone(), status)).or_default() += 1;
+        *container_seconds.entry(container.implementation.clone()).or_default() += container.execution_time;
+        if let Some(cpu) = container.cpu_seconds {
+            *cpu_seconds.entry(container.implementation.clone()).or_default() += cpu;
+        }
+        if let Some(bytes) = container.peak_memory {
+            let peak = peak_memory.entry(container.implementation).or_default();
+            *peak = (*peak).max(bytes);
+        }
+    }
+    for ((implementation, status), count) in statuses {
+        containers.push((vec![("implementation", implementation), ("status", status.to_string())], count as f64));
+    }
+
+    // Each case's result copy and verdict, as the run filed them
+    let mut case_seconds: Samples = Vec::new();
+    let mut verdicts: BTreeMap<String, Verdicts> = BTreeMap::new();
+    for impl_dir in sorted_dirs(&run_dir)? {
+        let implementation = impl_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        for case_dir in sorted_dirs(&impl_dir)? {
+            let test_case = case_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let result = ["result.json", "result.json.gz"].iter().map(|name| case_dir.join(name)).find(|path| path.is_file());
+            if let Some(path) = result
+                && let Ok(result) = serde_json::from_str::<Value>(&read_result(&path)?)
+                && let Some(seconds) = result["executionTime"].as_f64()
+            {
+                case_seconds.push((vec![("implementation", implementation.clone()), ("test_case", test_case.clone())], seconds));
+            }
+
+            let Ok(content) = fs::read_to_string(case_dir.join("verdict.json")) else {
+                continue;
+            };
+            let verdict: Value = serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", case_dir.join("verdict.json").display()))?;
+            let kind: Verdict = serde_json::from_value(verdict["verdict"].clone()).context("Failed to parse recorded verdict")?;
+            let tally = verdicts.entry(implementation.clone()).or_default();
+            *tally.counts.entry(verdict["verdict"].as_str().unwrap_or_default().to_string()).or_default() += 1;
+            if verdict["expected_failure"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            let off: BTreeSet<u64> = verdict["deltas"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|delta| delta["window"].as_u64())
+                .collect();
+            let result_windows = verdict["result_windows"].as_u64().unwrap_or_default() as usize;
+            let reference_windows = verdict["reference_windows"].as_u64().map(|windows| windows as usize);
+            if let Some(score) = gate::score(kind, result_windows, reference_windows, off.len()) {
+                tally.cells += 1;
+                tally.matched += usize::from(kind == Verdict::Match);
+                tally.total_score += score;
+            }
+        }
+    }
+
+    let by_implementation = |values: BTreeMap<String, f64>| -> Samples {
+        values
+            .into_iter()
+            .map(|(implementation, value)| (vec![("implementation", implementation)], value))
+            .collect()
+    };
+    let energy = cpu_seconds.iter().map(|(name, cpu)| (name.clone(), cpu * host.watts_per_core)).collect();
+    let mut cases: Samples = Vec::new();
+    let mut pass_rate = BTreeMap::new();
+    let mut score = BTreeMap::new();
+    for (implementation, tally) in &verdicts {
+        for (verdict, count) in &tally.counts {
+            cases.push((vec![("implementation", implementation.clone()), ("verdict", verdict.clone())], *count as f64));
+        }
+        if tally.cells > 0 {
+            pass_rate.insert(implementation.clone(), tally.matched as f64 / tally.cells as f64);
+            score.insert(implementation.clone(), tally.total_score / tally.cells as f64);
+        }
+    }
+
+    let info = vec![(
+        vec![
+            ("run_id", run_id.to_string()),
+            ("hostname", host.hostname.clone()),
+            ("arch", host.arch.clone()),
+            ("profile", host.profile.clone().unwrap_or_default()),
+        ],
+        1.0,
+    )];
+    let families: [(&str, &str, Option<&str>, &str, Samples); 10] = [
+        ("visibility_run", "info", None, "The run and the host it ran on", info),
+        ("visibility_containers", "gauge", None, "Test containers by exit status", containers),
+        ("visibility_container_seconds", "gauge", Some("seconds"), "Wall-clock time of the test containers", by_implementation(container_seconds)),
+        ("visibility_cpu_seconds", "gauge", Some("seconds"), "CPU time of the test containers", by_implementation(cpu_seconds.clone())),
+        (
+            "visibility_peak_memory_bytes",
+            "gauge",
+            Some("bytes"),
+            "Highest peak memory of any test container",
+            by_implementation(peak_memory.into_iter().map(|(name, bytes)| (name, bytes as f64)).collect()),
+        ),
+        (
+            "visibility_energy_joules",
+            "gauge",
+            Some("joules"),
+            "Energy estimated from CPU time and the host's watts per core",
+            by_implementation(energy),
+        ),
+        ("visibility_case_execution_seconds", "gauge", Some("seconds"), "Execution time each result reports", case_seconds),
+        ("visibility_cases", "gauge", None, "Validated test cases by verdict", cases),
+        ("visibility_pass_rate", "gauge", None, "Share of judged cases that match the reference", by_implementation(pass_rate)),
+        ("visibility_score", "gauge", None, "Mean score of judged cases, with partial credit for windows within tolerance", by_implementation(score)),
+    ];
+
+    let mut text = String::new();
+    for (name, kind, unit, help, samples) in families {
+        if samples.is_empty() {
+            continue;
+        }
+        writeln!(text, "# TYPE {} {}", name, kind)?;
+        if let Some(unit) = unit {
+            writeln!(text, "# UNIT {} {}", name, unit)?;
+        }
+        writeln!(text, "# HELP {} {}", name, help)?;
+        let sample_name = if kind == "info" { format!("{}_info", name) } else { name.to_string() };
+        for (labels, value) in samples {
+            let labels: Vec<String> = labels.iter().map(|(label, value)| format!("{}=\"{}\"", label, escape(value))).collect();
+            write!(text, "{}{{{}}} {}", sample_name, labels.join(","), value)?;
+            if let Some(timestamp) = timestamp {
+                write!(text, " {}", timestamp)?;
+            }
+            text.push('\n');
+        }
+    }
+    text.push_str("# EOF\n");
+
+    let path = run_dir.join("metrics.txt");
+    fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Subdirectories of `dir`, by name.
+fn sorted_dirs(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut dirs: Vec<_> = fs::read_dir(dir)?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// A label value with `\`, `"`, and newlines escaped.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,121 @@
+//! Orbital elements of a test case's satellite, from its TLE.
+//!
+//! Line 2 gives the inclination, eccentricity, and mean motion, and from
+//! them the approximate period, perigee, and apogee (two-body, ignoring
+//! drag and the bulge of the Earth, so to within a few kilometres).
+//!
+//! The orbit regime decides a case's default validation tolerances (see
+//! [`tolerance::for_case`](crate::tolerance::for_case)): a geostationary
+//! satellite hardly moves across the sky, so where its elevation crosses
+//! the minimum, and with it the window boundaries, depends heavily on the
+//! time step, while a LEO pass rises and sets within seconds of the
+//! reference. Reports group test cases by it.
+//!
+//! Classified from eccentricity and mean motion:
+//!
+//! - HEO: eccentricity of 0.25 or more (Molniya, Tundra)
+//! - GEO: about one revolution per day and nearly circular
+//! - LEO: a period of 128 minutes or less (11.25 revolutions per day)
+//! - MEO: anything else (GPS, Galileo)
+
+use serde::Serialize;
+use serde_json::Value;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Earth's gravitational parameter, km³/s² (WGS-84)
+const MU: f64 = 398_600.441_8;
+/// Earth's equatorial radius, km (WGS-84)
+const EARTH_RADIUS: f64 = 6378.137;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Regime {
+    #[serde(rename = "LEO")]
+    Leo,
+    #[serde(rename = "MEO")]
+    Meo,
+    #[serde(rename = "GEO")]
+    Geo,
+    #[serde(rename = "HEO")]
+    Heo,
+}
+
+/// The mean elements of TLE line 2 the rest derive from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elements {
+    /// Degrees
+    pub inclination: f64,
+    pub eccentricity: f64,
+    /// Revolutions per day
+    pub mean_motion: f64,
+}
+
+impl Elements {
+    /// Parse TLE line 2, or `None` if it can't be parsed.
+    pub fn from_tle_line2(line: &str) -> Option<Self> {
+        Some(Elements {
+            inclination: line.get(8..16)?.trim().parse().ok()?,
+            eccentricity: format!("0.{}", line.get(26..33)?.trim()).parse().ok()?,
+            mean_motion: line.get(52..63)?.trim().parse().ok()?,
+        })
+    }
+
+    /// The elements of a case's `satellite`, once TLE set references are
+    /// resolved.
+    pub fn of_case(case: &Value) -> Option<Self> {
+        let tle = case["satellite"]["tle"].as_array()?;
+        Self::from_tle_line2(tle.iter().filter_map(Value::as_str).find(|line| line.starts_with("2 "))?)
+    }
+
+    pub fn regime(&self) -> Regime {
+        if self.eccentricity >= 0.25 {
+            Regime::Heo
+        } else if (0.9..=1.1).contains(&self.mean_motion) && self.eccentricity < 0.1 {
+            Regime::Geo
+        } else if self.mean_motion >= 11.25 {
+            Regime::Leo
+        } else {
+            Regime::Meo
+        }
+    }
+
+    /// Minutes
+    pub fn period(&self) -> f64 {
+        1440.0 / self.mean_motion
+    }
+
+    /// Kilometres, from the centre of the Earth
+    pub fn semi_major_axis(&self) -> f64 {
+        let radians_per_second = self.mean_motion * 2.0 * PI / 86_400.0;
+        (MU / radians_per_second.powi(2)).cbrt()
+    }
+
+    /// Altitude of the lowest point, in kilometres
+    pub fn perigee(&self) -> f64 {
+        self.semi_major_axis() * (1.0 - self.eccentricity) - EARTH_RADIUS
+    }
+
+    /// Altitude of the highest point, in kilometres
+    pub fn apogee(&self) -> f64 {
+        self.semi_major_axis() * (1.0 + self.eccentricity) - EARTH_RADIUS
+    }
+}
+
+impl Regime {
+    /// The regime of a case's `satellite`, once TLE set references are
+    /// resolved.
+    pub fn of_case(case: &Value) -> Option<Self> {
+        Elements::of_case(case).map(|elements| elements.regime())
+    }
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Regime::Leo => "LEO",
+            Regime::Meo => "MEO",
+            Regime::Geo => "GEO",
+            Regime::Heo => "HEO",
+        })
+    }
+}
@@ -0,0 +1,82 @@
+//! Machine-readable output for `--json`.
+//!
+//! Commands print the same colored text either way, but with `--json` it
+//! goes to stderr, and stdout carries a single JSON document describing
+//! the outcome once the command finishes:
+//!
+//! ```text
+//! {"command": "validate", "ok": true, "summaries": [...]}
+//! {"command": "run", "ok": false, "kind": "implementation_not_found", "error": "Implementation not found: foo"}
+//! ```
+//!
+//! `ok` says whether the command completed, not whether the tests passed;
+//! per-implementation and per-case outcomes are in the command's fields.
+//! A failed command's `kind` says what went wrong (see
+//! [`error`](crate::error)).
+//! Output a command would otherwise print to stdout (a report without
+//! `--output`, say) is carried in the document's `content` instead.
+
+use crate::error;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::AsFd;
+
+pub struct JsonOutput {
+    /// The process's original stdout
+    stdout: File,
+}
+
+impl JsonOutput {
+    /// Keep the current stdout for the document and point stdout at stderr
+    /// from here on, for this process and the processes it starts.
+    pub fn redirect() -> Result<Self> {
+        io::stdout().flush()?;
+        let stdout = io::stdout().as_fd().try_clone_to_owned().context("Failed to duplicate stdout")?;
+        // SAFETY: dup2 only swaps which file descriptor 1 refers to; Rust's
+        // stdout handle writes to descriptor 1 and was flushed above.
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(io::Error::last_os_error()).context("Failed to redirect stdout to stderr");
+        }
+        Ok(Self {
+            stdout: File::from(stdout),
+        })
+    }
+
+    /// Write the document for `command`: the fields of `outcome`, or its
+    /// error, after the fields of a command that ran to the end.
+    pub fn write(mut self, command: &str, outcome: &Result<Value>) -> Result<()> {
+        io::stdout().flush()?;
+        let mut document = Map::new();
+        document.insert("command".to_string(), command.into());
+        match outcome {
+            Ok(value) => {
+                document.insert("ok".to_string(), true.into());
+                match value {
+                    Value::Object(fields) => document.extend(fields.clone()),
+                    Value::Null => {}
+                    other => {
+                        document.insert("result".to_string(), other.clone());
+                    }
+                }
+            }
+            Err(e) => {
+                if let Some(error::Failed {
+                    fields: Value::Object(fields),
+                    ..
+                }) = e.downcast_ref()
+                {
+                    document.extend(fields.clone());
+                }
+                document.insert("ok".to_string(), false.into());
+                document.insert("kind".to_string(), error::kind(e).into());
+                document.insert("error".to_string(), format!("{:#}", e).into());
+            }
+        }
+        serde_json::to_writer_pretty(&mut self.stdout, &document)?;
+        writeln!(self.stdout)?;
+        self.stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Pass tables in the style of gpredict and the AMSAT pass predictor.
+//!
+//! One row per visibility window of an implementation's latest results:
+//! AOS, TCA (time of maximum elevation), and LOS, the pass duration, the
+//! maximum elevation, and the azimuths at AOS and LOS. Azimuths come from
+//! the window's first and last sampled points; values a result doesn't
+//! carry are shown as `-`.
+
+use crate::time_display::Zone;
+use crate::{Orchestrator, read_result};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+
+const TIME_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+
+pub fn render(orchestrator: &Orchestrator, impl_name: &str, test_case: Option<&str>, zone: Option<&Zone>) -> Result<String> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let time = |time: DateTime<Utc>| match zone {
+        Some(zone) => zone.format_as(time, TIME_FORMAT),
+        None => time.format(TIME_FORMAT).to_string(),
+    };
+    let rule = "-".repeat(96);
+
+    let mut out = String::new();
+    for path in orchestrator.collect_results(impl_name)? {
+        let result: Value = serde_json::from_str(&read_result(&path)?)?;
+        let Some(name) = result["testCase"].as_str() else {
+            continue;
+        };
+        if test_case.is_some_and(|tc| tc != name) {
+            continue;
+        }
+
+        let case: Value = fs::read_to_string(cases_dir.join(format!("{}.json", name)))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let satellite = case["satellite"]["name"].as_str().unwrap_or(name);
+        let observer = &case["observer"];
+        let location = match (observer["latitude"].as_f64(), observer["longitude"].as_f64()) {
+            (Some(lat), Some(lon)) => format!(" ({:.4}, {:.4})", lat, lon),
+            _ => String::new(),
+        };
+
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        writeln!(
+            out,
+            "Pass list for {} from {}{}",
+            satellite,
+            observer["name"].as_str().unwrap_or("observer"),
+            location
+        )?;
+        writeln!(out, "Test case: {}, implementation: {}, times: {}", name, impl_name, zone_label(zone))?;
+        writeln!(out, "{}", rule)?;
+        writeln!(
+            out,
+            " {:<21} {:<21} {:<21} {:>8}  {:>6}  {:>6}  {:>6}",
+            "AOS", "TCA", "LOS", "Duration", "Max El", "AOS Az", "LOS Az"
+        )?;
+        writeln!(out, "{}", rule)?;
+
+        for window in result["visibilityWindows"].as_array().into_iter().flatten() {
+            let (Some(aos), Some(los)) = (parse_time(&window["start"]), parse_time(&window["end"])) else {
+                continue;
+            };
+            let tca = parse_time(&window["maxElevationTime"]).map(time).unwrap_or_else(|| "-".to_string());
+            let points = window["points"].as_array();
+            let azimuth = |point: Option<&Value>| degrees(point.and_then(|p| p["azimuth"].as_f64()));
+
+            writeln!(
+                out,
+                " {:<21} {:<21} {:<21} {:>8}  {:>6}  {:>6}  {:>6}",
+                time(aos),
+                tca,
+                time(los),
+                clock((los - aos).num_seconds()),
+                degrees(window["maxElevation"].as_f64()),
+                azimuth(points.and_then(|p| p.first())),
+                azimuth(points.and_then(|p| p.last()))
+            )?;
+        }
+        writeln!(out, "{}", rule)?;
+    }
+
+    if out.is_empty() {
+        match test_case {
+            Some(tc) => bail!("No results for {} on {}", impl_name, tc),
+            None => bail!("No results for {}", impl_name),
+        }
+    }
+    Ok(out)
+}
+
+fn zone_label(zone: Option<&Zone>) -> String {
+    match zone {
+        None => "UTC".to_string(),
+        Some(Zone::Local) => "local".to_string(),
+        Some(Zone::Fixed(offset)) => format!("UTC{}", offset),
+        Some(Zone::Named(tz)) => tz.name().to_string(),
+    }
+}
+
+/// Whole seconds as `HH:MM:SS`.
+fn clock(seconds: i64) -> String {
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+fn degrees(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+}
+
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
@@ -0,0 +1,80 @@
+//! Case payloads for `--isolate-cases`.
+//!
+//! By default a container gets all of `test-data` mounted read-only, so an
+//! implementation that lists `/test-data/cases` itself, rather than taking
+//! the case it was given, runs cases the orchestrator didn't select. With
+//! `--isolate-cases`, each container instead gets a fresh directory holding
+//! only its selected case files, mounted as `/test-data`:
+//!
+//! ```text
+//! results/.payload/<impl>-<case>-<pid>/
+//! └── cases/
+//!     └── 001_iss_nyc.json
+//! ```
+//!
+//! `test-data/eop`, which implementations read alongside the cases, is
+//! mounted over it when present. The directory is removed once the
+//! container exits.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct Payload {
+    dir: PathBuf,
+}
+
+impl Payload {
+    /// Copy the selected `cases`, or every case, from `cases_dir` (the
+    /// staged cases the container would otherwise see) into a new payload
+    /// for `implementation`'s container.
+    pub fn create(results_dir: &Path, implementation: &str, cases_dir: &Path, cases: Option<&[String]>) -> Result<Self> {
+        // Unique per container, since `all --jobs` runs several at once
+        let label = match cases {
+            Some([case]) => case.as_str(),
+            Some(_) => "selected",
+            None => "all",
+        };
+        let name = format!("{}-{}-{}", implementation, label, std::process::id());
+        let dir = results_dir.join(".payload").join(name);
+        let payload = Payload { dir };
+        fs::remove_dir_all(&payload.dir).ok();
+        let target = payload.dir.join("cases");
+        fs::create_dir_all(&target).with_context(|| format!("Failed to create {}", target.display()))?;
+
+        let mut copied = 0;
+        for entry in fs::read_dir(cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            if cases.is_some_and(|cases| !cases.iter().any(|case| *case == stem)) {
+                continue;
+            }
+            fs::copy(&path, target.join(path.file_name().unwrap_or_default()))
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+            copied += 1;
+        }
+        if let (Some(cases), 0) = (cases, copied) {
+            bail!("Test case {} not found in {}", cases.join(", "), cases_dir.display());
+        }
+        Ok(payload)
+    }
+
+    /// Bind mounts for the container: host path, container path, read-only.
+    pub fn mounts(&self, test_data_dir: &Path) -> Vec<(PathBuf, &'static str, bool)> {
+        let mut mounts = vec![(self.dir.clone(), "/test-data", true)];
+        let eop = test_data_dir.join("eop");
+        if eop.is_dir() {
+            mounts.push((eop, "/test-data/eop", true));
+        }
+        mounts
+    }
+}
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
@@ -0,0 +1,163 @@
+//! Profiles and the host a run executed on.
+//!
+//! A profile names a class of machine, such as a Raspberry Pi 4 or an x86
+//! workstation, so the same implementations can be compared across
+//! hardware, or a kind of invocation, such as `ci` or `benchmark`, bundling
+//! the options it always needs. Profiles are listed in
+//! `test-data/profiles.json`:
+//!
+//! ```json
+//! [
+//!   {"name": "rpi4", "description": "Raspberry Pi 4 Model B, 4 GB", "arch": "aarch64", "wattsPerCore": 1.0},
+//!   {"name": "ci", "timeout": 600, "isolateCases": true, "format": "junit"}
+//! ]
+//! ```
+//!
+//! `--profile <name>` tags a run with one, and its fields stand in for the
+//! command-line options not given. Every run records its host in
+//! `results/<run-id>/host.json`, with the profile if given, which
+//! [`merge`](crate::merge) uses to put results from different hosts side by
+//! side.
+
+use crate::junit::Format;
+use crate::runtime::{Limits, RuntimeKind};
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Expected CPU architecture, as in `uname -m` (`x86_64`, `aarch64`)
+    #[serde(default)]
+    pub arch: Option<String>,
+    /// Power drawn by one busy core, used unless `--watts-per-core` is given
+    #[serde(default)]
+    pub watts_per_core: Option<f64>,
+    /// Container runtime, used unless `--runtime` is given
+    #[serde(default)]
+    pub runtime: Option<RuntimeKind>,
+    /// CPUs per test container, used unless `--cpus` is given
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    /// Memory per test container (e.g. `"512m"`), used unless `--memory` is given
+    #[serde(default)]
+    pub memory: Option<String>,
+    /// Containers `run` starts at once, used unless `--parallel` is given
+    #[serde(default)]
+    pub parallel: Option<usize>,
+    /// Seconds before a test container is killed, used unless `--timeout` is given
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Seconds a test container may go without a heartbeat, used unless `--heartbeat` is given
+    #[serde(default)]
+    pub heartbeat: Option<u64>,
+    /// Mount only the selected cases into each container, as with `--isolate-cases`
+    #[serde(default)]
+    pub isolate_cases: bool,
+    /// Difficulty tiers to run, used unless `--tier` is given
+    #[serde(default)]
+    pub tiers: Vec<String>,
+    /// Output format of `run`, `validate`, and `all`, used unless `--format` is given
+    #[serde(default)]
+    pub format: Option<Format>,
+    /// Result files to keep per implementation and case: `run` and `all`
+    /// prune older ones afterwards, and `prune` uses it unless `--keep` is given
+    #[serde(default)]
+    pub keep: Option<usize>,
+}
+
+/// `<test_data_dir>/profiles.json`, empty if there is none.
+pub fn read(test_data_dir: &Path) -> Result<Vec<Profile>> {
+    let path = test_data_dir.join("profiles.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&fs::read_to_string(&path)?).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// The profile called `name`, warning if this host's architecture isn't the
+/// one it expects.
+pub fn find(test_data_dir: &Path, name: &str) -> Result<Profile> {
+    let profiles = read(test_data_dir)?;
+    let Some(profile) = profiles.iter().find(|profile| profile.name == name) else {
+        let names: Vec<&str> = profiles.iter().map(|profile| profile.name.as_str()).collect();
+        bail!(
+            "Unknown profile: {} (profiles in {}: {})",
+            name,
+            test_data_dir.join("profiles.json").display(),
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        );
+    };
+    if let Some(arch) = profile.arch.as_deref().filter(|arch| *arch != std::env::consts::ARCH) {
+        eprintln!(
+            "{} Profile {} expects {} but this host is {}",
+            "⚠".yellow(),
+            name.bright_white(),
+            arch,
+            std::env::consts::ARCH
+        );
+    }
+    Ok(profile.clone())
+}
+
+/// The machine a run executed on, filed as `results/<run-id>/host.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Host {
+    #[serde(default)]
+    pub profile: Option<String>,
+    pub hostname: String,
+    pub arch: String,
+    #[serde(default)]
+    pub cpu: Option<String>,
+    #[serde(default)]
+    pub cpus: usize,
+    /// Power per busy core the run's energy estimates used
+    pub watts_per_core: f64,
+    /// Resource caps on the run's containers
+    #[serde(default, skip_serializing_if = "Limits::is_empty")]
+    pub limits: Limits,
+}
+
+impl Host {
+    pub fn current(profile: Option<&Profile>, watts_per_core: f64, limits: Limits) -> Self {
+        let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        Self {
+            profile: profile.map(|profile| profile.name.clone()),
+            hostname,
+            arch: std::env::consts::ARCH.to_string(),
+            cpu: cpu_model(),
+            cpus: std::thread::available_parallelism().map_or(0, |cpus| cpus.get()),
+            watts_per_core,
+            limits,
+        }
+    }
+
+    /// The profile name, or the hostname for untagged runs.
+    pub fn label(&self) -> &str {
+        self.profile.as_deref().unwrap_or(&self.hostname)
+    }
+}
+
+/// The CPU model from `/proc/cpuinfo`: `model name` on x86, `Model` (the
+/// board) on a Raspberry Pi.
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    ["model name", "Model"].iter().find_map(|key| {
+        cpuinfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == *key).then(|| value.trim().to_string())
+        })
+    })
+}
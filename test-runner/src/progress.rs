@@ -0,0 +1,156 @@
+//! Progress bars for `build`, `run`, and `all`.
+//!
+//! On a terminal, each implementation gets a bar while it builds or runs,
+//! above an overall bar for the whole command. A running container's bar
+//! advances as its result files appear, so a long run shows how far each
+//! implementation has got even when several run at once. The usual
+//! "Building ..." and "Running tests for ..." lines are left out, since the
+//! bars show the same; outcomes and errors are still printed, above the
+//! bars.
+//!
+//! Bars are off when stdout isn't a terminal, with `--json`, and with
+//! `--no-progress`, leaving the plain line-by-line output for logs and CI.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::fmt::Display;
+use std::io::{IsTerminal, stdout};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(120);
+
+pub struct Progress {
+    /// `None` when bars are off
+    multi: Option<MultiProgress>,
+    /// Overall bar of the current command
+    suite: Mutex<Option<ProgressBar>>,
+}
+
+/// One implementation's bar, removed when dropped.
+pub struct Task {
+    bar: Option<ProgressBar>,
+    suite: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Bars when `enabled` and stdout is a terminal.
+    pub fn new(enabled: bool) -> Self {
+        Progress {
+            multi: (enabled && stdout().is_terminal()).then(MultiProgress::new),
+            suite: Mutex::new(None),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.multi.is_some()
+    }
+
+    /// Start the overall bar, replacing any earlier one: `total` steps, e.g.
+    /// images to build or cases to run.
+    pub fn start_suite(&self, label: &str, total: usize) {
+        let Some(multi) = &self.multi else {
+            return;
+        };
+        let bar = multi.add(ProgressBar::new(total as u64));
+        bar.set_style(style("{prefix:>24.bold} [{bar:30.magenta/blue}] {pos}/{len} {msg} ({elapsed})"));
+        bar.set_prefix("Total");
+        bar.set_message(label.to_string());
+        bar.enable_steady_tick(TICK);
+        if let Some(previous) = self.suite.lock().unwrap().replace(bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    /// Clear the overall bar once the command's work is done.
+    pub fn finish_suite(&self) {
+        if let Some(bar) = self.suite.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+
+    /// A bar for `implementation` doing `what`, with `total` steps, or a
+    /// spinner when there's no telling how long it will take.
+    pub fn task(&self, implementation: &str, what: &str, total: Option<usize>) -> Task {
+        let Some(multi) = &self.multi else {
+            return Task { bar: None, suite: None };
+        };
+        let suite = self.suite.lock().unwrap().clone();
+        let bar = match total {
+            Some(total) => {
+                let bar = ProgressBar::new(total as u64);
+                bar.set_style(style("{prefix:>24.cyan} [{bar:30.cyan/blue}] {pos}/{len} {msg}"));
+                bar
+            }
+            None => {
+                let bar = ProgressBar::new_spinner();
+                bar.set_style(style("{prefix:>24.cyan} {spinner} {msg} ({elapsed})"));
+                bar
+            }
+        };
+        let bar = match &suite {
+            Some(suite) => multi.insert_before(suite, bar),
+            None => multi.add(bar),
+        };
+        bar.set_prefix(implementation.to_string());
+        bar.set_message(what.to_string());
+        bar.enable_steady_tick(TICK);
+        Task { bar: Some(bar), suite }
+    }
+
+    /// Print a line above the bars.
+    pub fn println(&self, line: impl Display) {
+        match &self.multi {
+            Some(multi) => {
+                multi.println(line.to_string()).ok();
+            }
+            None => println!("{}", line),
+        }
+    }
+
+    /// Print a line above the bars, or on stderr without them.
+    pub fn eprintln(&self, line: impl Display) {
+        match &self.multi {
+            Some(multi) => {
+                multi.println(line.to_string()).ok();
+            }
+            None => eprintln!("{}", line),
+        }
+    }
+}
+
+impl Task {
+    /// Move the bar to `position`, advancing the overall bar to match.
+    pub fn set_position(&self, position: usize) {
+        let Some(bar) = &self.bar else {
+            return;
+        };
+        let position = (position as u64).min(bar.length().unwrap_or(u64::MAX));
+        if position > bar.position() {
+            if let Some(suite) = &self.suite {
+                suite.inc(position - bar.position());
+            }
+            bar.set_position(position);
+        }
+    }
+}
+
+/// The bar is removed when the task is dropped, and whatever it had left is
+/// counted as done overall: a failed step still leaves less to do.
+impl Drop for Task {
+    fn drop(&mut self) {
+        let Some(bar) = &self.bar else {
+            return;
+        };
+        if let Some(suite) = &self.suite {
+            let remaining = bar.length().map_or(1, |length| length.saturating_sub(bar.position()));
+            suite.inc(remaining);
+        }
+        bar.finish_and_clear();
+    }
+}
+
+fn style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template)
+        .expect("progress bar templates are valid")
+        .progress_chars("=> ")
+}
@@ -0,0 +1,481 @@
+//! Static results site built from the history of runs.
+//!
+//! `publish` turns the runs in the results database into plain HTML pages that can be
+//! served from GitHub Pages or any static host, so the comparison can be
+//! browsed without running anything:
+//!
+//! - `index.html` - charts of agreement with the reference and execution
+//!   time across runs, and a table of runs with each implementation's score
+//! - `runs/<run-id>.html` - the implementation × test case verdict matrix
+//!   of one run, with execution times
+//! - `runs/<run-id>/<implementation>/<case>.html` - the implementation's
+//!   windows next to the reference's, window by window, with the fields
+//!   outside tolerance highlighted
+//!
+//! Diff pages need the run's artifacts (`results/<run-id>/`); runs whose
+//! artifacts have been removed only get their matrix. Windows are compared
+//! with the current reference results. Links are relative, so the site can
+//! live under any path.
+
+use crate::events::Verdict;
+use crate::database::Database;
+use crate::history::{self, Entry};
+use crate::report::{STYLE, THEME_SCRIPT, bar_chart, escape, memory};
+use crate::time_display;
+use crate::{Orchestrator, REFERENCE_IMPLEMENTATION, read_result};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Line colors, one per implementation in order.
+const PALETTE: [&str; 8] = ["#4a90d9", "#e8743b", "#19a979", "#ed4a7b", "#945ecf", "#13a4b4", "#bf399e", "#6c8893"];
+
+pub struct Published {
+    pub runs: usize,
+    pub pages: usize,
+}
+
+/// Write the site for the last `last` runs (all if `None`) to `site_dir`.
+pub fn publish(orchestrator: &Orchestrator, site_dir: &Path, last: Option<usize>) -> Result<Published> {
+    let entries = Database::open(&orchestrator.results_dir)?.history(last)?;
+
+    // Runs no longer published would otherwise linger
+    let runs_dir = site_dir.join("runs");
+    if runs_dir.exists() {
+        fs::remove_dir_all(&runs_dir).with_context(|| format!("Failed to clear {}", runs_dir.display()))?;
+    }
+    fs::create_dir_all(&runs_dir).with_context(|| format!("Failed to create {}", runs_dir.display()))?;
+    // Keep GitHub Pages from running the site through Jekyll
+    fs::write(site_dir.join(".nojekyll"), "")?;
+
+    let implementations: BTreeSet<&str> = entries
+        .iter()
+        .flat_map(|entry| {
+            let runs = entry.runs.iter().map(|run| run.implementation.as_str());
+            runs.chain(entry.validation.iter().map(|validation| validation.implementation.as_str()))
+        })
+        .collect();
+    let implementations: Vec<&str> = implementations.into_iter().collect();
+
+    let mut pages = 1;
+    fs::write(site_dir.join("index.html"), index(&entries, &implementations))?;
+    for entry in &entries {
+        let run_id = entry.run_id.as_deref().unwrap_or_default();
+        let diffs = diff_pages(orchestrator, site_dir, entry)?;
+        pages += diffs.len() + 1;
+        fs::write(site_dir.join("runs").join(format!("{}.html", run_id)), matrix(entry, &diffs))?;
+    }
+
+    Ok(Published {
+        runs: entries.len(),
+        pages,
+    })
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}\n</style>\n<script>\n{2}\n</script>\n</head>\n<body>\n<button id=\"theme-toggle\" type=\"button\">Toggle dark mode</button>\n<h1>{0}</h1>\n{3}<p><small>Generated {4}</small></p>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        THEME_SCRIPT,
+        body,
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    )
+}
+
+fn started(entry: &Entry) -> String {
+    entry.started().map_or("-".to_string(), time_display::utc)
+}
+
+fn verdict_cell(verdict: Verdict) -> (&'static str, &'static str) {
+    match verdict {
+        Verdict::Match => ("pass", "✓"),
+        Verdict::Mismatch => ("fail", "✗"),
+        Verdict::NoReference => ("none", "-"),
+        Verdict::Unsupported => ("none", "○"),
+    }
+}
+
+fn index(entries: &[Entry], implementations: &[&str]) -> String {
+    let mut body = String::new();
+    let _ = writeln!(body, "<p>{} run(s).</p>", entries.len());
+
+    let agreement: Vec<(&str, Vec<Option<f64>>)> = implementations
+        .iter()
+        .map(|name| {
+            let points = entries
+                .iter()
+                .map(|entry| {
+                    let validation = entry.validation.iter().find(|validation| validation.implementation == *name)?;
+                    (validation.total > 0).then(|| validation.matched as f64 / validation.total as f64 * 100.0)
+                })
+                .collect();
+            (*name, points)
+        })
+        .collect();
+    let times: Vec<(&str, Vec<Option<f64>>)> = implementations
+        .iter()
+        .map(|name| (*name, entries.iter().map(|entry| Some(entry.run(name)?.execution_time)).collect()))
+        .collect();
+    let _ = writeln!(body, "<h2>Cases Matching the Reference</h2>");
+    body.push_str(&line_chart(&agreement, "%"));
+    let _ = writeln!(body, "<h2>Execution Time</h2>");
+    body.push_str(&line_chart(&times, "s"));
+
+    let _ = writeln!(body, "<h2>Runs</h2>");
+    let _ = writeln!(body, "<table>");
+    let _ = write!(body, "<tr><th>Run</th><th>Started</th><th>Result</th>");
+    for name in implementations {
+        let _ = write!(body, "<th>{}</th>", escape(name));
+    }
+    let _ = writeln!(body, "</tr>");
+    for entry in entries.iter().rev() {
+        let run_id = entry.run_id.as_deref().unwrap_or_default();
+        let _ = write!(
+            body,
+            "<tr><td><a href=\"runs/{0}.html\">{0}</a></td><td>{1}</td><td class=\"{2}\">{3}</td>",
+            escape(run_id),
+            started(entry),
+            if entry.passed { "pass" } else { "fail" },
+            if entry.passed { "passed" } else { "failed" }
+        );
+        for name in implementations {
+            match entry.validation.iter().find(|validation| validation.implementation == *name) {
+                Some(validation) => {
+                    let class = if validation.total == 0 {
+                        "none"
+                    } else if validation.matched == validation.total {
+                        "pass"
+                    } else {
+                        "fail"
+                    };
+                    let _ = write!(body, "<td class=\"{}\">{}/{}</td>", class, validation.matched, validation.total);
+                }
+                None => body.push_str("<td>-</td>"),
+            }
+        }
+        let _ = writeln!(body, "</tr>");
+    }
+    let _ = writeln!(body, "</table>");
+
+    page("Satellite Visibility Results", &body)
+}
+
+/// SVG line chart over runs, one line per series; `None` leaves a gap.
+fn line_chart(series: &[(&str, Vec<Option<f64>>)], unit: &str) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 200.0;
+    const LEFT: f64 = 50.0;
+    const LEGEND_WIDTH: f64 = 200.0;
+
+    let runs = series.iter().map(|(_, points)| points.len()).max().unwrap_or_default();
+    let highest = series
+        .iter()
+        .flat_map(|(_, points)| points.iter().flatten().copied())
+        .fold(0.0, f64::max);
+    if runs == 0 || highest <= 0.0 {
+        return "<p>No data yet.</p>\n".to_string();
+    }
+    let x = |i: usize| LEFT + if runs > 1 { i as f64 / (runs - 1) as f64 * WIDTH } else { WIDTH / 2.0 };
+    let y = |value: f64| 10.0 + HEIGHT - value / highest * HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        LEFT + WIDTH + LEGEND_WIDTH,
+        HEIGHT + 30.0
+    );
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{0}\" y1=\"10\" x2=\"{0}\" y2=\"{1}\" stroke=\"#888\"/><line x1=\"{0}\" y1=\"{1}\" x2=\"{2}\" y2=\"{1}\" stroke=\"#888\"/>",
+        LEFT,
+        10.0 + HEIGHT,
+        LEFT + WIDTH
+    );
+    let _ = writeln!(svg, "<text x=\"0\" y=\"15\">{:.1}{}</text><text x=\"0\" y=\"{}\">0{}</text>", highest, unit, 10.0 + HEIGHT, unit);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\">runs, oldest to newest</text>", LEFT, HEIGHT + 28.0);
+
+    for (i, (label, points)) in series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        // A polyline per stretch of consecutive points
+        let mut stretch = Vec::new();
+        let mut stretches = Vec::new();
+        for (run, point) in points.iter().enumerate() {
+            match point {
+                Some(value) => stretch.push(format!("{:.1},{:.1}", x(run), y(*value))),
+                None => stretches.push(std::mem::take(&mut stretch)),
+            }
+        }
+        stretches.push(stretch);
+        for stretch in stretches.iter().filter(|stretch| !stretch.is_empty()) {
+            if stretch.len() == 1 {
+                let (cx, cy) = stretch[0].split_once(',').unwrap_or_default();
+                let _ = writeln!(svg, "<circle cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"{}\"/>", cx, cy, color);
+            } else {
+                let _ = writeln!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                    stretch.join(" "),
+                    color
+                );
+            }
+        }
+        let legend_y = 15.0 + i as f64 * 18.0;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{0}\" y=\"{1:.1}\" width=\"12\" height=\"12\" fill=\"{2}\"/><text x=\"{3}\" y=\"{4:.1}\">{5}</text>",
+            LEFT + WIDTH + 20.0,
+            legend_y - 10.0,
+            color,
+            LEFT + WIDTH + 38.0,
+            legend_y,
+            escape(label)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The implementation × test case matrix of one run. `diffs` holds the
+/// (implementation, case) pairs that have a diff page.
+fn matrix(entry: &Entry, diffs: &BTreeSet<(String, String)>) -> String {
+    let run_id = entry.run_id.as_deref().unwrap_or_default();
+    let cases: BTreeSet<&str> = entry
+        .validation
+        .iter()
+        .flat_map(|validation| validation.cases.iter().map(|case| case.test_case.as_str()))
+        .collect();
+
+    let mut body = String::new();
+    let _ = writeln!(body, "<p><a href=\"../index.html\">All runs</a></p>");
+    let took = match (entry.started_at, entry.finished_at) {
+        (Some(start), Some(end)) if end >= start => format!(", took {}", time_display::duration((end - start) as i64)),
+        _ => String::new(),
+    };
+    let _ = writeln!(
+        body,
+        "<p>Started {}{}. {}.</p>",
+        started(entry),
+        took,
+        if entry.passed { "Passed" } else { "Failed" }
+    );
+
+    let _ = writeln!(body, "<table>");
+    let _ = write!(body, "<tr><th>Implementation</th><th>Time</th><th>CPU</th><th>Memory</th><th>Image</th><th>Matching</th>");
+    for case in &cases {
+        let _ = write!(body, "<th>{}</th>", escape(case));
+    }
+    let _ = writeln!(body, "</tr>");
+    for validation in &entry.validation {
+        let run = entry.run(&validation.implementation);
+        let image = run
+            .and_then(|run| run.image_id.as_deref())
+            .map_or("-".to_string(), |id| id.trim_start_matches("sha256:").chars().take(12).collect());
+        let _ = write!(
+            body,
+            "<tr><td>{}</td><td{}>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}/{}</td>",
+            escape(&validation.implementation),
+            if run.is_some_and(|run| !run.success) { " class=\"fail\" title=\"Container failed\"" } else { "" },
+            run.map_or("-".to_string(), |run| format!("{:.2}s", run.execution_time)),
+            run.and_then(|run| run.cpu_seconds).map_or("-".to_string(), |seconds| format!("{:.2}s", seconds)),
+            memory(run.and_then(|run| run.peak_memory)),
+            escape(&image),
+            validation.matched,
+            validation.total
+        );
+        for case in &cases {
+            let Some(result) = validation.cases.iter().find(|result| result.test_case == *case) else {
+                body.push_str("<td></td>");
+                continue;
+            };
+            let (class, mark) = verdict_cell(result.verdict);
+            let mark = if diffs.contains(&(validation.implementation.clone(), case.to_string())) {
+                format!(
+                    "<a href=\"{}/{}/{}.html\">{}</a>",
+                    escape(run_id),
+                    escape(&validation.implementation),
+                    escape(case),
+                    mark
+                )
+            } else {
+                mark.to_string()
+            };
+            let _ = write!(body, "<td class=\"{}\" title=\"{}\">{}</td>", class, result.result_windows, mark);
+        }
+        let _ = writeln!(body, "</tr>");
+    }
+    let _ = writeln!(body, "</table>");
+    let _ = writeln!(body, "<p>✓ matches the reference, ✗ differs, - no reference, ○ unsupported.</p>");
+
+    let times: Vec<(&str, f64)> = entry.runs.iter().map(|run| (run.implementation.as_str(), run.execution_time)).collect();
+    if !times.is_empty() {
+        let _ = writeln!(body, "<h2>Execution Time</h2>");
+        body.push_str(&bar_chart(&times));
+    }
+
+    page(&format!("Run {}", run_id), &body)
+}
+
+/// Write a diff page for every validated case whose result is still in the
+/// run's artifacts, returning the pairs written.
+fn diff_pages(orchestrator: &Orchestrator, site_dir: &Path, entry: &Entry) -> Result<BTreeSet<(String, String)>> {
+    let run_id = entry.run_id.as_deref().unwrap_or_default();
+    let artifacts_dir = orchestrator.results_dir.join(run_id);
+    let mut written = BTreeSet::new();
+    if !artifacts_dir.is_dir() {
+        return Ok(written);
+    }
+
+    let reference_dir = orchestrator.test_data_dir.join("reference-results");
+    let mut references: BTreeMap<&str, Option<Vec<Value>>> = BTreeMap::new();
+    for validation in &entry.validation {
+        for case in &validation.cases {
+            let case_dir = artifacts_dir.join(&validation.implementation).join(&case.test_case);
+            let Some(result_path) = ["result.json", "result.json.gz"].iter().map(|name| case_dir.join(name)).find(|path| path.exists())
+            else {
+                continue;
+            };
+            let result: Value = serde_json::from_str(&read_result(&result_path)?)?;
+            let reference = references.entry(&case.test_case).or_insert_with(|| {
+                let path = reference_dir.join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, case.test_case));
+                let reference: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+                reference["visibilityWindows"].as_array().cloned()
+            });
+            let elevation = fs::read_to_string(case_dir.join("elevation.svg")).ok();
+
+            let body = diff(
+                entry,
+                &validation.implementation,
+                case,
+                result["visibilityWindows"].as_array().map(Vec::as_slice).unwrap_or_default(),
+                reference.as_deref(),
+                elevation.as_deref(),
+            );
+            let page_dir = site_dir.join("runs").join(run_id).join(&validation.implementation);
+            fs::create_dir_all(&page_dir)?;
+            fs::write(
+                page_dir.join(format!("{}.html", case.test_case)),
+                page(&format!("{} / {}", validation.implementation, case.test_case), &body),
+            )?;
+            written.insert((validation.implementation.clone(), case.test_case.clone()));
+        }
+    }
+    Ok(written)
+}
+
+fn diff(
+    entry: &Entry,
+    implementation: &str,
+    case: &history::Case,
+    windows: &[Value],
+    reference: Option<&[Value]>,
+    elevation: Option<&str>,
+) -> String {
+    let run_id = entry.run_id.as_deref().unwrap_or_default();
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p><a href=\"../../../index.html\">All runs</a> / <a href=\"../../{0}.html\">Run {0}</a></p>",
+        escape(run_id)
+    );
+    let (class, _) = verdict_cell(case.verdict);
+    let verdict = match case.verdict {
+        Verdict::Match => "Matches the reference".to_string(),
+        Verdict::Mismatch if case.reference_windows != Some(case.result_windows) => format!(
+            "{} window(s) vs {} in the reference",
+            case.result_windows,
+            case.reference_windows.unwrap_or_default()
+        ),
+        Verdict::Mismatch => format!("{} field(s) outside tolerance", case.deltas.len()),
+        Verdict::NoReference => "No reference result".to_string(),
+        Verdict::Unsupported => "Unsupported".to_string(),
+    };
+    let _ = writeln!(body, "<p class=\"{}\">{}</p>", class, escape(&verdict));
+
+    let Some(reference) = reference else {
+        let _ = writeln!(body, "<p>No reference windows to compare against.</p>");
+        return body;
+    };
+    let windows = sorted(windows);
+    let reference = sorted(reference);
+    let _ = writeln!(body, "<h2>Windows</h2>");
+    let _ = writeln!(
+        body,
+        "<p>{} against the current {} reference, paired in order of start time.</p>",
+        escape(implementation),
+        REFERENCE_IMPLEMENTATION
+    );
+    let _ = writeln!(body, "<table>");
+    let _ = writeln!(
+        body,
+        "<tr><th>#</th><th>Start</th><th>Reference start</th><th>End</th><th>Reference end</th><th>Max elevation</th><th>Reference max elevation</th></tr>"
+    );
+    for i in 0..windows.len().max(reference.len()) {
+        let ours = windows.get(i);
+        let theirs = reference.get(i);
+        let outside = |field: &str| case.deltas.iter().any(|delta| delta.window == i + 1 && delta.field == field);
+        let cell = |window: Option<&&Value>, field: &str| match window.map(|window| &window[field]) {
+            Some(Value::String(text)) => escape(text),
+            Some(Value::Number(number)) => number.as_f64().map_or("-".to_string(), |value| format!("{:.2}°", value)),
+            _ => "-".to_string(),
+        };
+        let class = |field: &str| if outside(field) { " class=\"fail\"" } else { "" };
+        let _ = writeln!(
+            body,
+            "<tr{}><td>{}</td><td{}>{}</td><td>{}</td><td{}>{}</td><td>{}</td><td{}>{}</td><td>{}</td></tr>",
+            if ours.is_none() || theirs.is_none() { " class=\"fail\"" } else { "" },
+            i + 1,
+            class("start"),
+            cell(ours, "start"),
+            cell(theirs, "start"),
+            class("end"),
+            cell(ours, "end"),
+            cell(theirs, "end"),
+            class("maxElevation"),
+            cell(ours, "maxElevation"),
+            cell(theirs, "maxElevation")
+        );
+    }
+    let _ = writeln!(body, "</table>");
+
+    if !case.deltas.is_empty() {
+        let _ = writeln!(body, "<h2>Outside Tolerance</h2>");
+        let _ = writeln!(body, "<ul>");
+        for delta in &case.deltas {
+            let unit = if delta.field == "maxElevation" { "°" } else { "s" };
+            let _ = writeln!(
+                body,
+                "<li>Window {} {}: {:+.1}{} (tolerance ±{}{})</li>",
+                delta.window,
+                escape(&delta.field),
+                delta.delta,
+                unit,
+                delta.tolerance,
+                unit
+            );
+        }
+        let _ = writeln!(body, "</ul>");
+    }
+
+    if let Some(svg) = elevation {
+        let _ = writeln!(body, "<h2>Elevation</h2>");
+        body.push_str(svg);
+    }
+    body
+}
+
+fn sorted(windows: &[Value]) -> Vec<&Value> {
+    let mut windows: Vec<&Value> = windows.iter().collect();
+    windows.sort_by_key(|window| {
+        window["start"]
+            .as_str()
+            .and_then(|start| DateTime::parse_from_rfc3339(start).ok())
+            .map(|start| start.with_timezone(&Utc))
+    });
+    windows
+}
@@ -0,0 +1,230 @@
+//! Integrity of the reference results, for `check-reference`.
+//!
+//! Validation takes `test-data/reference-results/` as ground truth, so a
+//! damaged or stale reference file silently skews every verdict. This
+//! checks that:
+//!
+//! - every test case has a reference file, and every reference file has a
+//!   test case (orphans are left behind by renamed or removed cases)
+//! - every reference file is JSON matching the schema's `TestCaseOutput`,
+//!   and its `testCase` and `implementation` match its file name
+//! - each window ends after it starts, lies inside the case's time window,
+//!   and starts after the previous one ends; its `duration` matches its
+//!   span, its `maxElevation` reaches the case's `minElevation`, and its
+//!   `maxElevationTime` and sampled points lie inside it, no point higher
+//!   than `maxElevation`
+//!
+//! Cases are read as materialized, so TLE set and site references are
+//! resolved.
+
+use crate::error::Error;
+use crate::schema::Schema;
+use crate::{Orchestrator, REFERENCE_IMPLEMENTATION, case_names};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+
+/// Reported values are rounded to hundredths of a degree
+const ELEVATION_SLACK: f64 = 0.01;
+/// Seconds `duration` may differ from the window's span
+const DURATION_SLACK: f64 = 1.0;
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub cases: usize,
+    pub references: usize,
+    /// Test cases without a reference file, also among the problems
+    pub missing: Vec<String>,
+    pub problems: Vec<Problem>,
+}
+
+impl Check {
+    /// The error to fail `check-reference` with, if there are problems.
+    pub fn error(&self) -> Option<Error> {
+        if !self.missing.is_empty() {
+            Some(Error::ReferenceMissing {
+                cases: self.missing.clone(),
+            })
+        } else if !self.problems.is_empty() {
+            Some(Error::ReferenceInvalid {
+                problems: self.problems.len(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    /// Test case the problem concerns
+    pub test_case: String,
+    pub message: String,
+}
+
+pub fn run(orchestrator: &Orchestrator) -> Result<Check> {
+    let reference_dir = orchestrator.test_data_dir.join("reference-results");
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let schema = Schema::read(&orchestrator.test_data_dir)?;
+    let cases = case_names(&cases_dir)?;
+
+    let prefix = format!("{}_", REFERENCE_IMPLEMENTATION);
+    let mut references = BTreeSet::new();
+    let mut problems = Vec::new();
+    for entry in fs::read_dir(&reference_dir).with_context(|| format!("Failed to read {}", reference_dir.display()))? {
+        let file_name = entry?.file_name().to_string_lossy().to_string();
+        let Some(stem) = file_name.strip_suffix(".json") else {
+            continue;
+        };
+        match stem.strip_prefix(&prefix) {
+            Some(case) => {
+                references.insert(case.to_string());
+            }
+            None => problems.push(Problem {
+                test_case: stem.to_string(),
+                message: format!("{} is not named {}<test-case>.json", file_name, prefix),
+            }),
+        }
+    }
+
+    let missing: Vec<String> = cases.iter().filter(|case| !references.contains(*case)).cloned().collect();
+    for case in &missing {
+        problems.push(Problem {
+            test_case: case.clone(),
+            message: "No reference file".to_string(),
+        });
+    }
+    for case in &references {
+        let mut flag = |message: String| {
+            problems.push(Problem {
+                test_case: case.clone(),
+                message,
+            })
+        };
+        if !cases.contains(case) {
+            flag(format!("Orphaned reference file {}{}.json: no such test case", prefix, case));
+            continue;
+        }
+
+        let path = reference_dir.join(format!("{}{}.json", prefix, case));
+        let reference: Value = match serde_json::from_str(&fs::read_to_string(&path)?) {
+            Ok(reference) => reference,
+            Err(e) => {
+                flag(format!("Invalid JSON: {}", e));
+                continue;
+            }
+        };
+        let errors = schema.validate("TestCaseOutput", &reference);
+        let valid = errors.is_empty();
+        for error in errors {
+            flag(format!("Schema: {}", error));
+        }
+        if reference["testCase"] != case.as_str() {
+            flag(format!("testCase is {}, not {}", reference["testCase"], case));
+        }
+        if reference["implementation"] != REFERENCE_IMPLEMENTATION {
+            flag(format!("implementation is {}, not {}", reference["implementation"], REFERENCE_IMPLEMENTATION));
+        }
+        // Invariants assume the shape the schema guarantees
+        if valid {
+            let case_path = cases_dir.join(format!("{}.json", case));
+            let test_case: Value = serde_json::from_str(&fs::read_to_string(&case_path)?)
+                .with_context(|| format!("Failed to parse {}", case_path.display()))?;
+            for message in invariants(&test_case, &reference) {
+                flag(message);
+            }
+        }
+    }
+    problems.sort_by(|a, b| a.test_case.cmp(&b.test_case));
+
+    Ok(Check {
+        cases: cases.len(),
+        references: references.len(),
+        missing,
+        problems,
+    })
+}
+
+/// Violations of the window invariants in a schema-valid reference.
+fn invariants(test_case: &Value, reference: &Value) -> Vec<String> {
+    let time = |value: &Value| {
+        DateTime::parse_from_rfc3339(value.as_str()?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let window_start = time(&test_case["timeWindow"]["start"]);
+    let window_end = time(&test_case["timeWindow"]["end"]);
+    let min_elevation = test_case["minElevation"].as_f64();
+
+    let mut problems = Vec::new();
+    let mut previous_end: Option<DateTime<Utc>> = None;
+    for (i, window) in reference["visibilityWindows"].as_array().into_iter().flatten().enumerate() {
+        let n = i + 1;
+        let (Some(start), Some(end)) = (time(&window["start"]), time(&window["end"])) else {
+            continue;
+        };
+        if end <= start {
+            problems.push(format!("Window {} ends at or before its start", n));
+        }
+        if let Some(previous_end) = previous_end
+            && start <= previous_end
+        {
+            problems.push(format!("Window {} starts before the previous window ends", n));
+        }
+        previous_end = Some(end);
+        if window_start.is_some_and(|window_start| start < window_start) || window_end.is_some_and(|window_end| end > window_end) {
+            problems.push(format!("Window {} lies outside the case's time window", n));
+        }
+
+        if let Some(duration) = window["duration"].as_f64() {
+            let span = (end - start).num_milliseconds() as f64 / 1000.0;
+            if (duration - span).abs() > DURATION_SLACK {
+                problems.push(format!("Window {} duration is {}s, but it spans {}s", n, duration, span));
+            }
+        }
+
+        let max_elevation = window["maxElevation"].as_f64().unwrap_or_default();
+        if let Some(min_elevation) = min_elevation
+            && max_elevation < min_elevation - ELEVATION_SLACK
+        {
+            problems.push(format!(
+                "Window {} maxElevation {}° is below the case's minElevation {}°",
+                n, max_elevation, min_elevation
+            ));
+        }
+        if time(&window["maxElevationTime"]).is_some_and(|at| at < start || at > end) {
+            problems.push(format!("Window {} maxElevationTime lies outside the window", n));
+        }
+
+        let points = window["points"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let outside = points.iter().filter(|point| time(&point["time"]).is_some_and(|at| at < start || at > end)).count();
+        if outside > 0 {
+            problems.push(format!("Window {}: {} point(s) lie outside the window", n, outside));
+        }
+        let higher = points
+            .iter()
+            .filter(|point| point["elevation"].as_f64().is_some_and(|elevation| elevation > max_elevation + ELEVATION_SLACK))
+            .count();
+        if higher > 0 {
+            problems.push(format!("Window {}: {} point(s) higher than maxElevation", n, higher));
+        }
+    }
+    problems
+}
+
+pub fn print(check: &Check) {
+    println!(
+        "{}",
+        format!("Reference results: {} test case(s), {} reference file(s)", check.cases, check.references).bold()
+    );
+    for problem in &check.problems {
+        println!("  {} {}: {}", "✗".red(), problem.test_case.bright_white(), problem.message);
+    }
+    if check.problems.is_empty() {
+        println!("  {} No problems", "✓".green());
+    }
+}
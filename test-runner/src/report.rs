@@ -0,0 +1,801 @@
+//! Comparison reports in Markdown or HTML.
+//!
+//! One row per implementation with its latest results checked against the
+//! reference, ordered by runtime family and language (see [`languages`]),
+//! then the same figures aggregated per family and per language, followed
+//! by attribution for every library (description, upstream repository,
+//! license) from [`LibraryInfo`].
+//!
+//! Both formats then break the results down per test case, grouped by the
+//! satellite's orbit regime (see [`orbit`]) and headed with its period,
+//! perigee, apogee, and inclination: each implementation's window count
+//! against the reference and its verdict, judged as `validate` does with
+//! each case's default tolerances. The HTML page
+//! is self-contained, with inline styles, pass/fail coloring, and SVG bar
+//! charts of execution times, so it can be published as a CI artifact. It
+//! also embeds each case's input, reference result, and every
+//! implementation's raw result JSON in collapsible sections, and has a
+//! light/dark theme toggle (following the system theme until used), so the
+//! page can be shared on its own without the results directory.
+//!
+//! The implementation table also shows CPU time, estimated energy per test
+//! case, and peak container memory from the latest run that measured them
+//! (see [`usage`]), for picking a library for a constrained machine. Where
+//! no run measured memory, an implementation that reports its own peak RSS
+//! as `metadata.peakRss` shows the highest one, marked "(RSS)".
+//!
+//! A point density table compares the points each implementation emits per
+//! window with its cases' time steps (see [`density`]), flagging
+//! implementations that decimate or resample, whose point-level comparisons
+//! cover less than they seem to.
+//!
+//! [`density`]: crate::density
+//! [`languages`]: crate::languages
+//! [`orbit`]: crate::orbit
+//! [`usage`]: crate::usage
+
+use crate::density::{Density, Sampling};
+use crate::events::Verdict;
+use crate::languages::{self, Language};
+use crate::library_info::LibraryInfo;
+use crate::orbit::{Elements, Regime};
+use crate::tolerance;
+use crate::truth;
+use crate::usage;
+use crate::{Orchestrator, REFERENCE_IMPLEMENTATION, VisibilityResult, read_result};
+use anyhow::Result;
+use chrono::Utc;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+struct Row {
+    info: LibraryInfo,
+    language: Language,
+    cases: usize,
+    matched: usize,
+    with_reference: usize,
+    /// Execution times of the results that report one
+    times: Vec<f64>,
+    results: Vec<CaseResult>,
+    /// CPU seconds of the latest measured run
+    cpu_seconds: Option<f64>,
+    /// Estimated joules per test case in that run
+    joules_per_case: Option<f64>,
+    /// Peak memory in bytes in that run
+    peak_memory: Option<u64>,
+    /// Highest peak RSS in bytes the implementation reports itself in its
+    /// latest results' `metadata.peakRss`
+    peak_rss: Option<u64>,
+    /// Points of the latest results against their cases' steps
+    density: Density,
+}
+
+/// One implementation's latest result for a test case.
+struct CaseResult {
+    test_case: String,
+    windows: usize,
+    reference_windows: Option<usize>,
+    verdict: Verdict,
+    /// Window fields outside tolerance
+    deltas: usize,
+    execution_time: Option<f64>,
+    /// The result file, pretty-printed
+    raw: String,
+}
+
+/// Orbit of a test case's satellite, and its input and reference result,
+/// pretty-printed, for embedding in the HTML report.
+#[derive(Default)]
+struct CaseData {
+    orbit: Option<Elements>,
+    input: Option<String>,
+    reference: Option<String>,
+}
+
+/// A runtime family or language and the totals of its implementations.
+struct GroupRow {
+    label: String,
+    /// Whether this is a language within the family above it
+    nested: bool,
+    implementations: usize,
+    cases: usize,
+    matched: usize,
+    with_reference: usize,
+    times: Vec<f64>,
+}
+
+pub fn generate(orchestrator: &Orchestrator, format: ReportFormat) -> Result<String> {
+    let mut rows = Vec::new();
+    let mut implementations = orchestrator.discover_implementations()?;
+    implementations.extend(truth::external_tools(&orchestrator.test_data_dir)?);
+    for impl_ in implementations {
+        rows.push(summarize(orchestrator, LibraryInfo::load(&impl_), impl_.language.clone())?);
+    }
+    rows.sort_by(|a, b| {
+        (a.language.runtime, &a.language.name, &a.info.implementation).cmp(&(b.language.runtime, &b.language.name, &b.info.implementation))
+    });
+
+    let groups = group_rows(&rows);
+    let case_data = case_data(orchestrator, &rows)?;
+    Ok(match format {
+        ReportFormat::Markdown => markdown(&rows, &groups, &case_data),
+        ReportFormat::Html => html(&rows, &groups, &case_data),
+    })
+}
+
+/// Orbit, input, and reference result of every test case some
+/// implementation has a result for.
+fn case_data(orchestrator: &Orchestrator, rows: &[Row]) -> Result<BTreeMap<String, CaseData>> {
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let reference_dir = orchestrator.test_data_dir.join("reference-results");
+    let mut data = BTreeMap::new();
+    for test_case in by_test_case(rows).into_keys() {
+        let read = |path: std::path::PathBuf| fs::read_to_string(path).ok();
+        let input = read(cases_dir.join(format!("{}.json", test_case)));
+        data.insert(
+            test_case.to_string(),
+            CaseData {
+                orbit: input
+                    .as_deref()
+                    .and_then(|content| serde_json::from_str(content).ok())
+                    .and_then(|case| Elements::of_case(&case)),
+                input: input.map(|content| pretty(&content)),
+                reference: read(reference_dir.join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, test_case))).map(|content| pretty(&content)),
+            },
+        );
+    }
+    Ok(data)
+}
+
+/// `json` pretty-printed, or as is if it doesn't parse.
+fn pretty(json: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(json)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| json.to_string())
+}
+
+/// Totals per runtime family, each followed by its languages.
+fn group_rows(rows: &[Row]) -> Vec<GroupRow> {
+    let total = |label: &str, nested: bool, rows: &[&Row]| GroupRow {
+        label: label.to_string(),
+        nested,
+        implementations: rows.len(),
+        cases: rows.iter().map(|row| row.cases).sum(),
+        matched: rows.iter().map(|row| row.matched).sum(),
+        with_reference: rows.iter().map(|row| row.with_reference).sum(),
+        times: rows.iter().flat_map(|row| row.times.iter().copied()).collect(),
+    };
+
+    let mut groups = Vec::new();
+    for (runtime, by_language) in languages::group(rows.iter().map(|row| (row.language.clone(), row))) {
+        let family: Vec<&Row> = by_language.values().flatten().copied().collect();
+        groups.push(total(runtime.label(), false, &family));
+        for (language, rows) in &by_language {
+            groups.push(total(language, true, rows));
+        }
+    }
+    groups
+}
+
+/// Agreement with the reference and mean execution time over the
+/// implementation's latest results.
+fn summarize(orchestrator: &Orchestrator, info: LibraryInfo, language: Language) -> Result<Row> {
+    let judge = Judge::new(orchestrator)?;
+    let results = orchestrator.collect_results(&info.implementation)?;
+    let usage = usage::latest(&orchestrator.results_dir, &info.implementation)?;
+
+    let mut row = Row {
+        info,
+        language,
+        cases: results.len(),
+        matched: 0,
+        with_reference: 0,
+        times: Vec::new(),
+        results: Vec::new(),
+        cpu_seconds: usage.as_ref().map(|usage| usage.cpu_seconds),
+        peak_memory: usage.as_ref().and_then(|usage| usage.peak_memory),
+        joules_per_case: usage.and_then(|usage| usage.joules_per_case(orchestrator.watts_per_core)),
+        peak_rss: None,
+        density: Density::default(),
+    };
+
+    for path in &results {
+        let content = read_result(path)?;
+        let result: VisibilityResult = serde_json::from_str(&content)?;
+        row.times.extend(result.execution_time);
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        row.peak_rss = row.peak_rss.max(value.pointer("/metadata/peakRss").and_then(|bytes| bytes.as_u64()));
+        let case = fs::read_to_string(judge.cases_dir.join(format!("{}.json", result.test_case)))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        if let Some(case) = case {
+            row.density.merge(Density::of(&value, &case));
+        }
+
+        let judged = judge.judge(&result)?;
+        if judged.reference_windows.is_some() {
+            row.with_reference += 1;
+        }
+        if judged.verdict == Verdict::Match {
+            row.matched += 1;
+        }
+        row.results.push(CaseResult {
+            test_case: result.test_case.clone(),
+            windows: result.visibility_windows.len(),
+            reference_windows: judged.reference_windows,
+            verdict: judged.verdict,
+            deltas: judged.deltas,
+            execution_time: result.execution_time,
+            raw: pretty(&content),
+        });
+    }
+
+    Ok(row)
+}
+
+/// A result checked against the reference as `validate` does, with its
+/// case's default tolerances.
+pub(crate) struct Judgement {
+    pub verdict: Verdict,
+    /// `None` without a reference result
+    pub reference_windows: Option<usize>,
+    /// Window fields outside tolerance
+    pub deltas: usize,
+}
+
+/// Where [`Judge::judge`] finds reference results and the cases their
+/// tolerances come from.
+pub(crate) struct Judge {
+    reference_dir: PathBuf,
+    cases_dir: PathBuf,
+}
+
+impl Judge {
+    pub fn new(orchestrator: &Orchestrator) -> Result<Self> {
+        Ok(Judge {
+            reference_dir: orchestrator.test_data_dir.join("reference-results"),
+            cases_dir: orchestrator
+                .materialize_cases()?
+                .unwrap_or_else(|| orchestrator.test_data_dir.join("cases")),
+        })
+    }
+
+    /// Judge `result` as `validate` does, with its case's default tolerances.
+    pub fn judge(&self, result: &VisibilityResult) -> Result<Judgement> {
+        let ref_file = self.reference_dir.join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, result.test_case));
+        if !ref_file.exists() {
+            return Ok(Judgement {
+                verdict: Verdict::NoReference,
+                reference_windows: None,
+                deltas: 0,
+            });
+        }
+        let reference: VisibilityResult = serde_json::from_str(&fs::read_to_string(&ref_file)?)?;
+        let mut judged = Judgement {
+            verdict: Verdict::Mismatch,
+            reference_windows: Some(reference.visibility_windows.len()),
+            deltas: 0,
+        };
+        if reference.visibility_windows.len() == result.visibility_windows.len() {
+            let (tolerances, _) = tolerance::for_case(&self.cases_dir, &result.test_case, &tolerance::Overrides::default());
+            judged.deltas = tolerance::compare(&result.visibility_windows, &reference.visibility_windows, &tolerances).deltas.len();
+            if judged.deltas == 0 {
+                judged.verdict = Verdict::Match;
+            }
+        }
+        Ok(judged)
+    }
+}
+
+/// Every implementation's result for each test case, by test case.
+fn by_test_case(rows: &[Row]) -> BTreeMap<&str, Vec<(&str, &CaseResult)>> {
+    let mut cases: BTreeMap<&str, Vec<(&str, &CaseResult)>> = BTreeMap::new();
+    for row in rows {
+        for case in &row.results {
+            cases.entry(&case.test_case).or_default().push((&row.info.implementation, case));
+        }
+    }
+    cases
+}
+
+/// [`by_test_case`] grouped by orbit regime, LEO to HEO, with cases whose
+/// TLE couldn't be read last.
+#[allow(clippy::type_complexity)]
+fn by_regime<'a>(
+    rows: &'a [Row],
+    case_data: &BTreeMap<String, CaseData>,
+) -> Vec<(Option<Regime>, Vec<(&'a str, Vec<(&'a str, &'a CaseResult)>)>)> {
+    let mut regimes: BTreeMap<(bool, Option<Regime>), Vec<_>> = BTreeMap::new();
+    for (test_case, results) in by_test_case(rows) {
+        let regime = case_data.get(test_case).and_then(|data| data.orbit).map(|orbit| orbit.regime());
+        regimes.entry((regime.is_none(), regime)).or_default().push((test_case, results));
+    }
+    regimes.into_iter().map(|((_, regime), cases)| (regime, cases)).collect()
+}
+
+fn regime_label(regime: Option<Regime>) -> String {
+    regime.map_or("Unclassified".to_string(), |regime| regime.to_string())
+}
+
+/// e.g. `Period 92.9 min, perigee 415 km, apogee 422 km, inclination 51.6°`
+pub(crate) fn orbit_label(orbit: &Elements) -> String {
+    format!(
+        "Period {:.1} min, perigee {:.0} km, apogee {:.0} km, inclination {:.1}°",
+        orbit.period(),
+        orbit.perigee(),
+        orbit.apogee(),
+        orbit.inclination
+    )
+}
+
+fn verdict_label(case: &CaseResult) -> String {
+    match case.verdict {
+        Verdict::Match => "match".to_string(),
+        Verdict::Mismatch if case.deltas > 0 => format!("{} field(s) outside tolerance", case.deltas),
+        Verdict::Mismatch => "window count differs".to_string(),
+        Verdict::NoReference => "no reference".to_string(),
+        Verdict::Unsupported => "unsupported".to_string(),
+    }
+}
+
+pub(crate) fn seconds(time: Option<f64>) -> String {
+    time.map_or("-".to_string(), |t| format!("{:.3}s", t))
+}
+
+fn or_dash(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("-")
+}
+
+pub(crate) fn joules(energy: Option<f64>) -> String {
+    energy.map_or("-".to_string(), |joules| format!("{:.2} J", joules))
+}
+
+pub(crate) fn memory(bytes: Option<u64>) -> String {
+    bytes.map_or("-".to_string(), usage::format_memory)
+}
+
+/// Peak container memory, or else the peak RSS the implementation reports
+/// itself, marked as such.
+fn peak_memory(row: &Row) -> String {
+    match (row.peak_memory, row.peak_rss) {
+        (None, Some(rss)) => format!("{} (RSS)", usage::format_memory(rss)),
+        (peak, _) => memory(peak),
+    }
+}
+
+/// Points against the expected count, e.g. "410/412 (99.5%)".
+fn coverage(density: &Density) -> String {
+    match density.expected {
+        0 => "-".to_string(),
+        expected => format!("{}/{} ({:.1}%)", density.points, expected, density.points as f64 / expected as f64 * 100.0),
+    }
+}
+
+fn spacing(density: &Density) -> String {
+    density.median_spacing().map_or("-".to_string(), |spacing| format!("{:.2}× step", spacing))
+}
+
+fn grid_share(density: &Density) -> String {
+    density.grid_share().map_or("-".to_string(), |share| format!("{:.1}%", share * 100.0))
+}
+
+fn sampling_label(sampling: Option<Sampling>) -> String {
+    match sampling {
+        Some(sampling) if sampling.flagged() => format!("⚠ {}", sampling.label()),
+        Some(sampling) => format!("✓ {}", sampling.label()),
+        None => "-".to_string(),
+    }
+}
+
+fn average_time(times: &[f64]) -> String {
+    if times.is_empty() {
+        return "-".to_string();
+    }
+    format!("{:.3}s", times.iter().sum::<f64>() / times.len() as f64)
+}
+
+const DENSITY_NOTE: &str = "Points emitted per window against the count each case's time step implies. \
+Point-level comparisons pair points by timestamp, so those of a decimated or resampled implementation cover less of each pass.";
+
+fn markdown(rows: &[Row], groups: &[GroupRow], case_data: &BTreeMap<String, CaseData>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Satellite Visibility Comparison Report");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Generated {}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "| Implementation | Language | Library | License | Cases | Matching reference | Avg time | CPU time | Energy/case | Peak memory |"
+    );
+    let _ = writeln!(
+        out,
+        "|----------------|----------|---------|---------|-------|--------------------|----------|----------|-------------|-------------|"
+    );
+    for row in rows {
+        let library = match (row.info.library_label(), &row.info.repository) {
+            (Some(library), Some(url)) => format!("[{}]({})", library, url),
+            (library, _) => or_dash(&library).to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {}/{} | {} | {} | {} | {} |",
+            row.info.title(),
+            row.language.name,
+            library,
+            or_dash(&row.info.license),
+            row.cases,
+            row.matched,
+            row.with_reference,
+            average_time(&row.times),
+            seconds(row.cpu_seconds),
+            joules(row.joules_per_case),
+            peak_memory(row)
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## By Language");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Group | Implementations | Cases | Matching reference | Avg time |");
+    let _ = writeln!(out, "|-------|-----------------|-------|--------------------|----------|");
+    for group in groups {
+        let label = if group.nested { format!("↳ {}", group.label) } else { format!("**{}**", group.label) };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {}/{} | {} |",
+            label,
+            group.implementations,
+            group.cases,
+            group.matched,
+            group.with_reference,
+            average_time(&group.times)
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Point Density");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{}", DENSITY_NOTE);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Implementation | Points/expected | Median spacing | On step grid | Sampling |");
+    let _ = writeln!(out, "|----------------|-----------------|----------------|--------------|----------|");
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            row.info.title(),
+            coverage(&row.density),
+            spacing(&row.density),
+            grid_share(&row.density),
+            sampling_label(row.density.sampling())
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Test Cases");
+    for (regime, cases) in by_regime(rows, case_data) {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "### {}", regime_label(regime));
+        for (test_case, results) in cases {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "#### {}", test_case);
+            let _ = writeln!(out);
+            if let Some(orbit) = case_data.get(test_case).and_then(|data| data.orbit.as_ref()) {
+                let _ = writeln!(out, "{}", orbit_label(orbit));
+                let _ = writeln!(out);
+            }
+            let _ = writeln!(out, "| Implementation | Windows | Reference | Verdict | Time |");
+            let _ = writeln!(out, "|----------------|---------|-----------|---------|------|");
+            for (implementation, case) in results {
+                let mark = match case.verdict {
+                    Verdict::Match => "✓ ",
+                    Verdict::Mismatch => "✗ ",
+                    _ => "",
+                };
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {}{} | {} |",
+                    implementation,
+                    case.windows,
+                    case.reference_windows.map_or("-".to_string(), |n| n.to_string()),
+                    mark,
+                    verdict_label(case),
+                    seconds(case.execution_time)
+                );
+            }
+        }
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Libraries");
+    for row in rows {
+        let info = &row.info;
+        let _ = writeln!(out);
+        let _ = writeln!(out, "### {}", info.title());
+        let _ = writeln!(out);
+        let _ = writeln!(out, "- **Library:** {}", or_dash(&info.library_label()));
+        let _ = writeln!(out, "- **Description:** {}", or_dash(&info.description));
+        let _ = writeln!(out, "- **Repository:** {}", or_dash(&info.repository));
+        let _ = writeln!(out, "- **License:** {}", or_dash(&info.license));
+    }
+    out
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn agreement_class(matched: usize, with_reference: usize) -> &'static str {
+    match (matched, with_reference) {
+        (_, 0) => "none",
+        (matched, total) if matched == total => "pass",
+        _ => "fail",
+    }
+}
+
+/// Horizontal SVG bar chart of execution times, one bar per label.
+pub(crate) fn bar_chart(bars: &[(&str, f64)]) -> String {
+    const LABEL_WIDTH: f64 = 200.0;
+    const BAR_WIDTH: f64 = 400.0;
+    const ROW_HEIGHT: f64 = 22.0;
+
+    if bars.is_empty() {
+        return String::new();
+    }
+    let longest = bars.iter().map(|(_, time)| *time).fold(0.0, f64::max);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        LABEL_WIDTH + BAR_WIDTH + 80.0,
+        bars.len() as f64 * ROW_HEIGHT
+    );
+    for (i, (label, time)) in bars.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT;
+        let width = if longest > 0.0 { time / longest * BAR_WIDTH } else { 0.0 };
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{:.1}\">{}</text><rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4a90d9\"/><text x=\"{:.1}\" y=\"{:.1}\">{:.3}s</text>",
+            y + 15.0,
+            escape(label),
+            LABEL_WIDTH,
+            y + 3.0,
+            width,
+            ROW_HEIGHT - 6.0,
+            LABEL_WIDTH + width + 4.0,
+            y + 15.0,
+            time
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Light theme colors, then dark. The page follows the system theme until
+/// the toggle sets `data-theme` on `<html>`; the choice is kept in
+/// `localStorage`.
+pub(crate) const STYLE: &str = "\
+:root { --bg: #ffffff; --fg: #1a1a1a; --border: #cccccc; --link: #0645ad; --pass: #d4edda; --fail: #f8d7da; --none: #eeeeee; --code: #f6f8fa; }
+:root[data-theme=\"dark\"] { --bg: #1b1d21; --fg: #e3e3e3; --border: #454950; --link: #8ab4f8; --pass: #1e4620; --fail: #5c2126; --none: #2e3136; --code: #24272c; }
+@media (prefers-color-scheme: dark) { :root:not([data-theme=\"light\"]) { --bg: #1b1d21; --fg: #e3e3e3; --border: #454950; --link: #8ab4f8; --pass: #1e4620; --fail: #5c2126; --none: #2e3136; --code: #24272c; } }
+body { font-family: sans-serif; margin: 2em; background: var(--bg); color: var(--fg); }
+a { color: var(--link); }
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border); padding: 4px 8px; text-align: left; }
+.pass { background: var(--pass); }
+.fail { background: var(--fail); }
+.none { background: var(--none); }
+svg { display: block; margin: 1em 0; font-size: 12px; }
+svg text { fill: var(--fg); }
+details { margin: 0.3em 0; }
+summary { cursor: pointer; }
+pre { background: var(--code); border: 1px solid var(--border); padding: 0.5em; max-height: 30em; overflow: auto; }
+#theme-toggle { position: absolute; top: 1em; right: 2em; }";
+
+pub(crate) const THEME_SCRIPT: &str = "\
+const root = document.documentElement;
+const saved = localStorage.getItem('report-theme');
+if (saved) root.dataset.theme = saved;
+document.addEventListener('DOMContentLoaded', () => {
+  document.getElementById('theme-toggle').addEventListener('click', () => {
+    const dark = root.dataset.theme ? root.dataset.theme === 'dark' : matchMedia('(prefers-color-scheme: dark)').matches;
+    root.dataset.theme = dark ? 'light' : 'dark';
+    localStorage.setItem('report-theme', root.dataset.theme);
+  });
+});";
+
+/// Collapsible `<pre>` block of raw JSON.
+fn raw_json(summary: &str, json: &str) -> String {
+    format!("<details><summary>{}</summary><pre>{}</pre></details>\n", escape(summary), escape(json))
+}
+
+fn html(rows: &[Row], groups: &[GroupRow], case_data: &BTreeMap<String, CaseData>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>Satellite Visibility Comparison Report</title>");
+    let _ = writeln!(out, "<style>\n{}\n</style>", STYLE);
+    // In the head, so a saved theme applies before the page is drawn
+    let _ = writeln!(out, "<script>\n{}\n</script>", THEME_SCRIPT);
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<button id=\"theme-toggle\" type=\"button\">Toggle dark mode</button>");
+    let _ = writeln!(out, "<h1>Satellite Visibility Comparison Report</h1>");
+    let _ = writeln!(out, "<p>Generated {}</p>", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Implementation</th><th>Language</th><th>Library</th><th>License</th><th>Cases</th><th>Matching reference</th><th>Avg time</th><th>CPU time</th><th>Energy/case</th><th>Peak memory</th></tr>"
+    );
+    for row in rows {
+        let library = escape(or_dash(&row.info.library_label()));
+        let library = match &row.info.repository {
+            Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), library),
+            None => library,
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}/{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(row.info.title()),
+            escape(&row.language.name),
+            library,
+            escape(or_dash(&row.info.license)),
+            row.cases,
+            agreement_class(row.matched, row.with_reference),
+            row.matched,
+            row.with_reference,
+            average_time(&row.times),
+            seconds(row.cpu_seconds),
+            joules(row.joules_per_case),
+            peak_memory(row)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Execution Time</h2>");
+    let _ = writeln!(out, "<p>Average over each implementation's latest results.</p>");
+    let averages: Vec<(&str, f64)> = rows
+        .iter()
+        .filter(|row| !row.times.is_empty())
+        .map(|row| (row.info.implementation.as_str(), row.times.iter().sum::<f64>() / row.times.len() as f64))
+        .collect();
+    out.push_str(&bar_chart(&averages));
+
+    let _ = writeln!(out, "<h2>By Language</h2>");
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Group</th><th>Implementations</th><th>Cases</th><th>Matching reference</th><th>Avg time</th></tr>"
+    );
+    for group in groups {
+        let label = if group.nested {
+            format!("<td style=\"padding-left: 2em\">{}</td>", escape(&group.label))
+        } else {
+            format!("<td><strong>{}</strong></td>", escape(&group.label))
+        };
+        let _ = writeln!(
+            out,
+            "<tr>{}<td>{}</td><td>{}</td><td class=\"{}\">{}/{}</td><td>{}</td></tr>",
+            label,
+            group.implementations,
+            group.cases,
+            agreement_class(group.matched, group.with_reference),
+            group.matched,
+            group.with_reference,
+            average_time(&group.times)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Point Density</h2>");
+    let _ = writeln!(out, "<p>{}</p>", DENSITY_NOTE);
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Implementation</th><th>Points/expected</th><th>Median spacing</th><th>On step grid</th><th>Sampling</th></tr>"
+    );
+    for row in rows {
+        let sampling = row.density.sampling();
+        let class = match sampling {
+            Some(sampling) if sampling.flagged() => "fail",
+            Some(_) => "pass",
+            None => "none",
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td></tr>",
+            escape(row.info.title()),
+            coverage(&row.density),
+            spacing(&row.density),
+            grid_share(&row.density),
+            class,
+            sampling_label(sampling)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Test Cases</h2>");
+    for (regime, cases) in by_regime(rows, case_data) {
+        let _ = writeln!(out, "<h3>{}</h3>", regime_label(regime));
+        for (test_case, results) in cases {
+            let _ = writeln!(out, "<h4 id=\"{0}\">{0}</h4>", escape(test_case));
+            let data = case_data.get(test_case);
+            if let Some(orbit) = data.and_then(|data| data.orbit.as_ref()) {
+                let _ = writeln!(out, "<p>{}</p>", orbit_label(orbit));
+            }
+            let _ = writeln!(out, "<table>");
+            let _ = writeln!(out, "<tr><th>Implementation</th><th>Windows</th><th>Reference</th><th>Verdict</th><th>Time</th></tr>");
+            for (implementation, case) in &results {
+                let class = match case.verdict {
+                    Verdict::Match => "pass",
+                    Verdict::Mismatch => "fail",
+                    _ => "none",
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    class,
+                    escape(implementation),
+                    case.windows,
+                    case.reference_windows.map_or("-".to_string(), |n| n.to_string()),
+                    verdict_label(case),
+                    seconds(case.execution_time)
+                );
+            }
+            let _ = writeln!(out, "</table>");
+            let times: Vec<(&str, f64)> = results
+                .iter()
+                .filter_map(|(implementation, case)| Some((*implementation, case.execution_time?)))
+                .collect();
+            out.push_str(&bar_chart(&times));
+
+            if let Some(input) = data.and_then(|data| data.input.as_deref()) {
+                out.push_str(&raw_json("Test case input", input));
+            }
+            if let Some(reference) = data.and_then(|data| data.reference.as_deref()) {
+                out.push_str(&raw_json(&format!("Reference result ({})", REFERENCE_IMPLEMENTATION), reference));
+            }
+            for (implementation, case) in &results {
+                out.push_str(&raw_json(&format!("{} result", implementation), &case.raw));
+            }
+        }
+    }
+
+    let _ = writeln!(out, "<h2>Libraries</h2>");
+    let _ = writeln!(out, "<dl>");
+    for row in rows {
+        let info = &row.info;
+        let repository = match &info.repository {
+            Some(url) => format!("<a href=\"{0}\">{0}</a>", escape(url)),
+            None => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "<dt><strong>{}</strong> - {}</dt><dd>{}<br>Repository: {}<br>License: {}</dd>",
+            escape(info.title()),
+            escape(or_dash(&info.library_label())),
+            escape(or_dash(&info.description)),
+            repository,
+            escape(or_dash(&info.license))
+        );
+    }
+    let _ = writeln!(out, "</dl>");
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
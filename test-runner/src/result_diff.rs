@@ -0,0 +1,267 @@
+//! Structured diffs of two result files.
+//!
+//! Windows are paired as validation pairs them, by overlap and nearest
+//! start rather than by position (see [`tolerance::pair`]), so one added or
+//! lost pass doesn't shift every window after it. Each pair is compared on
+//! its start, end, and `maxElevation`; windows left unpaired on either side
+//! were removed or added. Other fields are compared by dotted path (e.g.
+//! `metadata.libraryVersion`), except those that differ on every run anyway:
+//! `timestamp` and `executionTime`. Sampled `points` aren't compared.
+//!
+//! Either file may be gzip-compressed (`.json.gz`).
+
+use crate::read_result;
+use crate::tolerance;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Fields left out of the comparison, at any depth
+const VOLATILE: [&str; 2] = ["timestamp", "executionTime"];
+
+#[derive(Debug, Serialize)]
+pub struct ResultDiff {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub fields: Vec<FieldChange>,
+    pub windows: Vec<WindowChange>,
+}
+
+/// A field other than the windows with a different value, or present on
+/// one side only (`null`).
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum WindowChange {
+    /// Only in the new result
+    Added { start: String, end: String },
+    /// Only in the old result
+    Removed { start: String, end: String },
+    /// Overlapping windows that differ, with new minus old in seconds and
+    /// degrees; unchanged fields are left out
+    #[serde(rename_all = "camelCase")]
+    Shifted {
+        start: String,
+        end: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        start_shift: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        end_shift: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_elevation_delta: Option<f64>,
+    },
+}
+
+impl ResultDiff {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.windows.is_empty()
+    }
+}
+
+/// A window with its parsed span.
+#[derive(Clone, Copy)]
+pub(crate) struct Window<'a> {
+    pub value: &'a Value,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Windows of two results paired as validation pairs them (see
+/// [`tolerance::pair`]), each paired at most once.
+pub(crate) struct Pairing<'a> {
+    /// Old window, then the new window paired with it
+    pub pairs: Vec<(Window<'a>, Window<'a>)>,
+    /// Old windows left unpaired
+    pub removed: Vec<Window<'a>>,
+    /// New windows left unpaired
+    pub added: Vec<Window<'a>>,
+}
+
+/// Pair the `old` windows with the `new`, in order of start time. Windows
+/// without a parseable start and end are left out.
+pub(crate) fn pair<'a>(old: &'a [Value], new: &'a [Value]) -> Pairing<'a> {
+    let (old_windows, new_windows) = (windows(old), windows(new));
+    let values = |windows: &[Window<'a>]| windows.iter().map(|window| window.value).collect::<Vec<_>>();
+    let pairs = tolerance::pair(&values(&old_windows), &values(&new_windows));
+    Pairing {
+        pairs: pairs.iter().map(|&(i, j)| (old_windows[i], new_windows[j])).collect(),
+        removed: (0..old_windows.len())
+            .filter(|&i| !pairs.iter().any(|&(old, _)| old == i))
+            .map(|i| old_windows[i])
+            .collect(),
+        added: (0..new_windows.len())
+            .filter(|&j| !pairs.iter().any(|&(_, new)| new == j))
+            .map(|j| new_windows[j])
+            .collect(),
+    }
+}
+
+/// Diff the result file `old` against `new`.
+pub fn diff(old: &Path, new: &Path) -> Result<ResultDiff> {
+    let read = |path: &Path| -> Result<Value> {
+        serde_json::from_str(&read_result(path)?).with_context(|| format!("Failed to parse {}", path.display()))
+    };
+    let (old_result, new_result) = (read(old)?, read(new)?);
+
+    let mut fields = Vec::new();
+    compare_fields("", &without_windows(&old_result), &without_windows(&new_result), &mut fields);
+
+    let pairing = pair(result_windows(&old_result), result_windows(&new_result));
+    // Changes by the start of the window they concern, for printing in order
+    let mut changes: Vec<(DateTime<Utc>, WindowChange)> = Vec::new();
+    for old_window in &pairing.removed {
+        changes.push((old_window.start, WindowChange::Removed {
+            start: text(old_window.value, "start"),
+            end: text(old_window.value, "end"),
+        }));
+    }
+    for (old_window, new_window) in &pairing.pairs {
+        let shift = |old: DateTime<Utc>, new: DateTime<Utc>| {
+            let seconds = (new - old).num_milliseconds() as f64 / 1000.0;
+            (seconds != 0.0).then_some(seconds)
+        };
+        let start_shift = shift(old_window.start, new_window.start);
+        let end_shift = shift(old_window.end, new_window.end);
+        let max_elevation_delta = match (old_window.value["maxElevation"].as_f64(), new_window.value["maxElevation"].as_f64()) {
+            (Some(old), Some(new)) => Some(new - old).filter(|delta| delta.abs() > 1e-9),
+            _ => None,
+        };
+        if start_shift.is_some() || end_shift.is_some() || max_elevation_delta.is_some() {
+            changes.push((old_window.start, WindowChange::Shifted {
+                start: text(old_window.value, "start"),
+                end: text(old_window.value, "end"),
+                start_shift,
+                end_shift,
+                max_elevation_delta,
+            }));
+        }
+    }
+    for new_window in &pairing.added {
+        changes.push((new_window.start, WindowChange::Added {
+            start: text(new_window.value, "start"),
+            end: text(new_window.value, "end"),
+        }));
+    }
+    changes.sort_by_key(|(start, _)| *start);
+
+    Ok(ResultDiff {
+        old: old.to_path_buf(),
+        new: new.to_path_buf(),
+        fields,
+        windows: changes.into_iter().map(|(_, change)| change).collect(),
+    })
+}
+
+/// Print `diff`, removals in red, additions in green, and changes in yellow.
+pub fn print(diff: &ResultDiff) {
+    println!("{}", format!("--- {}", diff.old.display()).red());
+    println!("{}", format!("+++ {}", diff.new.display()).green());
+    if diff.is_empty() {
+        println!("  {} No differences", "✓".green());
+        return;
+    }
+
+    for change in &diff.fields {
+        println!("  {} {}: {} → {}", "~".yellow(), change.field.bright_white(), change.old, change.new);
+    }
+    for change in &diff.windows {
+        match change {
+            WindowChange::Added { start, end } => println!("  {} window {} .. {}", "+".green(), start, end),
+            WindowChange::Removed { start, end } => println!("  {} window {} .. {}", "-".red(), start, end),
+            WindowChange::Shifted {
+                start,
+                end,
+                start_shift,
+                end_shift,
+                max_elevation_delta,
+            } => {
+                let mut what = Vec::new();
+                if let Some(shift) = start_shift {
+                    what.push(format!("start {:+.1}s", shift));
+                }
+                if let Some(shift) = end_shift {
+                    what.push(format!("end {:+.1}s", shift));
+                }
+                if let Some(delta) = max_elevation_delta {
+                    what.push(format!("maxElevation {:+.2}°", delta));
+                }
+                println!("  {} window {} .. {}: {}", "~".yellow(), start, end, what.join(", "));
+            }
+        }
+    }
+    println!(
+        "{} field(s) changed, {} window(s) added, {} removed, {} shifted",
+        diff.fields.len(),
+        diff.windows.iter().filter(|change| matches!(change, WindowChange::Added { .. })).count(),
+        diff.windows.iter().filter(|change| matches!(change, WindowChange::Removed { .. })).count(),
+        diff.windows.iter().filter(|change| matches!(change, WindowChange::Shifted { .. })).count()
+    );
+}
+
+fn without_windows(result: &Value) -> Value {
+    let mut result = result.clone();
+    if let Some(object) = result.as_object_mut() {
+        object.remove("visibilityWindows");
+    }
+    result
+}
+
+/// Differences between `old` and `new` under `path`, recursing into objects.
+/// Other values, arrays included, are compared whole.
+fn compare_fields(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    if let (Value::Object(old), Value::Object(new)) = (old, new) {
+        let mut keys: Vec<&String> = old.keys().chain(new.keys().filter(|key| !old.contains_key(*key))).collect();
+        keys.sort();
+        for key in keys {
+            if VOLATILE.contains(&key.as_str()) {
+                continue;
+            }
+            let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            compare_fields(&field, old.get(key).unwrap_or(&Value::Null), new.get(key).unwrap_or(&Value::Null), changes);
+        }
+    } else if old != new {
+        changes.push(FieldChange {
+            field: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        });
+    }
+}
+
+fn result_windows(result: &Value) -> &[Value] {
+    result["visibilityWindows"].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The `windows` with a parseable span, by start time.
+fn windows(windows: &[Value]) -> Vec<Window<'_>> {
+    let parse = |window: &Value, field: &str| {
+        DateTime::parse_from_rfc3339(window[field].as_str()?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let mut windows: Vec<Window> = windows
+        .iter()
+        .filter_map(|value| {
+            Some(Window {
+                value,
+                start: parse(value, "start")?,
+                end: parse(value, "end")?,
+            })
+        })
+        .collect();
+    windows.sort_by_key(|window| window.start);
+    windows
+}
+
+fn text(window: &Value, field: &str) -> String {
+    window[field].as_str().unwrap_or_default().to_string()
+}
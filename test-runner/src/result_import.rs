@@ -0,0 +1,449 @@
+//! `import-results <source>`: results produced outside the orchestrator.
+//!
+//! An implementation run by hand, say on a machine without Docker, writes
+//! the same result files a container would. Importing them from a
+//! directory, a `.tar` or `.tar.gz` archive, or a single file makes them
+//! count like any other: every `.json` and `.json.gz` file in the source is
+//!
+//! - validated: it must satisfy `TestCaseOutput` in `test-data/schema.json`,
+//!   for a test case that exists
+//! - normalized: times become RFC 3339 UTC (`2025-10-26T06:32:30Z`),
+//!   windows are sorted by start, and `--implementation` replaces the
+//!   `implementation` it names, if given
+//! - written to `results/` as `<impl>_<case>_<timestamp>.json`, where
+//!   `validate`, reports, and the dashboard read it
+//!
+//! Accepted files are then filed as one new run (see
+//! [`artifacts`](crate::artifacts)) and recorded in the results database,
+//! tagged with the `host.json` at the top of the source if it has one (as a
+//! copied run directory does), or else with this machine. Rejected files
+//! are listed with the reason, and fail the command.
+
+use crate::artifacts::RunArtifacts;
+use crate::error::Error;
+use crate::profiles::Host;
+use crate::schema::Schema;
+use crate::{Orchestrator, TestResult};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Window fields holding a time
+const TIME_FIELDS: [&str; 3] = ["start", "end", "maxElevationTime"];
+
+#[derive(Debug, Serialize)]
+pub struct Imported {
+    pub implementation: String,
+    pub test_case: String,
+    /// Where it came from, inside the archive for one
+    pub source: PathBuf,
+    /// The result file written
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Rejected {
+    pub source: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Import {
+    /// The run the results were filed under, if any were accepted
+    pub run_id: Option<String>,
+    pub imported: Vec<Imported>,
+    pub rejected: Vec<Rejected>,
+}
+
+impl Import {
+    /// The first rejection, as the failure to end the command with.
+    pub fn failure(&self) -> Option<Error> {
+        self.rejected.first().map(|rejected| Error::SchemaViolation {
+            file: rejected.source.clone(),
+            detail: format!(
+                "{} ({} of {} file(s) rejected)",
+                rejected.reason,
+                self.rejected.len(),
+                self.rejected.len() + self.imported.len()
+            ),
+        })
+    }
+}
+
+/// A file read from the source, with its path there.
+struct Entry {
+    source: PathBuf,
+    content: Vec<u8>,
+}
+
+/// Import the results in `source`, as results of `implementation` if given.
+pub fn import(orchestrator: &Orchestrator, source: &Path, implementation: Option<&str>) -> Result<Import> {
+    let schema = Schema::read(&orchestrator.test_data_dir)?;
+    let cases: BTreeSet<String> = orchestrator.test_cases()?.into_iter().collect();
+    let (entries, host) = read_source(source)?;
+
+    let mut accepted: BTreeMap<(String, String), (PathBuf, Value)> = BTreeMap::new();
+    let mut rejected = Vec::new();
+    for entry in entries {
+        match normalize(&entry, &schema, &cases, implementation) {
+            Ok((key, result)) => {
+                if let Some((earlier, _)) = accepted.get(&key) {
+                    rejected.push(Rejected {
+                        source: entry.source,
+                        reason: format!("{} {} was already imported from {}", key.0, key.1, earlier.display()),
+                    });
+                } else {
+                    accepted.insert(key, (entry.source, result));
+                }
+            }
+            Err(reason) => rejected.push(Rejected {
+                source: entry.source,
+                reason: format!("{:#}", reason),
+            }),
+        }
+    }
+
+    let mut import = Import {
+        run_id: None,
+        imported: Vec::new(),
+        rejected,
+    };
+    if accepted.is_empty() {
+        return Ok(import);
+    }
+
+    fs::create_dir_all(&orchestrator.results_dir)?;
+    let host = host.unwrap_or_else(|| orchestrator.host());
+    let artifacts = RunArtifacts::create(&orchestrator.results_dir, &host)?;
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let mut runs: BTreeMap<String, TestResult> = BTreeMap::new();
+    for ((implementation, test_case), (source, result)) in accepted {
+        let path = orchestrator
+            .results_dir
+            .join(format!("{}_{}_{}.json", implementation, test_case, timestamp));
+        fs::write(&path, serde_json::to_string_pretty(&result)?).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        // One pseudo-container per implementation, whose output says where
+        // its results came from
+        let run = runs.entry(implementation.clone()).or_insert_with(|| TestResult {
+            implementation: implementation.clone(),
+            success: true,
+            execution_time: 0.0,
+            stdout: String::new(),
+            stderr: String::new(),
+            result_files: Vec::new(),
+            unsupported: Vec::new(),
+            cpu_seconds: None,
+            peak_memory: None,
+            image_id: None,
+            timed_out: false,
+            hung: false,
+            quarantined: Vec::new(),
+        });
+        run.execution_time += result["executionTime"].as_f64().unwrap_or_default();
+        run.stdout.push_str(&format!("Imported {} from {}\n", test_case, source.display()));
+        run.result_files.push(path.clone());
+        import.imported.push(Imported {
+            implementation,
+            test_case,
+            source,
+            path,
+        });
+    }
+    for run in runs.values() {
+        artifacts.record_run(run)?;
+    }
+    import.run_id = Some(artifacts.run_id().to_string());
+    Ok(import)
+}
+
+/// Check `entry` and normalize it into a result, keyed by its
+/// implementation and case.
+fn normalize(entry: &Entry, schema: &Schema, cases: &BTreeSet<String>, implementation: Option<&str>) -> Result<((String, String), Value)> {
+    let mut result: Value = serde_json::from_slice(&entry.content).context("Not JSON")?;
+    let errors = schema.validate("TestCaseOutput", &result);
+    if let Some(error) = errors.first() {
+        bail!("Schema: {} ({} violation(s))", error, errors.len());
+    }
+
+    let test_case = result["testCase"].as_str().unwrap_or_default().to_string();
+    if !cases.contains(&test_case) {
+        bail!("No test case {}", test_case);
+    }
+    if let Some(implementation) = implementation {
+        result["implementation"] = Value::from(implementation);
+    }
+    // The implementation names the file, so it mustn't reach outside `results/`
+    let implementation = result["implementation"]
+        .as_str()
+        .filter(|name| !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']))
+        .with_context(|| format!("Implementation {} can't name a result file; pass --implementation", result["implementation"]))?
+        .to_string();
+
+    if let Some(windows) = result["visibilityWindows"].as_array_mut() {
+        for window in windows.iter_mut() {
+            for field in TIME_FIELDS {
+                normalize_time(window.get_mut(field))?;
+            }
+            if let Some(points) = window.get_mut("points").and_then(Value::as_array_mut) {
+                for point in points {
+                    normalize_time(point.get_mut("time"))?;
+                }
+            }
+        }
+        windows.sort_by(|a, b| a["start"].as_str().cmp(&b["start"].as_str()));
+    }
+    Ok(((implementation, test_case), result))
+}
+
+/// Rewrite a time in UTC with a `Z`, leaving absent fields alone.
+fn normalize_time(value: Option<&mut Value>) -> Result<()> {
+    let Some(value) = value else {
+        return Ok(());
+    };
+    let Some(time) = value.as_str() else {
+        return Ok(());
+    };
+    let parsed = DateTime::parse_from_rfc3339(time).with_context(|| format!("Invalid time {}", time))?;
+    *value = Value::from(parsed.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::AutoSi, true));
+    Ok(())
+}
+
+/// The result files in `source`, and its `host.json`, if any.
+fn read_source(source: &Path) -> Result<(Vec<Entry>, Option<Host>)> {
+    let mut entries = Vec::new();
+    let mut host = None;
+    if source.is_dir() {
+        for entry in WalkDir::new(source).sort_by_file_name() {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(source)?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if relative == Path::new("host.json") {
+                host = Some(read_host(&fs::read(entry.path())?, entry.path())?);
+            } else if is_result(relative) {
+                let content = fs::read(entry.path()).with_context(|| format!("Failed to read {}", entry.path().display()))?;
+                entries.push(decompressed(entry.path().to_path_buf(), content)?);
+            }
+        }
+        return Ok((entries, host));
+    }
+
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    if name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        let file = BufReader::new(File::open(source).with_context(|| format!("Failed to read {}", source.display()))?);
+        let reader: Box<dyn Read> = if name.ends_with(".tar") { Box::new(file) } else { Box::new(GzDecoder::new(file)) };
+        read_archive(reader, |path, content| {
+            // Archives of a directory usually start with it
+            let relative: PathBuf = path.components().skip_while(|component| component.as_os_str() == ".").collect();
+            if relative.file_name().is_some_and(|name| name == "host.json") && relative.components().count() <= 2 {
+                host = Some(read_host(&content, &source.join(&relative))?);
+            } else if is_result(&relative) {
+                entries.push(decompressed(source.join(&relative), content)?);
+            }
+            Ok(())
+        })
+        .with_context(|| format!("Failed to read archive {}", source.display()))?;
+    } else if source.is_file() {
+        let content = fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?;
+        entries.push(decompressed(source.to_path_buf(), content)?);
+    } else {
+        bail!("{} does not exist", source.display());
+    }
+    Ok((entries, host))
+}
+
+fn is_result(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.ends_with(".json") || name.ends_with(".json.gz")
+}
+
+fn decompressed(source: PathBuf, content: Vec<u8>) -> Result<Entry> {
+    if !source.to_string_lossy().ends_with(".gz") {
+        return Ok(Entry { source, content });
+    }
+    let mut decompressed = Vec::new();
+    GzDecoder::new(content.as_slice())
+        .read_to_end(&mut decompressed)
+        .with_context(|| format!("Failed to decompress {}", source.display()))?;
+    Ok(Entry {
+        source,
+        content: decompressed,
+    })
+}
+
+fn read_host(content: &[u8], path: &Path) -> Result<Host> {
+    serde_json::from_slice(content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Call `visit` with each regular file in a tar archive, by its full
+/// path (GNU long names and PAX `path` headers included).
+fn read_archive(reader: impl Read, mut visit: impl FnMut(&Path, Vec<u8>) -> Result<()>) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        visit(&path, content)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use serde_json::json;
+    use tar::{Builder, EntryType, Header};
+
+    /// Deeper than a ustar header's 100-byte name field
+    const LONG_DIR: &str = "from-laptop/a-directory-name-long-enough-to-overflow-the-ustar-name-field/and-then-some-more";
+
+    fn result(test_case: &str) -> Value {
+        json!({
+            "testCase": test_case,
+            "implementation": "rust",
+            "version": "1.0",
+            "visibilityWindows": [
+                {
+                    "start": "2025-10-26T08:00:00+02:00",
+                    "end": "2025-10-26T08:10:00+02:00",
+                    "maxElevation": 40.0,
+                    "maxElevationTime": "2025-10-26T08:05:00+02:00"
+                },
+                {
+                    "start": "2025-10-26T04:00:00Z",
+                    "end": "2025-10-26T04:08:00Z",
+                    "maxElevation": 12.5,
+                    "maxElevationTime": "2025-10-26T04:04:00Z"
+                }
+            ]
+        })
+    }
+
+    fn entry(source: &str, result: &Value) -> Entry {
+        Entry {
+            source: PathBuf::from(source),
+            content: serde_json::to_vec(result).unwrap(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::read(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../test-data")).unwrap()
+    }
+
+    fn cases() -> BTreeSet<String> {
+        BTreeSet::from(["001_iss_nyc".to_string()])
+    }
+
+    /// A file whose path is only in a PAX extended header, as bsdtar and
+    /// `tar --format=posix` write long paths.
+    fn append_pax(builder: &mut Builder<impl std::io::Write>, path: &str, content: &[u8]) {
+        let record = |length: usize| format!("{} path={}\n", length, path);
+        let mut length = record(0).len();
+        while record(length).len() != length {
+            length = record(length).len();
+        }
+        let mut extensions = Header::new_ustar();
+        extensions.set_path("PaxHeaders/result").unwrap();
+        extensions.set_entry_type(EntryType::XHeader);
+        extensions.set_size(length as u64);
+        extensions.set_cksum();
+        builder.append(&extensions, record(length).as_bytes()).unwrap();
+
+        let mut header = Header::new_ustar();
+        header.set_path("truncated.json").unwrap();
+        header.set_mode(0o644);
+        header.set_size(content.len() as u64);
+        header.set_cksum();
+        builder.append(&header, content).unwrap();
+    }
+
+    #[test]
+    fn long_paths_come_through_an_archive() {
+        let dir = std::env::temp_dir().join(format!("visibility-test-runner-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("results.tar.gz");
+
+        let mut builder = Builder::new(GzEncoder::new(File::create(&archive).unwrap(), Compression::default()));
+        let content = serde_json::to_vec(&result("001_iss_nyc")).unwrap();
+        let mut header = Header::new_gnu();
+        header.set_mode(0o644);
+        header.set_size(content.len() as u64);
+        builder
+            .append_data(&mut header, format!("{}/gnu/rust_001_iss_nyc.json", LONG_DIR), content.as_slice())
+            .unwrap();
+        append_pax(&mut builder, &format!("{}/pax/rust_001_iss_nyc.json", LONG_DIR), &content);
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let read = read_source(&archive);
+        fs::remove_dir_all(&dir).ok();
+        let (entries, host) = read.unwrap();
+        let sources: Vec<PathBuf> = entries.iter().map(|entry| entry.source.clone()).collect();
+        assert_eq!(
+            sources,
+            [
+                archive.join(format!("{}/gnu/rust_001_iss_nyc.json", LONG_DIR)),
+                archive.join(format!("{}/pax/rust_001_iss_nyc.json", LONG_DIR)),
+            ]
+        );
+        assert!(entries.iter().all(|entry| entry.content == content));
+        assert!(host.is_none());
+    }
+
+    #[test]
+    fn results_are_normalized() {
+        let ((implementation, test_case), result) =
+            normalize(&entry("out.json", &result("001_iss_nyc")), &schema(), &cases(), Some("rust_fast")).unwrap();
+        assert_eq!((implementation.as_str(), test_case.as_str()), ("rust_fast", "001_iss_nyc"));
+        assert_eq!(result["implementation"], "rust_fast");
+        let starts: Vec<&str> = result["visibilityWindows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|window| window["start"].as_str().unwrap())
+            .collect();
+        assert_eq!(starts, ["2025-10-26T04:00:00Z", "2025-10-26T06:00:00Z"]);
+        assert_eq!(result["visibilityWindows"][1]["maxElevationTime"], "2025-10-26T06:05:00Z");
+    }
+
+    #[test]
+    fn results_for_missing_cases_are_rejected() {
+        let error = normalize(&entry("out.json", &result("999_no_such_case")), &schema(), &cases(), None).unwrap_err();
+        assert_eq!(error.to_string(), "No test case 999_no_such_case");
+    }
+
+    #[test]
+    fn bad_results_are_rejected() {
+        let mut out_of_range = result("001_iss_nyc");
+        out_of_range["visibilityWindows"][0]["maxElevation"] = json!(120.0);
+        let error = normalize(&entry("out.json", &out_of_range), &schema(), &cases(), None).unwrap_err();
+        assert!(error.to_string().starts_with("Schema: /visibilityWindows/0/maxElevation"), "{}", error);
+
+        let not_json = Entry {
+            source: PathBuf::from("out.json"),
+            content: b"{\"testCase\":".to_vec(),
+        };
+        let error = normalize(&not_json, &schema(), &cases(), None).unwrap_err();
+        assert_eq!(error.to_string(), "Not JSON");
+
+        let mut unnameable = result("001_iss_nyc");
+        unnameable["implementation"] = json!("../rust");
+        assert!(normalize(&entry("out.json", &unnameable), &schema(), &cases(), None).is_err());
+    }
+}
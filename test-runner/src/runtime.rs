@@ -0,0 +1,516 @@
+//! Container runtimes: Docker and Podman.
+//!
+//! Every container command the orchestrator issues goes through
+//! [`ContainerRuntime`]. Docker is driven through its Engine API (see
+//! [`docker_api`](crate::docker_api)); Podman through its CLI, which the
+//! trait's default methods run.
+//!
+//! The runtime is chosen with `--runtime`, or the `runtime` of the
+//! `--profile` (see [`profiles`](crate::profiles)), and defaults to Docker.
+
+use crate::docker_api::Docker;
+use crate::error::Error;
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often a run with a timeout or heartbeat checks whether its container
+/// has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuntimeKind {
+    /// Through the Docker Engine API; `docker-api` is accepted for it too
+    #[value(alias = "docker-api")]
+    #[serde(alias = "docker-api")]
+    Docker,
+    Podman,
+}
+
+impl RuntimeKind {
+    pub fn runtime(self) -> &'static dyn ContainerRuntime {
+        match self {
+            RuntimeKind::Docker => &Docker,
+            RuntimeKind::Podman => &Podman,
+        }
+    }
+}
+
+/// CPU and memory caps for test containers, so every implementation is
+/// timed on the same resources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Limits {
+    /// CPUs, as in `run --cpus`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// Memory in bytes, as in `run --memory`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.cpus.is_none() && self.memory.is_none()
+    }
+
+    /// E.g. "2 CPUs, 512 MiB"
+    pub fn describe(&self) -> String {
+        let cpus = self.cpus.map(|cpus| format!("{} CPU{}", cpus, if cpus == 1.0 { "" } else { "s" }));
+        let memory = self.memory.map(|bytes| match bytes {
+            bytes if bytes >= 1 << 30 && bytes % (1 << 30) == 0 => format!("{} GiB", bytes >> 30),
+            bytes if bytes >= 1 << 20 => format!("{:.0} MiB", bytes as f64 / (1u64 << 20) as f64),
+            bytes => format!("{} bytes", bytes),
+        });
+        cpus.into_iter().chain(memory).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Parse a memory size as `docker run --memory` takes it: a number of
+/// bytes with an optional `b`, `k`, `m`, or `g` suffix (powers of 1024).
+pub fn parse_memory(size: &str) -> Result<u64> {
+    let lower = size.trim().to_ascii_lowercase();
+    let (number, unit) = match lower.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(split) => lower.split_at(split),
+        None => (lower.as_str(), ""),
+    };
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => bail!("Invalid memory size {:?}: expected a number with an optional b, k, m, or g suffix", size),
+    };
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid memory size {:?}: expected a number with an optional b, k, m, or g suffix", size))?;
+    let bytes = (number * multiplier as f64) as u64;
+    if bytes == 0 {
+        bail!("Memory limit {:?} is zero", size);
+    }
+    Ok(bytes)
+}
+
+/// A container to run to completion and then remove.
+#[derive(Debug, Default)]
+pub struct RunSpec<'a> {
+    pub image: &'a str,
+    /// Arguments passed to the image's entrypoint
+    pub args: Vec<&'a str>,
+    /// Bind mounts: host path, container path, read-only
+    pub mounts: Vec<(PathBuf, &'a str, bool)>,
+    /// `KEY=value` environment variables
+    pub env: Vec<String>,
+    pub cpuset: Option<&'a str>,
+    /// Where to write the container's ID once it exists, as `run --cidfile`
+    pub cidfile: Option<&'a Path>,
+    /// Kill the container if it is still running after this long
+    pub timeout: Option<Duration>,
+    /// Kill the container if it goes silent for longer than this allows
+    pub heartbeat: Option<Heartbeat>,
+    pub limits: Limits,
+}
+
+/// Liveness a run requires of its container: touching `file` (a host path),
+/// or printing anything, at least every `silence`. A container silent for
+/// longer is killed as hung.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    pub file: PathBuf,
+    pub silence: Duration,
+}
+
+impl Heartbeat {
+    /// Whether a container started at `started` has been silent for longer
+    /// than `silence`. `last_output` gives when it last printed, and is only
+    /// asked when the file is stale too.
+    pub fn silent(&self, started: SystemTime, last_output: impl FnOnce() -> Option<SystemTime>) -> bool {
+        let touched = fs::metadata(&self.file).and_then(|metadata| metadata.modified()).ok();
+        let quiet = |since: Option<SystemTime>| since.is_none_or(|since| since.elapsed().is_ok_and(|quiet| quiet > self.silence));
+        quiet(Some(started)) && quiet(touched) && quiet(last_output())
+    }
+
+    /// Count a sign of life seen elsewhere, such as a streamed record, by
+    /// touching the file.
+    pub fn beat(&self) {
+        let touched = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if touched.is_ok() {
+            // Left writable for containers running as another user
+            fs::set_permissions(&self.file, fs::Permissions::from_mode(0o666)).ok();
+        }
+    }
+}
+
+pub struct RunOutput {
+    /// `None` if the container was killed by a signal before reporting one
+    pub exit_code: Option<i64>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// Whether the container was killed for running past the timeout
+    pub timed_out: bool,
+    /// Whether the container was killed for going silent past its heartbeat
+    pub hung: bool,
+}
+
+impl RunOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+pub trait ContainerRuntime: Send + Sync {
+    /// The runtime's CLI
+    fn program(&self) -> &'static str;
+
+    fn command(&self) -> Command {
+        Command::new(self.program())
+    }
+
+    /// Build `context_dir`'s Dockerfile as `image`, with `label` (`key=value`).
+    fn build(&self, context_dir: &Path, image: &str, label: &str) -> Result<()> {
+        let output = self
+            .command()
+            .args(["build", "--label", label, "-t", image, "."])
+            .current_dir(context_dir)
+            .output()
+            .map_err(|e| self.unavailable("build", e))?;
+
+        self.check_daemon(&output)?;
+        if !output.status.success() {
+            bail!("Build failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(())
+    }
+
+    fn run(&self, spec: &RunSpec) -> Result<RunOutput> {
+        let mut cmd = self.command();
+        cmd.args(["run", "--rm"]);
+        for (host, container, read_only) in &spec.mounts {
+            let mode = if *read_only { ":ro" } else { "" };
+            cmd.args(["-v", &format!("{}:{}{}", host.display(), container, mode)]);
+        }
+        for var in &spec.env {
+            cmd.args(["-e", var]);
+        }
+        if let Some(cpuset) = spec.cpuset {
+            cmd.arg(format!("--cpuset-cpus={}", cpuset));
+        }
+        if let Some(cpus) = spec.limits.cpus {
+            cmd.arg(format!("--cpus={}", cpus));
+        }
+        if let Some(memory) = spec.limits.memory {
+            cmd.arg(format!("--memory={}", memory));
+        }
+        // Killing the client doesn't stop the container, so a timeout or
+        // heartbeat needs its ID to `kill` it
+        let watched = spec.timeout.is_some() || spec.heartbeat.is_some();
+        let own_cidfile = (watched && spec.cidfile.is_none()).then(|| {
+            std::env::temp_dir().join(format!("visibility-test-runner-{}-{}.cid", std::process::id(), spec.image.replace(['/', ':'], "_")))
+        });
+        let cidfile = spec.cidfile.or(own_cidfile.as_deref());
+        if let Some(cidfile) = cidfile {
+            cmd.arg(format!("--cidfile={}", cidfile.display()));
+        }
+        cmd.arg(spec.image).args(&spec.args);
+
+        if !watched {
+            let output = cmd.output().map_err(|e| self.unavailable("run", e))?;
+            return Ok(RunOutput {
+                exit_code: output.status.code().map(i64::from),
+                stdout: output.stdout,
+                stderr: output.stderr,
+                timed_out: false,
+                hung: false,
+            });
+        }
+
+        let started = SystemTime::now();
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.unavailable("run", e))?;
+        let last_output = Arc::new(Mutex::new(None));
+        let stdout = read_pipe(child.stdout.take(), last_output.clone());
+        let stderr = read_pipe(child.stderr.take(), last_output.clone());
+        let deadline = spec.timeout.map(|timeout| Instant::now() + timeout);
+        let (mut timed_out, mut hung) = (false, false);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if !timed_out && !hung {
+                timed_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                hung = !timed_out
+                    && spec
+                        .heartbeat
+                        .as_ref()
+                        .is_some_and(|heartbeat| heartbeat.silent(started, || *last_output.lock().unwrap()));
+                if timed_out || hung {
+                    let id = cidfile.and_then(|cidfile| fs::read_to_string(cidfile).ok());
+                    match id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+                        Some(id) => {
+                            self.command().args(["kill", id]).output().ok();
+                        }
+                        // Not created yet; stopping the client stops its creation
+                        None => {
+                            child.kill().ok();
+                        }
+                    }
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        if let Some(own_cidfile) = &own_cidfile {
+            fs::remove_file(own_cidfile).ok();
+        }
+        Ok(RunOutput {
+            exit_code: status.code().map(i64::from),
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+            timed_out,
+            hung,
+        })
+    }
+
+    /// Local image ID, or `None` if the image isn't present.
+    fn image_id(&self, image: &str) -> Result<Option<String>> {
+        let output = self
+            .command()
+            .args(["image", "inspect", "--format", "{{.Id}}", image])
+            .output()
+            .map_err(|e| self.unavailable("image inspect", e))?;
+        self.check_daemon(&output)?;
+
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// Value of `label` on a local image; `None` if the image or label is missing.
+    fn image_label(&self, image: &str, label: &str) -> Result<Option<String>> {
+        let format = format!("{{{{ index .Config.Labels \"{}\" }}}}", label);
+        let output = self
+            .command()
+            .args(["image", "inspect", "--format", &format, image])
+            .output()
+            .map_err(|e| self.unavailable("image inspect", e))?;
+        self.check_daemon(&output)?;
+
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((output.status.success() && !value.is_empty() && value != "<no value>").then_some(value))
+    }
+
+    fn pull(&self, image: &str) -> Result<()> {
+        println!("  {} Pulling {}", "●".bright_cyan(), image);
+        let output = self
+            .command()
+            .args(["pull", "--quiet", image])
+            .output()
+            .map_err(|e| self.unavailable("pull", e))?;
+        self.check_daemon(&output)?;
+
+        if !output.status.success() {
+            bail!("Pull of {} failed: {}", image, String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(())
+    }
+
+    /// Local images matching every filter (`key=value`, as `images --filter`
+    /// takes them): each image's ID and its `repository:tag`, named as it
+    /// was tagged, or `None` if it's dangling. An image tagged more than once
+    /// is listed once per tag.
+    fn images(&self, filters: &[&str]) -> Result<Vec<(String, Option<String>)>> {
+        let mut cmd = self.command();
+        cmd.args(["images", "--no-trunc", "--format", "{{.ID}}\t{{.Repository}}:{{.Tag}}"]);
+        for filter in filters {
+            cmd.args(["--filter", filter]);
+        }
+        let output = cmd.output().map_err(|e| self.unavailable("images", e))?;
+        self.check_daemon(&output)?;
+        if !output.status.success() {
+            bail!("{} images failed: {}", self.program(), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let prefix = self.local_image_prefix().unwrap_or_default();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (id, name) = line.split_once('\t')?;
+                let name = (name != "<none>:<none>").then(|| name.strip_prefix(prefix).unwrap_or(name).to_string());
+                Some((id.to_string(), name))
+            })
+            .collect())
+    }
+
+    /// Remove `image`, given as `repository:tag` or ID. An image with other
+    /// tags only loses this one.
+    fn remove_image(&self, image: &str) -> Result<()> {
+        let output = self
+            .command()
+            .args(["rmi", image])
+            .output()
+            .map_err(|e| self.unavailable("rmi", e))?;
+        self.check_daemon(&output)?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    /// Images that currently have a running container, named as they were
+    /// tagged.
+    fn running_images(&self) -> Result<Vec<String>> {
+        let output = self
+            .command()
+            .args(["ps", "--format", "{{.Image}}"])
+            .output()
+            .map_err(|e| self.unavailable("ps", e))?;
+        self.check_daemon(&output)?;
+        if !output.status.success() {
+            bail!("{} ps failed: {}", self.program(), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let prefix = self.local_image_prefix().unwrap_or_default();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|image| image.strip_prefix(prefix).unwrap_or(image).to_string())
+            .collect())
+    }
+
+    /// The error for failing to start the CLI for `subcommand`.
+    fn unavailable(&self, subcommand: &str, e: io::Error) -> anyhow::Error {
+        Error::DockerUnavailable {
+            runtime: self.program().to_string(),
+            detail: format!("failed to execute `{} {}`: {}", self.program(), subcommand, e),
+        }
+        .into()
+    }
+
+    /// Fail if the CLI failed for want of its daemon, which it otherwise
+    /// reports like any other failure (or a missing image).
+    fn check_daemon(&self, output: &Output) -> Result<()> {
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.lines().find(|line| line.contains("Cannot connect to")) {
+            Some(line) => Err(Error::DockerUnavailable {
+                runtime: self.program().to_string(),
+                detail: line.trim().to_string(),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Prefix `ps` adds to the names of locally built images.
+    fn local_image_prefix(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Candidate cgroups of container `id`, relative to the cgroup root (and
+    /// to the controller directory under cgroup v1).
+    fn cgroups(&self, id: &str) -> Vec<PathBuf>;
+}
+
+/// Read a child's output pipe to the end on its own thread, so neither pipe
+/// fills up while the other is read. Notes the time of the latest output in
+/// `last_output`, for heartbeats.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>, last_output: Arc<Mutex<Option<SystemTime>>>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return output;
+        };
+        let mut chunk = [0u8; 8192];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => {
+                    output.extend_from_slice(&chunk[..read]);
+                    *last_output.lock().unwrap() = Some(SystemTime::now());
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        output
+    })
+}
+
+pub struct Podman;
+
+impl ContainerRuntime for Podman {
+    fn program(&self) -> &'static str {
+        "podman"
+    }
+
+    fn local_image_prefix(&self) -> Option<&'static str> {
+        Some("localhost/")
+    }
+
+    fn cgroups(&self, id: &str) -> Vec<PathBuf> {
+        let scope = format!("libpod-{}.scope", id);
+        // SAFETY: getuid has no preconditions and cannot fail
+        let uid = unsafe { libc::getuid() };
+        vec![
+            // Rootful, systemd driver
+            Path::new("machine.slice").join(&scope),
+            // Rootless, systemd driver
+            PathBuf::from(format!("user.slice/user-{0}.slice/user@{0}.service/user.slice", uid)).join(&scope),
+            // cgroupfs driver
+            Path::new("libpod_parent").join(format!("libpod-{}", id)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_memory_takes_suffixes_in_powers_of_1024() {
+        assert_eq!(parse_memory("1024").unwrap(), 1024);
+        assert_eq!(parse_memory("512b").unwrap(), 512);
+        assert_eq!(parse_memory("4k").unwrap(), 4 << 10);
+        assert_eq!(parse_memory("512m").unwrap(), 512 << 20);
+        assert_eq!(parse_memory("512MiB").unwrap(), 512 << 20);
+        assert_eq!(parse_memory(" 2G ").unwrap(), 2 << 30);
+        assert_eq!(parse_memory("1.5g").unwrap(), 3 << 29);
+    }
+
+    #[test]
+    fn parse_memory_rejects_bad_sizes() {
+        for size in ["", "m", "512t", "-1m", "1.2.3m", "0", "0.0001b"] {
+            assert!(parse_memory(size).is_err(), "{:?} was accepted", size);
+        }
+    }
+
+    #[test]
+    fn limits_describe_cpus_and_memory() {
+        let limits = |cpus, memory| Limits { cpus, memory };
+        assert_eq!(limits(None, None).describe(), "");
+        assert_eq!(limits(Some(1.0), None).describe(), "1 CPU");
+        assert_eq!(limits(Some(2.0), Some(512 << 20)).describe(), "2 CPUs, 512 MiB");
+        assert_eq!(limits(Some(0.5), None).describe(), "0.5 CPUs");
+        assert_eq!(limits(None, Some(2 << 30)).describe(), "2 GiB");
+        assert_eq!(limits(None, Some(3 << 29)).describe(), "1536 MiB");
+        assert_eq!(limits(None, Some(1000)).describe(), "1000 bytes");
+        assert!(limits(None, None).is_empty());
+        assert!(!limits(None, Some(1)).is_empty());
+    }
+}
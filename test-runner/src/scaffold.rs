@@ -0,0 +1,140 @@
+//! Skeleton implementations, for `init`.
+//!
+//! A new implementation starts from a template in `templates/<language>/`:
+//! a Dockerfile, the language's manifest, and an entrypoint that already
+//! follows the container interface. It answers `--version`, reads one
+//! named case or every case from `/test-data/cases`, and writes
+//! `/results/<impl>_<case>.json` with the standard result fields. Only its
+//! `calculate` function is left to write, and until then it reports no
+//! windows. `templates/README.md` becomes the implementation's README, with
+//! empty [front-matter](crate::library_info) to fill in.
+//!
+//! Templates are built into the binary, and `{{implementation}}`,
+//! `{{library}}`, and the README's placeholders are substituted as they're
+//! written.
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Template {
+    Python,
+    Javascript,
+    Go,
+    Rust,
+}
+
+impl Template {
+    /// Directory name prefix, as in [`languages`](crate::languages)
+    fn prefix(self) -> &'static str {
+        match self {
+            Template::Python => "python",
+            Template::Javascript => "javascript",
+            Template::Go => "go",
+            Template::Rust => "rust",
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            Template::Python => "Python",
+            Template::Javascript => "JavaScript",
+            Template::Go => "Go",
+            Template::Rust => "Rust",
+        }
+    }
+
+    /// Relative path and content of each file, the entrypoint first.
+    fn files(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Template::Python => &[
+                ("src/main.py", include_str!("../templates/python/src/main.py")),
+                ("requirements.txt", include_str!("../templates/python/requirements.txt")),
+                ("Dockerfile", include_str!("../templates/python/Dockerfile")),
+            ],
+            Template::Javascript => &[
+                ("src/index.js", include_str!("../templates/javascript/src/index.js")),
+                ("package.json", include_str!("../templates/javascript/package.json")),
+                ("Dockerfile", include_str!("../templates/javascript/Dockerfile")),
+            ],
+            Template::Go => &[
+                ("main.go", include_str!("../templates/go/main.go")),
+                ("go.mod", include_str!("../templates/go/go.mod")),
+                ("Dockerfile", include_str!("../templates/go/Dockerfile")),
+            ],
+            Template::Rust => &[
+                ("src/main.rs", include_str!("../templates/rust/src/main.rs")),
+                ("Cargo.toml", include_str!("../templates/rust/Cargo.toml")),
+                ("Dockerfile", include_str!("../templates/rust/Dockerfile")),
+            ],
+        }
+    }
+
+    /// How to run the entrypoint outside Docker
+    fn native(self) -> &'static str {
+        match self {
+            Template::Python => "python src/main.py",
+            Template::Javascript => "node src/index.js",
+            Template::Go => "go run .",
+            Template::Rust => "cargo run --release --",
+        }
+    }
+}
+
+/// A skeleton written by [`create`].
+pub struct Scaffold {
+    pub name: String,
+    pub path: PathBuf,
+    /// Relative paths of the files written
+    pub files: Vec<String>,
+    /// File `calculate` is in
+    pub entrypoint: String,
+}
+
+/// Write a skeleton implementation of `library` in `template`'s language to
+/// `<implementations_dir>/<language>-<library>`. `library` may already carry
+/// the language prefix. Fails if the directory exists.
+pub fn create(implementations_dir: &Path, library: &str, template: Template) -> Result<Scaffold> {
+    let prefix = format!("{}-", template.prefix());
+    let library = library.strip_prefix(&prefix).unwrap_or(library);
+    // Image names are lowercase, and the directory name is the image name
+    if library.is_empty()
+        || !library
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!("Invalid library name '{}': use lowercase letters, digits, '-', '_' and '.'", library);
+    }
+
+    let name = format!("{}{}", prefix, library);
+    let path = implementations_dir.join(&name);
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+
+    let fill = |content: &str| content.replace("{{implementation}}", &name).replace("{{library}}", library);
+    let (entrypoint, _) = template.files()[0];
+    let readme = fill(include_str!("../templates/README.md"))
+        .replace("{{language}}", template.display_name())
+        .replace("{{source}}", entrypoint)
+        .replace("{{native}}", template.native());
+
+    let mut files = Vec::new();
+    for (file, content) in template.files().iter().map(|(file, content)| (*file, fill(content))).chain([("README.md", readme)]) {
+        let target = path.join(file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&target, content).with_context(|| format!("Failed to write {}", target.display()))?;
+        files.push(file.to_string());
+    }
+
+    Ok(Scaffold {
+        name,
+        path,
+        files,
+        entrypoint: entrypoint.to_string(),
+    })
+}
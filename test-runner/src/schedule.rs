@@ -0,0 +1,236 @@
+//! Cron-style scheduling for daemon mode.
+//!
+//! Expressions use the classic five fields - minute, hour, day of month,
+//! month, day of week - evaluated in local time. Each field accepts `*`,
+//! single values, ranges (`1-5`), steps (`*/15`, `0-30/10`), and
+//! comma-separated lists. Day of week is 0-7 with both 0 and 7 meaning Sunday.
+//!
+//! Across DST changes, a time skipped by the clock going forward doesn't fire
+//! that day, and a time repeated by it going back fires once, unless the
+//! expression fires every hour anyway.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike};
+
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    // Standard cron semantics: when both day fields are restricted, a day
+    // matches if either one does.
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!(
+                "Cron expression must have 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            );
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7).context("Invalid day-of-week field")?;
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(fields[0], 0, 59).context("Invalid minute field")?,
+            hours: parse_field(fields[1], 0, 23).context("Invalid hour field")?,
+            days_of_month: parse_field(fields[2], 1, 31).context("Invalid day-of-month field")?,
+            months: parse_field(fields[3], 1, 12).context("Invalid month field")?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first matching minute strictly after `after`, in its time zone.
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let zone = after.timezone();
+        // Truncated by subtraction: `with_second` and the like give up on
+        // local times that a DST change makes ambiguous
+        let mut t = after.clone() - Duration::seconds(after.second().into()) - Duration::nanoseconds(after.nanosecond().into())
+            + Duration::minutes(1);
+        // Every valid expression matches at least once within a leap cycle
+        let limit = after.clone() + Duration::days(366 * 4);
+        let hourly = self.hours.iter().all(|&hour| hour);
+
+        while t <= limit {
+            if !self.months[t.month() as usize] {
+                t = start_of_day(&zone, t.date_naive().with_day(1)? + chrono::Months::new(1))?;
+                continue;
+            }
+            if !self.day_matches(&t) {
+                t = start_of_day(&zone, t.date_naive() + Duration::days(1))?;
+                continue;
+            }
+            if !self.hours[t.hour() as usize] {
+                t = t.clone() - Duration::minutes(t.minute().into()) + Duration::hours(1);
+                continue;
+            }
+            // The clock went back over a time already passed
+            let repeated = !hourly && t.naive_local() <= after.naive_local();
+            if !self.minutes[t.minute() as usize] || repeated {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+
+        None
+    }
+
+    fn day_matches<Tz: TimeZone>(&self, t: &DateTime<Tz>) -> bool {
+        let dom = self.days_of_month[t.day() as usize];
+        let dow = self.days_of_week[t.weekday().num_days_from_sunday() as usize];
+
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+fn start_of_day<Tz: TimeZone>(zone: &Tz, date: chrono::NaiveDate) -> Option<DateTime<Tz>> {
+    // `earliest` resolves DST gaps at midnight to the first valid instant
+    zone.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()
+}
+
+/// Parse one cron field into a lookup table indexed by value.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>> {
+    let mut allowed = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().context("Invalid step")?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            bail!("Step must be positive in '{}'", part);
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse()?, b.parse()?)
+        } else {
+            let value: u32 = range.parse().with_context(|| format!("Invalid value '{}'", range))?;
+            // `5/10` means "from 5 to the end, every 10"
+            if part.contains('/') { (value, max) } else { (value, value) }
+        };
+
+        if start < min || end > max || start > end {
+            bail!("'{}' is outside {}-{}", part, min, max);
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+    use chrono_tz::Tz;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        New_York.with_ymd_and_hms(year, month, day, hour, minute, 0).earliest().unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Tz>) -> DateTime<Tz> {
+        CronSchedule::parse(expression).unwrap().next_after(after).unwrap()
+    }
+
+    fn allowed(table: &[bool]) -> Vec<usize> {
+        table.iter().enumerate().filter(|(_, allowed)| **allowed).map(|(value, _)| value).collect()
+    }
+
+    #[test]
+    fn fields_take_ranges_steps_and_lists() {
+        assert_eq!(allowed(&parse_field("1-5", 0, 59).unwrap()), [1, 2, 3, 4, 5]);
+        assert_eq!(allowed(&parse_field("*/15", 0, 59).unwrap()), [0, 15, 30, 45]);
+        assert_eq!(allowed(&parse_field("0-30/10", 0, 59).unwrap()), [0, 10, 20, 30]);
+        assert_eq!(allowed(&parse_field("50/5", 0, 59).unwrap()), [50, 55]);
+        assert_eq!(allowed(&parse_field("1,3,10-12", 1, 31).unwrap())[..], [1, 3, 10, 11, 12]);
+        assert_eq!(allowed(&parse_field("*", 1, 12).unwrap()), (1..=12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fields_reject_bad_values() {
+        for (field, min, max) in [("60", 0, 59), ("0", 1, 31), ("5-1", 0, 59), ("*/0", 0, 59), ("x", 0, 59), ("1-", 0, 59)] {
+            assert!(parse_field(field, min, max).is_err(), "{} was accepted", field);
+        }
+        assert!(CronSchedule::parse("0 3 * *").is_err());
+        assert!(CronSchedule::parse("0 3 * * * *").is_err());
+    }
+
+    #[test]
+    fn sunday_is_both_0_and_7() {
+        // 2026-10-18 is a Sunday
+        let after = at(2026, 10, 14, 12, 0);
+        assert_eq!(next("0 3 * * 0", after), at(2026, 10, 18, 3, 0));
+        assert_eq!(next("0 3 * * 7", after), at(2026, 10, 18, 3, 0));
+    }
+
+    #[test]
+    fn next_after_is_strictly_after() {
+        assert_eq!(next("30 2 * * *", at(2026, 10, 14, 2, 30)), at(2026, 10, 15, 2, 30));
+        assert_eq!(next("*/15 * * * *", at(2026, 10, 14, 2, 30)), at(2026, 10, 14, 2, 45));
+        assert_eq!(next("0 0 1 1 *", at(2026, 10, 14, 2, 30)), at(2027, 1, 1, 0, 0));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or a Monday; 2026-10-19 is the first Monday
+        // after Wednesday 2026-10-14, and 2026-11-01 the next 1st
+        let either = "0 9 1 * 1";
+        assert_eq!(next(either, at(2026, 10, 14, 12, 0)), at(2026, 10, 19, 9, 0));
+        assert_eq!(next(either, at(2026, 10, 26, 12, 0)), at(2026, 11, 1, 9, 0));
+        // With only one restricted, the other doesn't widen it
+        assert_eq!(next("0 9 1 * *", at(2026, 10, 14, 12, 0)), at(2026, 11, 1, 9, 0));
+        assert_eq!(next("0 9 * * 1", at(2026, 10, 14, 12, 0)), at(2026, 10, 19, 9, 0));
+    }
+
+    #[test]
+    fn skips_a_time_the_clock_jumps_over() {
+        // 02:00-03:00 doesn't exist on 2026-03-08 in New York
+        assert_eq!(next("30 2 * * *", at(2026, 3, 7, 12, 0)), at(2026, 3, 9, 2, 30));
+        assert_eq!(next("0 * * * *", at(2026, 3, 8, 1, 30)), at(2026, 3, 8, 3, 0));
+    }
+
+    #[test]
+    fn fires_once_in_a_repeated_hour() {
+        // 01:00-02:00 happens twice on 2026-11-01 in New York
+        let first = next("30 1 * * *", at(2026, 11, 1, 0, 0));
+        assert_eq!(first, at(2026, 11, 1, 1, 30));
+        assert_eq!(next("30 1 * * *", first), at(2026, 11, 2, 1, 30));
+    }
+
+    #[test]
+    fn hourly_expressions_keep_firing_in_a_repeated_hour() {
+        let first = next("30 * * * *", at(2026, 11, 1, 1, 0));
+        let second = next("30 * * * *", first);
+        assert_eq!(second - first, Duration::hours(1));
+        assert_eq!(second.naive_local(), at(2026, 11, 1, 1, 30).naive_local());
+    }
+}
@@ -0,0 +1,287 @@
+//! Validation against `test-data/schema.json`.
+//!
+//! Covers the JSON Schema (draft-07) keywords the schema uses: `$ref` to
+//! its own `definitions`, `type`, `required`, `properties`, `items`,
+//! `enum`, `minimum`, `maximum`, `exclusiveMinimum`, `minItems`,
+//! `maxItems`, `oneOf`, `not`, and the `date-time` format. Anything else is
+//! ignored, so adding a keyword to the schema means adding it here for it
+//! to be enforced.
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+pub struct Schema(Value);
+
+impl Schema {
+    pub fn read(test_data_dir: &Path) -> Result<Self> {
+        let path = test_data_dir.join("schema.json");
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Schema(serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?))
+    }
+
+    /// Violations of `value` against the named definition (e.g.
+    /// `TestCaseOutput`), each prefixed with the JSON pointer of the
+    /// offending value.
+    pub fn validate(&self, definition: &str, value: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        match self.0["definitions"].get(definition) {
+            Some(schema) => self.check(schema, value, "", &mut errors),
+            None => errors.push(format!("schema has no definition {}", definition)),
+        }
+        errors
+    }
+
+    fn check(&self, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        let at = if path.is_empty() { "/" } else { path };
+        if let Some(reference) = schema["$ref"].as_str() {
+            match reference.strip_prefix("#/").and_then(|pointer| self.0.pointer(&format!("/{}", pointer))) {
+                Some(target) => self.check(target, value, path, errors),
+                None => errors.push(format!("{}: unresolvable $ref {}", at, reference)),
+            }
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                other => other.as_str().into_iter().collect(),
+            };
+            if !types.is_empty() && !types.iter().any(|name| is_type(value, name)) {
+                errors.push(format!("{}: expected {}, found {}", at, types.join(" or "), type_name(value)));
+                // The remaining keywords assume the right type
+                return;
+            }
+        }
+
+        if let Some(allowed) = schema["enum"].as_array()
+            && !allowed.contains(value)
+        {
+            errors.push(format!("{}: {} is not one of {}", at, value, Value::from(allowed.clone())));
+        }
+
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = schema["minimum"].as_f64()
+                && number < minimum
+            {
+                errors.push(format!("{}: {} is below the minimum of {}", at, number, minimum));
+            }
+            if let Some(maximum) = schema["maximum"].as_f64()
+                && number > maximum
+            {
+                errors.push(format!("{}: {} is above the maximum of {}", at, number, maximum));
+            }
+            if let Some(minimum) = schema["exclusiveMinimum"].as_f64()
+                && number <= minimum
+            {
+                errors.push(format!("{}: {} must be greater than {}", at, number, minimum));
+            }
+        }
+
+        if schema["format"] == "date-time"
+            && let Some(text) = value.as_str()
+            && DateTime::parse_from_rfc3339(text).is_err()
+        {
+            errors.push(format!("{}: {} is not an RFC 3339 date-time", at, text));
+        }
+
+        if let Some(object) = value.as_object() {
+            for field in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+                if !object.contains_key(field) {
+                    errors.push(format!("{}: missing required field {}", at, field));
+                }
+            }
+            if let Some(properties) = schema["properties"].as_object() {
+                for (field, field_schema) in properties {
+                    if let Some(field_value) = object.get(field) {
+                        self.check(field_schema, field_value, &format!("{}/{}", path, field), errors);
+                    }
+                }
+            }
+        }
+
+        if let Some(items) = value.as_array() {
+            if let Some(min_items) = schema["minItems"].as_u64()
+                && (items.len() as u64) < min_items
+            {
+                errors.push(format!("{}: {} item(s), at least {} expected", at, items.len(), min_items));
+            }
+            if let Some(max_items) = schema["maxItems"].as_u64()
+                && (items.len() as u64) > max_items
+            {
+                errors.push(format!("{}: {} item(s), at most {} expected", at, items.len(), max_items));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    self.check(item_schema, item, &format!("{}/{}", path, i), errors);
+                }
+            }
+        }
+
+        if let Some(options) = schema["oneOf"].as_array() {
+            let matching = options.iter().filter(|option| self.matches(option, value, path)).count();
+            if matching != 1 {
+                errors.push(format!("{}: matches {} of the oneOf alternatives, exactly 1 expected", at, matching));
+            }
+        }
+        if let Some(excluded) = schema.get("not")
+            && self.matches(excluded, value, path)
+        {
+            errors.push(format!("{}: matches a schema it must not", at));
+        }
+    }
+
+    fn matches(&self, schema: &Value, value: &Value, path: &str) -> bool {
+        let mut errors = Vec::new();
+        self.check(schema, value, path, &mut errors);
+        errors.is_empty()
+    }
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Violations of `value` against `schema`, as a lone definition
+    fn violations(schema: Value, value: Value) -> Vec<String> {
+        Schema(json!({ "definitions": { "Test": schema } })).validate("Test", &value)
+    }
+
+    #[test]
+    fn type_is_checked() {
+        assert!(violations(json!({ "type": "string" }), json!("iss")).is_empty());
+        assert_eq!(violations(json!({ "type": "string" }), json!(25544)), ["/: expected string, found number"]);
+        assert!(violations(json!({ "type": ["number", "null"] }), json!(null)).is_empty());
+        assert!(violations(json!({ "type": "integer" }), json!(3.0)).is_empty());
+        assert_eq!(violations(json!({ "type": "integer" }), json!(3.5)), ["/: expected integer, found number"]);
+        // The remaining keywords assume the right type
+        assert_eq!(violations(json!({ "type": "number", "minimum": 0 }), json!("-1")).len(), 1);
+    }
+
+    #[test]
+    fn required_fields_are_checked() {
+        let schema = json!({ "type": "object", "required": ["start", "end"] });
+        assert!(violations(schema.clone(), json!({ "start": "a", "end": "b" })).is_empty());
+        assert_eq!(violations(schema, json!({ "start": "a" })), ["/: missing required field end"]);
+    }
+
+    #[test]
+    fn properties_are_checked_where_present() {
+        let schema = json!({ "type": "object", "properties": { "maxElevation": { "type": "number" } } });
+        assert!(violations(schema.clone(), json!({})).is_empty());
+        assert_eq!(
+            violations(schema, json!({ "maxElevation": "high" })),
+            ["/maxElevation: expected number, found string"]
+        );
+    }
+
+    #[test]
+    fn items_are_checked_with_their_index() {
+        let schema = json!({ "type": "array", "items": { "type": "object", "required": ["time"] } });
+        assert!(violations(schema.clone(), json!([{ "time": "t" }])).is_empty());
+        assert_eq!(
+            violations(schema, json!([{ "time": "t" }, {}])),
+            ["/1: missing required field time"]
+        );
+    }
+
+    #[test]
+    fn enum_is_checked() {
+        let schema = json!({ "enum": ["leo", "meo", "geo"] });
+        assert!(violations(schema.clone(), json!("leo")).is_empty());
+        assert_eq!(violations(schema, json!("heo")), [r#"/: "heo" is not one of ["leo","meo","geo"]"#]);
+    }
+
+    #[test]
+    fn minimum_and_maximum_are_inclusive() {
+        let schema = json!({ "type": "number", "minimum": -90, "maximum": 90 });
+        assert!(violations(schema.clone(), json!(-90)).is_empty());
+        assert!(violations(schema.clone(), json!(90)).is_empty());
+        assert_eq!(violations(schema.clone(), json!(-90.5)), ["/: -90.5 is below the minimum of -90"]);
+        assert_eq!(violations(schema, json!(91)), ["/: 91 is above the maximum of 90"]);
+    }
+
+    #[test]
+    fn exclusive_minimum_excludes_its_bound() {
+        let schema = json!({ "type": "number", "exclusiveMinimum": 0 });
+        assert!(violations(schema.clone(), json!(0.001)).is_empty());
+        assert_eq!(violations(schema, json!(0)), ["/: 0 must be greater than 0"]);
+    }
+
+    #[test]
+    fn item_counts_are_checked() {
+        let schema = json!({ "type": "array", "minItems": 1, "maxItems": 2 });
+        assert!(violations(schema.clone(), json!([1, 2])).is_empty());
+        assert_eq!(violations(schema.clone(), json!([])), ["/: 0 item(s), at least 1 expected"]);
+        assert_eq!(violations(schema, json!([1, 2, 3])), ["/: 3 item(s), at most 2 expected"]);
+    }
+
+    #[test]
+    fn one_of_needs_exactly_one_match() {
+        let schema = json!({ "oneOf": [{ "type": "number" }, { "type": "integer" }, { "type": "string" }] });
+        assert!(violations(schema.clone(), json!("iss")).is_empty());
+        assert_eq!(violations(schema.clone(), json!(1)), ["/: matches 2 of the oneOf alternatives, exactly 1 expected"]);
+        assert_eq!(violations(schema, json!(null)), ["/: matches 0 of the oneOf alternatives, exactly 1 expected"]);
+    }
+
+    #[test]
+    fn not_rejects_what_it_matches() {
+        let schema = json!({ "not": { "required": ["points"] } });
+        assert!(violations(schema.clone(), json!({})).is_empty());
+        assert_eq!(violations(schema, json!({ "points": [] })), ["/: matches a schema it must not"]);
+    }
+
+    #[test]
+    fn date_times_must_be_rfc_3339() {
+        let schema = json!({ "type": "string", "format": "date-time" });
+        assert!(violations(schema.clone(), json!("2025-10-26T06:32:30Z")).is_empty());
+        assert_eq!(
+            violations(schema, json!("2025-10-26 06:32")),
+            ["/: 2025-10-26 06:32 is not an RFC 3339 date-time"]
+        );
+    }
+
+    #[test]
+    fn refs_resolve_within_the_schema() {
+        let schema = Schema(json!({
+            "definitions": {
+                "Window": { "type": "object", "required": ["start"] },
+                "Output": { "type": "array", "items": { "$ref": "#/definitions/Window" } }
+            }
+        }));
+        assert_eq!(schema.validate("Output", &json!([{}])), ["/0: missing required field start"]);
+        assert_eq!(schema.validate("Input", &json!({})), ["schema has no definition Input"]);
+    }
+
+    #[test]
+    fn unsupported_keywords_are_ignored() {
+        // `pattern` isn't one the schema uses, so it isn't enforced
+        assert!(violations(json!({ "type": "string", "pattern": "^[0-9]+$" }), json!("iss")).is_empty());
+    }
+}
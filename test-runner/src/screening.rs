@@ -0,0 +1,400 @@
+//! Full-catalog screening benchmark, for `bench --screening`.
+//!
+//! A screening scenario measures throughput rather than the latency of a
+//! single case: every satellite of a TLE set, typically thousands, is
+//! screened for passes over one site, as a pass predictor does for a whole
+//! catalog. Scenarios live in `test-data/screening/<name>.json`:
+//!
+//! ```json
+//! {
+//!   "name": "active_nyc",
+//!   "description": "Every active satellite over New York City for a day",
+//!   "tleSet": "active",
+//!   "observer": { "site": "new-york" },
+//!   "timeWindow": { "start": "2025-10-26T00:00:00Z", "end": "2025-10-27T00:00:00Z", "step": 60 },
+//!   "minElevation": 10.0,
+//!   "sample": 25
+//! }
+//! ```
+//!
+//! The scenario is expanded into one ordinary case per entry of
+//! `test-data/tle-sets/<tleSet>.tle`, named `<scenario>_<noradId>`, and
+//! staged under `results/.screening/<scenario>/cases`. Each implementation
+//! then screens them all in one container, writing its results to
+//! `results/.screening/<scenario>/<impl>` rather than `results/`, and is
+//! timed like any benchmark.
+//!
+//! Reference results for thousands of satellites aren't kept, so accuracy
+//! is checked on a sample: `sample` satellites spread evenly through the
+//! set are run through the reference implementation, and each
+//! implementation's results for them are compared window by window as in
+//! `validate`.
+
+use crate::bench::{BenchStats, Pinning};
+use crate::runtime::{Limits, RunOutput, RunSpec};
+use crate::{heartbeat, stream};
+use crate::usage::{self, Reading};
+use crate::tolerance::{self, Overrides};
+use crate::{
+    Implementation, Orchestrator, REFERENCE_IMPLEMENTATION, capabilities, case_names, error, read_result, result_test_case, sites, tle_sets,
+};
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Satellites checked against the reference when the scenario doesn't say.
+const DEFAULT_SAMPLE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScenarioFile {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    tle_set: String,
+    observer: Value,
+    time_window: Value,
+    #[serde(default)]
+    min_elevation: Option<f64>,
+    #[serde(default)]
+    sample: Option<usize>,
+}
+
+/// A scenario expanded into its cases.
+pub struct Scenario {
+    pub name: String,
+    pub description: Option<String>,
+    pub tle_set: String,
+    /// Staged cases, one per satellite
+    cases_dir: PathBuf,
+    /// Every case name, in set order
+    pub cases: Vec<String>,
+    /// Cases checked against the reference, staged on their own
+    sample_dir: PathBuf,
+    pub sample: Vec<String>,
+}
+
+/// How an implementation's results for the sample compare to the reference.
+#[derive(Debug, Serialize)]
+pub struct SampleCheck {
+    pub checked: usize,
+    pub matched: usize,
+    /// Cases with a different window count or a field outside tolerance
+    pub mismatched: Vec<String>,
+    /// Cases without a result
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScreeningStats {
+    #[serde(flatten)]
+    pub timing: BenchStats,
+    /// Satellites with a result in the last measured run
+    pub screened: usize,
+    /// Satellites per second at the median time
+    pub throughput: f64,
+    /// Windows found across the catalog
+    pub windows: usize,
+    /// `None` for the reference itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<SampleCheck>,
+}
+
+/// Every scenario in `test-data/screening`, with whether its TLE set is
+/// present.
+pub fn scenarios(test_data_dir: &Path) -> Result<Vec<(String, bool)>> {
+    let dir = test_data_dir.join("screening");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut scenarios = Vec::new();
+    for name in case_names(&dir)? {
+        let path = dir.join(format!("{}.json", name));
+        let file: ScenarioFile = serde_json::from_str(&fs::read_to_string(&path)?).with_context(|| format!("Failed to parse {}", path.display()))?;
+        let present = test_data_dir.join("tle-sets").join(format!("{}.tle", file.tle_set)).exists();
+        scenarios.push((name, present));
+    }
+    Ok(scenarios)
+}
+
+impl Scenario {
+    /// Read `test-data/screening/<name>.json` and stage its cases, with
+    /// `sample` overriding the scenario's own sample size.
+    pub fn load(orchestrator: &Orchestrator, name: &str, sample: Option<usize>) -> Result<Self> {
+        let path = orchestrator.test_data_dir.join("screening").join(format!("{}.json", name));
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read screening scenario {}", path.display()))?;
+        let file: ScenarioFile = serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let mut template = serde_json::json!({
+            "observer": file.observer,
+            "timeWindow": file.time_window,
+        });
+        if let Some(min_elevation) = file.min_elevation {
+            template["minElevation"] = Value::from(min_elevation);
+        }
+        if sites::references(&template) {
+            sites::resolve(&mut template, &sites::read(&orchestrator.test_data_dir)?)
+                .with_context(|| format!("Failed to resolve observer site in {}", path.display()))?;
+        }
+        if template.get("minElevation").is_none() {
+            bail!("{} has no minElevation", path.display());
+        }
+
+        let dir = orchestrator.results_dir.join(".screening").join(&file.name);
+        let cases_dir = dir.join("cases");
+        let sample_dir = dir.join("sample").join("cases");
+        for dir in [&cases_dir, &sample_dir] {
+            if dir.exists() {
+                fs::remove_dir_all(dir).with_context(|| format!("Failed to clear {}", dir.display()))?;
+            }
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let satellites = tle_sets::satellites(&orchestrator.test_data_dir, &file.tle_set)?;
+        let mut cases = Vec::with_capacity(satellites.len());
+        let mut seen = BTreeSet::new();
+        for (norad_id, satellite) in satellites {
+            let Some(norad_id) = norad_id else {
+                bail!("An entry of TLE set {} has no catalogue number: {}", file.tle_set, satellite["tle"][1]);
+            };
+            if !seen.insert(norad_id) {
+                bail!("TLE set {} has several entries for NORAD {}; screening takes one per satellite", file.tle_set, norad_id);
+            }
+            let case_name = format!("{}_{}", file.name, norad_id);
+            let mut case = template.clone();
+            case["name"] = Value::from(case_name.as_str());
+            case["description"] = Value::from(format!("{} in screening scenario {}", satellite["name"].as_str().unwrap_or_default(), file.name));
+            case["satellite"] = satellite;
+            let file_name = format!("{}.json", case_name);
+            fs::write(cases_dir.join(&file_name), serde_json::to_string_pretty(&case)?)
+                .with_context(|| format!("Failed to write {}", file_name))?;
+            cases.push(case_name);
+        }
+        if cases.is_empty() {
+            bail!("TLE set {} has no entries", file.tle_set);
+        }
+
+        // Spread evenly through the set, so the sample doesn't come from
+        // one constellation
+        let size = sample.or(file.sample).unwrap_or(DEFAULT_SAMPLE).min(cases.len());
+        let sample: Vec<String> = (0..size).map(|i| cases[i * cases.len() / size].clone()).collect();
+        for case in &sample {
+            let file_name = format!("{}.json", case);
+            fs::copy(cases_dir.join(&file_name), sample_dir.join(&file_name)).with_context(|| format!("Failed to copy {}", file_name))?;
+        }
+
+        Ok(Scenario {
+            name: file.name,
+            description: file.description,
+            tle_set: file.tle_set,
+            cases_dir,
+            cases,
+            sample_dir,
+            sample,
+        })
+    }
+
+    /// The staged cases, one per satellite.
+    pub fn cases_dir(&self) -> &Path {
+        &self.cases_dir
+    }
+
+    /// Where `implementation`'s results go.
+    fn output_dir(&self, implementation: &str) -> PathBuf {
+        self.cases_dir.with_file_name(implementation)
+    }
+}
+
+/// Screen the whole catalog `iterations` times per implementation, after
+/// `warmup` unmeasured runs, then check each implementation's sample.
+pub fn run(
+    orchestrator: &Orchestrator,
+    implementations: &[Implementation],
+    scenario: &Scenario,
+    iterations: usize,
+    warmup: usize,
+    pinning: Option<&Pinning>,
+) -> Result<Vec<ScreeningStats>> {
+    let cpuset = pinning.map(|p| p.cpuset.as_str());
+    let mut measured = Vec::new();
+    for impl_ in implementations {
+        let mut times = Vec::new();
+        for iteration in 0..warmup + iterations {
+            if let Some(pinning) = pinning {
+                pinning.check_host(orchestrator.runtime, implementations)?;
+            }
+            println!("Screening {} satellites with {}...", scenario.cases.len(), impl_.name.bright_cyan());
+            let time = screen(orchestrator, impl_, &scenario.cases_dir, &scenario.output_dir(&impl_.name), cpuset)?;
+            println!("  {} {:.2}s", "✓".green(), time);
+            if iteration >= warmup {
+                times.push(time);
+            }
+        }
+        measured.push((impl_, BenchStats::new(&impl_.name, times)));
+    }
+
+    // The reference's own results when it was measured, else a run on the
+    // sample alone
+    let reference_dir = if implementations.iter().any(|impl_| impl_.name == REFERENCE_IMPLEMENTATION) {
+        scenario.output_dir(REFERENCE_IMPLEMENTATION)
+    } else {
+        let reference = orchestrator
+            .discover_implementations()?
+            .into_iter()
+            .find(|impl_| impl_.name == REFERENCE_IMPLEMENTATION)
+            .with_context(|| format!("The reference implementation {} is needed to check the sample", REFERENCE_IMPLEMENTATION))?;
+        println!("Running the reference on {} sampled satellites...", scenario.sample.len());
+        let dir = scenario.sample_dir.parent().unwrap_or(&scenario.sample_dir).join(REFERENCE_IMPLEMENTATION);
+        screen(orchestrator, &reference, &scenario.sample_dir, &dir, None)?;
+        dir
+    };
+    let reference = results(&reference_dir, REFERENCE_IMPLEMENTATION)?;
+
+    let mut stats = Vec::new();
+    for (impl_, timing) in measured {
+        let results = results(&scenario.output_dir(&impl_.name), &impl_.name)?;
+        let windows = results.iter().map(|(_, result)| windows(result).len()).sum();
+        let sample = (impl_.name != REFERENCE_IMPLEMENTATION).then(|| check_sample(scenario, &results, &reference));
+        stats.push(ScreeningStats {
+            throughput: results.len() as f64 / timing.median.max(f64::EPSILON),
+            screened: results.len(),
+            windows,
+            sample,
+            timing,
+        });
+    }
+    Ok(stats)
+}
+
+/// Run `impl_` on every case in `cases_dir`, with its results going to a
+/// fresh `output_dir`. Returns the container's wall-clock time.
+fn screen(orchestrator: &Orchestrator, impl_: &Implementation, cases_dir: &Path, output_dir: &Path, cpuset: Option<&str>) -> Result<f64> {
+    let (output, _, time) = run_container(orchestrator, impl_, cases_dir, output_dir, cpuset, orchestrator.limits)?;
+    if output.hung {
+        return Err(error::Error::Hung {
+            implementation: impl_.name.clone(),
+            test_case: None,
+            seconds: orchestrator.heartbeat_for(impl_).unwrap_or_default().as_secs(),
+        }
+        .into());
+    }
+    if output.timed_out {
+        return Err(error::Error::Timeout {
+            implementation: impl_.name.clone(),
+            test_case: None,
+            seconds: orchestrator.timeout_for(impl_).unwrap_or_default().as_secs(),
+        }
+        .into());
+    }
+    if !output.success() {
+        bail!("{} failed during screening:\n{}", impl_.name, String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(time)
+}
+
+/// Run `impl_`'s container on every case in `cases_dir` under `limits`,
+/// with its results going to a fresh `output_dir` instead of `results/`.
+/// Returns its output, what it used, and its wall-clock time.
+pub fn run_container(
+    orchestrator: &Orchestrator,
+    impl_: &Implementation,
+    cases_dir: &Path,
+    output_dir: &Path,
+    cpuset: Option<&str>,
+    limits: Limits,
+) -> Result<(RunOutput, Reading, f64)> {
+    if output_dir.exists() {
+        fs::remove_dir_all(output_dir).with_context(|| format!("Failed to clear {}", output_dir.display()))?;
+    }
+    fs::create_dir_all(output_dir).with_context(|| format!("Failed to create {}", output_dir.display()))?;
+
+    let mut env = Vec::new();
+    if let Some(version) = capabilities::schema_version(&orchestrator.test_data_dir)? {
+        env.push(format!("{}={}", capabilities::SCHEMA_VERSION_VAR, version));
+    }
+    let features = capabilities::declared(&impl_.path);
+    let enabled: Vec<&str> = features.iter().map(|feature| feature.name()).collect();
+    env.push(format!("{}={}", capabilities::FEATURES_VAR, enabled.join(",")));
+    let cidfile = std::env::temp_dir().join(format!("visibility-test-runner-{}-{}-catalog.cid", std::process::id(), impl_.name));
+    fs::remove_file(&cidfile).ok();
+
+    let meter = usage::Meter::start(orchestrator.runtime, cidfile.clone());
+    let start = Instant::now();
+    let mut spec = RunSpec {
+        image: &impl_.image_name,
+        args: Vec::new(),
+        mounts: vec![
+            (orchestrator.test_data_dir.clone(), "/test-data", true),
+            (cases_dir.to_path_buf(), "/test-data/cases", true),
+            (output_dir.to_path_buf(), "/results", false),
+        ],
+        env,
+        cpuset,
+        cidfile: Some(&cidfile),
+        timeout: orchestrator.timeout_for(impl_),
+        heartbeat: None,
+        limits,
+    };
+    let beacon = heartbeat::Beacon::attach(orchestrator, impl_, &mut spec)?;
+    let output = stream::run(orchestrator, impl_, spec, output_dir);
+    drop(beacon);
+    let time = start.elapsed().as_secs_f64();
+    let reading = meter.finish();
+    Ok((output?.0, reading, time))
+}
+
+/// `implementation`'s results in `dir` by case, unreadable ones left out.
+pub fn results(dir: &Path, implementation: &str) -> Result<Vec<(String, Value)>> {
+    let mut results = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(case) = result_test_case(&path.file_name().unwrap_or_default().to_string_lossy(), implementation) else {
+            continue;
+        };
+        if let Ok(result) = read_result(&path).and_then(|content| Ok(serde_json::from_str::<Value>(&content)?)) {
+            results.push((case, result));
+        }
+    }
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(results)
+}
+
+fn windows(result: &Value) -> &[Value] {
+    result["visibilityWindows"].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The windows of `case` in `results`, if it has a result.
+fn find<'a>(results: &'a [(String, Value)], case: &str) -> Option<&'a [Value]> {
+    results.iter().find(|(name, _)| name == case).map(|(_, result)| windows(result))
+}
+
+fn check_sample(scenario: &Scenario, results: &[(String, Value)], reference: &[(String, Value)]) -> SampleCheck {
+    let mut check = SampleCheck {
+        checked: 0,
+        matched: 0,
+        mismatched: Vec::new(),
+        missing: Vec::new(),
+    };
+    for case in &scenario.sample {
+        let Some(expected) = find(reference, case) else {
+            continue;
+        };
+        let Some(actual) = find(results, case) else {
+            check.missing.push(case.clone());
+            continue;
+        };
+        check.checked += 1;
+        let (tolerances, _) = tolerance::for_case(&scenario.sample_dir, case, &Overrides::default());
+        if tolerance::compare(actual, expected, &tolerances).is_match() {
+            check.matched += 1;
+        } else {
+            check.mismatched.push(case.clone());
+        }
+    }
+    check
+}
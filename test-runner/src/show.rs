@@ -0,0 +1,105 @@
+//! Visibility windows of an implementation's latest results, for reading.
+//!
+//! Each window's rise and set are shown in UTC, relative to the start of the
+//! case's time window and to now, and optionally in a local zone (see
+//! [`time_display`](crate::time_display)).
+
+use crate::time_display::{self, Zone};
+use crate::{Orchestrator, read_result};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde_json::Value;
+use std::fs;
+
+/// Print the windows, returning the results shown.
+pub fn print(orchestrator: &Orchestrator, impl_name: &str, test_case: Option<&str>, zone: Option<&Zone>) -> Result<Vec<Value>> {
+    let now = Utc::now();
+    let cases_dir = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+
+    let mut shown = Vec::new();
+    for path in orchestrator.collect_results(impl_name)? {
+        let result: Value = serde_json::from_str(&read_result(&path)?)?;
+        let Some(name) = result["testCase"].as_str() else {
+            continue;
+        };
+        if test_case.is_some_and(|tc| tc != name) {
+            continue;
+        }
+        // Offsets are from the case's time window, or the first rise when
+        // the case is gone
+        let case: Option<Value> = fs::read_to_string(cases_dir.join(format!("{}.json", name)))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        let case_window = case.as_ref().and_then(|case| {
+            let window = &case["timeWindow"];
+            Some((parse_time(&window["start"])?, parse_time(&window["end"])?))
+        });
+
+        let windows = result["visibilityWindows"].as_array().cloned().unwrap_or_default();
+        let origin = case_window
+            .map(|(start, _)| start)
+            .or_else(|| windows.first().and_then(|w| parse_time(&w["start"])));
+
+        match case_window {
+            Some((start, end)) => println!(
+                "{} - {} window(s), {} .. {} ({})",
+                name.bright_white().bold(),
+                windows.len(),
+                time_display::utc(start),
+                time_display::utc(end),
+                time_display::from_now(start, now)
+            ),
+            None => println!("{} - {} window(s)", name.bright_white().bold(), windows.len()),
+        }
+
+        for (i, window) in windows.iter().enumerate() {
+            let (Some(rise), Some(set), Some(origin)) = (parse_time(&window["start"]), parse_time(&window["end"]), origin)
+            else {
+                continue;
+            };
+
+            let line = |label: &str, time: DateTime<Utc>| {
+                let mut line = format!(
+                    "{} {}  {}  {}",
+                    label,
+                    time_display::utc(time),
+                    time_display::offset(time, origin).bright_white(),
+                    time_display::from_now(time, now).dimmed()
+                );
+                if let Some(zone) = zone {
+                    line.push_str(&format!("  [{}]", zone.format(time)));
+                }
+                line
+            };
+            println!("  {:>2}. {}", i + 1, line("rise", rise));
+            println!("      {}", line("set ", set));
+
+            let mut details = format!("duration {}", time_display::duration((set - rise).num_seconds()));
+            if let Some(max) = window["maxElevation"].as_f64() {
+                details.push_str(&format!(", max elevation {:.1}°", max));
+                if let Some(at) = parse_time(&window["maxElevationTime"]) {
+                    details.push_str(&format!(" at {}", time_display::offset(at, origin)));
+                }
+            }
+            println!("      {}", details.dimmed());
+        }
+        println!();
+        shown.push(result);
+    }
+
+    if shown.is_empty() {
+        match test_case {
+            Some(tc) => bail!("No results for {} on {}", impl_name, tc),
+            None => bail!("No results for {}", impl_name),
+        }
+    }
+    Ok(shown)
+}
+
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
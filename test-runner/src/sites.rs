@@ -0,0 +1,94 @@
+//! Named ground stations referenced by test cases.
+//!
+//! Well-known sites are listed once in `test-data/sites.json`, with their
+//! coordinates and a default elevation mask:
+//!
+//! ```json
+//! [
+//!   {"name": "svalsat", "description": "SvalSat, Svalbard", "latitude": 78.2298, "longitude": 15.4078, "altitude": 500, "minElevation": 5}
+//! ]
+//! ```
+//!
+//! and a case's `observer` can name one instead of giving coordinates:
+//!
+//! ```json
+//! "observer": { "site": "svalsat" }
+//! ```
+//!
+//! Fields given alongside `site` override the site's, and the site's
+//! `minElevation` applies when the case doesn't set its own. References are
+//! resolved along with TLE set references when cases are materialized (see
+//! [`tle_sets::materialize`](crate::tle_sets::materialize)), so
+//! implementations only see concrete coordinates.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Site {
+    pub name: String,
+    /// Friendly name, used as the observer's `name`
+    #[serde(default)]
+    pub description: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters above sea level
+    pub altitude: f64,
+    /// Default elevation mask in degrees, for cases that don't set one
+    #[serde(default)]
+    pub min_elevation: Option<f64>,
+}
+
+/// `<test_data_dir>/sites.json`, empty if there is none.
+pub fn read(test_data_dir: &Path) -> Result<Vec<Site>> {
+    let path = test_data_dir.join("sites.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&fs::read_to_string(&path)?).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Whether `case`'s observer names a site.
+pub fn references(case: &Value) -> bool {
+    case.pointer("/observer/site").is_some()
+}
+
+/// Replace `case`'s `observer.site` reference with the site's coordinates,
+/// and fill in its `minElevation` if the case has none.
+pub fn resolve(case: &mut Value, sites: &[Site]) -> Result<()> {
+    let Some(observer) = case.get_mut("observer").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    let Some(name) = observer.remove("site") else {
+        return Ok(());
+    };
+    let name = name.as_str().context("site must be a string")?;
+    let Some(site) = sites.iter().find(|site| site.name == name) else {
+        let names: Vec<&str> = sites.iter().map(|site| site.name.as_str()).collect();
+        bail!(
+            "Unknown site: {} (sites in sites.json: {})",
+            name,
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        );
+    };
+
+    let mut fill = |field: &str, value: Value| {
+        observer.entry(field).or_insert(value);
+    };
+    fill("name", Value::from(site.description.as_deref().unwrap_or(&site.name)));
+    fill("latitude", Value::from(site.latitude));
+    fill("longitude", Value::from(site.longitude));
+    fill("altitude", Value::from(site.altitude));
+
+    if case.get("minElevation").is_none() {
+        let Some(min_elevation) = site.min_elevation else {
+            bail!("No minElevation in the case or site {}", name);
+        };
+        case["minElevation"] = Value::from(min_elevation);
+    }
+    Ok(())
+}
@@ -0,0 +1,241 @@
+//! Streamed results, for implementations whose [`impl.toml`](crate::impl_toml)
+//! sets `stream`.
+//!
+//! Instead of writing result files, such an implementation prints one JSON
+//! object per line, either to stdout (`stream = "stdout"`) or to a FIFO
+//! whose path it finds in `VISIBILITY_STREAM` (`stream = "fifo"`):
+//!
+//! ```text
+//! {"testCase": "001_iss_nyc", "window": {"start": "...", "end": "...", ...}}
+//! {"testCase": "001_iss_nyc", "implementation": "...", "version": "1.0.0", "executionTime": 0.41}
+//! ```
+//!
+//! A line with `window` adds a window to its case, in order. Any other
+//! object with `testCase` finishes the case: it's the case's result, with
+//! the streamed windows appended to its own `visibilityWindows`, if any, and
+//! it's written to `results/` as `<impl>_<case>_<timestamp>.json`, where
+//! the output contract then checks it like any other result. A whole result
+//! on one line is fine too. Other lines of stdout are kept as the
+//! container's output.
+//!
+//! The FIFO is read as lines arrive, so each case's file appears as soon as
+//! the case is finished: progress follows along, and a container killed at
+//! its timeout keeps the cases it finished. It lives in
+//! `results/.stream/<impl>-<pid>-<n>/`, mounted at `/stream`, which needs the
+//! container to share the host's kernel (not Docker Desktop). Stdout is
+//! assembled once the container exits.
+
+use crate::runtime::{Heartbeat, RunOutput, RunSpec};
+use crate::{Implementation, Orchestrator};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Environment variable holding the FIFO's path in the container
+pub const STREAM_VAR: &str = "VISIBILITY_STREAM";
+/// How often the FIFO is polled for lines
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Numbers FIFO directories, since containers of one implementation may
+/// run at the same time
+static NEXT_FIFO: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Fifo,
+}
+
+/// Turns records into result files.
+struct Assembler {
+    results_dir: PathBuf,
+    implementation: String,
+    /// Windows of each case not finished yet
+    windows: BTreeMap<String, Vec<Value>>,
+}
+
+impl Assembler {
+    fn new(results_dir: &Path, implementation: &str) -> Self {
+        Assembler {
+            results_dir: results_dir.to_path_buf(),
+            implementation: implementation.to_string(),
+            windows: BTreeMap::new(),
+        }
+    }
+
+    /// Take one line, writing the case's result if it finishes one. `false`
+    /// if the line isn't a record.
+    fn line(&mut self, line: &str) -> Result<bool> {
+        let Ok(Value::Object(mut record)) = serde_json::from_str::<Value>(line.trim()) else {
+            return Ok(false);
+        };
+        // The case names the file, so it mustn't reach outside `results/`
+        let Some(test_case) = record
+            .get("testCase")
+            .and_then(Value::as_str)
+            .filter(|name| !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']))
+            .map(str::to_string)
+        else {
+            return Ok(false);
+        };
+
+        if let Some(window) = record.remove("window") {
+            self.windows.entry(test_case).or_default().push(window);
+            return Ok(true);
+        }
+        let streamed = self.windows.remove(&test_case).unwrap_or_default();
+        if let Value::Array(windows) = record.entry("visibilityWindows").or_insert_with(|| Value::Array(Vec::new())) {
+            windows.extend(streamed);
+        }
+        let path = self.results_dir.join(format!(
+            "{}_{}_{}.json",
+            self.implementation,
+            test_case,
+            Utc::now().format("%Y%m%d_%H%M%S")
+        ));
+        fs::write(&path, serde_json::to_string_pretty(&record)?).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(true)
+    }
+
+    /// Cases with streamed windows that were never finished.
+    fn unfinished(self) -> Vec<String> {
+        self.windows.into_keys().collect()
+    }
+}
+
+/// Reads the FIFO on its own thread while the container runs.
+struct Reader {
+    dir: PathBuf,
+    done: Arc<AtomicBool>,
+    thread: JoinHandle<Result<Assembler>>,
+}
+
+impl Reader {
+    /// Create the FIFO in a new directory under `results/.stream` and start
+    /// reading it. Lines read count as beats of `heartbeat`.
+    fn start(orchestrator: &Orchestrator, implementation: &str, assembler: Assembler, heartbeat: Option<Heartbeat>) -> Result<Self> {
+        let dir = orchestrator.results_dir.join(".stream").join(format!(
+            "{}-{}-{}",
+            implementation,
+            std::process::id(),
+            NEXT_FIFO.fetch_add(1, Ordering::Relaxed)
+        ));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to clear {}", dir.display()))?;
+        }
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join("results.ndjson");
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `c_path` is a valid NUL-terminated path
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o666) } != 0 {
+            return Err(io::Error::last_os_error()).with_context(|| format!("Failed to create FIFO {}", path.display()));
+        }
+        // Past the umask, for containers running as another user
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666))?;
+        // Non-blocking, so opening doesn't wait for the container to
+        // open its end, and reads don't wait for lines
+        let fifo = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .with_context(|| format!("Failed to open FIFO {}", path.display()))?;
+
+        let done = Arc::new(AtomicBool::new(false));
+        let thread = thread::spawn({
+            let done = done.clone();
+            move || read(fifo, &done, assembler, heartbeat.as_ref())
+        });
+        Ok(Reader { dir, done, thread })
+    }
+
+    /// Read what's left once the container has exited.
+    fn finish(self) -> Result<Assembler> {
+        self.done.store(true, Ordering::Relaxed);
+        let assembler = self.thread.join().map_err(|_| anyhow::anyhow!("The stream reader panicked"))?;
+        fs::remove_dir_all(&self.dir).ok();
+        // Only once no other container streams
+        if let Some(parent) = self.dir.parent() {
+            fs::remove_dir(parent).ok();
+        }
+        assembler
+    }
+}
+
+fn read(mut fifo: fs::File, done: &AtomicBool, mut assembler: Assembler, heartbeat: Option<&Heartbeat>) -> Result<Assembler> {
+    let mut pending = Vec::new();
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        // Checked before reading, so the last read after the container
+        // exits drains the FIFO
+        let finishing = done.load(Ordering::Relaxed);
+        match fifo.read(&mut chunk) {
+            Ok(read) if read > 0 => {
+                pending.extend_from_slice(&chunk[..read]);
+                if let Some(heartbeat) = heartbeat {
+                    heartbeat.beat();
+                }
+                while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                    let line: Vec<u8> = pending.drain(..=end).collect();
+                    assembler.line(&String::from_utf8_lossy(&line))?;
+                }
+                continue;
+            }
+            // No writer, or no lines yet
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e).context("Failed to read the result stream"),
+        }
+        if finishing {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    if !pending.is_empty() {
+        assembler.line(&String::from_utf8_lossy(&pending))?;
+    }
+    Ok(assembler)
+}
+
+/// Run `spec` for `impl_`, writing the results it streams, if its
+/// `impl.toml` says it does, to `results_dir`. Also returns the cases it
+/// streamed windows of but didn't finish.
+pub fn run(orchestrator: &Orchestrator, impl_: &Implementation, mut spec: RunSpec, results_dir: &Path) -> Result<(RunOutput, Vec<String>)> {
+    let assembler = Assembler::new(results_dir, &impl_.name);
+    match impl_.manifest.stream {
+        None => Ok((orchestrator.runtime.run(&spec)?, Vec::new())),
+        Some(Stream::Stdout) => {
+            let mut output = orchestrator.runtime.run(&spec)?;
+            let mut assembler = assembler;
+            let mut kept = Vec::new();
+            let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+            for line in stdout.lines() {
+                if !assembler.line(line)? {
+                    kept.push(line);
+                }
+            }
+            output.stdout = kept.join("\n").into_bytes();
+            Ok((output, assembler.unfinished()))
+        }
+        Some(Stream::Fifo) => {
+            let reader = Reader::start(orchestrator, &impl_.name, assembler, spec.heartbeat.clone())?;
+            spec.mounts.push((reader.dir.clone(), "/stream", false));
+            spec.env.push(format!("{}=/stream/results.ndjson", STREAM_VAR));
+            let output = orchestrator.runtime.run(&spec);
+            let assembler = reader.finish()?;
+            Ok((output?, assembler.unfinished()))
+        }
+    }
+}
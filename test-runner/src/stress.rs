@@ -0,0 +1,269 @@
+//! Memory stress tier, for `stress`.
+//!
+//! Memory is a real selection criterion for embedded ground stations, which
+//! run on boards with a few hundred MiB to spare. The stress tier runs the
+//! workloads most likely to exhaust it under a ladder of memory limits,
+//! loosest first:
+//!
+//! - `long-window`: the selected cases with their time windows stretched to
+//!   a number of days from their start, all in one container
+//! - each [screening](crate::screening) scenario, a whole catalog in one
+//!   container
+//!
+//! A container that exits with code 137 (SIGKILL, which the kernel's OOM
+//! killer sends when a container reaches its limit) before the timeout ran
+//! out of memory. Once a workload fails at a limit, tighter ones aren't
+//! tried. Results go to `results/.stress/<impl>/<workload>`, and peak
+//! memory is reported where the runtime exposes the container's cgroup.
+
+use crate::runtime::Limits;
+use crate::screening::{self, Scenario};
+use crate::usage::format_memory;
+use crate::{Implementation, Orchestrator, capabilities, result_test_case};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Exit code of a container killed with SIGKILL
+const KILLED: i64 = 137;
+
+/// Cases one container runs under each limit.
+pub struct Workload {
+    pub name: String,
+    /// E.g. "10 case(s) over 7 days"
+    pub description: String,
+    cases_dir: PathBuf,
+    /// Plan the cases against the features each implementation declares
+    plan_features: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    OutOfMemory,
+    Failed,
+    TimedOut,
+    Hung,
+}
+
+/// One workload run by one implementation under one limit.
+#[derive(Debug, Serialize)]
+pub struct Attempt {
+    pub implementation: String,
+    pub workload: String,
+    /// Memory limit in bytes
+    pub memory: u64,
+    pub outcome: Outcome,
+    pub execution_time: f64,
+    /// Bytes, when measured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+    /// Result files written
+    pub results: usize,
+}
+
+/// The cases matching `patterns`, or every case in the selected tiers, with
+/// their time windows stretched to `days` days. Cases with a moving
+/// observer are left out, since their waypoints only span their own window.
+pub fn long_window(orchestrator: &Orchestrator, patterns: &[String], days: u32) -> Result<Workload> {
+    let names = if patterns.is_empty() { orchestrator.test_cases()? } else { orchestrator.resolve_cases(patterns)? };
+    let source = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let cases_dir = orchestrator.results_dir.join(".stress").join("long-window").join("cases");
+    if cases_dir.exists() {
+        fs::remove_dir_all(&cases_dir).with_context(|| format!("Failed to clear {}", cases_dir.display()))?;
+    }
+    fs::create_dir_all(&cases_dir).with_context(|| format!("Failed to create {}", cases_dir.display()))?;
+
+    let mut staged = 0;
+    for name in &names {
+        let file_name = format!("{}.json", name);
+        let path = source.join(&file_name);
+        let mut case: Value = serde_json::from_str(&fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if case.get("observerTrajectory").is_some() {
+            println!("  {} {} left out: its trajectory doesn't span a longer window", "○".dimmed(), name);
+            continue;
+        }
+        let start = case["timeWindow"]["start"]
+            .as_str()
+            .and_then(|start| DateTime::parse_from_rfc3339(start).ok())
+            .with_context(|| format!("{} has no valid timeWindow.start", name))?;
+        let end = start + TimeDelta::days(i64::from(days));
+        case["timeWindow"]["end"] = Value::from(end.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        if let Some(case) = case.as_object_mut() {
+            case.remove("expectedWindows");
+        }
+        fs::write(cases_dir.join(&file_name), serde_json::to_string_pretty(&case)?).with_context(|| format!("Failed to write {}", file_name))?;
+        staged += 1;
+    }
+    if staged == 0 {
+        bail!("No case to stretch into a long window");
+    }
+
+    Ok(Workload {
+        name: "long-window".to_string(),
+        description: format!("{} case(s) over {} day(s)", staged, days),
+        cases_dir,
+        plan_features: true,
+    })
+}
+
+/// The screening scenarios `names`, or every one whose TLE set is present.
+pub fn catalogs(orchestrator: &Orchestrator, names: &[String]) -> Result<Vec<Workload>> {
+    let names = if names.is_empty() {
+        let mut present = Vec::new();
+        for (name, available) in screening::scenarios(&orchestrator.test_data_dir)? {
+            if available {
+                present.push(name);
+            } else {
+                println!("  {} Screening scenario {} left out: its TLE set isn't in test-data/tle-sets", "○".dimmed(), name);
+            }
+        }
+        present
+    } else {
+        names.to_vec()
+    };
+
+    let mut workloads = Vec::new();
+    for name in names {
+        let scenario = Scenario::load(orchestrator, &name, Some(0))?;
+        workloads.push(Workload {
+            description: format!("{} satellites from TLE set {}", scenario.cases.len(), scenario.tle_set),
+            name: scenario.name.clone(),
+            cases_dir: scenario.cases_dir().to_path_buf(),
+            plan_features: false,
+        });
+    }
+    Ok(workloads)
+}
+
+/// Run every workload on every implementation under each of `limits`
+/// (bytes), which are tried in the order given.
+pub fn run(orchestrator: &Orchestrator, implementations: &[Implementation], workloads: &[Workload], limits: &[u64]) -> Result<Vec<Attempt>> {
+    let mut attempts = Vec::new();
+    for impl_ in implementations {
+        println!("{}", impl_.name.bright_cyan());
+        let dir = orchestrator.results_dir.join(".stress").join(&impl_.name);
+        for workload in workloads {
+            let mut cases_dir = workload.cases_dir.clone();
+            if workload.plan_features {
+                let staged = capabilities::stage(&workload.cases_dir, &dir, &capabilities::declared(&impl_.path))?;
+                for (name, features) in &staged.unsupported {
+                    println!("  {} {} unsupported (needs {})", "○".dimmed(), name, capabilities::names(features));
+                }
+                cases_dir = staged.cases_dir.unwrap_or(cases_dir);
+            }
+
+            for &memory in limits {
+                let limit = Limits {
+                    memory: Some(memory),
+                    ..orchestrator.limits
+                };
+                let output_dir = dir.join(&workload.name);
+                let (output, reading, time) = screening::run_container(orchestrator, impl_, &cases_dir, &output_dir, None, limit)?;
+                // Killed containers report no exit code, like out of memory ones
+                let outcome = if output.hung {
+                    Outcome::Hung
+                } else if output.timed_out {
+                    Outcome::TimedOut
+                } else if output.success() {
+                    Outcome::Passed
+                } else if output.exit_code.is_none_or(|code| code == KILLED) {
+                    Outcome::OutOfMemory
+                } else {
+                    Outcome::Failed
+                };
+                let results = fs::read_dir(&output_dir)
+                    .with_context(|| format!("Failed to read {}", output_dir.display()))?
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| result_test_case(&entry.file_name().to_string_lossy(), &impl_.name).is_some())
+                    .count();
+
+                let peak = reading.peak_memory.map(|bytes| format!(", peak {}", format_memory(bytes))).unwrap_or_default();
+                let (mark, what) = match outcome {
+                    Outcome::Passed => ("✓".green(), format!("{:.2}s{}", time, peak)),
+                    Outcome::OutOfMemory => ("✗".red(), format!("out of memory after {:.2}s{}", time, peak)),
+                    Outcome::Failed => ("✗".red(), format!("failed (exit {})", output.exit_code.unwrap_or_default())),
+                    Outcome::TimedOut => ("✗".red(), format!("timed out after {:.0}s", time)),
+                    Outcome::Hung => ("✗".red(), format!("hung, killed after {:.0}s", time)),
+                };
+                println!("  {} {} at {} - {}", mark, workload.name, limit_label(memory), what);
+
+                attempts.push(Attempt {
+                    implementation: impl_.name.clone(),
+                    workload: workload.name.clone(),
+                    memory,
+                    outcome,
+                    execution_time: time,
+                    peak_memory: reading.peak_memory,
+                    results,
+                });
+                if outcome != Outcome::Passed {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(attempts)
+}
+
+/// Per implementation and workload, the tightest limit it passed and where
+/// it stopped.
+pub fn print(attempts: &[Attempt], implementations: &[Implementation], workloads: &[Workload]) {
+    let mut out_of_memory = Vec::new();
+    for impl_ in implementations {
+        println!("{}", impl_.name.bright_white());
+        for workload in workloads {
+            let tried: Vec<&Attempt> = attempts
+                .iter()
+                .filter(|attempt| attempt.implementation == impl_.name && attempt.workload == workload.name)
+                .collect();
+            let passed = tried.iter().filter(|attempt| attempt.outcome == Outcome::Passed).map(|attempt| attempt.memory).min();
+            let stopped = tried.iter().find(|attempt| attempt.outcome != Outcome::Passed);
+            match stopped {
+                None => println!("  {} {}: passed down to {}", "✓".green(), workload.name, passed.map(limit_label).unwrap_or_default()),
+                Some(attempt) => {
+                    let reason = match attempt.outcome {
+                        Outcome::OutOfMemory => {
+                            out_of_memory.push(format!("{} ({} at {})", impl_.name, workload.name, limit_label(attempt.memory)));
+                            "out of memory"
+                        }
+                        Outcome::TimedOut => "timed out",
+                        Outcome::Hung => "hung",
+                        _ => "failed",
+                    };
+                    match passed {
+                        Some(memory) => println!(
+                            "  {} {}: passed down to {}, {} at {}",
+                            "●".yellow(),
+                            workload.name,
+                            limit_label(memory),
+                            reason,
+                            limit_label(attempt.memory)
+                        ),
+                        None => println!("  {} {}: {} at {}", "✗".red(), workload.name, reason, limit_label(attempt.memory)),
+                    }
+                }
+            }
+        }
+    }
+    println!();
+    if out_of_memory.is_empty() {
+        println!("{} No implementation ran out of memory", "✓".green());
+    } else {
+        println!("{} Out of memory: {}", "✗".red(), out_of_memory.join(", "));
+    }
+}
+
+fn limit_label(memory: u64) -> String {
+    Limits {
+        memory: Some(memory),
+        cpus: None,
+    }
+    .describe()
+}
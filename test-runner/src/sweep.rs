@@ -0,0 +1,401 @@
+//! Parameter sweeps, for `sweep`.
+//!
+//! How much do an implementation's windows move with the elevation mask,
+//! the time step, or where the observer stands? A sweep takes one base case
+//! and a grid of values for its `minElevation`, `timeWindow.step`, and
+//! `observer.latitude` (axes without values keep the base case's), and
+//! writes one variant per point of the grid, `<base>_sweep_<n>`, to
+//! `results/.sweep/cases`. Each implementation runs every variant in one
+//! container, with its results going to `results/.sweep/<impl>/results`,
+//! and each variant's result is summed up as its windows, their total
+//! visible time, and the highest elevation reached: the implementation's
+//! surface over the grid.
+//!
+//! With the reference among the implementations, every point also gets its
+//! difference from the reference's. For each axis with several values, the
+//! sensitivity is the mean change in windows and visible time per unit of
+//! the axis between neighbouring points that agree on the other axes.
+
+use crate::{Implementation, Orchestrator, REFERENCE_IMPLEMENTATION, capabilities, read_result, result_test_case, screening};
+use anyhow::{Context, Result, bail};
+use chrono::DateTime;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Values to sweep each parameter over; empty keeps the base case's.
+#[derive(Debug, Default)]
+pub struct Grid {
+    pub min_elevations: Vec<f64>,
+    pub steps: Vec<f64>,
+    pub latitudes: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Axis {
+    MinElevation,
+    Step,
+    Latitude,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::MinElevation, Axis::Step, Axis::Latitude];
+
+    fn label(self) -> &'static str {
+        match self {
+            Axis::MinElevation => "min elevation",
+            Axis::Step => "step",
+            Axis::Latitude => "latitude",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Axis::Step => "s",
+            _ => "°",
+        }
+    }
+}
+
+/// One point of the grid, with the values of the swept axes.
+#[derive(Debug, Clone, Serialize)]
+pub struct Variant {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_elevation: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+}
+
+impl Variant {
+    fn value(&self, axis: Axis) -> Option<f64> {
+        match axis {
+            Axis::MinElevation => self.min_elevation,
+            Axis::Step => self.step,
+            Axis::Latitude => self.latitude,
+        }
+    }
+}
+
+/// A base case's variants, staged for the containers.
+pub struct Sweep {
+    pub base: String,
+    pub variants: Vec<Variant>,
+    /// Axes with values
+    pub axes: Vec<Axis>,
+    cases_dir: PathBuf,
+}
+
+/// What one implementation's result for a variant sums up to. `None` where
+/// it wrote no result.
+#[derive(Debug, Serialize)]
+pub struct Point {
+    #[serde(flatten)]
+    pub variant: Variant,
+    pub windows: Option<usize>,
+    pub visible_seconds: Option<f64>,
+    pub max_elevation: Option<f64>,
+    /// Windows minus the reference's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows_delta: Option<i64>,
+    /// Visible seconds minus the reference's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible_delta: Option<f64>,
+}
+
+/// Mean change per unit of an axis.
+#[derive(Debug, Serialize)]
+pub struct Sensitivity {
+    pub axis: Axis,
+    pub windows_per_unit: f64,
+    pub visible_seconds_per_unit: f64,
+}
+
+/// One implementation's results over the grid.
+#[derive(Debug, Serialize)]
+pub struct Surface {
+    pub implementation: String,
+    /// Why some or all variants have no result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub points: Vec<Point>,
+    pub sensitivity: Vec<Sensitivity>,
+}
+
+/// Write a variant of `base` for every point of `grid`.
+pub fn prepare(orchestrator: &Orchestrator, base: &str, grid: &Grid) -> Result<Sweep> {
+    let axes: Vec<Axis> = Axis::ALL.into_iter().filter(|axis| !values(grid, *axis).is_empty()).collect();
+    if axes.is_empty() {
+        bail!("Give at least one --min-elevation, --step, or --latitude to sweep");
+    }
+    for (axis, min, max) in [(Axis::MinElevation, -90.0, 90.0), (Axis::Step, 1.0, 3600.0), (Axis::Latitude, -90.0, 90.0)] {
+        if let Some(value) = values(grid, axis).iter().find(|value| !(min..=max).contains(*value)) {
+            bail!("{} {} is outside {} to {}", axis.label(), value, min, max);
+        }
+    }
+
+    let source = orchestrator.materialize_cases()?.unwrap_or_else(|| orchestrator.test_data_dir.join("cases"));
+    let path = source.join(format!("{}.json", base));
+    if !path.exists() {
+        bail!("No test case {}; list-tests shows them", base);
+    }
+    let case: Value = serde_json::from_str(&fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if !grid.latitudes.is_empty() && case.get("observerTrajectory").is_some() {
+        bail!("{} has a moving observer, so its latitude can't be swept", base);
+    }
+
+    let cases_dir = orchestrator.results_dir.join(".sweep").join("cases");
+    if cases_dir.exists() {
+        fs::remove_dir_all(&cases_dir).with_context(|| format!("Failed to clear {}", cases_dir.display()))?;
+    }
+    fs::create_dir_all(&cases_dir).with_context(|| format!("Failed to create {}", cases_dir.display()))?;
+
+    // An axis without values contributes one point, the base case's
+    let choices = |axis| -> Vec<Option<f64>> {
+        let values = values(grid, axis);
+        if values.is_empty() { vec![None] } else { values.iter().copied().map(Some).collect() }
+    };
+    let mut variants = Vec::new();
+    for min_elevation in choices(Axis::MinElevation) {
+        for step in choices(Axis::Step) {
+            for latitude in choices(Axis::Latitude) {
+                let name = format!("{}_sweep_{:03}", base, variants.len() + 1);
+                let mut variant = case.clone();
+                variant["name"] = Value::from(name.as_str());
+                if let Some(min_elevation) = min_elevation {
+                    variant["minElevation"] = Value::from(min_elevation);
+                }
+                if let Some(step) = step {
+                    variant["timeWindow"]["step"] = Value::from(step);
+                }
+                if let Some(latitude) = latitude {
+                    variant["observer"]["latitude"] = Value::from(latitude);
+                }
+                // The base case's expectations don't hold for its variants
+                if let Some(variant) = variant.as_object_mut() {
+                    variant.remove("expectedWindows");
+                }
+                let file = cases_dir.join(format!("{}.json", name));
+                fs::write(&file, serde_json::to_string_pretty(&variant)?).with_context(|| format!("Failed to write {}", file.display()))?;
+                variants.push(Variant {
+                    name,
+                    min_elevation,
+                    step,
+                    latitude,
+                });
+            }
+        }
+    }
+
+    Ok(Sweep {
+        base: base.to_string(),
+        variants,
+        axes,
+        cases_dir,
+    })
+}
+
+fn values(grid: &Grid, axis: Axis) -> &[f64] {
+    match axis {
+        Axis::MinElevation => &grid.min_elevations,
+        Axis::Step => &grid.steps,
+        Axis::Latitude => &grid.latitudes,
+    }
+}
+
+/// Run every variant on every implementation, and sum up the results.
+pub fn run(orchestrator: &Orchestrator, implementations: &[Implementation], sweep: &Sweep) -> Result<Vec<Surface>> {
+    let mut surfaces = Vec::new();
+    for impl_ in implementations {
+        let dir = orchestrator.results_dir.join(".sweep").join(&impl_.name);
+        let staged = capabilities::stage(&sweep.cases_dir, &dir, &capabilities::declared(&impl_.path))?;
+        // The variants only differ in numbers, so they need the same features
+        if let Some((_, features)) = staged.unsupported.first() {
+            let needs = capabilities::names(features);
+            println!("  {} {} - unsupported (needs {})", "○".dimmed(), impl_.name.bright_white(), needs);
+            surfaces.push(Surface {
+                implementation: impl_.name.clone(),
+                error: Some(format!("Unsupported: needs {}", needs)),
+                points: Vec::new(),
+                sensitivity: Vec::new(),
+            });
+            continue;
+        }
+
+        let output_dir = dir.join("results");
+        let (output, _, time) = screening::run_container(orchestrator, impl_, &sweep.cases_dir, &output_dir, None, orchestrator.limits)?;
+        let error = if output.hung {
+            Some(format!("Hung, killed after {:.0}s", time))
+        } else if output.timed_out {
+            Some(format!("Timed out after {:.0}s", time))
+        } else if !output.success() {
+            Some(format!("Container failed (exit {})", output.exit_code.unwrap_or_default()))
+        } else {
+            None
+        };
+
+        let mut results: BTreeMap<String, Value> = BTreeMap::new();
+        for entry in fs::read_dir(&output_dir).with_context(|| format!("Failed to read {}", output_dir.display()))? {
+            let path = entry?.path();
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if let Some(test_case) = result_test_case(&file_name, &impl_.name)
+                && let Ok(result) = serde_json::from_str::<Value>(&read_result(&path)?)
+            {
+                results.insert(test_case, result);
+            }
+        }
+        let points: Vec<Point> = sweep.variants.iter().map(|variant| summarize(variant, results.get(&variant.name))).collect();
+
+        let written = points.iter().filter(|point| point.windows.is_some()).count();
+        let (mark, what) = match &error {
+            None if written == points.len() => ("✓".green(), format!("{:.2}s", time)),
+            None => ("●".yellow(), format!("{:.2}s, some variants without a result", time)),
+            Some(error) => ("✗".red(), error.to_lowercase()),
+        };
+        println!("  {} {} - {}/{} variant(s), {}", mark, impl_.name.bright_white(), written, points.len(), what);
+
+        surfaces.push(Surface {
+            implementation: impl_.name.clone(),
+            error: error.or_else(|| (written < points.len()).then(|| "Some variants have no result".to_string())),
+            sensitivity: sensitivity(&sweep.axes, &points),
+            points,
+        });
+    }
+
+    // Differences from the reference, point by point
+    let reference: Option<Vec<(Option<usize>, Option<f64>)>> = surfaces
+        .iter()
+        .find(|surface| surface.implementation == REFERENCE_IMPLEMENTATION && !surface.points.is_empty())
+        .map(|surface| surface.points.iter().map(|point| (point.windows, point.visible_seconds)).collect());
+    if let Some(reference) = reference {
+        for surface in surfaces.iter_mut().filter(|surface| surface.implementation != REFERENCE_IMPLEMENTATION) {
+            for (point, (windows, visible)) in surface.points.iter_mut().zip(&reference) {
+                point.windows_delta = point.windows.zip(*windows).map(|(own, reference)| own as i64 - reference as i64);
+                point.visible_delta = point.visible_seconds.zip(*visible).map(|(own, reference)| own - reference);
+            }
+        }
+    }
+    Ok(surfaces)
+}
+
+/// Sum up `result`, the implementation's result for `variant`, if any.
+fn summarize(variant: &Variant, result: Option<&Value>) -> Point {
+    let windows = result.and_then(|result| result["visibilityWindows"].as_array());
+    let seconds = |window: &Value| {
+        window["duration"].as_f64().or_else(|| {
+            let time = |field: &str| window[field].as_str().and_then(|time| DateTime::parse_from_rfc3339(time).ok());
+            Some((time("end")? - time("start")?).num_milliseconds() as f64 / 1000.0)
+        })
+    };
+    Point {
+        variant: variant.clone(),
+        windows: windows.map(Vec::len),
+        visible_seconds: windows.map(|windows| windows.iter().filter_map(seconds).sum()),
+        max_elevation: windows.and_then(|windows| windows.iter().filter_map(|window| window["maxElevation"].as_f64()).reduce(f64::max)),
+        windows_delta: None,
+        visible_delta: None,
+    }
+}
+
+/// Mean change per unit of each axis between neighbouring points that agree
+/// on the other axes, for axes with several values.
+fn sensitivity(axes: &[Axis], points: &[Point]) -> Vec<Sensitivity> {
+    let mut sensitivities = Vec::new();
+    for &axis in axes {
+        // Points along the axis, for each combination of the other axes
+        let mut lines: BTreeMap<String, Vec<&Point>> = BTreeMap::new();
+        for point in points {
+            let others: Vec<String> = Axis::ALL
+                .iter()
+                .filter(|other| **other != axis)
+                .map(|other| format!("{:?}", point.variant.value(*other)))
+                .collect();
+            lines.entry(others.join(",")).or_default().push(point);
+        }
+
+        let mut windows = Vec::new();
+        let mut visible = Vec::new();
+        for line in lines.values_mut() {
+            line.sort_by(|a, b| a.variant.value(axis).partial_cmp(&b.variant.value(axis)).unwrap_or(std::cmp::Ordering::Equal));
+            for pair in line.windows(2) {
+                let (Some(from), Some(to)) = (pair[0].variant.value(axis), pair[1].variant.value(axis)) else {
+                    continue;
+                };
+                if to == from {
+                    continue;
+                }
+                if let (Some(a), Some(b)) = (pair[0].windows, pair[1].windows) {
+                    windows.push((b as f64 - a as f64) / (to - from));
+                }
+                if let (Some(a), Some(b)) = (pair[0].visible_seconds, pair[1].visible_seconds) {
+                    visible.push((b - a) / (to - from));
+                }
+            }
+        }
+        if windows.is_empty() {
+            continue;
+        }
+        sensitivities.push(Sensitivity {
+            axis,
+            windows_per_unit: windows.iter().sum::<f64>() / windows.len() as f64,
+            visible_seconds_per_unit: visible.iter().sum::<f64>() / visible.len().max(1) as f64,
+        });
+    }
+    sensitivities
+}
+
+pub fn print(sweep: &Sweep, surfaces: &[Surface]) {
+    let has_reference = surfaces.iter().any(|surface| surface.points.iter().any(|point| point.windows_delta.is_some()));
+    for surface in surfaces {
+        println!("{}", surface.implementation.bright_white());
+        if surface.points.is_empty() {
+            println!("  {} {}", "○".dimmed(), surface.error.as_deref().unwrap_or("No results"));
+            continue;
+        }
+
+        let mut header: Vec<String> = sweep.axes.iter().map(|axis| format!("{:>13}", axis.label())).collect();
+        header.extend([format!("{:>8}", "windows"), format!("{:>11}", "visible"), format!("{:>9}", "max elev")]);
+        if has_reference {
+            header.push("  vs reference".to_string());
+        }
+        println!("  {}", header.join(" ").dimmed());
+        for point in &surface.points {
+            let mut row: Vec<String> = sweep
+                .axes
+                .iter()
+                .map(|axis| format!("{:>13}", format!("{}{}", point.variant.value(*axis).unwrap_or_default(), axis.unit())))
+                .collect();
+            match (point.windows, point.visible_seconds) {
+                (Some(windows), Some(visible)) => {
+                    row.push(format!("{:>8}", windows));
+                    row.push(format!("{:>11}", format!("{:.1} min", visible / 60.0)));
+                    row.push(format!("{:>9}", point.max_elevation.map(|elevation| format!("{:.1}°", elevation)).unwrap_or_else(|| "-".to_string())));
+                }
+                _ => row.push(format!("{:>8}", "-").dimmed().to_string()),
+            }
+            if let (Some(windows), Some(visible)) = (point.windows_delta, point.visible_delta) {
+                let delta = format!("  {:+} window(s), {:+.1}s", windows, visible);
+                row.push(if windows == 0 { delta.green().to_string() } else { delta.yellow().to_string() });
+            }
+            println!("  {}", row.join(" "));
+        }
+        for sensitivity in &surface.sensitivity {
+            let unit = sensitivity.axis.unit();
+            println!(
+                "  {} per {} of {}: {:+.3} window(s), {:+.1}s visible",
+                "●".bright_cyan(),
+                if unit == "s" { "second" } else { "degree" },
+                sensitivity.axis.label(),
+                sensitivity.windows_per_unit,
+                sensitivity.visible_seconds_per_unit
+            );
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Test Anything Protocol output.
+//!
+//! With `--format tap`, `run`, `validate`, and `all` also write a TAP
+//! version 13 stream, for `prove`, `tap-parser`, and other generic TAP
+//! consumers. There's one test point per (implementation, test case),
+//! judged as for [JUnit](crate::junit):
+//!
+//! ```text
+//! TAP version 13
+//! 1..3
+//! ok 1 - python-sgp4 001_iss_nyc
+//! not ok 2 - python-sgp4 002_starlink_sf
+//!   ---
+//!   message: "1 field(s) outside tolerance"
+//!   severity: fail
+//!   data: |
+//!     window 2 maxElevation: +0.7° (tolerance ±0.5°)
+//!   ...
+//! ok 3 - python-sgp4 t_isl # SKIP Unsupported: ...
+//! ```
+//!
+//! Skipped cases are `ok` with a `SKIP` directive. An implementation that
+//! couldn't be run or validated at all is a single `not ok` point for the
+//! stage, with `severity: error`.
+
+use crate::junit::{Case, Outcome};
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Render `cases` as a TAP stream, grouped by implementation.
+pub fn render(cases: &[Case]) -> String {
+    let mut ordered: Vec<&Case> = cases.iter().collect();
+    ordered.sort_by(|a, b| a.implementation.cmp(&b.implementation));
+
+    let mut tap = String::from("TAP version 13\n");
+    let _ = writeln!(tap, "1..{}", ordered.len());
+    for (number, case) in ordered.iter().enumerate() {
+        let description = escape(&format!("{} {}", case.implementation, case.test_case));
+        match &case.outcome {
+            Outcome::Passed => {
+                let _ = writeln!(tap, "ok {} - {}", number + 1, description);
+            }
+            Outcome::Skipped { message } => {
+                let _ = writeln!(tap, "ok {} - {} # SKIP {}", number + 1, description, escape(message));
+            }
+            Outcome::Failed { message, details } | Outcome::Errored { message, details } => {
+                let severity = if matches!(case.outcome, Outcome::Failed { .. }) { "fail" } else { "error" };
+                let _ = writeln!(tap, "not ok {} - {}", number + 1, description);
+                tap.push_str("  ---\n");
+                let _ = writeln!(tap, "  message: {}", serde_json::Value::from(printable(message)));
+                let _ = writeln!(tap, "  severity: {}", severity);
+                let details = printable(details);
+                if !details.trim().is_empty() {
+                    tap.push_str("  data: |\n");
+                    for line in details.trim_end().lines() {
+                        let _ = writeln!(tap, "    {}", line);
+                    }
+                }
+                tap.push_str("  ...\n");
+            }
+        }
+    }
+    tap
+}
+
+/// `text` without control characters other than newlines (containers'
+/// stderr may contain them).
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control() || *c == '\n').collect()
+}
+
+/// A description or directive on one line, with `#` and `\` escaped so
+/// neither starts a directive.
+fn escape(text: &str) -> String {
+    printable(text).replace('\n', " ").replace('\\', "\\\\").replace('#', "\\#")
+}
+
+pub fn write(path: &Path, cases: &[Case]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, render(cases)).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(implementation: &str, test_case: &str, outcome: Outcome) -> Case {
+        Case {
+            implementation: implementation.to_string(),
+            test_case: test_case.to_string(),
+            time: None,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn printable_drops_control_characters_but_newlines() {
+        assert_eq!(printable("a\x1b[31mred\x1b[0m\tb\nc\r"), "a[31mred[0mb\nc");
+    }
+
+    #[test]
+    fn escape_keeps_descriptions_on_one_line_without_directives() {
+        assert_eq!(escape("case # SKIP"), "case \\# SKIP");
+        assert_eq!(escape("back\\slash"), "back\\\\slash");
+        assert_eq!(escape("two\nlines"), "two lines");
+        assert_eq!(escape("bell\x07"), "bell");
+    }
+
+    #[test]
+    fn render_numbers_points_by_implementation() {
+        let cases = [
+            case("rust-sgp4", "001_iss_nyc", Outcome::Passed),
+            case(
+                "python-sgp4",
+                "002_starlink_sf",
+                Outcome::Failed {
+                    message: "1 field(s) \"outside\" tolerance".to_string(),
+                    details: "window 2 maxElevation: +0.7°\x1b[0m\n".to_string(),
+                },
+            ),
+            case(
+                "python-sgp4",
+                "t_isl",
+                Outcome::Skipped {
+                    message: "Unsupported: isl # later".to_string(),
+                },
+            ),
+            case(
+                "go-sgp4",
+                "(run)",
+                Outcome::Errored {
+                    message: "Container failed".to_string(),
+                    details: String::new(),
+                },
+            ),
+        ];
+
+        assert_eq!(
+            render(&cases),
+            "TAP version 13
+1..4
+not ok 1 - go-sgp4 (run)
+  ---
+  message: \"Container failed\"
+  severity: error
+  ...
+not ok 2 - python-sgp4 002_starlink_sf
+  ---
+  message: \"1 field(s) \\\"outside\\\" tolerance\"
+  severity: fail
+  data: |
+    window 2 maxElevation: +0.7°[0m
+  ...
+ok 3 - python-sgp4 t_isl # SKIP Unsupported: isl \\# later
+ok 4 - rust-sgp4 001_iss_nyc
+"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Human-readable window times.
+//!
+//! Window boundaries are always shown in UTC, alongside their offset from the
+//! start of the case's time window ("+06:32:30"), how far they are from now
+//! ("in 2h 13m", "3d 4h ago"), and optionally the wall-clock time in a zone
+//! given with `--tz`, for observers planning real passes.
+
+use chrono::{DateTime, FixedOffset, Local, TimeDelta, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+/// Zone for `--tz`: `local`, a fixed offset like `+05:30`, or an IANA name
+/// like `America/New_York`.
+#[derive(Debug, Clone)]
+pub enum Zone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        if let Ok(offset) = FixedOffset::from_str(s) {
+            return Ok(Zone::Fixed(offset));
+        }
+        s.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| format!("unknown time zone '{}' (use local, an offset like +05:30, or an IANA name)", s))
+    }
+}
+
+impl Zone {
+    /// `time` as wall-clock time in this zone, with its abbreviation or offset.
+    pub fn format(&self, time: DateTime<Utc>) -> String {
+        match self {
+            Zone::Named(_) => self.format_as(time, "%Y-%m-%d %H:%M:%S %Z"),
+            _ => self.format_as(time, "%Y-%m-%d %H:%M:%S %:z"),
+        }
+    }
+
+    /// `time` as wall-clock time in this zone, in a strftime `format`.
+    pub fn format_as(&self, time: DateTime<Utc>, format: &str) -> String {
+        match self {
+            Zone::Local => time.with_timezone(&Local).format(format).to_string(),
+            Zone::Fixed(offset) => time.with_timezone(offset).format(format).to_string(),
+            Zone::Named(tz) => time.with_timezone(tz).format(format).to_string(),
+        }
+    }
+}
+
+pub fn utc(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%SZ").to_string()
+}
+
+/// Signed offset from `origin`, e.g. "+06:32:30" or "+1d 02:00:00".
+pub fn offset(time: DateTime<Utc>, origin: DateTime<Utc>) -> String {
+    let delta = time - origin;
+    let sign = if delta < TimeDelta::zero() { '-' } else { '+' };
+    let seconds = delta.num_seconds().abs();
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let clock = format!("{:02}:{:02}:{:02}", rest / 3600, rest % 3600 / 60, rest % 60);
+    if days > 0 {
+        format!("{}{}d {}", sign, days, clock)
+    } else {
+        format!("{}{}", sign, clock)
+    }
+}
+
+/// Distance from `now` in the two largest units, e.g. "in 2h 13m" or "3d 4h ago".
+pub fn from_now(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = time - now;
+    let seconds = delta.num_seconds().abs();
+    if seconds < 60 {
+        return "now".to_string();
+    }
+    let amount = duration(seconds);
+    if delta > TimeDelta::zero() {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// A whole-second span in its two largest units, e.g. "6m 40s" or "3d 4h".
+pub fn duration(seconds: i64) -> String {
+    let units = [("d", 86_400), ("h", 3600), ("m", 60), ("s", 1)];
+    let Some(first) = units.iter().position(|&(_, size)| seconds >= size) else {
+        return "0s".to_string();
+    };
+
+    let (name, size) = units[first];
+    let mut text = format!("{}{}", seconds / size, name);
+    if let Some(&(next_name, next_size)) = units.get(first + 1) {
+        let rest = seconds % size / next_size;
+        if rest > 0 {
+            text.push_str(&format!(" {}{}", rest, next_name));
+        }
+    }
+    text
+}
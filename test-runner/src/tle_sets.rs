@@ -0,0 +1,257 @@
+//! Named TLE sets referenced by test cases.
+//!
+//! Instead of embedding TLE lines, a case's `satellite` (or
+//! `observerSatellite`) may name a set file under `test-data/tle-sets/` and
+//! pick one entry from it:
+//!
+//! ```json
+//! "satellite": { "name": "ISS (ZARYA)", "tleSet": "stations", "noradId": 25544 }
+//! ```
+//!
+//! The entry is chosen by `noradId` when given, otherwise by `name`.
+//!
+//! A satellite can also be given by catalogue number and date alone, taking
+//! the elements current on that date from the committed history of that
+//! object in `test-data/tle-history/<noradId>.tle`:
+//!
+//! ```json
+//! "satellite": { "name": "ISS (ZARYA)", "noradId": 25544, "epoch": "2024-03-01" }
+//! ```
+//!
+//! The newest entry with an epoch on or before the given date (the end of
+//! the day) or RFC 3339 time is used. `epoch` also picks between several
+//! entries for the same object in a set file. Before
+//! containers run, every case is written to a staging directory with the
+//! concrete lines filled in as `tle`, so implementations only ever see
+//! self-contained cases and never read the set files themselves. Named
+//! ground stations (see [`sites`](crate::sites)) are resolved in the same
+//! pass.
+
+use crate::sites;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Case fields that hold a satellite with a TLE.
+const SATELLITE_FIELDS: [&str; 2] = ["satellite", "observerSatellite"];
+
+/// One entry of a set file.
+struct Entry {
+    /// The name line, if the entry has one
+    name: Option<String>,
+    norad_id: Option<u32>,
+    epoch: Option<DateTime<Utc>>,
+    /// Lines 1 and 2
+    lines: [String; 2],
+}
+
+/// Write every case in `<test_data_dir>/cases` to `<staging_dir>/cases` with
+/// TLE set and site references resolved. Returns the staged cases directory,
+/// or `None` when no case references either and the originals can be used
+/// as they are.
+pub fn materialize(test_data_dir: &Path, staging_dir: &Path) -> Result<Option<PathBuf>> {
+    let cases_dir = test_data_dir.join("cases");
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(&cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let content = fs::read_to_string(&path)?;
+            cases.push((path, content));
+        }
+    }
+
+    let references = |case: &Value| {
+        SATELLITE_FIELDS.iter().any(|field| ["tleSet", "epoch"].iter().any(|key| case[field].get(key).is_some())) || sites::references(case)
+    };
+    let mut parsed = Vec::new();
+    for (path, content) in &cases {
+        let case: Value =
+            serde_json::from_str(content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        parsed.push(case);
+    }
+    if !parsed.iter().any(references) {
+        return Ok(None);
+    }
+
+    let staged_dir = staging_dir.join("cases");
+    fs::create_dir_all(&staged_dir).with_context(|| format!("Failed to create {}", staged_dir.display()))?;
+
+    let sites = if parsed.iter().any(sites::references) { sites::read(test_data_dir)? } else { Vec::new() };
+    let mut sets: HashMap<PathBuf, Vec<Entry>> = HashMap::new();
+    for ((path, content), mut case) in cases.into_iter().zip(parsed) {
+        let staged = if references(&case) {
+            for field in SATELLITE_FIELDS {
+                if let Some(satellite) = case.get_mut(field).and_then(Value::as_object_mut) {
+                    resolve(satellite, test_data_dir, &mut sets)
+                        .with_context(|| format!("Failed to resolve {} in {}", field, path.display()))?;
+                }
+            }
+            sites::resolve(&mut case, &sites).with_context(|| format!("Failed to resolve observer site in {}", path.display()))?;
+            serde_json::to_string_pretty(&case)?
+        } else {
+            content
+        };
+
+        let target = staged_dir.join(path.file_name().unwrap_or_default());
+        // Concurrent runs stage identical content; skip unchanged files and
+        // replace the rest atomically so a running container never sees a
+        // partial write
+        if fs::read_to_string(&target).ok().as_deref() != Some(staged.as_str()) {
+            let temp = target.with_extension("json.tmp");
+            fs::write(&temp, staged)?;
+            fs::rename(&temp, &target)?;
+        }
+    }
+
+    Ok(Some(staged_dir))
+}
+
+/// Every entry of set file `name` as a case's `satellite` (its name, or
+/// `NORAD <number>` for two-line entries, and its `tle` lines), with its
+/// catalogue number, for scenarios that take a whole set such as
+/// [`screening`](crate::screening).
+pub fn satellites(test_data_dir: &Path, name: &str) -> Result<Vec<(Option<u32>, Value)>> {
+    let path = test_data_dir.join("tle-sets").join(format!("{}.tle", name));
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read TLE set {} {}", name, path.display()))?;
+    Ok(parse_set(&content)
+        .into_iter()
+        .map(|entry| {
+            let name = entry.name.unwrap_or_else(|| format!("NORAD {}", entry.norad_id.unwrap_or_default()));
+            let [line1, line2] = entry.lines;
+            (entry.norad_id, json!({ "name": name, "tle": [name, line1, line2] }))
+        })
+        .collect())
+}
+
+/// Replace a `tleSet` or catalogue (`noradId` plus `epoch`) reference with
+/// the concrete `tle` lines.
+fn resolve(satellite: &mut Map<String, Value>, test_data_dir: &Path, sets: &mut HashMap<PathBuf, Vec<Entry>>) -> Result<()> {
+    let set_name = satellite
+        .remove("tleSet")
+        .map(|name| name.as_str().map(str::to_string).context("tleSet must be a string"))
+        .transpose()?;
+    let epoch = satellite.remove("epoch").map(|epoch| parse_epoch(&epoch)).transpose()?;
+    if set_name.is_none() && epoch.is_none() {
+        return Ok(());
+    }
+    let norad_id = satellite.remove("noradId").map(|id| id.as_u64().context("noradId must be a number")).transpose()?;
+
+    if satellite.contains_key("tle") {
+        bail!("Give either tle or a tleSet or epoch reference, not both");
+    }
+
+    let (path, source) = match (&set_name, norad_id) {
+        (Some(set_name), _) => (test_data_dir.join("tle-sets").join(format!("{}.tle", set_name)), format!("TLE set {}", set_name)),
+        (None, Some(id)) => (test_data_dir.join("tle-history").join(format!("{}.tle", id)), "its TLE history".to_string()),
+        (None, None) => bail!("An epoch reference needs a noradId"),
+    };
+    if !sets.contains_key(&path) {
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {} {}", source, path.display()))?;
+        sets.insert(path.clone(), parse_set(&content));
+    }
+    let entries = &sets[&path];
+
+    let name = satellite.get("name").and_then(Value::as_str);
+    let matches: Vec<&Entry> = match (norad_id, name) {
+        (Some(id), _) => entries.iter().filter(|e| e.norad_id.map(u64::from) == Some(id)).collect(),
+        (None, Some(name)) => entries.iter().filter(|e| e.name.as_deref() == Some(name)).collect(),
+        (None, None) => bail!("A tleSet reference needs a noradId or name"),
+    };
+
+    let entry = match (epoch, matches.as_slice()) {
+        (_, []) => bail!("No entry for {} in {}", describe(norad_id, name), source),
+        // The elements current at the epoch: the newest published by then
+        (Some(epoch), _) => match matches.iter().filter(|e| e.epoch.is_some_and(|t| t <= epoch)).max_by_key(|e| e.epoch) {
+            Some(entry) => entry,
+            None => bail!(
+                "No entry for {} in {} with an epoch on or before {} (earliest: {})",
+                describe(norad_id, name),
+                source,
+                epoch.format("%Y-%m-%d %H:%M:%S UTC"),
+                matches
+                    .iter()
+                    .filter_map(|e| e.epoch)
+                    .min()
+                    .map_or("none".to_string(), |t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            ),
+        },
+        (None, [entry]) => entry,
+        (None, _) => bail!(
+            "{} matches several entries in {}; use noradId, or epoch to pick by date",
+            describe(norad_id, name),
+            source
+        ),
+    };
+
+    // Two-line entries take the case's name for the name line
+    let name_line = entry
+        .name
+        .clone()
+        .or(name.map(str::to_string))
+        .unwrap_or_else(|| format!("NORAD {}", entry.norad_id.unwrap_or_default()));
+    satellite.insert("tle".to_string(), Value::from(vec![name_line, entry.lines[0].clone(), entry.lines[1].clone()]));
+    Ok(())
+}
+
+/// The latest TLE epoch a case's `epoch` admits: the given instant for an
+/// RFC 3339 time, or the end of the day for a date.
+fn parse_epoch(value: &Value) -> Result<DateTime<Utc>> {
+    let text = value.as_str().context("epoch must be a string")?;
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .with_context(|| format!("epoch must be a date (YYYY-MM-DD) or an RFC 3339 time, not {}", text))?;
+    Ok(date.and_hms_milli_opt(23, 59, 59, 999).context("Invalid epoch")?.and_utc())
+}
+
+fn describe(norad_id: Option<u64>, name: Option<&str>) -> String {
+    match norad_id {
+        Some(id) => format!("NORAD {}", id),
+        None => format!("'{}'", name.unwrap_or_default()),
+    }
+}
+
+/// Entries of a set file in the usual three-line (name, line 1, line 2)
+/// format, as served by CelesTrak, or two-line entries without the name
+/// line, as in Space-Track's TLE history. Blank lines and `#` comments are
+/// skipped.
+fn parse_set(content: &str) -> Vec<Entry> {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .collect();
+    let is_element_line = |line: &str| line.starts_with("1 ") || line.starts_with("2 ");
+
+    lines
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0].starts_with("1 ") && w[1].starts_with("2 "))
+        .map(|(i, w)| Entry {
+            // Space-Track's 3LE format prefixes names with "0 "
+            name: i
+                .checked_sub(1)
+                .map(|previous| lines[previous])
+                .filter(|line| !is_element_line(line))
+                .map(|line| line.trim().trim_start_matches("0 ").to_string()),
+            norad_id: w[0].get(2..7).and_then(|id| id.trim().parse().ok()),
+            epoch: w[0].get(18..32).and_then(tle_epoch),
+            lines: [w[0].to_string(), w[1].to_string()],
+        })
+        .collect()
+}
+
+/// The epoch field of TLE line 1 (`YYDDD.DDDDDDDD`) as a time. Two-digit
+/// years from 57 are 19xx, the rest 20xx.
+fn tle_epoch(field: &str) -> Option<DateTime<Utc>> {
+    let field = field.trim();
+    let year: i32 = field.get(..2)?.parse().ok()?;
+    let day: f64 = field.get(2..)?.parse().ok()?;
+    let year = if year >= 57 { 1900 + year } else { 2000 + year };
+    let start = NaiveDate::from_yo_opt(year, 1)?.and_hms_opt(0, 0, 0)?.and_utc();
+    Some(start + TimeDelta::milliseconds(((day - 1.0) * 86_400_000.0).round() as i64))
+}
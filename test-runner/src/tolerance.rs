@@ -0,0 +1,415 @@
+//! Window-by-window comparison against the reference.
+//!
+//! An implementation's windows are paired with the reference's (see
+//! [`pair`]), and each pair's start, end, duration, and maximum elevation
+//! must agree within [`Tolerances`]. Windows left without a pair, such as
+//! when the two sides find different numbers of windows, are listed as
+//! unmatched. Fields missing on either side (e.g. `maxElevation` in
+//! truth data that doesn't report it) aren't compared.
+//!
+//! Each case's tolerances default to those of its orbit
+//! [`Regime`], and a case can set its own:
+//!
+//! ```json
+//! "tolerances": { "time": 120, "elevation": 1.0 }
+//! ```
+//!
+//! Tolerances given to `validate` on the command line apply to every case.
+//!
+//! Windows may carry 1σ uncertainties of their edges in seconds
+//! (`startUncertainty`, `endUncertainty`), e.g. from a TLE error model that
+//! grows with the TLE's age. A start or end is then allowed
+//! [`UNCERTAINTY_SIGMAS`] times the combined uncertainty of both sides when
+//! that's more than its tolerance, and a duration, which moves with both
+//! edges, that of both edges of both sides, so an old TLE doesn't fail a case over
+//! differences its own error swamps. Tolerances are never tightened, and
+//! windows without uncertainties get the static ones.
+
+use crate::orbit::Regime;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Multiple of a window edge's combined 1σ uncertainty it's allowed to be off
+pub const UNCERTAINTY_SIGMAS: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerances {
+    /// Seconds, for start, end, and duration
+    pub time: f64,
+    /// Degrees, for maximum elevation
+    pub elevation: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Tolerances {
+            time: 30.0,
+            elevation: 0.5,
+        }
+    }
+}
+
+impl Tolerances {
+    /// Defaults for cases in `regime`. Window boundaries of slow-moving
+    /// satellites shift by whole time steps, so they get more time.
+    pub fn for_regime(regime: Regime) -> Self {
+        let time = match regime {
+            Regime::Leo => return Tolerances::default(),
+            Regime::Meo | Regime::Heo => 60.0,
+            Regime::Geo => 300.0,
+        };
+        Tolerances {
+            time,
+            ..Tolerances::default()
+        }
+    }
+}
+
+/// Tolerances from the command line, each applying to every case when given.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Overrides {
+    pub time: Option<f64>,
+    pub elevation: Option<f64>,
+}
+
+/// Tolerances for `test_case` in `cases_dir`, and its orbit regime: each
+/// field from `overrides`, else the case's `tolerances`, else its regime's
+/// defaults. Cases that can't be read get the defaults.
+pub fn for_case(cases_dir: &Path, test_case: &str, overrides: &Overrides) -> (Tolerances, Option<Regime>) {
+    let case: Value = fs::read_to_string(cases_dir.join(format!("{}.json", test_case)))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let regime = Regime::of_case(&case);
+    let defaults = regime.map(Tolerances::for_regime).unwrap_or_default();
+    let own = &case["tolerances"];
+    let tolerances = Tolerances {
+        time: overrides.time.or(own["time"].as_f64()).unwrap_or(defaults.time),
+        elevation: overrides.elevation.or(own["elevation"].as_f64()).unwrap_or(defaults.elevation),
+    };
+    (tolerances, regime)
+}
+
+/// A window field outside its tolerance.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDelta {
+    /// The result's window, 1-based in order of start time
+    pub window: usize,
+    /// The reference window it's paired with, numbered likewise
+    pub reference_window: usize,
+    pub field: &'static str,
+    /// Result minus reference, in seconds or degrees
+    pub delta: f64,
+    pub tolerance: f64,
+}
+
+impl FieldDelta {
+    pub fn unit(&self) -> &'static str {
+        if self.field == "maxElevation" { "°" } else { "s" }
+    }
+
+    /// E.g. "window 2", or "window 2 (reference window 3)" when paired
+    /// across a missing or extra window.
+    pub fn windows(&self) -> String {
+        if self.window == self.reference_window {
+            format!("window {}", self.window)
+        } else {
+            format!("window {} (reference window {})", self.window, self.reference_window)
+        }
+    }
+}
+
+/// A window with no counterpart on the other side.
+#[derive(Debug, Clone, Serialize)]
+pub struct Unmatched {
+    pub side: Side,
+    /// 1-based, in order of start time on its side
+    pub window: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Result,
+    Reference,
+}
+
+impl fmt::Display for Unmatched {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (side, other) = match self.side {
+            Side::Result => ("result", "reference"),
+            Side::Reference => ("reference", "result"),
+        };
+        write!(f, "{} window {}", side, self.window)?;
+        if let Some(start) = &self.start {
+            write!(f, " starting {}", start)?;
+        }
+        write!(f, " has no {} window", other)
+    }
+}
+
+/// How an implementation's windows compare with the reference's.
+#[derive(Debug, Default)]
+pub struct Comparison {
+    /// Fields of paired windows outside tolerance
+    pub deltas: Vec<FieldDelta>,
+    /// Windows of either side left without a pair
+    pub unmatched: Vec<Unmatched>,
+}
+
+impl Comparison {
+    pub fn is_match(&self) -> bool {
+        self.deltas.is_empty() && self.unmatched.is_empty()
+    }
+}
+
+/// Compare `result` windows with `reference` windows, paired by nearest
+/// start time.
+pub fn compare(result: &[Value], reference: &[Value], tolerances: &Tolerances) -> Comparison {
+    let (result, reference) = (sorted(result), sorted(reference));
+    let pairs = pair(&result, &reference);
+    let mut comparison = Comparison::default();
+    for (side, windows) in [(Side::Result, &result), (Side::Reference, &reference)] {
+        for (i, window) in windows.iter().enumerate() {
+            let paired = pairs.iter().any(|&(r, e)| if side == Side::Result { r == i } else { e == i });
+            if !paired {
+                comparison.unmatched.push(Unmatched {
+                    side,
+                    window: i + 1,
+                    start: window["start"].as_str().map(str::to_string),
+                });
+            }
+        }
+    }
+
+    let deltas = &mut comparison.deltas;
+    for &(i, j) in &pairs {
+        let (actual, expected) = (result[i], reference[j]);
+        let time_delta = |field: &str| Some((parse_time(&actual[field])? - parse_time(&expected[field])?).num_milliseconds() as f64 / 1000.0);
+        let number_delta = |field: &str| Some(actual[field].as_f64()? - expected[field].as_f64()?);
+
+        let fields = [
+            ("start", time_delta("start"), widened(tolerances.time, actual, expected, &["startUncertainty"])),
+            ("end", time_delta("end"), widened(tolerances.time, actual, expected, &["endUncertainty"])),
+            (
+                "duration",
+                number_delta("duration"),
+                widened(tolerances.time, actual, expected, &["startUncertainty", "endUncertainty"]),
+            ),
+            ("maxElevation", number_delta("maxElevation"), tolerances.elevation),
+        ];
+        for (field, delta, tolerance) in fields {
+            if let Some(delta) = delta
+                && delta.abs() > tolerance
+            {
+                deltas.push(FieldDelta {
+                    window: i + 1,
+                    reference_window: j + 1,
+                    field,
+                    delta,
+                    tolerance,
+                });
+            }
+        }
+    }
+    comparison
+}
+
+/// Pair the windows of two sides, each sorted by start time. Only windows
+/// whose spans overlap are paired, nearest starts first, so a missing or
+/// extra window on one side doesn't shift every pair after it, and passes
+/// hours apart are never compared as one. Returns the pairs' indices in
+/// order; windows without a start and end aren't paired.
+///
+/// Validation, `diff` (see [`result_diff`]), and `leaderboard` all pair
+/// windows this way.
+///
+/// [`result_diff`]: crate::result_diff
+pub(crate) fn pair(a: &[&Value], b: &[&Value]) -> Vec<(usize, usize)> {
+    let span = |window: &Value| Some((parse_time(&window["start"])?, parse_time(&window["end"])?));
+    let mut candidates = Vec::new();
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            if let (Some((a_start, a_end)), Some((b_start, b_end))) = (span(a), span(b))
+                && a_start <= b_end
+                && b_start <= a_end
+            {
+                candidates.push(((a_start - b_start).num_milliseconds().abs(), i, j));
+            }
+        }
+    }
+    candidates.sort();
+    let (mut a_paired, mut b_paired) = (vec![false; a.len()], vec![false; b.len()]);
+    let mut pairs = Vec::new();
+    for (_, i, j) in candidates {
+        if !a_paired[i] && !b_paired[j] {
+            a_paired[i] = true;
+            b_paired[j] = true;
+            pairs.push((i, j));
+        }
+    }
+    pairs.sort();
+    pairs
+}
+
+/// `tolerance`, or [`UNCERTAINTY_SIGMAS`] times the root sum square of the
+/// windows' uncertainties in `fields` if more.
+fn widened(tolerance: f64, actual: &Value, expected: &Value, fields: &[&str]) -> f64 {
+    let sigmas: Vec<f64> = [actual, expected]
+        .iter()
+        .flat_map(|window| fields.iter().filter_map(|field| window[*field].as_f64()))
+        .collect();
+    if sigmas.is_empty() {
+        return tolerance;
+    }
+    let combined = sigmas.iter().map(|sigma| sigma * sigma).sum::<f64>().sqrt();
+    tolerance.max(UNCERTAINTY_SIGMAS * combined)
+}
+
+fn sorted(windows: &[Value]) -> Vec<&Value> {
+    let mut windows: Vec<&Value> = windows.iter().collect();
+    windows.sort_by_key(|window| parse_time(&window["start"]));
+    windows
+}
+
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn window(start: &str, end: &str, duration: f64) -> Value {
+        json!({ "start": start, "end": end, "duration": duration, "maxElevation": 45.0 })
+    }
+
+    #[test]
+    fn duration_is_widened_by_both_edges_uncertainties() {
+        let mut actual = window("2025-01-01T00:01:00Z", "2025-01-01T00:09:00Z", 480.0);
+        let mut expected = window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0);
+        for window in [&mut actual, &mut expected] {
+            window["startUncertainty"] = json!(20.0);
+            window["endUncertainty"] = json!(20.0);
+        }
+
+        // Edges off by 60s pass on 3 × √(20² + 20²) ≈ 84.9s, and the 120s
+        // duration difference on 3 × √(4 × 20²) = 120s
+        assert!(compare(&[actual], &[expected], &Tolerances::default()).is_match());
+    }
+
+    #[test]
+    fn duration_keeps_its_tolerance_without_uncertainties() {
+        let actual = window("2025-01-01T00:00:10Z", "2025-01-01T00:09:50Z", 580.0);
+        let expected = window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0);
+        let tolerances = Tolerances {
+            time: 15.0,
+            elevation: 0.5,
+        };
+
+        let deltas = compare(&[actual], &[expected], &tolerances).deltas;
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].field, "duration");
+        assert_eq!(deltas[0].delta, -20.0);
+        assert_eq!(deltas[0].tolerance, 15.0);
+    }
+
+    #[test]
+    fn uncertainties_never_tighten_tolerances() {
+        let mut actual = window("2025-01-01T00:00:20Z", "2025-01-01T00:10:00Z", 580.0);
+        actual["startUncertainty"] = json!(1.0);
+        let expected = window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0);
+
+        assert!(compare(&[actual], &[expected], &Tolerances::default()).is_match());
+    }
+
+    #[test]
+    fn windows_are_paired_by_start_whatever_the_order() {
+        let early = window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0);
+        let late = window("2025-01-01T06:00:00Z", "2025-01-01T06:10:00Z", 600.0);
+
+        let comparison = compare(&[late.clone(), early.clone()], &[early, late], &Tolerances::default());
+        assert!(comparison.is_match(), "{:?}", comparison);
+    }
+
+    #[test]
+    fn a_missing_window_leaves_the_others_paired() {
+        let windows = [
+            window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0),
+            window("2025-01-01T01:30:00Z", "2025-01-01T01:40:00Z", 600.0),
+            window("2025-01-01T03:00:00Z", "2025-01-01T03:10:00Z", 600.0),
+        ];
+        let mut result = vec![windows[0].clone(), windows[2].clone()];
+        result[1]["end"] = json!("2025-01-01T03:12:00Z");
+        result[1]["duration"] = json!(720.0);
+
+        let comparison = compare(&result, &windows, &Tolerances::default());
+        assert_eq!(comparison.unmatched.len(), 1);
+        assert_eq!(comparison.unmatched[0].side, Side::Reference);
+        assert_eq!(comparison.unmatched[0].window, 2);
+        assert_eq!(
+            comparison.unmatched[0].to_string(),
+            "reference window 2 starting 2025-01-01T01:30:00Z has no result window"
+        );
+        // The third reference window is compared with the second result
+        // window, not the reference's second
+        let fields: Vec<(&str, usize, usize)> =
+            comparison.deltas.iter().map(|delta| (delta.field, delta.window, delta.reference_window)).collect();
+        assert_eq!(fields, [("end", 2, 3), ("duration", 2, 3)]);
+        assert_eq!(comparison.deltas[0].windows(), "window 2 (reference window 3)");
+    }
+
+    #[test]
+    fn extra_windows_are_unmatched() {
+        let shared = window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0);
+        let extra = window("2025-01-01T02:00:00Z", "2025-01-01T02:01:00Z", 60.0);
+
+        let comparison = compare(&[shared.clone(), extra], &[shared], &Tolerances::default());
+        assert!(comparison.deltas.is_empty());
+        assert_eq!(comparison.unmatched.len(), 1);
+        assert_eq!(comparison.unmatched[0].side, Side::Result);
+        assert_eq!(comparison.unmatched[0].window, 2);
+        assert!(!comparison.is_match());
+    }
+
+    #[test]
+    fn closest_starts_pair_first() {
+        // Both result windows overlap the reference, but the one starting
+        // at 00:15 is closer to its 00:10 start than the one at 00:02, so
+        // it takes the pair, and the other stays unmatched rather than
+        // shifting the pairing
+        let result = [
+            window("2025-01-01T00:02:00Z", "2025-01-01T00:15:00Z", 780.0),
+            window("2025-01-01T00:15:00Z", "2025-01-01T00:30:00Z", 900.0),
+        ];
+        let reference = [window("2025-01-01T00:10:00Z", "2025-01-01T00:40:00Z", 1800.0)];
+
+        let comparison = compare(&result, &reference, &Tolerances::default());
+        assert_eq!(comparison.unmatched.len(), 1);
+        assert_eq!(comparison.unmatched[0].window, 1);
+        assert!(comparison.deltas.iter().all(|delta| delta.window == 2 && delta.reference_window == 1));
+    }
+
+    #[test]
+    fn windows_that_dont_overlap_stay_unpaired() {
+        // One window each, hours apart: two different passes, not one pass
+        // hours off
+        let result = [window("2025-01-01T00:00:00Z", "2025-01-01T00:10:00Z", 600.0)];
+        let reference = [window("2025-01-01T05:00:00Z", "2025-01-01T05:10:00Z", 600.0)];
+
+        let comparison = compare(&result, &reference, &Tolerances::default());
+        assert!(comparison.deltas.is_empty());
+        let sides: Vec<Side> = comparison.unmatched.iter().map(|unmatched| unmatched.side).collect();
+        assert_eq!(sides, [Side::Result, Side::Reference]);
+    }
+}
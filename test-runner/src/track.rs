@@ -0,0 +1,250 @@
+//! Rotator tracking schedules for hamlib's rotctld.
+//!
+//! Turns the sampled `points` of one pass into a time-tagged script, one
+//! `<UTC time> P <azimuth> <elevation>` line per point, where `P` is
+//! rotctld's set_pos command. A first command moves the rotator to the AOS
+//! position `lead` seconds before AOS.
+//!
+//! Azimuths are unwrapped so the rotator slews as little as its range
+//! allows when a pass crosses north:
+//!
+//! - `none`: azimuths as computed, 0-360
+//! - `continuous`: each azimuth is placed at whichever of its equivalent
+//!   positions (± 360°) within the rotator's range gives the least total
+//!   slew, so an overlap rotator (e.g. 0-450) follows a pass across north
+//! - `flip`: for rotators with 180° of elevation, the whole pass is tracked
+//!   "over the top" at azimuth + 180° and elevation 180° - el, then placed
+//!   as above
+//!
+//! When the range leaves no choice but to slew the long way round, the
+//! script says so in a comment.
+
+use crate::{Orchestrator, read_result};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Unwrap {
+    None,
+    Continuous,
+    Flip,
+}
+
+pub struct Options {
+    /// 1-based pass number within the result
+    pub pass: usize,
+    /// Seconds before AOS to pre-position the rotator
+    pub lead: u32,
+    pub unwrap: Unwrap,
+    /// The rotator's azimuth range, in degrees
+    pub az_min: f64,
+    pub az_max: f64,
+}
+
+struct Point {
+    time: DateTime<Utc>,
+    azimuth: f64,
+    elevation: f64,
+}
+
+pub fn render(orchestrator: &Orchestrator, impl_name: &str, test_case: &str, options: &Options) -> Result<String> {
+    let path = orchestrator.latest_result(impl_name, test_case)?;
+    let result: Value = serde_json::from_str(&read_result(&path)?)?;
+
+    let windows = result["visibilityWindows"].as_array().cloned().unwrap_or_default();
+    let window = options
+        .pass
+        .checked_sub(1)
+        .and_then(|i| windows.get(i))
+        .with_context(|| format!("{} has {} pass(es); there is no pass {}", test_case, windows.len(), options.pass))?;
+    schedule(window, impl_name, test_case, options)
+}
+
+/// The tracking script for `window`, pass `options.pass` of `test_case`.
+fn schedule(window: &Value, impl_name: &str, test_case: &str, options: &Options) -> Result<String> {
+    let mut points: Vec<Point> = window["points"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|point| {
+            Some(Point {
+                time: DateTime::parse_from_rfc3339(point["time"].as_str()?).ok()?.with_timezone(&Utc),
+                azimuth: point["azimuth"].as_f64()?,
+                elevation: point["elevation"].as_f64()?.max(0.0),
+            })
+        })
+        .collect();
+    if points.is_empty() {
+        bail!("Pass {} of {} has no sampled points to track", options.pass, test_case);
+    }
+    points.sort_by_key(|point| point.time);
+
+    if let Unwrap::Flip = options.unwrap {
+        for point in &mut points {
+            point.azimuth = (point.azimuth + 180.0) % 360.0;
+            point.elevation = 180.0 - point.elevation;
+        }
+    }
+    if !matches!(options.unwrap, Unwrap::None) {
+        fit_range(&mut points, options)?;
+    }
+    let long_slew = points
+        .windows(2)
+        .find(|pair| (pair[1].azimuth - pair[0].azimuth).abs() > 180.0);
+
+    let (aos, los) = (&points[0], &points[points.len() - 1]);
+    let mut out = String::new();
+    writeln!(out, "# rotctld tracking schedule: pass {} of {} ({})", options.pass, test_case, impl_name)?;
+    writeln!(out, "# AOS {}, LOS {}, {} point(s)", format_time(aos.time), format_time(los.time), points.len())?;
+    let unwrap = options.unwrap.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+    writeln!(out, "# azimuth unwrap: {}, range {}..{}", unwrap, options.az_min, options.az_max)?;
+    if let Some([from, to]) = long_slew {
+        writeln!(
+            out,
+            "# warning: slews the long way round from {:.2} to {:.2} at {}; try --unwrap flip or a wider --az-max",
+            from.azimuth,
+            to.azimuth,
+            format_time(to.time)
+        )?;
+    }
+    writeln!(out, "# <UTC time> <rotctld command>")?;
+    writeln!(
+        out,
+        "{} {}",
+        format_time(aos.time - Duration::seconds(options.lead.into())),
+        set_pos(aos)
+    )?;
+    for point in &points {
+        writeln!(out, "{} {}", format_time(point.time), set_pos(point))?;
+    }
+    Ok(out)
+}
+
+/// Place every azimuth within the rotator's range, choosing among each
+/// point's equivalent positions the track with the least total slew.
+fn fit_range(points: &mut [Point], options: &Options) -> Result<()> {
+    let mut candidates = Vec::new();
+    for point in points.iter() {
+        let lowest = point.azimuth + ((options.az_min - point.azimuth) / 360.0).ceil() * 360.0;
+        let positions: Vec<f64> = std::iter::successors(Some(lowest), |azimuth| Some(azimuth + 360.0))
+            .take_while(|azimuth| *azimuth <= options.az_max)
+            .collect();
+        if positions.is_empty() {
+            bail!(
+                "Azimuth {:.2} at {} is outside the rotator range {}..{}",
+                point.azimuth,
+                format_time(point.time),
+                options.az_min,
+                options.az_max
+            );
+        }
+        candidates.push(positions);
+    }
+
+    // Least total slew to reach each position of the current point, and for
+    // every point the position of the previous one it was reached from
+    let mut cost = vec![0.0; candidates[0].len()];
+    let mut from = vec![vec![0; candidates[0].len()]];
+    for pair in candidates.windows(2) {
+        let (previous, current) = (&pair[0], &pair[1]);
+        let (next_cost, next_from): (Vec<f64>, Vec<usize>) = current
+            .iter()
+            .map(|azimuth| {
+                previous
+                    .iter()
+                    .zip(&cost)
+                    .map(|(before, cost)| cost + (azimuth - before).abs())
+                    .enumerate()
+                    .map(|(j, total)| (total, j))
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .unwrap_or_default()
+            })
+            .unzip();
+        cost = next_cost;
+        from.push(next_from);
+    }
+
+    let mut j = (0..cost.len()).min_by(|&a, &b| cost[a].total_cmp(&cost[b])).unwrap_or_default();
+    for (i, point) in points.iter_mut().enumerate().rev() {
+        point.azimuth = candidates[i][j];
+        j = from[i][j];
+    }
+    Ok(())
+}
+
+fn set_pos(point: &Point) -> String {
+    format!("P {:.2} {:.2}", point.azimuth, point.elevation)
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A pass from north-east over the zenith to south-west, sampled every
+    /// minute from 12:00
+    fn overhead_pass() -> Value {
+        let points: Vec<Value> = [(30.0, 5.0), (30.0, 45.0), (120.0, 89.0), (210.0, 45.0), (210.0, 5.0)]
+            .iter()
+            .enumerate()
+            .map(|(minute, (azimuth, elevation))| {
+                json!({ "time": format!("2025-01-01T12:0{}:00Z", minute), "azimuth": azimuth, "elevation": elevation })
+            })
+            .collect();
+        json!({ "points": points })
+    }
+
+    fn options(unwrap: Unwrap, az_max: f64) -> Options {
+        Options {
+            pass: 1,
+            lead: 90,
+            unwrap,
+            az_min: 0.0,
+            az_max,
+        }
+    }
+
+    /// The script's commands, without comments
+    fn commands(script: &str) -> Vec<&str> {
+        script.lines().filter(|line| !line.starts_with('#')).collect()
+    }
+
+    #[test]
+    fn the_rotator_is_pre_positioned_at_aos() {
+        let script = schedule(&overhead_pass(), "rust", "001_iss_nyc", &options(Unwrap::None, 360.0)).unwrap();
+        let commands = commands(&script);
+        assert_eq!(commands[0], "2025-01-01T11:58:30Z P 30.00 5.00");
+        assert_eq!(commands[1], "2025-01-01T12:00:00Z P 30.00 5.00");
+        assert_eq!(commands.len(), 6);
+    }
+
+    #[test]
+    fn flip_tracks_over_the_top() {
+        let script = schedule(&overhead_pass(), "rust", "001_iss_nyc", &options(Unwrap::Flip, 450.0)).unwrap();
+        assert_eq!(
+            commands(&script)[1..],
+            [
+                "2025-01-01T12:00:00Z P 210.00 175.00",
+                "2025-01-01T12:01:00Z P 210.00 135.00",
+                "2025-01-01T12:02:00Z P 300.00 91.00",
+                "2025-01-01T12:03:00Z P 390.00 135.00",
+                "2025-01-01T12:04:00Z P 390.00 175.00",
+            ]
+        );
+        assert!(!script.contains("warning"), "{}", script);
+    }
+
+    #[test]
+    fn slewing_the_long_way_round_is_flagged() {
+        // Without the overlap, the flipped pass must swing back from 300 to 30
+        let script = schedule(&overhead_pass(), "rust", "001_iss_nyc", &options(Unwrap::Flip, 360.0)).unwrap();
+        assert!(script.contains("# warning: slews the long way round from 300.00 to 30.00"), "{}", script);
+    }
+}
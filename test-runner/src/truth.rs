@@ -0,0 +1,285 @@
+//! Reference data and exports from mission analysis tools.
+//!
+//! By default results are checked against the committed python-skyfield
+//! reference results. `validate --truth <dir>` checks them against
+//! third-party truth data instead, one file per test case named
+//! `<test-case>.csv` (or `.txt`/`.rpt`), as exported by:
+//!
+//! - STK access reports: `"Access","Start Time (UTCG)","Stop Time (UTCG)","Duration (sec)"`
+//!   as CSV, or the fixed-width text report
+//! - GMAT contact locators: `Start Time (UTC),Stop Time (UTC),Duration (s)`
+//!   as CSV, or the text `ReportFile` the locator writes
+//!
+//! The format is detected from the header row. Both tools write times like
+//! `26 Oct 2025 06:32:30.000`.
+//!
+//! The same exports can stand in for an implementation: every directory
+//! `test-data/external/<tool>/` is a pseudo-implementation whose results are
+//! imported from the files in it rather than computed in a container.
+
+use crate::error::Error;
+use crate::languages::Language;
+use crate::{Implementation, Orchestrator, REFERENCE_IMPLEMENTATION, VisibilityResult};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extensions tried, in order, for a test case's export.
+const EXTENSIONS: [&str; 3] = ["csv", "txt", "rpt"];
+
+pub enum Reference {
+    /// `<test_data_dir>/reference-results/python-skyfield_<case>.json`
+    Skyfield(PathBuf),
+    /// A directory of third-party truth files
+    Truth(PathBuf),
+}
+
+impl Reference {
+    pub fn name(&self) -> String {
+        match self {
+            Reference::Skyfield(_) => REFERENCE_IMPLEMENTATION.to_string(),
+            Reference::Truth(dir) => format!("truth data in {}", dir.display()),
+        }
+    }
+
+    /// The reference result for `test_case`, or `None` when there is none.
+    pub fn load(&self, test_case: &str) -> Result<Option<VisibilityResult>> {
+        match self {
+            Reference::Skyfield(dir) => {
+                let path = dir.join(format!("{}_{}.json", REFERENCE_IMPLEMENTATION, test_case));
+                if !path.exists() {
+                    return Ok(None);
+                }
+                let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+                let reference = serde_json::from_str(&content).map_err(|e| Error::SchemaViolation {
+                    file: path.clone(),
+                    detail: e.to_string(),
+                })?;
+                Ok(Some(reference))
+            }
+            Reference::Truth(dir) => {
+                let Some(path) = export_path(dir, test_case) else {
+                    return Ok(None);
+                };
+                let export = read_export(&path)?;
+                Ok(Some(VisibilityResult {
+                    test_case: test_case.to_string(),
+                    implementation: export.tool.to_string(),
+                    version: String::new(),
+                    visibility_windows: export.windows,
+                    execution_time: None,
+                    warnings: Vec::new(),
+                }))
+            }
+        }
+    }
+}
+
+/// Fail early on a missing truth directory rather than reporting every case
+/// as having no reference.
+pub fn check_dir(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        bail!("Truth directory {} does not exist", dir.display());
+    }
+    Ok(())
+}
+
+/// Pseudo-implementations under `<test_data_dir>/external/`. They have no
+/// image; their path is the directory holding the exports, which may carry
+/// a README.md with the same front-matter as an implementation's.
+pub fn external_tools(test_data_dir: &Path) -> Result<Vec<Implementation>> {
+    let dir = test_data_dir.join("external");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut tools = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir()
+            && let Some(name) = path.file_name()
+        {
+            let name = name.to_string_lossy().to_string();
+            let manifest = Default::default();
+            tools.push(Implementation {
+                language: Language::detect(&name, &path, &manifest),
+                name,
+                path,
+                image_name: String::new(),
+                manifest,
+            });
+        }
+    }
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tools)
+}
+
+/// Convert every export of `tool` into a result file in the results
+/// directory, as if the tool had run each case. Returns the files written.
+pub fn import(orchestrator: &Orchestrator, tool: &Implementation) -> Result<Vec<PathBuf>> {
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    fs::create_dir_all(&orchestrator.results_dir)?;
+
+    let mut written = Vec::new();
+    for test_case in orchestrator.test_cases()? {
+        let Some(path) = export_path(&tool.path, &test_case) else {
+            continue;
+        };
+        let export = read_export(&path)?;
+        let result = json!({
+            "testCase": test_case,
+            "implementation": tool.name,
+            "version": "",
+            "visibilityWindows": export.windows,
+            "executionTime": null,
+            "warnings": [],
+        });
+
+        let output = orchestrator
+            .results_dir
+            .join(format!("{}_{}_{}.json", tool.name, test_case, timestamp));
+        fs::write(&output, serde_json::to_string_pretty(&result)?)?;
+        written.push(output);
+    }
+    Ok(written)
+}
+
+fn export_path(dir: &Path, test_case: &str) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", test_case, ext)))
+        .find(|path| path.exists())
+}
+
+/// Windows read from one export.
+struct Export {
+    tool: &'static str,
+    windows: Vec<Value>,
+}
+
+type Window = (DateTime<Utc>, DateTime<Utc>);
+
+fn read_export(path: &Path) -> Result<Export> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Parse an export in any supported format, detected from its header row.
+fn parse(content: &str) -> Result<Export> {
+    let lines: Vec<&str> = content.lines().collect();
+    let header_index = lines
+        .iter()
+        .position(|line| line.contains("Start Time (UTC"))
+        .context("No 'Start Time' header; expected an STK access or GMAT contact locator export")?;
+    let header = lines[header_index];
+    let tool = if header.contains("Start Time (UTCG)") { "stk" } else { "gmat" };
+
+    let rows = &lines[header_index + 1..];
+    let windows = if header.contains(',') { parse_csv(header, rows)? } else { parse_text(rows) };
+
+    Ok(Export {
+        tool,
+        windows: windows
+            .into_iter()
+            .map(|(start, stop)| {
+                json!({
+                    "start": start.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                    "end": stop.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                    "duration": (stop - start).num_milliseconds() as f64 / 1000.0,
+                })
+            })
+            .collect(),
+    })
+}
+
+/// CSV exports: the start and stop columns of each row, up to the first row
+/// without two times (STK appends "Min Duration" and similar statistics rows
+/// after a blank line).
+fn parse_csv(header: &str, rows: &[&str]) -> Result<Vec<Window>> {
+    let header = split_csv(header);
+    let column = |prefix: &str| {
+        header
+            .iter()
+            .position(|h| h.starts_with(prefix))
+            .with_context(|| format!("No '{}' column", prefix))
+    };
+    let (start_index, stop_index) = (column("Start Time")?, column("Stop Time")?);
+
+    let mut windows = Vec::new();
+    for row in rows.iter().skip_while(|row| row.trim().is_empty()) {
+        let fields = split_csv(row);
+        let time = |index: usize| fields.get(index).and_then(|field| parse_tool_time(field));
+        match (time(start_index), time(stop_index)) {
+            (Some(start), Some(stop)) => windows.push((start, stop)),
+            _ if windows.is_empty() => bail!("Unreadable first row: {}", row),
+            _ => break,
+        }
+    }
+    Ok(windows)
+}
+
+/// Text reports: every line with exactly a start and a stop time, outside
+/// statistics sections. STK prints each access as
+/// `1    26 Oct 2025 06:32:30.000    26 Oct 2025 06:39:10.000    400.000`,
+/// GMAT the same without the index. Both repeat the header for further
+/// objects, so data resumes after each header.
+fn parse_text(rows: &[&str]) -> Vec<Window> {
+    let mut windows = Vec::new();
+    let mut in_statistics = false;
+    for row in rows {
+        let row = row.trim();
+        if row.contains("Statistics") || row.starts_with("Number of events") {
+            in_statistics = true;
+        } else if row.contains("Start Time (UTC") {
+            in_statistics = false;
+        } else if !in_statistics && let [start, stop] = times_in(row)[..] {
+            windows.push((start, stop));
+        }
+    }
+    windows
+}
+
+/// Times in a whitespace-separated line, as `26 Oct 2025 06:32:30.000`
+/// (four tokens) or RFC 3339 (one token).
+fn times_in(line: &str) -> Vec<DateTime<Utc>> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut times = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some(time) = tokens.get(i..i + 4).and_then(|t| parse_tool_time(&t.join(" "))) {
+            times.push(time);
+            i += 4;
+        } else {
+            times.extend(parse_tool_time(tokens[i]));
+            i += 1;
+        }
+    }
+    times
+}
+
+/// Split one CSV line, honouring double quotes.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// STK UTCG and GMAT UTCGregorian times, e.g. `26 Oct 2025 06:32:30.000`,
+/// or RFC 3339.
+fn parse_tool_time(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), "%d %b %Y %H:%M:%S%.f")
+        .map(|t| t.and_utc())
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value.trim()).ok().map(|t| t.with_timezone(&Utc)))
+}
@@ -0,0 +1,190 @@
+//! CPU time, peak memory, and estimated energy of container runs.
+//!
+//! The container runtime keeps no accounting once a container is gone, so
+//! while a container runs, [`Meter`] samples its cgroup's CPU usage and keeps the
+//! last reading. Usage in the final sampling interval before exit can be
+//! missed, so short runs read slightly low. Peak memory comes from the
+//! kernel's own high-water mark (`memory.peak`, or `memory.max_usage_in_bytes`
+//! on cgroup v1) where available, and otherwise from the highest sampled
+//! `memory.current`, which can miss short spikes. Hosts where the cgroup
+//! isn't visible (e.g. Docker Desktop's VM) report neither.
+//!
+//! Energy is estimated from CPU time alone, at a configurable power draw per
+//! busy core (`--watts-per-core`). It ignores idle, memory, and I/O power,
+//! so it ranks implementations rather than measuring a board.
+
+use crate::runtime::ContainerRuntime;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Power drawn by one fully busy core, in watts, unless configured. Roughly
+/// a desktop x86 core; a Raspberry Pi 4 core draws closer to 1 W.
+pub const DEFAULT_WATTS_PER_CORE: f64 = 5.0;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// CPU and memory usage of one container, sampled from its cgroup while it
+/// runs.
+pub struct Meter {
+    cidfile: PathBuf,
+    done: Arc<AtomicBool>,
+    sampler: JoinHandle<Reading>,
+}
+
+/// What a [`Meter`] measured; `None` where no reading was taken.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reading {
+    pub cpu_seconds: Option<f64>,
+    /// Peak memory in bytes
+    pub peak_memory: Option<u64>,
+}
+
+impl Meter {
+    /// Start sampling the container whose ID `run --cidfile` writes to
+    /// `cidfile`. The file must not exist yet.
+    pub fn start(runtime: &'static dyn ContainerRuntime, cidfile: PathBuf) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        let sampler = {
+            let cidfile = cidfile.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut id = None;
+                let mut reading = Reading::default();
+                while !done.load(Ordering::Relaxed) {
+                    if id.is_none() {
+                        id = fs::read_to_string(&cidfile).ok().filter(|id| !id.trim().is_empty());
+                    }
+                    if let Some(id) = id.as_deref() {
+                        let cgroups = runtime.cgroups(id.trim());
+                        if let Some(seconds) = cgroup_cpu_seconds(&cgroups) {
+                            reading.cpu_seconds = Some(seconds);
+                        }
+                        if let Some(bytes) = cgroup_peak_memory(&cgroups) {
+                            reading.peak_memory = Some(reading.peak_memory.map_or(bytes, |peak| peak.max(bytes)));
+                        }
+                    }
+                    thread::sleep(SAMPLE_INTERVAL);
+                }
+                reading
+            })
+        };
+        Meter { cidfile, done, sampler }
+    }
+
+    /// Stop sampling once the container has exited, returning what was
+    /// measured.
+    pub fn finish(self) -> Reading {
+        self.done.store(true, Ordering::Relaxed);
+        let reading = self.sampler.join().unwrap_or_default();
+        fs::remove_file(&self.cidfile).ok();
+        reading
+    }
+}
+
+/// CPU seconds used so far by the container in `cgroups`, from cgroup v2
+/// or v1.
+fn cgroup_cpu_seconds(cgroups: &[PathBuf]) -> Option<f64> {
+    let root = Path::new("/sys/fs/cgroup");
+
+    // cgroup v2: `usage_usec` in cpu.stat
+    for cgroup in cgroups {
+        if let Ok(stat) = fs::read_to_string(root.join(cgroup).join("cpu.stat")) {
+            let usec = stat
+                .lines()
+                .find_map(|line| line.strip_prefix("usage_usec "))
+                .and_then(|value| value.trim().parse::<f64>().ok())?;
+            return Some(usec / 1e6);
+        }
+    }
+    // cgroup v1: nanoseconds in cpuacct.usage
+    for controller in ["cpuacct", "cpu,cpuacct"] {
+        for cgroup in cgroups {
+            if let Ok(usage) = fs::read_to_string(root.join(controller).join(cgroup).join("cpuacct.usage")) {
+                return usage.trim().parse::<f64>().ok().map(|ns| ns / 1e9);
+            }
+        }
+    }
+    None
+}
+
+/// Peak memory in bytes of the container in `cgroups` so far, or its
+/// current usage where the kernel keeps no peak.
+fn cgroup_peak_memory(cgroups: &[PathBuf]) -> Option<u64> {
+    let root = Path::new("/sys/fs/cgroup");
+    let read = |path: PathBuf| fs::read_to_string(path).ok()?.trim().parse::<u64>().ok();
+
+    // cgroup v2: memory.peak since Linux 5.19
+    for cgroup in cgroups {
+        let dir = root.join(cgroup);
+        if let Some(bytes) = read(dir.join("memory.peak")).or_else(|| read(dir.join("memory.current"))) {
+            return Some(bytes);
+        }
+    }
+    // cgroup v1
+    for cgroup in cgroups {
+        if let Some(bytes) = read(root.join("memory").join(cgroup).join("memory.max_usage_in_bytes")) {
+            return Some(bytes);
+        }
+    }
+    None
+}
+
+/// E.g. "45.2 MiB"
+pub fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1u64 << 20) as f64)
+}
+
+/// Estimated energy in joules for `cpu_seconds` of CPU time.
+pub fn joules(cpu_seconds: f64, watts_per_core: f64) -> f64 {
+    cpu_seconds * watts_per_core
+}
+
+/// CPU time and memory of an implementation's containers in one run, filed
+/// as `results/<run-id>/<implementation>/usage.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    /// Containers measured; a resumed run starts one per case
+    pub containers: usize,
+    pub cpu_seconds: f64,
+    pub execution_time: f64,
+    /// Result files the measured containers wrote
+    pub cases: usize,
+    /// Highest peak memory of any measured container, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+}
+
+impl Usage {
+    /// CPU seconds per test case, if any case was run.
+    pub fn cpu_seconds_per_case(&self) -> Option<f64> {
+        (self.cases > 0).then(|| self.cpu_seconds / self.cases as f64)
+    }
+
+    /// Joules per test case, if any case was run.
+    pub fn joules_per_case(&self, watts_per_core: f64) -> Option<f64> {
+        (self.cases > 0).then(|| joules(self.cpu_seconds, watts_per_core) / self.cases as f64)
+    }
+}
+
+/// The implementation's usage in the latest run that recorded one.
+pub fn latest(results_dir: &Path, implementation: &str) -> Result<Option<Usage>> {
+    let Ok(entries) = fs::read_dir(results_dir) else {
+        return Ok(None);
+    };
+    let mut runs: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.join(implementation).join("usage.json").is_file())
+        .collect();
+    // Run IDs are timestamps, so they sort by time
+    runs.sort();
+    let Some(run) = runs.last() else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_str(&fs::read_to_string(run.join(implementation).join("usage.json"))?)?))
+}
@@ -0,0 +1,208 @@
+//! `run --watch`: re-running tests as files change, for a tight edit-test
+//! loop while porting a library.
+//!
+//! After the first run, the selected implementations' directories and the
+//! test data directory are polled for changes to the modification time or
+//! size of any file. Edits are collected until the files have been quiet
+//! for one poll, so saving several at once triggers one run. Then:
+//!
+//! - a change in an implementation's directory rebuilds its image and re-runs
+//!   it on every case (or the one selected with `--test-case`)
+//! - a change to a case file re-runs that case on every implementation
+//! - a change to the reference results re-runs nothing
+//! - any other change to the test data (TLE sets, sites) re-runs everything
+//!
+//! and the re-run implementations are validated (every implementation, when
+//! the reference results changed). Hidden files and the usual local build
+//! output directories are ignored, so building or testing an implementation
+//! outside its container doesn't trigger runs.
+
+use crate::{Implementation, Orchestrator};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Directories of local build output, skipped at any depth
+const IGNORED_DIRS: [&str; 5] = ["target", "node_modules", "__pycache__", "build", "venv"];
+
+/// Modification time and size of every watched file.
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Watch until interrupted, re-running `implementations` on changes.
+pub fn watch(orchestrator: &Orchestrator, implementations: &[Implementation], selected: Option<&[String]>, parallel: usize) -> ! {
+    let mut dirs: Vec<&Path> = implementations.iter().map(|impl_| impl_.path.as_path()).collect();
+    dirs.push(&orchestrator.test_data_dir);
+    println!();
+    println!("{}", "Watching for changes (Ctrl+C to stop):".bold().bright_blue());
+    for dir in &dirs {
+        println!("  {}", dir.display());
+    }
+
+    let mut snapshot = take(&dirs);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let mut changed = BTreeSet::new();
+        loop {
+            let next = take(&dirs);
+            let more = changes(&snapshot, &next);
+            snapshot = next;
+            if more.is_empty() {
+                break;
+            }
+            changed.extend(more);
+            thread::sleep(POLL_INTERVAL);
+        }
+        if !changed.is_empty() {
+            rerun(orchestrator, implementations, selected, parallel, &changed);
+            // Ignore whatever the run itself touched
+            snapshot = take(&dirs);
+        }
+    }
+}
+
+fn rerun(orchestrator: &Orchestrator, implementations: &[Implementation], selected: Option<&[String]>, parallel: usize, changed: &BTreeSet<PathBuf>) {
+    let cases_dir = orchestrator.test_data_dir.join("cases");
+    let reference_dir = orchestrator.test_data_dir.join("reference-results");
+    let mut rebuild = Vec::new();
+    let mut cases = BTreeSet::new();
+    let mut all_cases = false;
+    let mut reference_changed = false;
+    for path in changed {
+        if let Some(impl_) = implementations.iter().find(|impl_| path.starts_with(&impl_.path)) {
+            if !rebuild.iter().any(|rebuilt: &&Implementation| rebuilt.name == impl_.name) {
+                rebuild.push(impl_);
+            }
+        } else if path.parent() == Some(cases_dir.as_path()) && path.extension().is_some_and(|ext| ext == "json") {
+            cases.insert(path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+        } else if path.starts_with(&reference_dir) {
+            reference_changed = true;
+        } else {
+            all_cases = true;
+        }
+    }
+    // A removed case has nothing left to run
+    cases.retain(|case| cases_dir.join(format!("{}.json", case)).exists() && selected.is_none_or(|selected| selected.contains(case)));
+
+    println!();
+    println!("{} {}", "Changed:".bold().bright_blue(), changed.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", "));
+    println!("{}", "=".repeat(50).dimmed());
+
+    let mut runs: Vec<(Vec<Implementation>, Option<&[String]>)> = Vec::new();
+    let rebuilt: Vec<Implementation> = rebuild
+        .into_iter()
+        .filter(|impl_| match orchestrator.build_image(impl_) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("  {} Error building {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red());
+                false
+            }
+        })
+        .cloned()
+        .collect();
+    let others: Vec<Implementation> = implementations
+        .iter()
+        .filter(|impl_| !changed.iter().any(|path| path.starts_with(&impl_.path)))
+        .cloned()
+        .collect();
+    if !rebuilt.is_empty() {
+        runs.push((rebuilt, selected));
+    }
+    if !others.is_empty() {
+        if all_cases {
+            runs.push((others, selected));
+        } else {
+            for case in &cases {
+                runs.push((others.clone(), Some(std::slice::from_ref(case))));
+            }
+        }
+    }
+    if runs.is_empty() && !reference_changed {
+        println!("  {} Nothing to re-run", "○".dimmed());
+        return;
+    }
+
+    let mut ran = BTreeSet::new();
+    if reference_changed {
+        ran.extend(implementations.iter().map(|impl_| impl_.name.clone()));
+    }
+    for (implementations, cases) in &runs {
+        match orchestrator.run_parallel(implementations, *cases, parallel, false) {
+            Ok(outcomes) => {
+                for (impl_, outcome) in implementations.iter().zip(outcomes.into_iter().flatten()) {
+                    match outcome {
+                        Ok(result) if result.success => {
+                            ran.insert(impl_.name.clone());
+                        }
+                        Ok(result) => println!(
+                            "  {} {} failed{}",
+                            "✗".red(),
+                            impl_.name.bright_white(),
+                            result.killed_note()
+                        ),
+                        Err(e) => eprintln!("  {} Error running {}: {}", "✗".red(), impl_.name.bright_white(), e.to_string().red()),
+                    }
+                }
+            }
+            Err(e) => eprintln!("  {} {}", "✗".red(), e.to_string().red()),
+        }
+    }
+
+    for name in &ran {
+        match orchestrator.validate_results(name) {
+            Ok(summary) => {
+                let mark = if summary.matched == summary.total { "✓".green() } else { "✗".red() };
+                println!("  {} {}: {}/{} cases match", mark, name.bright_white(), summary.matched, summary.total);
+            }
+            Err(e) => eprintln!("  {} Error validating {}: {}", "✗".red(), name.bright_white(), e.to_string().red()),
+        }
+    }
+}
+
+fn take(dirs: &[&Path]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for dir in dirs {
+        walk(dir, &mut snapshot);
+    }
+    snapshot
+}
+
+/// Add the files under `dir` to `snapshot`. Unreadable entries are skipped;
+/// files come and go while an editor saves.
+fn walk(dir: &Path, snapshot: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if !IGNORED_DIRS.contains(&name.as_ref()) {
+                walk(&entry.path(), snapshot);
+            }
+        } else {
+            snapshot.insert(entry.path(), (metadata.modified().ok(), metadata.len()));
+        }
+    }
+}
+
+/// Files added, removed, or modified between `before` and `after`.
+fn changes(before: &Snapshot, after: &Snapshot) -> BTreeSet<PathBuf> {
+    let mut changed: BTreeSet<PathBuf> = after
+        .iter()
+        .filter(|(path, state)| before.get(*path) != Some(state))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(before.keys().filter(|path| !after.contains_key(*path)).cloned());
+    changed
+}
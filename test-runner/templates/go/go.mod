@@ -0,0 +1,3 @@
+module visibility-calculator
+
+go 1.22
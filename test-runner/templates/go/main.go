@@ -0,0 +1,135 @@
+// Satellite Visibility Calculator - {{implementation}}
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"math"
+	"os"
+	"path/filepath"
+	"runtime"
+	"sort"
+	"strings"
+	"time"
+)
+
+const (
+	implementation = "{{implementation}}"
+	version        = "0.1.0"
+)
+
+type TestCase struct {
+	Name      string `json:"name"`
+	Satellite struct {
+		Name string   `json:"name"`
+		TLE  []string `json:"tle"`
+	} `json:"satellite"`
+	Observer struct {
+		Latitude  float64 `json:"latitude"`
+		Longitude float64 `json:"longitude"`
+		Altitude  float64 `json:"altitude"`
+	} `json:"observer"`
+	TimeWindow struct {
+		Start time.Time `json:"start"`
+		End   time.Time `json:"end"`
+		Step  float64   `json:"step"`
+	} `json:"timeWindow"`
+	MinElevation float64 `json:"minElevation"`
+}
+
+type Window struct {
+	Start            string           `json:"start"`
+	End              string           `json:"end"`
+	MaxElevation     float64          `json:"maxElevation"`
+	MaxElevationTime string           `json:"maxElevationTime"`
+	Duration         float64          `json:"duration"`
+	Points           []map[string]any `json:"points"`
+}
+
+// calculate returns the visibility windows of the case's satellite from its
+// observer.
+//
+// TODO: propagate testCase.Satellite.TLE with {{library}} over
+// testCase.TimeWindow and return the windows where the elevation seen from
+// testCase.Observer is at least testCase.MinElevation.
+func calculate(testCase TestCase) ([]Window, error) {
+	return []Window{}, nil
+}
+
+func run(args []string) error {
+	// Self-check for the orchestrator: report versions and exit
+	if len(args) == 1 && args[0] == "--version" {
+		return json.NewEncoder(os.Stdout).Encode(map[string]string{
+			"implementation": implementation,
+			"version":        version,
+			"libraryName":    "{{library}}",
+			"platform":       "Go " + strings.TrimPrefix(runtime.Version(), "go"),
+		})
+	}
+
+	// Prefer Docker paths, fall back to local
+	casesDir, resultsDir := "/test-data/cases", "/results"
+	if _, err := os.Stat(casesDir); err != nil {
+		casesDir, resultsDir = "../../test-data/cases", "../../results"
+	}
+
+	// One test case from the command line, or all of them
+	var caseFiles []string
+	if len(args) > 0 {
+		caseFiles = []string{filepath.Join(casesDir, args[0]+".json")}
+	} else {
+		matches, err := filepath.Glob(filepath.Join(casesDir, "*.json"))
+		if err != nil {
+			return err
+		}
+		sort.Strings(matches)
+		caseFiles = matches
+	}
+
+	if err := os.MkdirAll(resultsDir, 0o755); err != nil {
+		return err
+	}
+	for _, caseFile := range caseFiles {
+		content, err := os.ReadFile(caseFile)
+		if err != nil {
+			return err
+		}
+		var testCase TestCase
+		if err := json.Unmarshal(content, &testCase); err != nil {
+			return fmt.Errorf("%s: %w", caseFile, err)
+		}
+
+		start := time.Now()
+		windows, err := calculate(testCase)
+		if err != nil {
+			return fmt.Errorf("%s: %w", testCase.Name, err)
+		}
+		executionTime := time.Since(start).Seconds()
+
+		result := map[string]any{
+			"testCase":          testCase.Name,
+			"implementation":    implementation,
+			"version":           version,
+			"visibilityWindows": windows,
+			"executionTime":     math.Round(executionTime*1000) / 1000,
+			"timestamp":         time.Now().UTC().Format("2006-01-02T15:04:05Z"),
+		}
+		output, err := json.MarshalIndent(result, "", "  ")
+		if err != nil {
+			return err
+		}
+		path := filepath.Join(resultsDir, fmt.Sprintf("%s_%s.json", implementation, testCase.Name))
+		if err := os.WriteFile(path, output, 0o644); err != nil {
+			return err
+		}
+		fmt.Printf("✓ %s: %d window(s)\n", testCase.Name, len(windows))
+	}
+	return nil
+}
+
+func main() {
+	if err := run(os.Args[1:]); err != nil {
+		fmt.Fprintln(os.Stderr, "Error:", err)
+		os.Exit(1)
+	}
+}
@@ -0,0 +1,67 @@
+// Satellite Visibility Calculator - {{implementation}}
+
+import fs from 'fs';
+import path from 'path';
+
+const IMPLEMENTATION = '{{implementation}}';
+const VERSION = '0.1.0';
+
+/**
+ * Visibility windows of the case's satellite from its observer.
+ *
+ * TODO: propagate testCase.satellite.tle with {{library}} over
+ * testCase.timeWindow and return the windows where the elevation seen from
+ * testCase.observer is at least testCase.minElevation, as
+ * { start, end, maxElevation, maxElevationTime, duration, points }.
+ */
+function calculate(testCase) {
+  return [];
+}
+
+function main() {
+  const args = process.argv.slice(2);
+
+  // Self-check for the orchestrator: report versions and exit
+  if (args.length === 1 && args[0] === '--version') {
+    console.log(JSON.stringify({
+      implementation: IMPLEMENTATION,
+      version: VERSION,
+      libraryName: '{{library}}',
+      platform: `Node.js ${process.versions.node}`,
+    }));
+    return;
+  }
+
+  // Prefer Docker paths, fall back to local
+  const docker = fs.existsSync('/test-data/cases');
+  const casesDir = docker ? '/test-data/cases' : '../../test-data/cases';
+  const resultsDir = docker ? '/results' : '../../results';
+
+  // One test case from the command line, or all of them
+  const caseFiles = args.length > 0
+    ? [`${args[0]}.json`]
+    : fs.readdirSync(casesDir).filter((file) => file.endsWith('.json')).sort();
+
+  fs.mkdirSync(resultsDir, { recursive: true });
+  for (const caseFile of caseFiles) {
+    const testCase = JSON.parse(fs.readFileSync(path.join(casesDir, caseFile), 'utf8'));
+
+    const start = process.hrtime.bigint();
+    const windows = calculate(testCase);
+    const executionTime = Number(process.hrtime.bigint() - start) / 1e9;
+
+    const result = {
+      testCase: testCase.name,
+      implementation: IMPLEMENTATION,
+      version: VERSION,
+      visibilityWindows: windows,
+      executionTime: Math.round(executionTime * 1000) / 1000,
+      timestamp: new Date().toISOString().replace(/\.\d+Z$/, 'Z'),
+    };
+    const output = path.join(resultsDir, `${IMPLEMENTATION}_${testCase.name}.json`);
+    fs.writeFileSync(output, JSON.stringify(result, null, 2));
+    console.log(`✓ ${testCase.name}: ${windows.length} window(s)`);
+  }
+}
+
+main();
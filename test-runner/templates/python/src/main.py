@@ -0,0 +1,74 @@
+#!/usr/bin/env python3
+"""
+Satellite Visibility Calculator - {{implementation}}
+"""
+
+import json
+import sys
+import time
+from datetime import datetime, timezone
+from pathlib import Path
+
+IMPLEMENTATION = "{{implementation}}"
+VERSION = "0.1.0"
+
+
+def calculate(test_case):
+    """Visibility windows of the case's satellite from its observer.
+
+    TODO: propagate test_case["satellite"]["tle"] with {{library}} over
+    test_case["timeWindow"] and return the windows where the elevation seen
+    from test_case["observer"] is at least test_case["minElevation"], as
+    {"start", "end", "maxElevation", "maxElevationTime", "duration", "points"}.
+    """
+    return []
+
+
+def main():
+    # Self-check for the orchestrator: report versions and exit
+    if sys.argv[1:] == ["--version"]:
+        print(json.dumps({
+            "implementation": IMPLEMENTATION,
+            "version": VERSION,
+            "libraryName": "{{library}}",
+            "platform": f"Python {sys.version.split()[0]}",
+        }))
+        return 0
+
+    # Prefer Docker paths, fall back to local
+    if Path("/test-data/cases").exists():
+        cases_dir, results_dir = Path("/test-data/cases"), Path("/results")
+    else:
+        cases_dir, results_dir = Path("../../test-data/cases"), Path("../../results")
+
+    # One test case from the command line, or all of them
+    if len(sys.argv) > 1:
+        case_files = [cases_dir / f"{sys.argv[1]}.json"]
+    else:
+        case_files = sorted(cases_dir.glob("*.json"))
+
+    results_dir.mkdir(parents=True, exist_ok=True)
+    for case_file in case_files:
+        test_case = json.loads(case_file.read_text())
+
+        start = time.time()
+        windows = calculate(test_case)
+        execution_time = time.time() - start
+
+        result = {
+            "testCase": test_case["name"],
+            "implementation": IMPLEMENTATION,
+            "version": VERSION,
+            "visibilityWindows": windows,
+            "executionTime": round(execution_time, 3),
+            "timestamp": datetime.now(timezone.utc).strftime("%Y-%m-%dT%H:%M:%SZ"),
+        }
+        output = results_dir / f"{IMPLEMENTATION}_{test_case['name']}.json"
+        output.write_text(json.dumps(result, indent=2))
+        print(f"✓ {test_case['name']}: {len(windows)} window(s)")
+
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,84 @@
+//! Satellite Visibility Calculator - {{implementation}}
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+const IMPLEMENTATION: &str = "{{implementation}}";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Visibility windows of the case's satellite from its observer.
+///
+/// TODO: propagate `test_case["satellite"]["tle"]` with {{library}} over
+/// `test_case["timeWindow"]` and return the windows where the elevation
+/// seen from `test_case["observer"]` is at least `test_case["minElevation"]`,
+/// as `{start, end, maxElevation, maxElevationTime, duration, points}`.
+fn calculate(_test_case: &Value) -> Result<Vec<Value>> {
+    Ok(Vec::new())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Self-check for the orchestrator: report versions and exit
+    if args == ["--version"] {
+        println!(
+            "{}",
+            json!({
+                "implementation": IMPLEMENTATION,
+                "version": VERSION,
+                "libraryName": "{{library}}",
+                "platform": "Rust",
+            })
+        );
+        return Ok(());
+    }
+
+    // Prefer Docker paths, fall back to local
+    let (cases_dir, results_dir) = if Path::new("/test-data/cases").exists() {
+        (Path::new("/test-data/cases"), Path::new("/results"))
+    } else {
+        (Path::new("../../test-data/cases"), Path::new("../../results"))
+    };
+
+    // One test case from the command line, or all of them
+    let mut case_files = Vec::new();
+    if let Some(name) = args.first() {
+        case_files.push(cases_dir.join(format!("{}.json", name)));
+    } else {
+        for entry in fs::read_dir(cases_dir).with_context(|| format!("Failed to read {}", cases_dir.display()))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                case_files.push(path);
+            }
+        }
+        case_files.sort();
+    }
+
+    fs::create_dir_all(results_dir)?;
+    for case_file in case_files {
+        let test_case: Value = serde_json::from_str(
+            &fs::read_to_string(&case_file).with_context(|| format!("Failed to read {}", case_file.display()))?,
+        )?;
+        let name = test_case["name"].as_str().unwrap_or_default();
+
+        let start = Instant::now();
+        let windows = calculate(&test_case).with_context(|| format!("Failed to calculate {}", name))?;
+        let execution_time = start.elapsed().as_secs_f64();
+
+        let result = json!({
+            "testCase": name,
+            "implementation": IMPLEMENTATION,
+            "version": VERSION,
+            "visibilityWindows": windows,
+            "executionTime": (execution_time * 1000.0).round() / 1000.0,
+            "timestamp": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        });
+        let output = results_dir.join(format!("{}_{}.json", IMPLEMENTATION, name));
+        fs::write(&output, serde_json::to_string_pretty(&result)?)?;
+        println!("✓ {}: {} window(s)", name, windows.len());
+    }
+    Ok(())
+}